@@ -16,6 +16,78 @@ fn main() {
     } else {
         verify_libraw_for_development();
     }
+
+    // 3. libheif solo si se activó la feature "heic"
+    if env::var("CARGO_FEATURE_HEIC").is_ok() {
+        configure_libheif();
+    }
+
+    // 4. Transformaciones JPEG sin pérdida (jpegtran con setjmp/longjmp)
+    compile_jpeg_lossless();
+}
+
+/// Compilar native/jpeg_lossless.c contra las cabeceras de mozjpeg-sys.
+/// Los errores de libjpeg se manejan en C con longjmp: con panic = "abort" no
+/// se puede hacer unwinding desde su error_exit.
+fn compile_jpeg_lossless() {
+    println!("cargo:rerun-if-changed=native/jpeg_lossless.c");
+
+    let mut build = cc::Build::new();
+    build.file("native/jpeg_lossless.c");
+    // mozjpeg-sys (links = "jpeg") publica jconfig.h y sus cabeceras
+    if let Some(include) = env::var_os("DEP_JPEG_INCLUDE") {
+        build.includes(env::split_paths(&include));
+    }
+    build.compile("jpeg_lossless");
+}
+
+/// Detectar libheif y añadir su ruta de enlace (libheif-sys enlaza la biblioteca)
+fn configure_libheif() {
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    println!("cargo:rerun-if-env-changed=VCPKG_ROOT");
+
+    match os.as_str() {
+        "windows" => {
+            // En Windows libheif-sys busca libheif con vcpkg
+            if env::var("VCPKG_ROOT").is_ok() {
+                println!("cargo:warning=✓ VCPKG_ROOT definido, libheif se busca con vcpkg");
+            } else {
+                println!("cargo:warning=⚠️ VCPKG_ROOT no definido (feature heic).");
+                println!(
+                    "cargo:warning=   Instala con: vcpkg install libheif:x64-windows-static-md"
+                );
+            }
+        }
+        _ => {
+            let found = Command::new("pkg-config")
+                .args(["--atleast-version=1.18", "libheif"])
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false);
+
+            if found {
+                println!("cargo:warning=✓ libheif encontrado (feature heic)");
+            } else {
+                println!("cargo:warning=⚠️ libheif >= 1.18 no encontrado (feature heic).");
+                if os == "macos" {
+                    println!("cargo:warning=   Instala con: brew install libheif");
+                } else {
+                    println!("cargo:warning=   Instala con: sudo apt install libheif-dev");
+                }
+            }
+
+            // Homebrew no está en la ruta de enlace por defecto
+            if os == "macos" {
+                let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+                let homebrew_prefix = if arch == "aarch64" {
+                    "/opt/homebrew"
+                } else {
+                    "/usr/local"
+                };
+                println!("cargo:rustc-link-search=native={}/lib", homebrew_prefix);
+            }
+        }
+    }
 }
 
 /// Verificación para desarrollo con enlace dinámico
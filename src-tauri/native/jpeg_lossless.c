@@ -0,0 +1,146 @@
+/*
+ * Transformaciones de JPEG a nivel de coeficientes DCT (jpegtran) para
+ * JpegOptimizer::transform_lossless / optimize_lossless.
+ *
+ * Los errores fatales de libjpeg vuelven con longjmp a este archivo y se
+ * devuelven como código: nada hace unwinding a través de Rust, así que un JPEG
+ * corrupto también es un Err en release (panic = "abort").
+ */
+#include <setjmp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "jpeglib.h"
+#include "transupp.h"
+
+#define TI_JPEG_OK 0
+#define TI_JPEG_ERROR 1
+#define TI_JPEG_NOT_PERFECT 2
+
+struct ti_error_mgr {
+    struct jpeg_error_mgr pub;
+    jmp_buf jump;
+};
+
+/* En el heap: sigue siendo válido después de longjmp */
+struct ti_state {
+    struct jpeg_decompress_struct src;
+    struct jpeg_compress_struct dst;
+    struct ti_error_mgr err;
+    int src_created;
+    int dst_created;
+    unsigned char *buffer;
+    unsigned long buffer_size;
+};
+
+static void ti_error_exit(j_common_ptr cinfo)
+{
+    struct ti_error_mgr *err = (struct ti_error_mgr *)cinfo->err;
+    longjmp(err->jump, 1);
+}
+
+/* Los avisos (datos truncados, marcadores extraños) no detienen la transformación */
+static void ti_emit_message(j_common_ptr cinfo, int msg_level)
+{
+    (void)cinfo;
+    (void)msg_level;
+}
+
+/*
+ * Aplica `transform` (JXFORM_CODE) a `data` y reescribe la codificación entrópica
+ * con tablas Huffman optimizadas; `progressive` = 0 escribe un único scan.
+ * Devuelve TI_JPEG_OK con el resultado en `*out` (liberarlo con ti_jpeg_free),
+ * TI_JPEG_NOT_PERFECT si un borde no es múltiplo del MCU, o TI_JPEG_ERROR con
+ * el mensaje de libjpeg en `message`.
+ */
+int ti_jpeg_transform(const unsigned char *data, unsigned long size, int transform,
+                      int progressive, unsigned char **out, unsigned long *out_size,
+                      char *message, size_t message_size)
+{
+    struct ti_state *s = calloc(1, sizeof *s);
+    jpeg_transform_info info;
+    jvirt_barray_ptr *src_coefs;
+    jvirt_barray_ptr *dst_coefs;
+    int result;
+
+    *out = NULL;
+    *out_size = 0;
+    if (s == NULL) {
+        snprintf(message, message_size, "Out of memory");
+        return TI_JPEG_ERROR;
+    }
+
+    s->src.err = jpeg_std_error(&s->err.pub);
+    s->dst.err = &s->err.pub;
+    s->err.pub.error_exit = ti_error_exit;
+    s->err.pub.emit_message = ti_emit_message;
+
+    if (setjmp(s->err.jump)) {
+        char buffer[JMSG_LENGTH_MAX];
+        s->err.pub.format_message((j_common_ptr)&s->src, buffer);
+        snprintf(message, message_size, "%s", buffer);
+        result = TI_JPEG_ERROR;
+        goto done;
+    }
+
+    jpeg_create_decompress(&s->src);
+    s->src_created = 1;
+    jpeg_create_compress(&s->dst);
+    s->dst_created = 1;
+
+    jpeg_mem_src(&s->src, data, size);
+    jcopy_markers_setup(&s->src, JCOPYOPT_NONE);
+    jpeg_read_header(&s->src, TRUE);
+
+    memset(&info, 0, sizeof info);
+    info.transform = (JXFORM_CODE)transform;
+    /* Fallar en lugar de dejar bloques parciales en el borde equivocado */
+    info.perfect = TRUE;
+    if (!jtransform_request_workspace(&s->src, &info)) {
+        snprintf(message, message_size, "Image size %ux%u is not a multiple of the MCU size",
+                 s->src.image_width, s->src.image_height);
+        result = TI_JPEG_NOT_PERFECT;
+        goto done;
+    }
+
+    src_coefs = jpeg_read_coefficients(&s->src);
+    jpeg_copy_critical_parameters(&s->src, &s->dst);
+    dst_coefs = jtransform_adjust_parameters(&s->src, &s->dst, src_coefs, &info);
+
+    /* Tablas Huffman propias de la imagen; progresivo viene de los valores por defecto */
+    s->dst.optimize_coding = TRUE;
+    if (!progressive) {
+        jpeg_c_set_bool_param(&s->dst, JBOOLEAN_OPTIMIZE_SCANS, FALSE);
+        s->dst.scan_info = NULL;
+        s->dst.num_scans = 0;
+    }
+
+    jpeg_mem_dest(&s->dst, &s->buffer, &s->buffer_size);
+    jpeg_write_coefficients(&s->dst, dst_coefs);
+    jcopy_markers_execute(&s->src, &s->dst, JCOPYOPT_NONE);
+    jtransform_execute_transform(&s->src, &s->dst, src_coefs, &info);
+
+    jpeg_finish_compress(&s->dst);
+    jpeg_finish_decompress(&s->src);
+
+    *out = s->buffer;
+    *out_size = s->buffer_size;
+    s->buffer = NULL;
+    result = TI_JPEG_OK;
+
+done:
+    if (s->dst_created)
+        jpeg_destroy_compress(&s->dst);
+    if (s->src_created)
+        jpeg_destroy_decompress(&s->src);
+    /* jpeg_mem_dest reserva con malloc */
+    free(s->buffer);
+    free(s);
+    return result;
+}
+
+void ti_jpeg_free(unsigned char *buffer)
+{
+    free(buffer);
+}
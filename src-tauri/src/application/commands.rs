@@ -1,13 +1,34 @@
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tauri::{Emitter, State, Window};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tokio::sync::mpsc;
 
+use crate::application::config_loader::ConfigLoader;
 use crate::application::dto::{
-    BatchProcessRequest, ImageDto, ProcessedImageDto, ProcessingStatsDto, ProgressPayload,
+    merge_settings, BatchProcessRequest, BatchSummaryDto, DirectoryStatsDto, DiscoveryOptionsDto,
+    EstimateDto, HistogramDto, ImageDto, OptimizationOptionsDto, PaletteColorDto,
+    ProcessedBatchDto, ProcessedImageDto, ProcessingStatsDto, ProgressPayload, QueueStatusDto,
+    QueuedTaskDto, SuggestedSizeDto, TaskStatusDto, TransformationOptionsDto, ValidationResultDto,
 };
+use crate::application::error::{CommandError, CommandResult};
+use crate::application::progress_throttle::ProgressThrottle;
+use crate::application::report::BatchReport;
 use crate::application::state::AppState;
-use crate::domain::ImageProcessor;
+use crate::application::task_manager::{
+    PreflightResult, StatusListener, TaskId, TaskManager, WatchBatchError,
+};
+use crate::domain::error::DomainError;
+use crate::domain::{
+    Image, ImageFormat, ImageProcessor, LoadMode, ProcessingSettings, Transformation,
+};
 use crate::infrastructure::file_system::FileHandler;
-use crate::infrastructure::image_processor::{ImageProcessorImpl, ProgressCallback};
+use crate::infrastructure::image_processor::{
+    ColorPaletteExtractor, DuplicateDetector, ImageHistogram, ImageProcessorImpl, ImageValidator,
+    ProcessingResult, ProgressCallback, RawProcessor,
+};
+use crate::infrastructure::metadata_reader::MetadataReader;
 
 /// Test command - greet
 #[tauri::command]
@@ -17,24 +38,59 @@ pub fn greet(name: &str, _state: State<AppState>) -> String {
 
 /// Load image metadata from file path
 #[tauri::command]
-pub async fn load_image_info(path: String) -> Result<ImageDto, String> {
+pub async fn load_image_info(
+    path: String,
+    mode: Option<LoadMode>,
+    include_thumbnail: Option<bool>,
+) -> CommandResult<ImageDto> {
     let processor = ImageProcessorImpl::new();
+    let path = std::path::Path::new(&path);
     let image = processor
-        .load_image(std::path::Path::new(&path))
-        .map_err(|e| e.to_string())?;
+        .load_image_with_mode(path, mode.unwrap_or_default())
+        .map_err(|e| CommandError::from(e).with_path(path))?;
+
+    let dto = ImageDto::from(&image);
+    // Leer la miniatura cuesta otra lectura del archivo: solo si se pide
+    if include_thumbnail.unwrap_or(false) {
+        Ok(dto.with_thumbnail(embedded_thumbnail(path)))
+    } else {
+        Ok(dto)
+    }
+}
+
+/// Load image metadata plus its embedded JPEG thumbnail, for instant previews
+#[tauri::command]
+pub async fn load_image_info_with_thumbnail(path: String) -> CommandResult<ImageDto> {
+    load_image_info(path, None, Some(true)).await
+}
 
-    Ok(ImageDto::from(&image))
+/// JPEG thumbnail stored in the file: the preview of a RAW file, or the EXIF
+/// thumbnail of a JPEG/WebP
+fn embedded_thumbnail(path: &std::path::Path) -> Option<Vec<u8>> {
+    let is_raw = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(RawProcessor::is_raw_format);
+    if is_raw {
+        RawProcessor::extract_embedded_jpeg(path).ok()
+    } else {
+        MetadataReader::new().read_thumbnail_from_bytes(&std::fs::read(path).ok()?)
+    }
 }
 
 /// Load multiple images metadata
 #[tauri::command]
-pub async fn load_images_info(paths: Vec<String>) -> Result<Vec<ImageDto>, String> {
+pub async fn load_images_info(
+    paths: Vec<String>,
+    mode: Option<LoadMode>,
+) -> CommandResult<Vec<ImageDto>> {
     let processor = ImageProcessorImpl::new();
+    let mode = mode.unwrap_or_default();
     let mut images = Vec::new();
     let mut errors = Vec::new();
 
     for path in paths {
-        match processor.load_image(std::path::Path::new(&path)) {
+        match processor.load_image_with_mode(std::path::Path::new(&path), mode) {
             Ok(image) => images.push(ImageDto::from(&image)),
             Err(e) => {
                 let error_msg = format!("Failed to load {}: {}", path, e);
@@ -47,11 +103,14 @@ pub async fn load_images_info(paths: Vec<String>) -> Result<Vec<ImageDto>, Strin
 
     if images.is_empty() {
         if errors.is_empty() {
-            return Err("No valid images found".to_string());
+            return Err(CommandError::new(
+                CommandError::NO_IMAGES,
+                "No valid images found",
+            ));
         } else {
-            return Err(format!(
-                "No valid images found. Errors:\n{}",
-                errors.join("\n")
+            return Err(CommandError::new(
+                CommandError::NO_IMAGES,
+                format!("No valid images found. Errors:\n{}", errors.join("\n")),
             ));
         }
     }
@@ -59,16 +118,47 @@ pub async fn load_images_info(paths: Vec<String>) -> Result<Vec<ImageDto>, Strin
     Ok(images)
 }
 
+/// Embedded preview JPEG of a RAW file, without demosaicing the sensor data
+#[tauri::command]
+pub async fn load_raw_preview(path: String) -> CommandResult<Vec<u8>> {
+    let path = std::path::Path::new(&path);
+    RawProcessor::extract_embedded_jpeg(path).map_err(|e| CommandError::from(e).with_path(path))
+}
+
+/// Image count, size and dates of a folder without decoding anything, for a quick
+/// overview before a batch (`max_depth` as in `batch_process_folder`, None = unlimited)
+#[tauri::command]
+pub async fn get_directory_stats(
+    folder: String,
+    max_depth: Option<usize>,
+) -> CommandResult<DirectoryStatsDto> {
+    let folder = std::path::PathBuf::from(folder);
+    if !folder.is_dir() {
+        return Err(folder_not_found(&folder));
+    }
+
+    let stats = FileHandler::compute_directory_stats(&folder, max_depth);
+    Ok(DirectoryStatsDto::from(&stats))
+}
+
 /// Discover and load images from a directory
 #[tauri::command]
-pub async fn load_images_from_folder(folder_path: String) -> Result<Vec<ImageDto>, String> {
+pub async fn load_images_from_folder(
+    folder_path: String,
+    options: Option<DiscoveryOptionsDto>,
+) -> CommandResult<Vec<ImageDto>> {
     let processor = ImageProcessorImpl::new();
 
-    // Discover all image files in the folder
-    let image_paths = FileHandler::discover_images(std::path::Path::new(&folder_path));
+    // Discover the image files in the folder that pass the filters
+    let options = options.unwrap_or_default().to_options();
+    let image_paths =
+        FileHandler::discover_images_with_options(std::path::Path::new(&folder_path), &options)?;
 
     if image_paths.is_empty() {
-        return Err("No image files found in the selected folder".to_string());
+        return Err(CommandError::new(
+            CommandError::NO_IMAGES,
+            "No image files found in the selected folder",
+        ));
     }
 
     let mut images = Vec::new();
@@ -84,32 +174,274 @@ pub async fn load_images_from_folder(folder_path: String) -> Result<Vec<ImageDto
     }
 
     if images.is_empty() {
-        return Err("No valid images found in the folder".to_string());
+        return Err(CommandError::new(
+            CommandError::NO_IMAGES,
+            "No valid images found in the folder",
+        ));
     }
 
     Ok(images)
 }
 
-/// Process a batch of images
+/// Check image files for corruption (empty, truncated, wrong extension) before processing
+#[tauri::command]
+pub async fn validate_images(paths: Vec<String>) -> CommandResult<Vec<ValidationResultDto>> {
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let result = ImageValidator::validate(std::path::Path::new(&path));
+            ValidationResultDto::new(path, &result)
+        })
+        .collect())
+}
+
+/// Approximate disk space a batch will need, from image dimensions only (no encoding).
+/// Rough heuristics for typical photos, not guaranteed values.
+#[tauri::command]
+pub async fn estimate_batch_size(
+    paths: Vec<String>,
+    options: OptimizationOptionsDto,
+) -> CommandResult<EstimateDto> {
+    let settings = options.to_domain().map_err(CommandError::invalid_options)?;
+    let processor = ImageProcessorImpl::for_settings(&settings);
+
+    let mut per_image = Vec::with_capacity(paths.len());
+    for path in paths {
+        let estimate = processor
+            .load_image(std::path::Path::new(&path))
+            .and_then(|image| processor.estimate_output_size(&image, &settings));
+        match estimate {
+            Ok(bytes) => per_image.push((path, bytes)),
+            Err(e) => eprintln!("Failed to estimate {}: {}", path, e),
+        }
+    }
+
+    Ok(EstimateDto {
+        total_estimated_bytes: per_image.iter().map(|(_, bytes)| bytes).sum(),
+        per_image,
+    })
+}
+
+/// Dominant colors of an image, most common first (for swatches next to thumbnails)
+#[tauri::command]
+pub async fn extract_palette(path: String, num_colors: u8) -> CommandResult<Vec<PaletteColorDto>> {
+    let path = std::path::Path::new(&path);
+    let palette = ColorPaletteExtractor::extract_from_path(path, num_colors as usize)
+        .map_err(|e| CommandError::from(e).with_path(path))?;
+
+    Ok(palette.iter().map(PaletteColorDto::from).collect())
+}
+
+/// Standard output sizes an image can be reduced to without upscaling, for the
+/// size picker. The closest match is flagged as recommended
+#[tauri::command]
+pub async fn get_suggested_sizes(path: String) -> CommandResult<Vec<SuggestedSizeDto>> {
+    let path = std::path::Path::new(&path);
+    let image = ImageProcessorImpl::new()
+        .load_image(path)
+        .map_err(|e| CommandError::from(e).with_path(path))?;
+    let dimensions = image.dimensions();
+    let best = dimensions.best_fit_standard_size();
+
+    Ok(dimensions
+        .standard_sizes_within()
+        .into_iter()
+        .map(|size| SuggestedSizeDto::new(size, Some(size) == best))
+        .collect())
+}
+
+/// RGB and luma histograms of an image with clipping statistics
+#[tauri::command]
+pub async fn compute_histogram(path: String) -> CommandResult<HistogramDto> {
+    let path = std::path::Path::new(&path);
+    let histogram =
+        ImageHistogram::from_path(path).map_err(|e| CommandError::from(e).with_path(path))?;
+    Ok(HistogramDto::from(&histogram))
+}
+
+/// Process image bytes (e.g. pasted or received over the network) without writing
+/// any file; `format` is the input's, detected from its contents when omitted
+#[tauri::command]
+pub async fn process_image_bytes(
+    data: Vec<u8>,
+    format: Option<String>,
+    options: OptimizationOptionsDto,
+    transform: Option<TransformationOptionsDto>,
+) -> CommandResult<Vec<u8>> {
+    let format = format
+        .map(|format| format.parse::<ImageFormat>())
+        .transpose()?;
+    let settings = options.to_domain().map_err(CommandError::invalid_options)?;
+    let transformation = if let Some(trans_dto) = transform {
+        trans_dto
+            .to_domain()
+            .map_err(CommandError::invalid_options)?
+    } else {
+        None
+    };
+
+    Ok(
+        ImageProcessorImpl::for_settings(&settings).process_in_memory(
+            &data,
+            format,
+            transformation.as_ref(),
+            &settings,
+        )?,
+    )
+}
+
+/// Process a batch of images as a new task: queued behind the batches already
+/// running (unless the request has `priority`), and resolved once it is done.
+/// Its progress events carry the task id.
 #[tauri::command]
 pub async fn process_images(
     request: BatchProcessRequest,
     state: State<'_, AppState>,
     window: Window,
-) -> Result<Vec<ProcessedImageDto>, String> {
-    // Verificar que no haya una tarea corriendo
-    if state.task_manager.is_running().await {
-        return Err("A processing task is already 
-  running"
-            .to_string());
+) -> CommandResult<ProcessedBatchDto> {
+    let batch = prepare_batch(request, &state)?;
+    let task_id = state.task_manager.create_task(batch.priority);
+
+    let started = Instant::now();
+    let results = state
+        .task_manager
+        .process_images(
+            task_id,
+            batch.images,
+            batch.transformation,
+            batch.settings,
+            Some(progress_callback(
+                window.clone(),
+                batch.events_per_second,
+                task_id,
+            )),
+        )
+        .await
+        .map_err(CommandError::task)?;
+
+    let processed = finish_batch(&state, results, started);
+    emit_summary(&window, &state, task_id);
+    Ok(ProcessedBatchDto {
+        task_id: task_id.to_string(),
+        results: processed,
+    })
+}
+
+/// Queue a batch of images and return right away with its task id and the number
+/// of batches ahead of it. Its results arrive as "processing-result" events and its
+/// summary as "processing-complete", like `process_images`.
+#[tauri::command]
+pub async fn enqueue_images(
+    request: BatchProcessRequest,
+    state: State<'_, AppState>,
+    window: Window,
+) -> CommandResult<QueuedTaskDto> {
+    let batch = prepare_batch(request, &state)?;
+    let status = state.task_manager.queue_status().await;
+    let ahead = if batch.priority {
+        0
+    } else {
+        status.pending + status.active
+    };
+    let task_id = state.task_manager.create_task(batch.priority);
+
+    let task_manager = Arc::clone(&state.task_manager);
+    let app = window.app_handle().clone();
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let results = task_manager
+            .process_images(
+                task_id,
+                batch.images,
+                batch.transformation,
+                batch.settings,
+                Some(progress_callback(
+                    window.clone(),
+                    batch.events_per_second,
+                    task_id,
+                )),
+            )
+            .await;
+        match results {
+            Ok(results) => {
+                let state = app.state::<AppState>();
+                finish_batch(&state, results, started);
+                emit_summary(&window, &state, task_id);
+            }
+            Err(e) => eprintln!("Queued batch did not run: {}", e),
+        }
+    });
+
+    Ok(QueuedTaskDto {
+        task_id: task_id.to_string(),
+        ahead,
+    })
+}
+
+/// Batches waiting in the queue and running
+#[tauri::command]
+pub async fn get_queue_status(state: State<'_, AppState>) -> CommandResult<QueueStatusDto> {
+    let status = state.task_manager.queue_status().await;
+    Ok(QueueStatusDto::from(status))
+}
+
+/// Drop every queued batch; the running one goes on (see `cancel_processing`)
+#[tauri::command]
+pub async fn clear_queue(state: State<'_, AppState>) -> CommandResult<usize> {
+    Ok(state.task_manager.clear_queue())
+}
+
+/// A batch request resolved into images and settings, ready to run
+struct PreparedBatch {
+    images: Vec<Image>,
+    transformation: Option<Transformation>,
+    settings: ProcessingSettings,
+    events_per_second: u32,
+    priority: bool,
+}
+
+/// Convert a request, load its images and check there is room for the outputs
+fn prepare_batch(request: BatchProcessRequest, state: &AppState) -> CommandResult<PreparedBatch> {
+    // Convertir DTOs a domain models
+    let mut settings = request
+        .optimization_options
+        .to_domain()
+        .map_err(CommandError::invalid_options)?;
+    settings.set_dry_run(request.dry_run);
+
+    // Ajustes por imagen: se combinan con los del lote
+    if let Some(ref overrides) = request.per_image_overrides {
+        let merged = overrides
+            .iter()
+            .map(|(path, options)| {
+                merge_settings(&settings, options)
+                    .map(|merged| (std::path::PathBuf::from(path), merged))
+                    .map_err(|e| {
+                        CommandError::invalid_options(format!(
+                            "Invalid options for {}: {}",
+                            path, e
+                        ))
+                        .with_path(std::path::Path::new(path))
+                    })
+            })
+            .collect::<CommandResult<HashMap<_, _>>>()?;
+        settings.set_image_overrides(merged);
     }
 
-    // Cargar imágenes
-    let processor = ImageProcessorImpl::new();
+    let transformation = if let Some(trans_dto) = request.transformation_options {
+        trans_dto
+            .to_domain()
+            .map_err(CommandError::invalid_options)?
+    } else {
+        None
+    };
+
+    // Cargar imágenes con el mismo procesador que las procesará
+    let processor = state.task_manager.processor();
     let mut images = Vec::new();
 
     for path in request.image_paths {
-        match processor.load_image(std::path::Path::new(&path)) {
+        match processor.load_image_for_settings(std::path::Path::new(&path), &settings) {
             Ok(image) => images.push(image),
             Err(e) => {
                 eprintln!("Failed to load {}: {}", path, e);
@@ -118,83 +450,450 @@ pub async fn process_images(
     }
 
     if images.is_empty() {
-        return Err("No valid images to process".to_string());
+        return Err(CommandError::new(
+            CommandError::NO_IMAGES,
+            "No valid images to process",
+        ));
     }
 
-    // Convertir DTOs a domain models
-    let settings = request.optimization_options.to_domain()?;
+    // Descartar duplicados, conservando la primera imagen de cada grupo
+    if request.optimization_options.deduplicate {
+        let duplicates: HashSet<std::path::PathBuf> =
+            DuplicateDetector::find_duplicates(&images, DuplicateDetector::DEFAULT_THRESHOLD)
+                .iter()
+                .flat_map(|group| group.duplicates().iter().cloned())
+                .collect();
+        images.retain(|image| !duplicates.contains(image.path()));
+    }
 
-    let transformation = if let Some(trans_dto) = request.transformation_options {
-        trans_dto.to_domain()?
+    // Sin espacio para las salidas todo fallaría a mitad de lote: avisar antes
+    if let PreflightResult::InsufficientSpace { needed, available } =
+        TaskManager::preflight_check(&images, &settings)
+    {
+        return Err(insufficient_disk_space(needed, available));
+    }
+
+    Ok(PreparedBatch {
+        images,
+        transformation,
+        settings,
+        events_per_second: request
+            .max_progress_events_per_second
+            .unwrap_or(ProgressThrottle::DEFAULT_EVENTS_PER_SECOND),
+        priority: request.priority,
+    })
+}
+
+/// Outputs of `needed` bytes won't fit in the `available` ones (`PreflightResult`)
+fn insufficient_disk_space(needed: u64, available: u64) -> CommandError {
+    let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    CommandError::new(
+        CommandError::INSUFFICIENT_DISK_SPACE,
+        format!(
+            "Insufficient disk space: need {:.1} MB, have {:.1} MB",
+            mb(needed),
+            mb(available)
+        ),
+    )
+}
+
+/// Discover and process every image in a folder (Some(1) max depth = no subfolders)
+#[tauri::command]
+pub async fn batch_process_folder(
+    folder_path: String,
+    max_depth: Option<usize>,
+    optimization_options: OptimizationOptionsDto,
+    transformation_options: Option<TransformationOptionsDto>,
+    state: State<'_, AppState>,
+    window: Window,
+) -> CommandResult<Vec<ProcessedImageDto>> {
+    let folder = std::path::PathBuf::from(&folder_path);
+    if !folder.is_dir() {
+        return Err(folder_not_found(&folder));
+    }
+
+    // Convertir DTOs a domain models
+    let settings = optimization_options
+        .to_domain()
+        .map_err(CommandError::invalid_options)?;
+    let transformation = if let Some(trans_dto) = transformation_options {
+        trans_dto
+            .to_domain()
+            .map_err(CommandError::invalid_options)?
     } else {
         None
     };
 
-    // Crear callback de progreso
-    let progress_callback: ProgressCallback = Arc::new(move |current, total, file_name| {
-        let payload = ProgressPayload::new(current, total, file_name.to_string());
-
-        // Emitir evento de progreso
-        if let Err(e) = window.emit("processing-progress", &payload) {
-            eprintln!("Failed to emit progress: {}", e);
-        }
-    });
+    // Como en prepare_batch: comprobar el espacio antes de empezar
+    let paths = FileHandler::discover_images_with_depth(&folder, max_depth);
+    if let PreflightResult::InsufficientSpace { needed, available } =
+        state.task_manager.preflight_paths(&paths, &settings)
+    {
+        return Err(insufficient_disk_space(needed, available));
+    }
 
-    // Procesar imágenes
+    let task_id = state.task_manager.create_task(false);
+    let started = Instant::now();
     let results = state
         .task_manager
-        .process_images(images, transformation, settings, Some(progress_callback))
-        .await?;
+        .process_folder(
+            task_id,
+            folder,
+            max_depth,
+            transformation,
+            settings,
+            Some(progress_callback(
+                window.clone(),
+                ProgressThrottle::DEFAULT_EVENTS_PER_SECOND,
+                task_id,
+            )),
+        )
+        .await
+        .map_err(CommandError::task)?;
+
+    let processed = finish_batch(&state, results, started);
+    emit_summary(&window, &state, task_id);
+    Ok(processed)
+}
+
+/// Progress callback that emits a "processing-result" event per output of every
+/// finished image, and "processing-progress" (tagged with `task_id`) at most
+/// `events_per_second` times
+fn progress_callback(window: Window, events_per_second: u32, task_id: TaskId) -> ProgressCallback {
+    // Los workers terminan en paralelo: los eventos de una imagen salen juntos
+    let throttle = Arc::new(Mutex::new(ProgressThrottle::new(events_per_second)));
+    Arc::new(move |progress, results| {
+        let mut throttle = throttle.lock();
+
+        // Emitir evento de progreso (lotes enormes saturarían el webview)
+        if throttle.should_emit(Instant::now(), progress) {
+            let mut payload = ProgressPayload::from(progress);
+            payload.task_id = Some(task_id.to_string());
+            if let Err(e) = window.emit("processing-progress", &payload) {
+                eprintln!("Failed to emit progress: {}", e);
+            }
+        }
+
+        for result in results {
+            let dto = ProcessedImageDto::from(result.clone());
+            if let Err(e) = window.emit("processing-result", &dto) {
+                eprintln!("Failed to emit result: {}", e);
+            }
+        }
+    })
+}
 
+/// Emit "processing-complete" with the summary of task `task_id`, which just finished
+fn emit_summary(window: &Window, state: &AppState, task_id: TaskId) {
+    if let Some(summary) = state.task_manager.task_summary(task_id) {
+        let mut dto = BatchSummaryDto::from(&summary);
+        dto.task_id = Some(task_id.to_string());
+        if let Err(e) = window.emit("processing-complete", &dto) {
+            eprintln!("Failed to emit batch summary: {}", e);
+        }
+    }
+}
+
+/// Update stats, store the batch report and convert results to DTOs
+fn finish_batch(
+    state: &AppState,
+    results: Vec<ProcessingResult>,
+    started: Instant,
+) -> Vec<ProcessedImageDto> {
     // Actualizar estadísticas
+    let format_of = |path: &std::path::Path| {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ImageFormat::from_extension(ext).ok())
+    };
     for result in &results {
-        if result.success {
-            state.update_stats(result.bytes_saved());
+        // Ni una simulación ni las salidas ya al día cuentan en las estadísticas
+        if result.success && !result.dry_run && !result.unchanged {
+            let formats = format_of(&result.original_path).zip(format_of(&result.output_path));
+            state.update_stats(
+                result.bytes_saved(),
+                formats,
+                (result.original_size, result.output_size),
+            );
         }
     }
 
+    // Guardar reporte del batch
+    state.set_last_report(BatchReport::from_results(&results, started.elapsed()));
+
     // Convertir resultados a DTOs
-    Ok(results.into_iter().map(ProcessedImageDto::from).collect())
+    results.into_iter().map(ProcessedImageDto::from).collect()
 }
 
-/// Cancel current processing operation
+/// Paths a folder watch can queue before the watcher waits for processing to catch up
+const WATCH_QUEUE_SIZE: usize = 256;
+
+/// Watch a folder (not its subfolders) and process images as they arrive, e.g. when
+/// shooting tethered. Results are emitted as "processing-result" events like any
+/// batch, and each batch emits "watch-processed" with all of them. Drops arriving
+/// while another batch runs are queued. Replaces any previous watch.
 #[tauri::command]
-pub async fn cancel_processing(state: State<'_, AppState>) -> Result<(), String> {
-    state.task_manager.cancel().await;
+pub async fn start_folder_watch(
+    folder: String,
+    optimization_options: OptimizationOptionsDto,
+    transformation_options: Option<TransformationOptionsDto>,
+    state: State<'_, AppState>,
+    window: Window,
+) -> CommandResult<()> {
+    let dir = std::path::PathBuf::from(&folder);
+    if !dir.is_dir() {
+        return Err(folder_not_found(&dir));
+    }
+
+    let mut settings = optimization_options
+        .to_domain()
+        .map_err(CommandError::invalid_options)?;
+    let transformation = if let Some(trans_dto) = transformation_options {
+        trans_dto
+            .to_domain()
+            .map_err(CommandError::invalid_options)?
+    } else {
+        None
+    };
+    // Cada lote trae pocas imágenes: la estructura se replica desde la carpeta vigilada
+    if settings.preserve_directory_structure() && settings.source_base().is_none() {
+        settings.set_source_base(Some(dir.clone()));
+    }
+
+    // Las salidas escritas en la carpeta vigilada se volverían a procesar
+    let canonical = |path: &std::path::Path| path.canonicalize().unwrap_or(path.to_path_buf());
+    if settings.in_place() || canonical(settings.output_directory()) == canonical(&dir) {
+        return Err(CommandError::new(
+            CommandError::INVALID_OPTIONS,
+            "The output folder must be different from the watched folder",
+        ));
+    }
+
+    let (sender, receiver) = mpsc::channel(WATCH_QUEUE_SIZE);
+    let app = window.app_handle().clone();
+    let watch_app = app.clone();
+    let watch_dir = dir.clone();
+    let handle = FileHandler::watch(&dir, move |event| match event {
+        // Hilo propio de notify: puede bloquear si la cola está llena
+        Ok(path) => {
+            let _ = sender.blocking_send(path);
+        }
+        Err(e) => emit_watch_error(&watch_app, CommandError::from(e).with_path(&watch_dir)),
+    })
+    .map_err(|e| CommandError::from(e).with_path(&dir))?;
+
+    // Al soltar la vigilancia anterior se cierra su cola y termina su tarea
+    *state.folder_watch.lock() = Some(handle);
+
+    let task_manager = Arc::clone(&state.task_manager);
+    tokio::spawn(async move {
+        task_manager
+            .process_watched(
+                receiver,
+                transformation,
+                settings,
+                |task_id| {
+                    Some(progress_callback(
+                        window.clone(),
+                        ProgressThrottle::DEFAULT_EVENTS_PER_SECOND,
+                        task_id,
+                    ))
+                },
+                move |results, started| match results {
+                    Ok(results) => {
+                        let state = app.state::<AppState>();
+                        let processed = finish_batch(&state, results, started);
+                        if let Err(e) = app.emit("watch-processed", &processed) {
+                            eprintln!("Failed to emit watch results: {}", e);
+                        }
+                    }
+                    Err(WatchBatchError::InsufficientSpace { needed, available }) => {
+                        emit_watch_error(&app, insufficient_disk_space(needed, available))
+                    }
+                    Err(WatchBatchError::Task(e)) => emit_watch_error(&app, CommandError::task(e)),
+                },
+            )
+            .await;
+    });
+
     Ok(())
 }
 
-/// Get current processing status
+/// Tell the frontend that the folder watch (or one of its batches) failed
+fn emit_watch_error(app: &AppHandle, error: CommandError) {
+    if let Err(e) = app.emit("watch-error", &error) {
+        eprintln!("Failed to emit watch error: {}", e);
+    }
+}
+
+/// Stop the active folder watch; batches already queued still finish
 #[tauri::command]
-pub async fn get_processing_status(state: State<'_, AppState>) -> Result<String, String> {
-    let status = state.task_manager.get_status().await;
-    Ok(format!("{:?}", status))
+pub async fn stop_folder_watch(state: State<'_, AppState>) -> CommandResult<()> {
+    state.folder_watch.lock().take();
+    Ok(())
+}
+
+/// Cancel a processing task (a queued one never starts), or every running task
+/// when no id is given
+#[tauri::command]
+pub async fn cancel_processing(
+    task_id: Option<String>,
+    state: State<'_, AppState>,
+) -> CommandResult<()> {
+    let task_id = task_id.as_deref().map(parse_task_id).transpose()?;
+    state
+        .task_manager
+        .cancel(task_id)
+        .await
+        .map_err(CommandError::task)
+}
+
+/// Skip one image of a running task (the latest one when no id is given), by its
+/// input path (`originalPath` of its results)
+#[tauri::command]
+pub async fn cancel_single_image(
+    path: String,
+    task_id: Option<String>,
+    state: State<'_, AppState>,
+) -> CommandResult<()> {
+    let task_id = task_id.as_deref().map(parse_task_id).transpose()?;
+    state
+        .task_manager
+        .cancel_image(task_id, std::path::Path::new(&path))
+        .await
+        .map_err(CommandError::task)
+}
+
+/// Get the status of a processing task, or of the latest one when no id is given
+#[tauri::command]
+pub async fn get_processing_status(
+    task_id: Option<String>,
+    state: State<'_, AppState>,
+) -> CommandResult<TaskStatusDto> {
+    let snapshot = match task_id {
+        Some(id) => Some(
+            state
+                .task_manager
+                .task_snapshot(parse_task_id(&id)?)
+                .ok_or_else(|| {
+                    CommandError::new(CommandError::UNKNOWN_TASK, format!("Unknown task {}", id))
+                })?,
+        ),
+        None => state.task_manager.latest_snapshot(),
+    };
+    Ok(snapshot
+        .as_ref()
+        .map_or_else(TaskStatusDto::idle, TaskStatusDto::from))
+}
+
+/// Status listener that emits "status-changed" with every status change of a task
+pub fn status_listener(app: AppHandle) -> StatusListener {
+    Arc::new(move |snapshot| {
+        if let Err(e) = app.emit("status-changed", &TaskStatusDto::from(snapshot)) {
+            eprintln!("Failed to emit status change: {}", e);
+        }
+    })
+}
+
+fn parse_task_id(id: &str) -> CommandResult<TaskId> {
+    TaskId::parse_str(id).map_err(|e| {
+        CommandError::new(
+            CommandError::UNKNOWN_TASK,
+            format!("Invalid task id {}: {}", id, e),
+        )
+    })
+}
+
+fn folder_not_found(folder: &std::path::Path) -> CommandError {
+    CommandError::from(DomainError::FileNotFound(format!(
+        "Folder not found: {}",
+        folder.display()
+    )))
+    .with_path(folder)
 }
 
 /// Check if processing is running
 #[tauri::command]
-pub async fn is_processing(state: State<'_, AppState>) -> Result<bool, String> {
+pub async fn is_processing(state: State<'_, AppState>) -> CommandResult<bool> {
     Ok(state.task_manager.is_running().await)
 }
 
 /// Get processing statistics
 #[tauri::command]
-pub async fn get_stats(state: State<'_, AppState>) -> Result<ProcessingStatsDto, String> {
-    let stats = state.get_stats();
-    Ok(ProcessingStatsDto {
-        total_processed: stats.total_processed,
-        total_saved_bytes: stats.total_saved_bytes,
-        average_savings: stats.average_savings(),
-    })
+pub async fn get_stats(state: State<'_, AppState>) -> CommandResult<ProcessingStatsDto> {
+    Ok(ProcessingStatsDto::from(&state.get_stats()))
 }
 
 /// Reset processing statistics
 #[tauri::command]
-pub async fn reset_stats(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn reset_stats(state: State<'_, AppState>) -> CommandResult<()> {
     state.reset_stats();
     Ok(())
 }
 
+/// Get the report of the last completed batch
+#[tauri::command]
+pub async fn get_last_report(state: State<'_, AppState>) -> CommandResult<Option<BatchReport>> {
+    Ok(state.get_last_report())
+}
+
+/// Get the summary of the last completed batch
+#[tauri::command]
+pub async fn get_last_summary(
+    state: State<'_, AppState>,
+) -> CommandResult<Option<BatchSummaryDto>> {
+    Ok(state
+        .task_manager
+        .get_last_summary()
+        .as_ref()
+        .map(BatchSummaryDto::from))
+}
+
+/// Export the report of the last completed batch as JSON
+#[tauri::command]
+pub async fn export_report(path: String, state: State<'_, AppState>) -> CommandResult<()> {
+    let path = std::path::Path::new(&path);
+    let report = state
+        .get_last_report()
+        .ok_or_else(|| CommandError::task("No batch report available".to_string()))?;
+    report
+        .export_json(path)
+        .map_err(|e| CommandError::task(e).with_path(path))
+}
+
+/// Export every result of the last completed batch, its summary and its settings:
+/// CSV for a `.csv` path, JSON otherwise
+#[tauri::command]
+pub async fn export_last_report(path: String, state: State<'_, AppState>) -> CommandResult<()> {
+    let path = std::path::Path::new(&path);
+    state
+        .task_manager
+        .export_last_report(path)
+        .map_err(|e| CommandError::task(e).with_path(path))
+}
+
+/// Names of the saved settings presets
+#[tauri::command]
+pub async fn list_profiles() -> CommandResult<Vec<String>> {
+    Ok(ConfigLoader::new().and_then(|loader| loader.list_profiles())?)
+}
+
+/// Load a saved settings preset, every option filled in
+#[tauri::command]
+pub async fn load_profile(name: String) -> CommandResult<OptimizationOptionsDto> {
+    let settings = ConfigLoader::new().and_then(|loader| loader.load_profile(&name))?;
+    Ok(OptimizationOptionsDto::from(&settings))
+}
+
+/// Save options as a named settings preset (replaces one with the same name)
+#[tauri::command]
+pub async fn save_profile(name: String, settings: OptimizationOptionsDto) -> CommandResult<()> {
+    let settings = settings
+        .to_domain()
+        .map_err(CommandError::invalid_options)?;
+    Ok(ConfigLoader::new().and_then(|loader| loader.save_profile(&name, &settings))?)
+}
+
 /// Get optimal thread count for processing
 #[tauri::command]
 pub fn get_optimal_threads() -> usize {
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::domain::ProcessingSettings;
+
+/// Errors reading or writing settings presets
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("No configuration directory available on this system")]
+    NoConfigDir,
+
+    #[error("Invalid profile name: '{0}' (letters, digits, spaces, '-' and '_' only)")]
+    InvalidName(String),
+
+    #[error("Profile not found: {0}")]
+    NotFound(String),
+
+    #[error("Failed to access profile: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid profile file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Failed to serialize profile: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+impl ConfigError {
+    /// Stable machine-readable name of the variant (see `DomainError::code`)
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConfigError::NoConfigDir => "no_config_dir",
+            ConfigError::InvalidName(_) => "invalid_profile_name",
+            ConfigError::NotFound(_) => "profile_not_found",
+            ConfigError::Io(_) => "io_error",
+            ConfigError::Parse(_) => "invalid_profile",
+            ConfigError::Serialize(_) => "serialize_error",
+        }
+    }
+}
+
+/// Named `ProcessingSettings` presets stored as `<name>.toml` files
+pub struct ConfigLoader {
+    dir: PathBuf,
+}
+
+impl ConfigLoader {
+    /// Presets in the platform config directory
+    /// (`~/.config/transform_images` on Linux, `Application Support` on macOS, `AppData` on Windows)
+    pub fn new() -> Result<Self, ConfigError> {
+        let dir = dirs::config_dir().ok_or(ConfigError::NoConfigDir)?;
+        Ok(Self::with_dir(dir.join("transform_images")))
+    }
+
+    /// Presets in another directory
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Load the preset saved as `name`
+    pub fn load_profile(&self, name: &str) -> Result<ProcessingSettings, ConfigError> {
+        let path = self.profile_path(name)?;
+        if !path.exists() {
+            return Err(ConfigError::NotFound(name.to_string()));
+        }
+        Ok(ProcessingSettings::from_toml(&fs::read_to_string(path)?)?)
+    }
+
+    /// Save `settings` as `name`, replacing any preset with that name
+    pub fn save_profile(
+        &self,
+        name: &str,
+        settings: &ProcessingSettings,
+    ) -> Result<(), ConfigError> {
+        let path = self.profile_path(name)?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, settings.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Names of the saved presets, sorted
+    pub fn list_profiles(&self) -> Result<Vec<String>, ConfigError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut names: Vec<String> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// File of a preset; names can't contain separators or dots (no paths outside `dir`)
+    fn profile_path(&self, name: &str) -> Result<PathBuf, ConfigError> {
+        let valid = !name.trim().is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+        if !valid {
+            return Err(ConfigError::InvalidName(name.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.toml", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ProcessingProfile;
+    use crate::domain::Quality;
+
+    #[test]
+    fn test_save_load_and_list_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ConfigLoader::with_dir(dir.path().join("presets"));
+        assert!(loader.list_profiles().unwrap().is_empty());
+
+        let mut web = ProcessingSettings::from_profile(ProcessingProfile::Web);
+        web.set_quality(Quality::new(72).unwrap());
+        loader.save_profile("Blog web", &web).unwrap();
+        loader
+            .save_profile("archive", &ProcessingSettings::default())
+            .unwrap();
+
+        assert_eq!(loader.list_profiles().unwrap(), vec!["Blog web", "archive"]);
+        let loaded = loader.load_profile("Blog web").unwrap();
+        assert_eq!(loaded.quality().value(), 72);
+        assert_eq!(loaded.max_long_edge(), web.max_long_edge());
+
+        assert!(matches!(
+            loader.load_profile("missing"),
+            Err(ConfigError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_names_outside_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ConfigLoader::with_dir(dir.path().to_path_buf());
+        for name in ["", "../evil", "a/b", "web.toml"] {
+            assert!(matches!(
+                loader.save_profile(name, &ProcessingSettings::default()),
+                Err(ConfigError::InvalidName(_))
+            ));
+        }
+
+        std::fs::write(dir.path().join("broken.toml"), "quality = [").unwrap();
+        assert!(matches!(
+            loader.load_profile("broken"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
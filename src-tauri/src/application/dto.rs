@@ -1,9 +1,25 @@
+use base64::prelude::{Engine, BASE64_STANDARD};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::domain::models::{ResizeFilter, ResizeTransformation, Rotation};
-use crate::domain::{Dimensions, Image, ImageFormat, ProcessingSettings, Quality, RawQualityMode, Transformation};
-use crate::infrastructure::image_processor::ProcessingResult;
+use crate::application::state::ProcessingStats;
+use crate::application::task_manager::{BatchSummary, QueueStatus, TaskSnapshot, TaskStatus};
+use crate::domain::models::{
+    ArbitraryRotation, Channel, CollisionStrategy, ColorAdjustment, FontSize, HighlightMode,
+    ImageMetadata, PartialProcessingSettings, PngBitDepth, PostAction, ProcessingProfile,
+    RenameMode, ResizeFilter, ResizeTransformation, Rotation, SharpenOptions, TextWatermark,
+    TonalAdjustments, Watermark, WatermarkPosition, WhiteBalance,
+};
+use crate::domain::{
+    ChromaSubsampling, Dimensions, DomainError, Image, ImageFormat, OutputMode, ProcessingSettings,
+    Quality, RawProcessingParams, RawQualityMode, SkipStrategy, StandardSize, Transformation,
+};
+use crate::infrastructure::file_system::{DirectoryStats, DiscoveryOptions};
+use crate::infrastructure::image_processor::{
+    BatchProgress, ImageHistogram, PaletteColor, PhaseTimings, ProcessingResult, ValidationResult,
+};
 
 /// Data Transfer Objects for frontend-backend communication
 
@@ -15,6 +31,21 @@ pub struct ImageDto {
     pub width: u32,
     pub height: u32,
     pub size_bytes: u64,
+    /// Format the file extension claims, when the contents are actually `format`
+    pub extension_format: Option<String>,
+    /// EXIF, IPTC and XMP fields found in the file
+    pub metadata: Option<ImageMetadataDto>,
+    /// Base64 of the JPEG thumbnail embedded in the file (RAW preview or EXIF
+    /// thumbnail); only filled in when requested
+    pub thumbnail_data: Option<String>,
+}
+
+impl ImageDto {
+    /// Attach an embedded JPEG thumbnail
+    pub fn with_thumbnail(mut self, thumbnail: Option<Vec<u8>>) -> Self {
+        self.thumbnail_data = thumbnail.map(|jpeg| BASE64_STANDARD.encode(jpeg));
+        self
+    }
 }
 
 impl From<&Image> for ImageDto {
@@ -25,6 +56,53 @@ impl From<&Image> for ImageDto {
             width: image.dimensions().width(),
             height: image.dimensions().height(),
             size_bytes: image.size_bytes(),
+            extension_format: image.extension_format().map(|format| format.to_string()),
+            metadata: image.metadata().map(ImageMetadataDto::from),
+            thumbnail_data: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageMetadataDto {
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub date_time: Option<String>,
+    pub iso_speed: Option<u32>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub focal_length: Option<f64>,
+    /// [latitude, longitude]
+    pub gps_coordinates: Option<(f64, f64)>,
+    pub orientation: Option<u32>,
+    pub iptc_keywords: Option<Vec<String>>,
+    pub iptc_copyright: Option<String>,
+    pub iptc_caption: Option<String>,
+    /// Star rating 0-5
+    pub xmp_rating: Option<u8>,
+    pub xmp_label: Option<String>,
+    pub xmp_title: Option<String>,
+}
+
+impl From<&ImageMetadata> for ImageMetadataDto {
+    fn from(metadata: &ImageMetadata) -> Self {
+        ImageMetadataDto {
+            camera_make: metadata.camera_make.clone(),
+            camera_model: metadata.camera_model.clone(),
+            date_time: metadata.date_time.clone(),
+            iso_speed: metadata.iso_speed,
+            exposure_time: metadata.exposure_time.clone(),
+            f_number: metadata.f_number,
+            focal_length: metadata.focal_length,
+            gps_coordinates: metadata.gps_coordinates,
+            orientation: metadata.orientation,
+            iptc_keywords: metadata.iptc_keywords.clone(),
+            iptc_copyright: metadata.iptc_copyright.clone(),
+            iptc_caption: metadata.iptc_caption.clone(),
+            xmp_rating: metadata.xmp_rating,
+            xmp_label: metadata.xmp_label.clone(),
+            xmp_title: metadata.xmp_title.clone(),
         }
     }
 }
@@ -32,65 +110,705 @@ impl From<&Image> for ImageDto {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OptimizationOptionsDto {
-    pub quality: u8,
+    /// "web" | "print" | "thumbnail" | "archive" | "custom": seeds the settings,
+    /// explicit fields below override it
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Quality 1-100 (default: the profile's, else 85, or `Quality::recommended_for` the
+    /// output format when converting)
+    #[serde(default)]
+    pub quality: Option<u8>,
     pub output_format: Option<String>,
+    /// Several output formats in one pass (overrides `output_format`)
+    #[serde(default)]
+    pub output_formats: Option<Vec<String>>,
+    /// Empty = next to each input. Ignored in per-image overrides, like
+    /// `overwrite_existing`, `deduplicate` and zip fields
+    #[serde(default)]
     pub output_directory: String,
-    pub preserve_metadata: bool,
+    /// Mirror the inputs' folders under the output directory (default false)
+    #[serde(default)]
+    pub preserve_directory_structure: bool,
+    /// Folder the mirrored structure starts at (default: the deepest folder that
+    /// contains every input)
+    #[serde(default)]
+    pub source_base: Option<String>,
+    /// Keep EXIF metadata (default: the profile's, else false)
+    #[serde(default)]
+    pub preserve_metadata: Option<bool>,
+    #[serde(default)]
     pub overwrite_existing: bool,
+    /// Copy an original to `<name>.orig.<ext>` before an output overwrites it (default false)
+    #[serde(default)]
+    pub create_backup: bool,
+    /// Skip images whose outputs are newer than them and were made with the same
+    /// settings (recorded in a manifest in the output folder); default false
+    #[serde(default)]
+    pub skip_unchanged: bool,
+    /// Done to each original once all its outputs are written: "keep" (default) | "trash"
+    /// | "delete" | "move" (into `post_action_directory`)
+    #[serde(default)]
+    pub post_action: Option<String>,
+    #[serde(default)]
+    pub post_action_directory: Option<String>,
+    /// Write a report of every result, the summary and the settings here once the
+    /// batch is done: CSV for a `.csv` path, JSON otherwise
+    #[serde(default)]
+    pub write_report: Option<String>,
+    /// When not overwriting: "error" (default) | "skip" | "rename" (_1, _2...) | "timestamp"
+    /// | "numbered" (" (1)", " (2)"...)
+    #[serde(default)]
+    pub collision_strategy: Option<String>,
     pub raw_quality_mode: Option<String>,
+    /// Minimum fraction saved (0.0-1.0) for the output to be written
+    #[serde(default)]
+    pub min_compression_ratio: Option<f64>,
+    /// "skip" | "copyOriginal" | "saveAnyway"
+    #[serde(default)]
+    pub on_no_improvement: Option<String>,
+    /// Don't keep outputs larger than the original: copy it ("copyOriginal") or skip
+    #[serde(default)]
+    pub skip_if_larger: Option<bool>,
+    /// Rotate (90/180/270) and flip JPEGs without re-encoding (default true)
+    #[serde(default)]
+    pub use_lossless_rotation: Option<bool>,
+    /// RAW development parameters (None = camera defaults)
+    #[serde(default)]
+    pub raw_processing: Option<RawProcessingOptionsDto>,
+    /// Image to decode from HEIC containers (0 = primary)
+    #[serde(default)]
+    pub heic_image_index: Option<usize>,
+    /// Skip near-identical images (perceptual hash), keeping the first of each group
+    #[serde(default)]
+    pub deduplicate: bool,
+    /// Apply the EXIF orientation tag on load (default true)
+    #[serde(default)]
+    pub auto_orient: Option<bool>,
+    /// Keep decoded images in memory for later batches (default false)
+    #[serde(default)]
+    pub use_cache: Option<bool>,
+    /// Write the whole batch into this .zip instead of loose files
+    #[serde(default)]
+    pub zip_path: Option<String>,
+    /// Zip entries use only the file name (default true)
+    #[serde(default)]
+    pub flatten_zip: Option<bool>,
+    /// Responsive widths (e.g. [320, 640, 1280]); one output per width
+    #[serde(default)]
+    pub sizes: Option<Vec<u32>>,
+    /// File name suffix for sized outputs, `{width}` is replaced (default "-{width}w")
+    #[serde(default)]
+    pub size_suffix: Option<String>,
+    /// "#RRGGBB" background for transparent images saved as JPEG (default white)
+    #[serde(default)]
+    pub background_color: Option<String>,
+    /// Maximum output size in bytes; JPEG/WebP lower the quality to fit it
+    #[serde(default)]
+    pub target_size_bytes: Option<u64>,
+    /// Hard limit in bytes for lossy WebP outputs: the quality is searched to fit it,
+    /// and images that don't fit even at the lowest quality fail
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    /// Quantize PNG outputs to a 256-color palette (lossy, default false)
+    #[serde(default)]
+    pub png_lossy: Option<bool>,
+    /// Scale 16-bit PNG sources down to 8 bits per channel in PNG outputs (default false)
+    #[serde(default)]
+    pub png_force_8bit: Option<bool>,
+    /// Downscale outputs whose longest side exceeds this many pixels
+    #[serde(default)]
+    pub max_long_edge: Option<u32>,
+    /// Progressive JPEG (default true); false writes baseline JPEGs
+    #[serde(default, alias = "jpegProgressive")]
+    pub progressive: Option<bool>,
+    /// JPEG chroma subsampling: "420" | "422" | "444" | "auto" (default: 4:4:4 from
+    /// quality 90, 4:2:0 below)
+    #[serde(default)]
+    pub jpeg_chroma: Option<String>,
+    /// Optimize JPEG Huffman tables and progressive scans per image (default true);
+    /// false uses the standard tables, faster but larger
+    #[serde(default)]
+    pub jpeg_optimize_huffman: Option<bool>,
+    /// Only lossless outputs (WebP lossless, unquantized PNG); JPEG outputs are refused
+    /// and inputs without a lossless format of their own default to PNG
+    #[serde(default)]
+    pub lossless: Option<bool>,
+    /// Retries when reading an input fails, e.g. on a busy network share (default 0).
+    /// Decode errors are never retried
+    #[serde(default, alias = "retryCount")]
+    pub max_retries: Option<u32>,
+    /// Milliseconds to wait between those retries (default 0)
+    #[serde(default)]
+    pub retry_delay_ms: Option<u64>,
+    /// Report decode/transform/encode/write times for every output (default false)
+    #[serde(default)]
+    pub phase_timings: Option<bool>,
+    /// Images processed at the same time (default: one per CPU core)
+    #[serde(default)]
+    pub max_workers: Option<usize>,
+    /// Stop the batch after this many images fail in a row (default: never)
+    #[serde(default)]
+    pub abort_after_failures: Option<usize>,
 }
 
 impl OptimizationOptionsDto {
     /// Convert DTO to domain ProcessingSettings
     pub fn to_domain(&self) -> Result<ProcessingSettings, String> {
-        let quality = Quality::new(self.quality).map_err(|e| e.to_string())?;
+        let mut settings = ProcessingSettings::from_profile(self.profile()?);
+        settings
+            .set_output_directory(PathBuf::from(&self.output_directory))
+            .set_preserve_directory_structure(self.preserve_directory_structure)
+            .set_source_base(self.source_base.as_ref().map(PathBuf::from))
+            .set_overwrite_existing(self.overwrite_existing)
+            .set_create_backup(self.create_backup)
+            .set_skip_unchanged(self.skip_unchanged)
+            .set_write_report(self.write_report.as_ref().map(PathBuf::from))
+            .set_flatten_zip(self.flatten_zip.unwrap_or(true))
+            .set_max_workers(self.max_workers);
 
-        let output_format = if let Some(ref fmt) = self.output_format {
-            Some(ImageFormat::from_extension(fmt).map_err(|e| e.to_string())?)
-        } else {
-            None
-        };
+        if self.max_workers == Some(0) {
+            return Err("Max workers must be greater than 0".to_string());
+        }
+
+        if let Some(ref zip_path) = self.zip_path {
+            settings.set_output_mode(OutputMode::Zip(PathBuf::from(zip_path)));
+        }
+        settings.set_post_action(self.post_action()?);
+
+        let settings = settings.merge(&PartialProcessingSettings::try_from(self.clone())?);
+        validate_merged(&settings)?;
+        Ok(settings)
+    }
+
+    fn profile(&self) -> Result<ProcessingProfile, String> {
+        match self.profile.as_deref() {
+            None | Some("custom") => Ok(ProcessingProfile::Custom),
+            Some("web") => Ok(ProcessingProfile::Web),
+            Some("print") => Ok(ProcessingProfile::Print),
+            Some("thumbnail") => Ok(ProcessingProfile::Thumbnail),
+            Some("archive") => Ok(ProcessingProfile::Archive),
+            Some(other) => Err(format!("Unknown processing profile: '{}'", other)),
+        }
+    }
+
+    fn post_action(&self) -> Result<PostAction, String> {
+        match self.post_action.as_deref() {
+            None | Some("keep") => Ok(PostAction::Keep),
+            Some("trash") => Ok(PostAction::MoveToTrash),
+            Some("delete") => Ok(PostAction::Delete),
+            Some("move") => match self.post_action_directory.as_deref() {
+                Some(directory) if !directory.is_empty() => {
+                    Ok(PostAction::MoveTo(PathBuf::from(directory)))
+                }
+                _ => Err("Post action 'move' needs a postActionDirectory".to_string()),
+            },
+            Some(other) => Err(format!("Unknown post action: '{}'", other)),
+        }
+    }
+}
+
+/// Every setting spelled out, e.g. to show a saved preset in the UI. `to_domain` on the
+/// result gives the same settings back (except per-image overrides).
+impl From<&ProcessingSettings> for OptimizationOptionsDto {
+    fn from(settings: &ProcessingSettings) -> Self {
+        let [r, g, b] = settings.background_color();
+        OptimizationOptionsDto {
+            profile: None,
+            quality: settings.explicit_quality().map(|quality| quality.value()),
+            output_format: None,
+            output_formats: Some(
+                settings
+                    .output_formats()
+                    .iter()
+                    .map(|format| format.to_string())
+                    .collect(),
+            ),
+            output_directory: settings.output_directory().to_string_lossy().to_string(),
+            preserve_directory_structure: settings.preserve_directory_structure(),
+            source_base: settings
+                .source_base()
+                .map(|path| path.to_string_lossy().to_string()),
+            preserve_metadata: Some(settings.preserve_metadata()),
+            overwrite_existing: settings.overwrite_existing(),
+            create_backup: settings.create_backup(),
+            skip_unchanged: settings.skip_unchanged(),
+            post_action: Some(
+                match settings.post_action() {
+                    PostAction::Keep => "keep",
+                    PostAction::MoveToTrash => "trash",
+                    PostAction::Delete => "delete",
+                    PostAction::MoveTo(_) => "move",
+                }
+                .to_string(),
+            ),
+            post_action_directory: match settings.post_action() {
+                PostAction::MoveTo(directory) => Some(directory.to_string_lossy().to_string()),
+                _ => None,
+            },
+            write_report: settings
+                .write_report()
+                .map(|path| path.to_string_lossy().to_string()),
+            collision_strategy: Some(
+                match settings.collision_strategy() {
+                    CollisionStrategy::Error => "error",
+                    CollisionStrategy::Skip => "skip",
+                    CollisionStrategy::Rename(RenameMode::Sequential) => "rename",
+                    CollisionStrategy::Rename(RenameMode::Timestamp) => "timestamp",
+                    CollisionStrategy::Rename(RenameMode::Numbered) => "numbered",
+                }
+                .to_string(),
+            ),
+            raw_quality_mode: Some(
+                match settings.raw_quality_mode() {
+                    RawQualityMode::Thumbnail => "thumbnail",
+                    RawQualityMode::Fast => "fast",
+                    RawQualityMode::Balanced => "balanced",
+                    RawQualityMode::Quality => "quality",
+                }
+                .to_string(),
+            ),
+            min_compression_ratio: settings.min_compression_ratio(),
+            on_no_improvement: Some(
+                match settings.on_no_improvement() {
+                    SkipStrategy::Skip => "skip",
+                    SkipStrategy::CopyOriginal => "copyOriginal",
+                    SkipStrategy::SaveAnyway => "saveAnyway",
+                }
+                .to_string(),
+            ),
+            skip_if_larger: Some(settings.skip_if_larger()),
+            use_lossless_rotation: Some(settings.use_lossless_rotation()),
+            raw_processing: Some(RawProcessingOptionsDto::from(settings.raw_params())),
+            heic_image_index: Some(settings.heic_image_index()),
+            deduplicate: false,
+            auto_orient: Some(settings.auto_orient()),
+            use_cache: Some(settings.use_cache()),
+            zip_path: match settings.output_mode() {
+                OutputMode::Zip(path) => Some(path.to_string_lossy().to_string()),
+                OutputMode::Files => None,
+            },
+            flatten_zip: Some(settings.flatten_zip()),
+            sizes: Some(settings.sizes().to_vec()),
+            size_suffix: Some(settings.size_suffix_template().to_string()),
+            background_color: Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+            target_size_bytes: settings.target_size_bytes(),
+            max_output_bytes: settings.max_output_bytes(),
+            png_lossy: Some(settings.png_lossy()),
+            png_force_8bit: Some(settings.png_bit_depth() == PngBitDepth::Force8bit),
+            max_long_edge: settings.max_long_edge(),
+            progressive: Some(settings.progressive()),
+            jpeg_chroma: Some(
+                match settings.jpeg_chroma() {
+                    ChromaSubsampling::S420 => "420",
+                    ChromaSubsampling::S422 => "422",
+                    ChromaSubsampling::S444 => "444",
+                    ChromaSubsampling::Auto => "auto",
+                }
+                .to_string(),
+            ),
+            jpeg_optimize_huffman: Some(settings.jpeg_optimize_huffman()),
+            lossless: Some(settings.lossless()),
+            max_retries: Some(settings.max_retries()),
+            retry_delay_ms: Some(settings.retry_delay_ms()),
+            phase_timings: Some(settings.phase_timings()),
+            max_workers: settings.max_workers(),
+            abort_after_failures: settings.abort_after_failures(),
+        }
+    }
+}
+
+/// The per-image fields that are set, validated. The profile and the batch-wide
+/// fields (output directory, overwrite, zip, deduplicate, workers) are left unset.
+impl TryFrom<OptimizationOptionsDto> for PartialProcessingSettings {
+    type Error = String;
+
+    fn try_from(dto: OptimizationOptionsDto) -> Result<Self, Self::Error> {
+        let mut partial = PartialProcessingSettings::default();
+
+        if let Some(quality) = dto.quality {
+            partial.quality = Some(Quality::new(quality).map_err(|e| e.to_string())?);
+        }
+
+        if let Some(ref fmt) = dto.output_format {
+            let format = ImageFormat::from_extension(fmt).map_err(|e| e.to_string())?;
+            partial.output_formats = Some(vec![format]);
+        }
+
+        // `output_formats` tiene prioridad sobre `output_format`
+        if let Some(ref formats) = dto.output_formats {
+            let formats = formats
+                .iter()
+                .map(|fmt| ImageFormat::from_extension(fmt).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            partial.output_formats = Some(formats);
+        }
 
-        let mut settings = ProcessingSettings::new(quality, PathBuf::from(&self.output_directory));
+        partial.preserve_metadata = dto.preserve_metadata;
 
-        let raw_mode = match self.raw_quality_mode.as_deref() {
-            Some("thumbnail") => RawQualityMode::Thumbnail,
-            Some("fast") => RawQualityMode::Fast,
-            Some("quality") => RawQualityMode::Quality,
+        if let Some(edge) = dto.max_long_edge {
+            if edge == 0 {
+                return Err("Max long edge must be greater than 0".to_string());
+            }
+            partial.max_long_edge = Some(Some(edge));
+        }
+
+        partial.raw_quality_mode = dto.raw_quality_mode.as_deref().map(|mode| match mode {
+            "thumbnail" => RawQualityMode::Thumbnail,
+            "fast" => RawQualityMode::Fast,
+            "quality" => RawQualityMode::Quality,
             _ => RawQualityMode::Balanced,
-        };
+        });
+
+        if let Some(ratio) = dto.min_compression_ratio {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(format!(
+                    "Invalid minimum compression ratio: {}. Must be between 0.0 and 1.0",
+                    ratio
+                ));
+            }
+            partial.min_compression_ratio = Some(Some(ratio));
+        }
+
+        if let Some(ref strategy) = dto.collision_strategy {
+            partial.collision_strategy = Some(match strategy.as_str() {
+                "error" => CollisionStrategy::Error,
+                "skip" => CollisionStrategy::Skip,
+                "rename" => CollisionStrategy::Rename(RenameMode::Sequential),
+                "timestamp" => CollisionStrategy::Rename(RenameMode::Timestamp),
+                "numbered" => CollisionStrategy::Rename(RenameMode::Numbered),
+                other => return Err(format!(
+                    "Invalid collision strategy: '{}' (error, skip, rename, timestamp, numbered)",
+                    other
+                )),
+            });
+        }
+
+        if let Some(ref strategy) = dto.on_no_improvement {
+            partial.on_no_improvement = Some(match strategy.as_str() {
+                "skip" => SkipStrategy::Skip,
+                "copyOriginal" => SkipStrategy::CopyOriginal,
+                "saveAnyway" => SkipStrategy::SaveAnyway,
+                other => {
+                    return Err(format!(
+                        "Invalid no-improvement strategy: '{}' (skip, copyOriginal, saveAnyway)",
+                        other
+                    ))
+                }
+            });
+        }
+
+        partial.skip_if_larger = dto.skip_if_larger;
+        partial.use_lossless_rotation = dto.use_lossless_rotation;
+        partial.heic_image_index = dto.heic_image_index;
+        partial.auto_orient = dto.auto_orient;
+        partial.use_cache = dto.use_cache;
+        // 0 = sin límite
+        partial.target_size_bytes = dto
+            .target_size_bytes
+            .map(|bytes| Some(bytes).filter(|&bytes| bytes > 0));
+        partial.max_output_bytes = dto
+            .max_output_bytes
+            .map(|bytes| Some(bytes).filter(|&bytes| bytes > 0));
+        partial.png_lossy = dto.png_lossy;
+        partial.png_bit_depth = dto.png_force_8bit.map(|force| {
+            if force {
+                PngBitDepth::Force8bit
+            } else {
+                PngBitDepth::Preserve
+            }
+        });
+        partial.progressive = dto.progressive;
+
+        if let Some(ref chroma) = dto.jpeg_chroma {
+            partial.jpeg_chroma = Some(match chroma.as_str() {
+                "420" => ChromaSubsampling::S420,
+                "422" => ChromaSubsampling::S422,
+                "444" => ChromaSubsampling::S444,
+                "auto" => ChromaSubsampling::Auto,
+                other => {
+                    return Err(format!(
+                        "Invalid JPEG chroma subsampling: '{}' (420, 422, 444, auto)",
+                        other
+                    ))
+                }
+            });
+        }
+
+        partial.jpeg_optimize_huffman = dto.jpeg_optimize_huffman;
+        partial.lossless = dto.lossless;
+        partial.max_retries = dto.max_retries;
+        partial.retry_delay_ms = dto.retry_delay_ms;
+        partial.phase_timings = dto.phase_timings;
+        if let Some(failures) = dto.abort_after_failures {
+            if failures == 0 {
+                return Err("Abort after failures must be greater than 0".to_string());
+            }
+            partial.abort_after_failures = Some(Some(failures));
+        }
+
+        if let Some(ref raw) = dto.raw_processing {
+            partial.raw_params = Some(raw.to_domain()?);
+        }
+
+        if let Some(ref hex) = dto.background_color {
+            let [r, g, b, _] = parse_hex_color(hex)?;
+            partial.background_color = Some([r, g, b]);
+        }
+
+        if let Some(ref sizes) = dto.sizes {
+            if sizes.contains(&0) {
+                return Err("Sizes must be greater than 0".to_string());
+            }
+        }
+        partial.sizes = dto.sizes;
+        partial.size_suffix = dto.size_suffix;
 
+        Ok(partial)
+    }
+}
+
+/// Checks that involve several fields, run once the overrides are merged
+fn validate_merged(settings: &ProcessingSettings) -> Result<(), String> {
+    // Sin {width} todas las variantes tendrían el mismo nombre
+    if settings.sizes().len() > 1 && settings.size_suffix(1) == settings.size_suffix(2) {
+        return Err("Size suffix must contain {width} when several sizes are set".to_string());
+    }
+
+    if settings.lossless() {
+        if let Some(format) = settings
+            .output_formats()
+            .iter()
+            .find(|format| !format.supports_lossless())
+        {
+            return Err(DomainError::LosslessNotSupported(format.to_string()).to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Settings for one image of a batch: `base` with the fields set in `overrides` taking
+/// priority. A profile in `overrides` replaces `base` before those fields are applied.
+/// Batch-wide fields (output directory, overwrite, zip, deduplicate, workers) are ignored.
+pub fn merge_settings(
+    base: &ProcessingSettings,
+    overrides: &OptimizationOptionsDto,
+) -> Result<ProcessingSettings, String> {
+    let mut settings = if overrides.profile.is_some() {
+        let mut settings = ProcessingSettings::from_profile(overrides.profile()?);
         settings
-            .set_output_format(output_format)
-            .set_preserve_metadata(self.preserve_metadata)
-            .set_overwrite_existing(self.overwrite_existing)
-            .set_raw_quality_mode(raw_mode);
+            .set_output_directory(base.output_directory().clone())
+            .set_preserve_directory_structure(base.preserve_directory_structure())
+            .set_source_base(base.source_base().map(PathBuf::from))
+            .set_output_mode(base.output_mode().clone())
+            .set_overwrite_existing(base.overwrite_existing())
+            .set_create_backup(base.create_backup())
+            .set_skip_unchanged(base.skip_unchanged())
+            .set_post_action(base.post_action().clone())
+            .set_write_report(base.write_report().map(PathBuf::from))
+            .set_flatten_zip(base.flatten_zip());
+        settings
+    } else {
+        base.clone()
+    };
+    settings.set_image_overrides(HashMap::new());
 
-        Ok(settings)
+    let settings = settings.merge(&PartialProcessingSettings::try_from(overrides.clone())?);
+    validate_merged(&settings)?;
+    Ok(settings)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawProcessingOptionsDto {
+    /// "camera" | "auto" | "daylight" | "custom"
+    pub white_balance: Option<String>,
+    /// R, G, B, G2 multipliers used when white balance is "custom"
+    pub custom_multipliers: Option<[f32; 4]>,
+    /// Exposure correction in stops (-2.0 to +3.0)
+    pub exposure_correction: Option<f32>,
+    /// "clip" | "unclip" | "blend" | "rebuild"
+    pub highlight_mode: Option<String>,
+    /// Wavelet denoising threshold (0 = off)
+    pub noise_threshold: Option<f32>,
+    /// 8 or 16
+    pub output_bps: Option<u8>,
+    #[serde(default)]
+    pub half_size: bool,
+}
+
+impl RawProcessingOptionsDto {
+    /// Convert DTO to domain RawProcessingParams
+    pub fn to_domain(&self) -> Result<RawProcessingParams, String> {
+        let white_balance = match self.white_balance.as_deref() {
+            None | Some("camera") => WhiteBalance::Camera,
+            Some("auto") => WhiteBalance::Auto,
+            Some("daylight") => WhiteBalance::Daylight,
+            Some("custom") => {
+                let [r, g, b, g2] = self
+                    .custom_multipliers
+                    .ok_or_else(|| "Custom white balance requires customMultipliers".to_string())?;
+                if [r, g, b, g2].iter().any(|m| !(m.is_finite() && *m > 0.0)) {
+                    return Err(format!(
+                        "Invalid white balance multipliers: {:?}. Must be greater than 0",
+                        [r, g, b, g2]
+                    ));
+                }
+                WhiteBalance::Custom(r, g, b, g2)
+            }
+            Some(other) => return Err(format!("Invalid white balance: {}", other)),
+        };
+
+        let exposure_correction = self.exposure_correction.unwrap_or(0.0);
+        if !(RawProcessingParams::MIN_EXPOSURE..=RawProcessingParams::MAX_EXPOSURE)
+            .contains(&exposure_correction)
+        {
+            return Err(format!(
+                "Invalid exposure correction: {}. Must be between {} and {} stops",
+                exposure_correction,
+                RawProcessingParams::MIN_EXPOSURE,
+                RawProcessingParams::MAX_EXPOSURE
+            ));
+        }
+
+        let highlight_mode = match self.highlight_mode.as_deref() {
+            None | Some("clip") => HighlightMode::Clip,
+            Some("unclip") => HighlightMode::Unclip,
+            Some("blend") => HighlightMode::Blend,
+            Some("rebuild") => HighlightMode::Rebuild,
+            Some(other) => return Err(format!("Invalid highlight mode: {}", other)),
+        };
+
+        let noise_threshold = self.noise_threshold.unwrap_or(0.0);
+        if !(noise_threshold.is_finite() && noise_threshold >= 0.0) {
+            return Err(format!(
+                "Invalid noise threshold: {}. Must be 0 or greater",
+                noise_threshold
+            ));
+        }
+
+        let output_bps = self.output_bps.unwrap_or(8);
+        if output_bps != 8 && output_bps != 16 {
+            return Err(format!(
+                "Invalid output bits per sample: {}. Must be 8 or 16",
+                output_bps
+            ));
+        }
+
+        Ok(RawProcessingParams {
+            white_balance,
+            exposure_correction,
+            highlight_mode,
+            noise_threshold,
+            output_bps,
+            half_size: self.half_size,
+        })
+    }
+}
+
+impl From<&RawProcessingParams> for RawProcessingOptionsDto {
+    fn from(params: &RawProcessingParams) -> Self {
+        let (white_balance, custom_multipliers) = match params.white_balance {
+            WhiteBalance::Camera => ("camera", None),
+            WhiteBalance::Auto => ("auto", None),
+            WhiteBalance::Daylight => ("daylight", None),
+            WhiteBalance::Custom(r, g, b, g2) => ("custom", Some([r, g, b, g2])),
+        };
+        RawProcessingOptionsDto {
+            white_balance: Some(white_balance.to_string()),
+            custom_multipliers,
+            exposure_correction: Some(params.exposure_correction),
+            highlight_mode: Some(
+                match params.highlight_mode {
+                    HighlightMode::Clip => "clip",
+                    HighlightMode::Unclip => "unclip",
+                    HighlightMode::Blend => "blend",
+                    HighlightMode::Rebuild => "rebuild",
+                }
+                .to_string(),
+            ),
+            noise_threshold: Some(params.noise_threshold),
+            output_bps: Some(params.output_bps),
+            half_size: params.half_size,
+        }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransformationOptionsDto {
+    /// Turn the image upright from its EXIF orientation first, even with the
+    /// optimization options' `autoOrient` off (default false)
+    #[serde(default)]
+    pub auto_orient: Option<bool>,
+    /// Crop transparent/solid-color borders before any other operation
+    #[serde(default)]
+    pub trim: bool,
+    /// Per-channel tolerance (0-255) for near-solid borders
+    #[serde(default)]
+    pub trim_tolerance: Option<u8>,
     pub resize: Option<ResizeOptionsDto>,
-    pub rotate: Option<i32>,
+    /// Rotation in degrees (clockwise). Multiples of 90 are lossless; any other angle
+    /// uses arbitrary rotation
+    pub rotate: Option<f32>,
+    /// Fill color for corners exposed by arbitrary rotation ("#RRGGBB" or "#RRGGBBAA")
+    #[serde(default)]
+    pub rotate_fill_color: Option<String>,
+    /// Crop arbitrary rotation to hide exposed corners instead of growing the canvas
+    #[serde(default)]
+    pub rotate_auto_crop: bool,
     pub flip_horizontal: bool,
     pub flip_vertical: bool,
+    #[serde(default)]
+    pub watermark: Option<WatermarkOptionsDto>,
+    #[serde(default)]
+    pub text_watermark: Option<TextWatermarkOptionsDto>,
+    #[serde(default)]
+    pub grayscale: bool,
+    /// "grayscale", "sepia", or a channel to extract: "red", "green", "blue", "luma"
+    #[serde(default)]
+    pub color_adjustment: Option<String>,
+    /// Sepia strength 0-1 (default 1)
+    #[serde(default)]
+    pub sepia_strength: Option<f32>,
+    /// Brightness/contrast/saturation corrections
+    #[serde(default)]
+    pub tonal: Option<TonalAdjustmentsDto>,
+    /// Unsharp mask, applied last
+    #[serde(default)]
+    pub sharpen: Option<SharpenOptionsDto>,
+    /// Gaussian blur sigma (0 = off)
+    #[serde(default)]
+    pub blur: Option<f32>,
 }
 
 impl TransformationOptionsDto {
     /// Convert DTO to domain Transformation
     pub fn to_domain(&self) -> Result<Option<Transformation>, String> {
-        if self.resize.is_none()
+        let auto_orient = self.auto_orient.unwrap_or(false);
+        if !auto_orient
+            && !self.trim
+            && self.resize.is_none()
             && self.rotate.is_none()
             && !self.flip_horizontal
             && !self.flip_vertical
+            && self.watermark.is_none()
+            && self.text_watermark.is_none()
+            && !self.grayscale
+            && self.color_adjustment.is_none()
+            && self.tonal.is_none()
+            && self.sharpen.is_none()
+            && self.blur.is_none()
         {
             return Ok(None);
         }
 
         let mut transformation = Transformation::new();
+        transformation.set_auto_orient(auto_orient);
+
+        if self.trim {
+            transformation.set_trim(true, self.trim_tolerance.unwrap_or(0));
+        }
 
         if let Some(ref resize_dto) = self.resize {
             let resize = resize_dto.to_domain()?;
@@ -98,42 +816,384 @@ impl TransformationOptionsDto {
         }
 
         if let Some(degrees) = self.rotate {
-            let rotation = Rotation::from_degrees(degrees).map_err(|e| e.to_string())?;
-            transformation.set_rotation(rotation);
+            let normalized = degrees.rem_euclid(360.0);
+            if normalized.fract() == 0.0 && normalized as i32 % 90 == 0 {
+                let rotation =
+                    Rotation::from_degrees(normalized as i32).map_err(|e| e.to_string())?;
+                transformation.set_rotation(rotation);
+            } else {
+                let fill_color = match self.rotate_fill_color.as_deref() {
+                    Some(hex) => parse_hex_color(hex)?,
+                    None => ArbitraryRotation::DEFAULT_FILL,
+                };
+                let rotation = ArbitraryRotation::new(degrees, fill_color, self.rotate_auto_crop)
+                    .map_err(|e| e.to_string())?;
+                transformation.set_arbitrary_rotation(rotation);
+            }
         }
 
         transformation.set_flip_horizontal(self.flip_horizontal);
         transformation.set_flip_vertical(self.flip_vertical);
+        transformation.set_grayscale(self.grayscale);
+
+        if let Some(ref adjustment) = self.color_adjustment {
+            transformation
+                .set_color_adjustment(parse_color_adjustment(adjustment, self.sepia_strength)?);
+        }
+
+        if let Some(ref tonal) = self.tonal {
+            transformation.set_tonal(tonal.to_domain()?);
+        }
+
+        if let Some(ref sharpen) = self.sharpen {
+            transformation.set_sharpen(sharpen.to_domain()?);
+        }
+        if let Some(sigma) = self.blur {
+            transformation.set_blur(validate_sigma("blur", sigma)?);
+        }
+
+        if let Some(ref watermark_dto) = self.watermark {
+            transformation.set_watermark(watermark_dto.to_domain()?);
+        }
+
+        if let Some(ref text_dto) = self.text_watermark {
+            transformation.set_text_watermark(text_dto.to_domain()?);
+        }
 
         Ok(Some(transformation))
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatermarkOptionsDto {
+    pub image_path: String,
+    /// "topLeft" | "top" | "topRight" | "left" | "center" | "right" |
+    /// "bottomLeft" | "bottom" | "bottomRight" | "custom"
+    pub position: String,
+    /// Pixel offset used when position is "custom"
+    pub x: Option<u32>,
+    pub y: Option<u32>,
+    /// 0-100
+    pub opacity: u8,
+    /// Overlay width relative to the image width (0.0-1.0]
+    pub scale: f32,
+}
+
+impl WatermarkOptionsDto {
+    /// Convert DTO to domain Watermark
+    pub fn to_domain(&self) -> Result<Watermark, String> {
+        let position = parse_watermark_position(&self.position, self.x, self.y)?;
+        Watermark::new(
+            PathBuf::from(&self.image_path),
+            position,
+            self.opacity,
+            self.scale,
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextWatermarkOptionsDto {
+    pub text: String,
+    /// Custom font file (None = bundled font)
+    pub font_path: Option<String>,
+    pub font_size: f32,
+    /// "px" (default) | "percent" (of image height)
+    pub font_size_unit: Option<String>,
+    /// "#RRGGBB" or "#RRGGBBAA" (alpha = opacity)
+    pub color: Option<String>,
+    /// Same anchors as the image watermark
+    pub position: String,
+    pub x: Option<u32>,
+    pub y: Option<u32>,
+    /// Distance from the edges in pixels
+    #[serde(default)]
+    pub margin: u32,
+}
+
+impl TextWatermarkOptionsDto {
+    /// Convert DTO to domain TextWatermark
+    pub fn to_domain(&self) -> Result<TextWatermark, String> {
+        let position = parse_watermark_position(&self.position, self.x, self.y)?;
+
+        let font_size = match self.font_size_unit.as_deref() {
+            None | Some("px") => FontSize::Pixels(self.font_size),
+            Some("percent") => FontSize::PercentOfHeight(self.font_size),
+            Some(other) => return Err(format!("Unknown font size unit: {}", other)),
+        };
+
+        let color = match self.color.as_deref() {
+            Some(hex) => parse_hex_color(hex)?,
+            None => TextWatermark::DEFAULT_COLOR,
+        };
+
+        let mut watermark =
+            TextWatermark::new(self.text.clone(), font_size, color, position, self.margin)
+                .map_err(|e| e.to_string())?;
+        watermark.set_font_path(self.font_path.as_ref().map(PathBuf::from));
+
+        Ok(watermark)
+    }
+}
+
+/// Validate a blur sigma (0-100)
+fn validate_sigma(name: &str, sigma: f32) -> Result<f32, String> {
+    if !(0.0..=100.0).contains(&sigma) {
+        return Err(format!(
+            "Invalid {} sigma: {}. Must be between 0 and 100",
+            name, sigma
+        ));
+    }
+    Ok(sigma)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharpenOptionsDto {
+    /// "mild" | "moderate" (default) | "strong"; the other fields override it
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Strength 0-5 (1 = add the full difference)
+    #[serde(default)]
+    pub amount: Option<f32>,
+    /// Gaussian sigma in pixels (0-100)
+    #[serde(default)]
+    pub radius: Option<f32>,
+    /// Minimum difference (0-255) to sharpen a pixel
+    #[serde(default)]
+    pub threshold: Option<u8>,
+}
+
+impl SharpenOptionsDto {
+    /// Convert DTO to domain SharpenOptions
+    pub fn to_domain(&self) -> Result<SharpenOptions, String> {
+        let preset = match self.preset.as_deref() {
+            Some("mild") => SharpenOptions::mild(),
+            None | Some("moderate") => SharpenOptions::moderate(),
+            Some("strong") => SharpenOptions::strong(),
+            Some(other) => {
+                return Err(format!(
+                    "Invalid sharpen preset: '{}' (mild, moderate, strong)",
+                    other
+                ))
+            }
+        };
+
+        SharpenOptions::new(
+            self.amount.unwrap_or(preset.amount()),
+            self.radius.unwrap_or(preset.radius()),
+            self.threshold.unwrap_or(preset.threshold()),
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TonalAdjustmentsDto {
+    /// Each -1..1, 0 or missing = unchanged
+    #[serde(default)]
+    pub brightness: Option<f32>,
+    #[serde(default)]
+    pub contrast: Option<f32>,
+    #[serde(default)]
+    pub saturation: Option<f32>,
+}
+
+impl TonalAdjustmentsDto {
+    /// Convert DTO to domain TonalAdjustments
+    pub fn to_domain(&self) -> Result<TonalAdjustments, String> {
+        TonalAdjustments::new(
+            self.brightness.unwrap_or(0.0),
+            self.contrast.unwrap_or(0.0),
+            self.saturation.unwrap_or(0.0),
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// Parse a color adjustment name
+pub fn parse_color_adjustment(
+    adjustment: &str,
+    sepia_strength: Option<f32>,
+) -> Result<ColorAdjustment, String> {
+    match adjustment {
+        "grayscale" => Ok(ColorAdjustment::Grayscale),
+        "sepia" => ColorAdjustment::sepia(sepia_strength.unwrap_or(1.0)).map_err(|e| e.to_string()),
+        "red" => Ok(ColorAdjustment::ExtractChannel(Channel::Red)),
+        "green" => Ok(ColorAdjustment::ExtractChannel(Channel::Green)),
+        "blue" => Ok(ColorAdjustment::ExtractChannel(Channel::Blue)),
+        "luma" => Ok(ColorAdjustment::ExtractChannel(Channel::Luma)),
+        _ => Err(format!("Unknown color adjustment: {}", adjustment)),
+    }
+}
+
+/// Parse a watermark anchor name (custom positions need x and y)
+pub fn parse_watermark_position(
+    position: &str,
+    x: Option<u32>,
+    y: Option<u32>,
+) -> Result<WatermarkPosition, String> {
+    match position {
+        "topLeft" => Ok(WatermarkPosition::TopLeft),
+        "top" => Ok(WatermarkPosition::Top),
+        "topRight" => Ok(WatermarkPosition::TopRight),
+        "left" => Ok(WatermarkPosition::Left),
+        "center" => Ok(WatermarkPosition::Center),
+        "right" => Ok(WatermarkPosition::Right),
+        "bottomLeft" => Ok(WatermarkPosition::BottomLeft),
+        "bottom" => Ok(WatermarkPosition::Bottom),
+        "bottomRight" => Ok(WatermarkPosition::BottomRight),
+        "custom" => match (x, y) {
+            (Some(x), Some(y)) => Ok(WatermarkPosition::Custom { x, y }),
+            _ => Err("Custom watermark position requires x and y".to_string()),
+        },
+        _ => Err(format!("Unknown watermark position: {}", position)),
+    }
+}
+
+/// Parse a "#RRGGBB" or "#RRGGBBAA" color into RGBA
+pub fn parse_hex_color(hex: &str) -> Result<[u8; 4], String> {
+    let digits = hex.trim().trim_start_matches('#');
+    if !matches!(digits.len(), 6 | 8) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid color: {}", hex));
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap_or(0);
+    let alpha = if digits.len() == 8 { channel(6) } else { 255 };
+    Ok([channel(0), channel(2), channel(4), alpha])
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResizeOptionsDto {
-    pub width: u32,
-    pub height: u32,
+    /// "absolute", "cover" (fill width x height, cropping the excess), "percentage",
+    /// "longEdge" or "shortEdge"; alternative to the per-target fields below
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Percent or pixels for the `mode` (unused by "absolute" and "cover", which take
+    /// width/height)
+    #[serde(default)]
+    pub value: Option<u32>,
+    /// Target size in pixels (use together with `height`)
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    /// Size relative to the original (50 = half, above 100 upscales)
+    #[serde(default)]
+    pub percentage: Option<f32>,
+    /// Longest side in pixels, whatever the orientation
+    #[serde(default, alias = "longest_edge")]
+    pub longest_edge: Option<u32>,
+    /// Shortest side in pixels, whatever the orientation
+    #[serde(default, alias = "shortest_edge")]
+    pub shortest_edge: Option<u32>,
+    /// Let the resize enlarge smaller images (default false: never upscale)
+    #[serde(default, alias = "allow_upscale")]
+    pub allow_upscale: bool,
+    /// Only used with width/height; the other targets always keep the aspect ratio
+    #[serde(default)]
     pub preserve_aspect_ratio: bool,
+    /// "nearest" | "triangle" | "catmullrom" | "gaussian" | "lanczos3" (default) |
+    /// "thumbnail" (fastest, for previews)
     pub filter: Option<String>,
 }
 
 impl ResizeOptionsDto {
     /// Convert DTO to domain ResizeTransformation
     pub fn to_domain(&self) -> Result<ResizeTransformation, String> {
-        let dimensions = Dimensions::new(self.width, self.height).map_err(|e| e.to_string())?;
-
         let filter = if let Some(ref f) = self.filter {
             Self::parse_filter(f)?
         } else {
             ResizeFilter::Lanczos3
         };
 
-        Ok(ResizeTransformation::new(
-            dimensions,
-            self.preserve_aspect_ratio,
-            filter,
-        ))
+        let mut resize = match self.mode.as_deref() {
+            Some(mode) => self.resize_for_mode(mode, filter)?,
+            None => self.resize_for_target(filter)?,
+        };
+
+        resize.set_allow_upscale(self.allow_upscale);
+        Ok(resize)
+    }
+
+    /// `mode` + `value` form: "percentage", "longEdge" and "shortEdge" take `value`,
+    /// "absolute" and "cover" take width/height
+    fn resize_for_mode(
+        &self,
+        mode: &str,
+        filter: ResizeFilter,
+    ) -> Result<ResizeTransformation, String> {
+        if self.percentage.is_some() || self.longest_edge.is_some() || self.shortest_edge.is_some()
+        {
+            return Err(
+                "Resize mode can't be combined with percentage, longestEdge or shortestEdge"
+                    .to_string(),
+            );
+        }
+
+        if mode == "absolute" || mode == "cover" {
+            let (Some(width), Some(height)) = (self.width, self.height) else {
+                return Err(format!("Resize mode '{}' requires width and height", mode));
+            };
+            let dimensions = Dimensions::new(width, height).map_err(|e| e.to_string())?;
+            return Ok(if mode == "cover" {
+                ResizeTransformation::with_cover(dimensions, filter)
+            } else {
+                ResizeTransformation::new(dimensions, self.preserve_aspect_ratio, filter)
+            });
+        }
+
+        let value = self
+            .value
+            .ok_or_else(|| format!("Resize mode '{}' requires a value", mode))?;
+        match mode {
+            "percentage" => ResizeTransformation::with_percentage(value as f32, filter),
+            "longEdge" => ResizeTransformation::with_longest_edge(value, filter),
+            "shortEdge" => ResizeTransformation::with_shortest_edge(value, filter),
+            _ => return Err(format!("Unknown resize mode: {}", mode)),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    /// Field form: exactly one of width/height, percentage, longestEdge or shortestEdge
+    fn resize_for_target(&self, filter: ResizeFilter) -> Result<ResizeTransformation, String> {
+        let has_dimensions = self.width.is_some() || self.height.is_some();
+        let target_count = [
+            has_dimensions,
+            self.percentage.is_some(),
+            self.longest_edge.is_some(),
+            self.shortest_edge.is_some(),
+        ]
+        .iter()
+        .filter(|&&set| set)
+        .count();
+        if target_count != 1 {
+            return Err(
+                "Resize requires exactly one of width/height, percentage, longestEdge or shortestEdge"
+                    .to_string(),
+            );
+        }
+
+        if let Some(percentage) = self.percentage {
+            ResizeTransformation::with_percentage(percentage, filter)
+        } else if let Some(edge) = self.longest_edge {
+            ResizeTransformation::with_longest_edge(edge, filter)
+        } else if let Some(edge) = self.shortest_edge {
+            ResizeTransformation::with_shortest_edge(edge, filter)
+        } else {
+            let (Some(width), Some(height)) = (self.width, self.height) else {
+                return Err("Resize requires both width and height".to_string());
+            };
+            Dimensions::new(width, height).map(|dimensions| {
+                ResizeTransformation::new(dimensions, self.preserve_aspect_ratio, filter)
+            })
+        }
+        .map_err(|e| e.to_string())
     }
 
     fn parse_filter(filter: &str) -> Result<ResizeFilter, String> {
@@ -143,6 +1203,7 @@ impl ResizeOptionsDto {
             "catmullrom" | "cubic" => Ok(ResizeFilter::CatmullRom),
             "gaussian" => Ok(ResizeFilter::Gaussian),
             "lanczos3" | "lanczos" => Ok(ResizeFilter::Lanczos3),
+            "thumbnail" => Ok(ResizeFilter::Thumbnail),
             _ => Err(format!("Unknown filter: {}", filter)),
         }
     }
@@ -157,7 +1218,54 @@ pub struct ProcessedImageDto {
     pub output_size: u64,
     pub compression_ratio: f64,
     pub success: bool,
+    pub skipped: bool,
+    /// The output came out larger than the original, which was kept instead
+    pub skipped_larger: bool,
+    pub upscale_prevented: bool,
+    pub target_width: Option<u32>,
+    pub final_quality: Option<u8>,
+    pub target_met: Option<bool>,
+    pub validation_warning: Option<String>,
     pub error_message: Option<String>,
+    /// Machine-readable kind of the failure (`DomainError::code`), when known
+    pub error_code: Option<String>,
+    /// Wall-clock time spent on the input image
+    pub duration_ms: u64,
+    /// Time per phase (only with `phaseTimings`)
+    pub timings: Option<PhaseTimingsDto>,
+    /// Times the input was read: 1 plus the retries used (0 = never decoded)
+    pub attempts: u32,
+    /// Planned by a dry run; `outputSize` is 0
+    pub dry_run: bool,
+    /// The existing output was up to date and left alone (`skipUnchanged`)
+    pub unchanged: bool,
+    /// Expected output size in a dry run (None = no sample to estimate from)
+    pub estimated_size: Option<u64>,
+    /// What happened to the original afterwards (`postAction`); None = left in place
+    pub post_action: Option<String>,
+    /// The `skipUnchanged` manifest couldn't be saved: it will be processed again
+    pub manifest_error: Option<String>,
+}
+
+/// Milliseconds spent in each phase of producing an output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTimingsDto {
+    pub decode_ms: u64,
+    pub transform_ms: u64,
+    pub encode_ms: u64,
+    pub write_ms: u64,
+}
+
+impl From<PhaseTimings> for PhaseTimingsDto {
+    fn from(timings: PhaseTimings) -> Self {
+        PhaseTimingsDto {
+            decode_ms: timings.decode_ms,
+            transform_ms: timings.transform_ms,
+            encode_ms: timings.encode_ms,
+            write_ms: timings.write_ms,
+        }
+    }
 }
 
 impl From<ProcessingResult> for ProcessedImageDto {
@@ -169,11 +1277,212 @@ impl From<ProcessingResult> for ProcessedImageDto {
             output_size: result.output_size,
             compression_ratio: result.compression_ratio(),
             success: result.success,
+            skipped: result.skipped,
+            skipped_larger: result.skipped_larger,
+            upscale_prevented: result.upscale_prevented,
+            target_width: result.target_width,
+            final_quality: result.final_quality,
+            target_met: result.target_met,
+            validation_warning: result.validation_warning,
             error_message: result.error_message,
+            error_code: result.error_code,
+            duration_ms: result.processing_time_ms,
+            timings: result.timings.map(PhaseTimingsDto::from),
+            attempts: result.attempts,
+            dry_run: result.dry_run,
+            unchanged: result.unchanged,
+            estimated_size: result.estimated_size,
+            post_action: result.post_action,
+            manifest_error: result.manifest_error,
+        }
+    }
+}
+
+/// Which files of a folder to load
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryOptionsDto {
+    /// Glob patterns (e.g. "IMG_2024*", "*.arw"); a file must match one of them.
+    /// Extensions match in any case
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Glob patterns of files to leave out
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Only these extensions (e.g. ["arw", "nef"]); empty = every image format
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Also load images in subfolders (default false)
+    #[serde(default)]
+    pub recursive: bool,
+    /// Also load files with a non-image extension whose contents are an image (default false)
+    #[serde(default)]
+    pub sniff_content: bool,
+}
+
+impl DiscoveryOptionsDto {
+    /// Convert DTO to file discovery options
+    pub fn to_options(&self) -> DiscoveryOptions {
+        DiscoveryOptions {
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            extensions: self.extensions.clone(),
+            max_depth: if self.recursive { None } else { Some(1) },
+            sniff_content: self.sniff_content,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationResultDto {
+    pub path: String,
+    /// "ok" | "truncated" | "wrongFormat" | "empty" | "unsupported"
+    pub status: String,
+    /// Description of the problem (None when "ok")
+    pub message: Option<String>,
+}
+
+impl ValidationResultDto {
+    pub fn new(path: String, result: &ValidationResult) -> Self {
+        let status = match result {
+            ValidationResult::Ok => "ok",
+            ValidationResult::Truncated(..) => "truncated",
+            ValidationResult::WrongFormat { .. } => "wrongFormat",
+            ValidationResult::Empty => "empty",
+            ValidationResult::Unsupported(_) => "unsupported",
+        };
+        Self {
+            path,
+            status: status.to_string(),
+            message: result.warning(),
+        }
+    }
+}
+
+/// Dominant color of an image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteColorDto {
+    pub rgb: [u8; 3],
+    /// "#rrggbb"
+    pub hex: String,
+    /// Share of the image's pixels, 0-100
+    pub percentage: f32,
+}
+
+impl From<&PaletteColor> for PaletteColorDto {
+    fn from(color: &PaletteColor) -> Self {
+        Self {
+            rgb: color.rgb,
+            hex: color.hex.clone(),
+            percentage: color.percentage,
+        }
+    }
+}
+
+/// Option of the output size picker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedSizeDto {
+    pub size: StandardSize,
+    /// e.g. "Full HD 1080p (1920x1080)"
+    pub label: String,
+    pub width: u32,
+    pub height: u32,
+    /// The best fit for the image (preselected in the picker)
+    pub recommended: bool,
+}
+
+impl SuggestedSizeDto {
+    pub fn new(size: StandardSize, recommended: bool) -> Self {
+        let dimensions = size.dimensions();
+        Self {
+            size,
+            label: size.to_string(),
+            width: dimensions.width(),
+            height: dimensions.height(),
+            recommended,
+        }
+    }
+}
+
+/// 256-bin histograms of an image plus exposure diagnostics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramDto {
+    pub r: Vec<u32>,
+    pub g: Vec<u32>,
+    pub b: Vec<u32>,
+    pub luma: Vec<u32>,
+    /// Average luma, 0-255
+    pub mean_luma: f32,
+    /// Pixels at pure white luma, 0-100
+    pub clipped_highlights_percent: f32,
+    /// Pixels at pure black luma, 0-100
+    pub clipped_shadows_percent: f32,
+}
+
+impl From<&ImageHistogram> for HistogramDto {
+    fn from(histogram: &ImageHistogram) -> Self {
+        Self {
+            r: histogram.r.to_vec(),
+            g: histogram.g.to_vec(),
+            b: histogram.b.to_vec(),
+            luma: histogram.luma.to_vec(),
+            mean_luma: histogram.mean_luma(),
+            clipped_highlights_percent: histogram.clipped_highlights_percent(),
+            clipped_shadows_percent: histogram.clipped_shadows_percent(),
+        }
+    }
+}
+
+/// What a folder holds, shown before committing to a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryStatsDto {
+    pub total_images: usize,
+    pub total_size_bytes: u64,
+    /// Image count by format ("raw", "jpg", ...)
+    pub by_format: BTreeMap<String, usize>,
+    /// Oldest modification time, in milliseconds since the Unix epoch
+    pub oldest_file: Option<u64>,
+    /// Newest modification time, in milliseconds since the Unix epoch
+    pub newest_file: Option<u64>,
+    pub largest_file_bytes: u64,
+}
+
+impl From<&DirectoryStats> for DirectoryStatsDto {
+    fn from(stats: &DirectoryStats) -> Self {
+        let epoch_ms = |time: SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_millis() as u64)
+        };
+
+        Self {
+            total_images: stats.total_images,
+            total_size_bytes: stats.total_size_bytes,
+            by_format: stats
+                .by_format
+                .iter()
+                .map(|(format, count)| (format.to_string(), *count))
+                .collect(),
+            oldest_file: stats.oldest_file.map(epoch_ms),
+            newest_file: stats.newest_file.map(epoch_ms),
+            largest_file_bytes: stats.largest_file_bytes,
         }
     }
 }
 
+/// Rough output size of a batch (heuristics, not guaranteed values)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateDto {
+    pub total_estimated_bytes: u64,
+    /// (path, estimated bytes of all its outputs) for every image that could be read
+    pub per_image: Vec<(String, u64)>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressPayload {
@@ -181,21 +1490,33 @@ pub struct ProgressPayload {
     pub total: usize,
     pub current_file: String,
     pub percentage: f64,
+    pub elapsed_ms: u64,
+    /// Estimated time left, smoothed over recent images (None until known)
+    pub estimated_remaining_ms: Option<u64>,
+    pub throughput_mb_per_sec: f64,
+    pub bytes_processed: u64,
+    /// Task the progress belongs to
+    pub task_id: Option<String>,
 }
 
-impl ProgressPayload {
-    pub fn new(current: usize, total: usize, current_file: String) -> Self {
-        let percentage = if total > 0 {
-            (current as f64 / total as f64) * 100.0
+impl From<&BatchProgress> for ProgressPayload {
+    fn from(progress: &BatchProgress) -> Self {
+        let percentage = if progress.total > 0 {
+            (progress.current as f64 / progress.total as f64) * 100.0
         } else {
             0.0
         };
 
         ProgressPayload {
-            current,
-            total,
-            current_file,
+            current: progress.current,
+            total: progress.total,
+            current_file: progress.current_file.clone(),
             percentage,
+            elapsed_ms: progress.elapsed_ms,
+            estimated_remaining_ms: progress.estimated_remaining_ms,
+            throughput_mb_per_sec: progress.throughput_mb_per_sec,
+            bytes_processed: progress.bytes_processed,
+            task_id: None,
         }
     }
 }
@@ -206,6 +1527,38 @@ pub struct BatchProcessRequest {
     pub image_paths: Vec<String>,
     pub optimization_options: OptimizationOptionsDto,
     pub transformation_options: Option<TransformationOptionsDto>,
+    /// Options for individual images keyed by their path in `image_paths`;
+    /// only the fields they set differ from `optimization_options`
+    #[serde(default)]
+    pub per_image_overrides: Option<HashMap<String, OptimizationOptionsDto>>,
+    /// Cap on "processing-progress" events per second (default 10, 0 = no limit)
+    #[serde(default)]
+    pub max_progress_events_per_second: Option<u32>,
+    /// Only plan the outputs and estimate their size; nothing is written and
+    /// the session stats are left alone (default false)
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Start right away instead of waiting for the running batch; meant for small
+    /// jobs such as single-image previews (default false)
+    #[serde(default)]
+    pub priority: bool,
+}
+
+/// Results of a batch run by `process_images`, with the id of its task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessedBatchDto {
+    pub task_id: String,
+    pub results: Vec<ProcessedImageDto>,
+}
+
+/// A batch queued by `enqueue_images`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedTaskDto {
+    pub task_id: String,
+    /// Batches queued or running ahead of it
+    pub ahead: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -214,4 +1567,313 @@ pub struct ProcessingStatsDto {
     pub total_processed: usize,
     pub total_saved_bytes: u64,
     pub average_savings: f64,
+    /// Breakdown by input format, sorted by format name
+    pub per_format: Vec<FormatStatsDto>,
+    /// Seconds since the first image of the session (None before any image)
+    pub session_duration_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatStatsDto {
+    /// Input format ("png", "jpg", "raw", ...)
+    pub format: String,
+    pub count: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// Images written in a different format than they were read
+    pub converted: usize,
+    /// Percentage of input bytes saved
+    pub average_compression_ratio: f64,
+}
+
+impl From<&ProcessingStats> for ProcessingStatsDto {
+    fn from(stats: &ProcessingStats) -> Self {
+        let mut per_format: Vec<FormatStatsDto> = stats
+            .per_format
+            .iter()
+            .map(|(format, format_stats)| FormatStatsDto {
+                format: format.to_string(),
+                count: format_stats.count,
+                bytes_in: format_stats.bytes_in,
+                bytes_out: format_stats.bytes_out,
+                converted: format_stats.converted,
+                average_compression_ratio: stats.average_compression_ratio_for_format(*format),
+            })
+            .collect();
+        per_format.sort_by(|a, b| a.format.cmp(&b.format));
+
+        Self {
+            total_processed: stats.total_processed,
+            total_saved_bytes: stats.total_saved_bytes,
+            average_savings: stats.average_savings(),
+            per_format,
+            session_duration_secs: stats.session_duration_secs(),
+        }
+    }
+}
+
+/// Batches waiting in the processing queue and running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStatusDto {
+    pub pending: usize,
+    /// Tasks running, priority ones included
+    pub active: usize,
+}
+
+impl From<QueueStatus> for QueueStatusDto {
+    fn from(status: QueueStatus) -> Self {
+        Self {
+            pending: status.pending,
+            active: status.active,
+        }
+    }
+}
+
+/// State of a processing task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStateDto {
+    Idle,
+    Queued,
+    Running,
+    Completed,
+    Cancelled,
+    Error,
+}
+
+/// Status of a processing task, returned by `get_processing_status` and emitted
+/// as "status-changed"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatusDto {
+    /// None when there is no task at all (state "idle")
+    pub task_id: Option<String>,
+    pub state: TaskStateDto,
+    /// Why the task failed (state "error")
+    pub error_message: Option<String>,
+    /// Outputs finished so far (None before the first one)
+    pub current: Option<usize>,
+    pub total: Option<usize>,
+    /// When the task started running, in milliseconds since the Unix epoch
+    pub started_at: Option<u64>,
+}
+
+impl TaskStatusDto {
+    /// Status when no task was ever created
+    pub fn idle() -> Self {
+        Self {
+            task_id: None,
+            state: TaskStateDto::Idle,
+            error_message: None,
+            current: None,
+            total: None,
+            started_at: None,
+        }
+    }
+}
+
+impl From<&TaskSnapshot> for TaskStatusDto {
+    fn from(snapshot: &TaskSnapshot) -> Self {
+        let (state, error_message) = match &snapshot.status {
+            TaskStatus::Idle => (TaskStateDto::Idle, None),
+            TaskStatus::Queued => (TaskStateDto::Queued, None),
+            TaskStatus::Running => (TaskStateDto::Running, None),
+            TaskStatus::Completed => (TaskStateDto::Completed, None),
+            TaskStatus::Cancelled => (TaskStateDto::Cancelled, None),
+            TaskStatus::Error(message) => (TaskStateDto::Error, Some(message.clone())),
+        };
+
+        Self {
+            task_id: Some(snapshot.task_id.to_string()),
+            state,
+            error_message,
+            current: snapshot.progress.map(|(current, _)| current),
+            total: snapshot.progress.map(|(_, total)| total),
+            started_at: snapshot.started_at.map(|time| {
+                time.duration_since(UNIX_EPOCH)
+                    .map_or(0, |since| since.as_millis() as u64)
+            }),
+        }
+    }
+}
+
+/// Summary of one batch, emitted as "processing-complete"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSummaryDto {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Up-to-date outputs left alone (`skipUnchanged`)
+    pub unchanged: usize,
+    pub bytes_saved: u64,
+    pub duration_ms: u64,
+    /// Mean percentage saved by the written outputs
+    pub average_compression_ratio: f64,
+    /// Phase times summed over every output (only with `phaseTimings`)
+    pub timing_totals: Option<PhaseTimingsDto>,
+    pub slowest_file: Option<String>,
+    pub slowest_file_ms: Option<u64>,
+    /// Why the batch stopped early (None = every image was processed)
+    pub stop_reason: Option<String>,
+    /// Nothing was written: the savings are estimates
+    pub dry_run: bool,
+    /// Why the `writeReport` report couldn't be written
+    pub report_error: Option<String>,
+    /// Task the batch ran as (set on "processing-complete" events)
+    pub task_id: Option<String>,
+}
+
+impl From<&BatchSummary> for BatchSummaryDto {
+    fn from(summary: &BatchSummary) -> Self {
+        Self {
+            succeeded: summary.succeeded,
+            failed: summary.failed,
+            skipped: summary.skipped,
+            unchanged: summary.unchanged,
+            bytes_saved: summary.bytes_saved,
+            duration_ms: summary.duration.as_millis() as u64,
+            average_compression_ratio: summary.average_compression_ratio,
+            timing_totals: summary.timing_totals.map(PhaseTimingsDto::from),
+            slowest_file: summary
+                .slowest
+                .as_ref()
+                .map(|(path, _)| path.to_string_lossy().to_string()),
+            slowest_file_ms: summary.slowest.as_ref().map(|(_, ms)| *ms),
+            stop_reason: summary.stop_reason.clone(),
+            dry_run: summary.dry_run,
+            report_error: summary.report_error.clone(),
+            task_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(json: &str) -> OptimizationOptionsDto {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_merge_settings_only_overrides_set_fields() {
+        let base = options(
+            r#"{"quality": 60, "outputFormat": "jpg", "outputDirectory": "/out",
+                "overwriteExisting": true, "progressive": false}"#,
+        )
+        .to_domain()
+        .unwrap();
+
+        let merged = merge_settings(&base, &options(r#"{"quality": 95}"#)).unwrap();
+        assert_eq!(merged.quality().value(), 95);
+        assert_eq!(merged.output_formats(), &[ImageFormat::Jpeg]);
+        assert!(!merged.progressive());
+        assert!(merged.overwrite_existing());
+        assert_eq!(merged.output_directory(), &PathBuf::from("/out"));
+
+        let profiled = merge_settings(&base, &options(r#"{"profile": "thumbnail"}"#)).unwrap();
+        assert_eq!(profiled.max_long_edge(), Some(256));
+        assert!(profiled.progressive());
+        assert_eq!(profiled.output_directory(), &PathBuf::from("/out"));
+
+        assert!(merge_settings(&base, &options(r#"{"quality": 0}"#)).is_err());
+    }
+
+    #[test]
+    fn test_post_action() {
+        let settings = options(r#"{"postAction": "move", "postActionDirectory": "/done"}"#)
+            .to_domain()
+            .unwrap();
+        assert_eq!(
+            settings.post_action(),
+            &PostAction::MoveTo(PathBuf::from("/done"))
+        );
+        let dto = OptimizationOptionsDto::from(&settings);
+        assert_eq!(dto.post_action.as_deref(), Some("move"));
+        assert_eq!(
+            dto.to_domain().unwrap().post_action(),
+            settings.post_action()
+        );
+
+        assert_eq!(
+            options(r#"{"postAction": "trash"}"#)
+                .to_domain()
+                .unwrap()
+                .post_action(),
+            &PostAction::MoveToTrash
+        );
+        assert!(options(r#"{"postAction": "move"}"#).to_domain().is_err());
+        assert!(options(r#"{"postAction": "shred"}"#).to_domain().is_err());
+    }
+
+    #[test]
+    fn test_collision_strategy() {
+        let settings = options(r#"{"collisionStrategy": "timestamp"}"#)
+            .to_domain()
+            .unwrap();
+        assert_eq!(
+            settings.collision_strategy(),
+            CollisionStrategy::Rename(RenameMode::Timestamp)
+        );
+
+        let error = options(r#"{"collisionStrategy": "replace"}"#)
+            .to_domain()
+            .unwrap_err();
+        assert!(error.contains("'replace'"));
+    }
+
+    #[test]
+    fn test_on_no_improvement() {
+        let settings = options(r#"{"onNoImprovement": "copyOriginal"}"#)
+            .to_domain()
+            .unwrap();
+        assert_eq!(settings.on_no_improvement(), SkipStrategy::CopyOriginal);
+
+        let error = options(r#"{"onNoImprovement": "copy"}"#)
+            .to_domain()
+            .unwrap_err();
+        assert!(error.contains("'copy'"));
+        assert!(error.contains("saveAnyway"));
+    }
+
+    #[test]
+    fn test_options_from_settings_round_trip() {
+        let settings = options(
+            r##"{"profile": "web", "quality": 72, "outputDirectory": "/out",
+                "collisionStrategy": "numbered", "zipPath": "/out/web.zip",
+                "sizes": [320, 640], "backgroundColor": "#102030", "jpegChroma": "444",
+                "jpegProgressive": false, "jpegOptimizeHuffman": false,
+                "rawProcessing": {"whiteBalance": "custom", "customMultipliers": [2, 1, 1.5, 1]}}"##,
+        )
+        .to_domain()
+        .unwrap();
+        assert!(!settings.progressive());
+        assert!(!settings.jpeg_optimize_huffman());
+
+        let dto = OptimizationOptionsDto::from(&settings);
+        assert_eq!(dto.background_color.as_deref(), Some("#102030"));
+        assert_eq!(dto.size_suffix.as_deref(), Some("-{width}w"));
+        let again = dto.to_domain().unwrap();
+        assert_eq!(again.to_toml().unwrap(), settings.to_toml().unwrap());
+    }
+
+    #[test]
+    fn test_sharpen_preset_with_overrides() {
+        let sharpen: SharpenOptionsDto =
+            serde_json::from_str(r#"{"preset": "strong", "threshold": 0}"#).unwrap();
+        let options = sharpen.to_domain().unwrap();
+        assert_eq!(options.amount(), SharpenOptions::strong().amount());
+        assert_eq!(options.threshold(), 0);
+
+        let default: SharpenOptionsDto = serde_json::from_str("{}").unwrap();
+        assert_eq!(default.to_domain().unwrap(), SharpenOptions::moderate());
+
+        let invalid: SharpenOptionsDto = serde_json::from_str(r#"{"amount": 10}"#).unwrap();
+        assert!(invalid.to_domain().is_err());
+        let unknown: SharpenOptionsDto = serde_json::from_str(r#"{"preset": "max"}"#).unwrap();
+        assert!(unknown.to_domain().unwrap_err().contains("'max'"));
+    }
 }
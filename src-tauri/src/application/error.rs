@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::application::config_loader::ConfigError;
+use crate::domain::error::DomainError;
+use crate::infrastructure::error::InfraError;
+
+/// Error returned by every command: a machine-readable `code` the frontend can
+/// branch on (and localize), the English `message` and the file it concerns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandError {
+    /// `DomainError::code`, `InfraError::code`, `ConfigError::code` or one of the
+    /// command-level codes below
+    pub code: String,
+    pub message: String,
+    pub path: Option<String>,
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl CommandError {
+    /// Options that don't convert into valid settings or transformations
+    pub const INVALID_OPTIONS: &'static str = "invalid_options";
+    /// Nothing left to work on (no image could be loaded)
+    pub const NO_IMAGES: &'static str = "no_images";
+    /// The outputs of a batch won't fit on the output volume
+    pub const INSUFFICIENT_DISK_SPACE: &'static str = "insufficient_disk_space";
+    /// Malformed task id, or a task that doesn't exist (anymore)
+    pub const UNKNOWN_TASK: &'static str = "unknown_task";
+    /// Task bookkeeping: cancelled before it started, nothing running, no report...
+    pub const TASK_ERROR: &'static str = "task_error";
+
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    /// Options rejected while converting them (the DTOs report plain strings)
+    pub fn invalid_options(message: String) -> Self {
+        Self::new(Self::INVALID_OPTIONS, message)
+    }
+
+    /// Task manager failure (reported as plain strings)
+    pub fn task(message: String) -> Self {
+        Self::new(Self::TASK_ERROR, message)
+    }
+
+    /// Set the file the error concerns
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_string_lossy().to_string());
+        self
+    }
+}
+
+impl From<DomainError> for CommandError {
+    fn from(err: DomainError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+impl From<InfraError> for CommandError {
+    fn from(err: InfraError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+impl From<ConfigError> for CommandError {
+    fn from(err: ConfigError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_domain_error_json() {
+        let error = CommandError::from(DomainError::FileNotFound("/photos/a.jpg".to_string()))
+            .with_path(Path::new("/photos/a.jpg"));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "file_not_found",
+                "message": "File not found: /photos/a.jpg",
+                "path": "/photos/a.jpg",
+            })
+        );
+    }
+
+    #[test]
+    fn test_infra_error_json() {
+        let error = CommandError::from(InfraError::UnsupportedFormat("tiff".to_string()));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "unsupported_format",
+                "message": "Unsupported format for optimization: tiff",
+                "path": null,
+            })
+        );
+
+        // Los errores de dominio envueltos y los archivos inexistentes conservan su código
+        let wrapped = InfraError::DomainError(DomainError::InvalidQuality(0));
+        assert_eq!(CommandError::from(wrapped).code, "invalid_quality");
+        let missing = InfraError::IoError(std::io::ErrorKind::NotFound.into());
+        assert_eq!(CommandError::from(missing).code, "file_not_found");
+    }
+}
@@ -1,4 +1,8 @@
 pub mod commands;
+pub mod config_loader;
 pub mod dto;
+pub mod error;
+pub mod progress_throttle;
+pub mod report;
 pub mod state;
 pub mod task_manager;
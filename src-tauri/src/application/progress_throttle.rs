@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use crate::infrastructure::image_processor::BatchProgress;
+
+/// Rate limit for "processing-progress" events. Every update is a cumulative
+/// snapshot, so skipping the ones in between loses nothing: the next event
+/// carries the coalesced state. The first and the last update always go out.
+pub struct ProgressThrottle {
+    /// None = no limit
+    min_interval: Option<Duration>,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressThrottle {
+    /// Progress events per second when the request doesn't set a rate
+    pub const DEFAULT_EVENTS_PER_SECOND: u32 = 10;
+
+    /// At most `events_per_second` events (0 = emit every update)
+    pub fn new(events_per_second: u32) -> Self {
+        Self {
+            min_interval: (events_per_second > 0)
+                .then(|| Duration::from_secs(1) / events_per_second),
+            last_emit: None,
+        }
+    }
+
+    /// Whether `progress`, reported at `now`, should be emitted
+    pub fn should_emit(&mut self, now: Instant, progress: &BatchProgress) -> bool {
+        let is_last = progress.current >= progress.total;
+        let due = match (self.last_emit, self.min_interval) {
+            (Some(last), Some(interval)) => now.saturating_duration_since(last) >= interval,
+            _ => true,
+        };
+
+        if due || is_last {
+            self.last_emit = Some(now);
+        }
+        due || is_last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(current: usize, total: usize) -> BatchProgress {
+        BatchProgress {
+            current,
+            total,
+            current_file: "a.png".to_string(),
+            elapsed_ms: 0,
+            estimated_remaining_ms: None,
+            bytes_processed: 0,
+            throughput_mb_per_sec: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_coalesces_updates_within_interval() {
+        let start = Instant::now();
+        let mut throttle = ProgressThrottle::new(10);
+
+        // Primero siempre; luego como mucho uno cada 100 ms
+        assert!(throttle.should_emit(start, &progress(1, 100)));
+        assert!(!throttle.should_emit(start + Duration::from_millis(30), &progress(2, 100)));
+        assert!(!throttle.should_emit(start + Duration::from_millis(99), &progress(3, 100)));
+        assert!(throttle.should_emit(start + Duration::from_millis(100), &progress(4, 100)));
+        assert!(!throttle.should_emit(start + Duration::from_millis(150), &progress(5, 100)));
+
+        // El último sale aunque no haya pasado el intervalo
+        assert!(throttle.should_emit(start + Duration::from_millis(160), &progress(100, 100)));
+    }
+
+    #[test]
+    fn test_zero_rate_disables_throttling() {
+        let start = Instant::now();
+        let mut throttle = ProgressThrottle::new(0);
+
+        for current in 1..=5 {
+            assert!(throttle.should_emit(start, &progress(current, 10)));
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::application::dto::{BatchSummaryDto, OptimizationOptionsDto, ProcessedImageDto};
+use crate::application::task_manager::BatchSummary;
+use crate::domain::{ImageFormat, ProcessingSettings};
+use crate::infrastructure::image_processor::ProcessingResult;
+
+/// Summary of a completed batch, kept in `AppState` and exportable as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchReport {
+    pub total_images: usize,
+    pub success_count: usize,
+    pub failure_count: usize,
+    /// Images left unwritten (or copied) because they didn't compress enough
+    pub skipped_count: usize,
+    /// Sum of original file sizes
+    pub total_bytes_in: u64,
+    /// Sum of output file sizes (successful images only)
+    pub total_bytes_out: u64,
+    /// Breakdown keyed by input format ("png", "jpg", "raw", ...)
+    pub format_breakdown: BTreeMap<String, FormatBreakdown>,
+    pub slowest_image: Option<ImageTiming>,
+    pub fastest_image: Option<ImageTiming>,
+    /// Mean processing time of the successful images (0 when there are none)
+    pub average_processing_time_ms: u64,
+    /// Wall-clock time for the whole batch
+    pub total_time_ms: u64,
+    /// Failures grouped by error type
+    pub errors: Vec<ProcessingErrorSummary>,
+}
+
+/// Per-format counters inside a `BatchReport`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatBreakdown {
+    pub count: usize,
+    pub success_count: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+/// Processing time of a single image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageTiming {
+    pub path: String,
+    pub processing_time_ms: u64,
+}
+
+/// Group of failures sharing the same error type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessingErrorSummary {
+    pub error_type: String,
+    pub count: usize,
+    pub files: Vec<String>,
+}
+
+impl BatchReport {
+    /// Build a report from the results of a batch and its wall-clock duration
+    pub fn from_results(results: &[ProcessingResult], elapsed: Duration) -> Self {
+        let mut report = BatchReport {
+            total_images: results.len(),
+            success_count: 0,
+            failure_count: 0,
+            skipped_count: 0,
+            total_bytes_in: 0,
+            total_bytes_out: 0,
+            format_breakdown: BTreeMap::new(),
+            slowest_image: None,
+            fastest_image: None,
+            average_processing_time_ms: 0,
+            total_time_ms: elapsed.as_millis() as u64,
+            errors: Vec::new(),
+        };
+
+        let mut processing_time_sum = 0;
+        for result in results {
+            let path = result.original_path.to_string_lossy().to_string();
+            let entry = report
+                .format_breakdown
+                .entry(Self::format_key(&result.original_path))
+                .or_default();
+            entry.count += 1;
+            entry.bytes_in += result.original_size;
+            report.total_bytes_in += result.original_size;
+
+            if !result.success {
+                report.failure_count += 1;
+                report.add_error(Self::error_type(result), path);
+                continue;
+            }
+
+            report.success_count += 1;
+            entry.success_count += 1;
+            if result.skipped {
+                report.skipped_count += 1;
+            }
+            entry.bytes_out += result.output_size;
+            report.total_bytes_out += result.output_size;
+            processing_time_sum += result.processing_time_ms;
+
+            let timing = ImageTiming {
+                path,
+                processing_time_ms: result.processing_time_ms,
+            };
+            if report
+                .slowest_image
+                .as_ref()
+                .map_or(true, |t| timing.processing_time_ms > t.processing_time_ms)
+            {
+                report.slowest_image = Some(timing.clone());
+            }
+            if report
+                .fastest_image
+                .as_ref()
+                .map_or(true, |t| timing.processing_time_ms < t.processing_time_ms)
+            {
+                report.fastest_image = Some(timing);
+            }
+        }
+        if report.success_count > 0 {
+            report.average_processing_time_ms = processing_time_sum / report.success_count as u64;
+        }
+
+        report
+    }
+
+    /// Write the report as pretty-printed JSON
+    pub fn export_json(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Failed to write report to {}: {}", path.display(), e))
+    }
+
+    fn add_error(&mut self, error_type: String, path: String) {
+        match self.errors.iter_mut().find(|e| e.error_type == error_type) {
+            Some(summary) => {
+                summary.count += 1;
+                summary.files.push(path);
+            }
+            None => self.errors.push(ProcessingErrorSummary {
+                error_type,
+                count: 1,
+                files: vec![path],
+            }),
+        }
+    }
+
+    /// Input format name used as breakdown key
+    fn format_key(path: &Path) -> String {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ImageFormat::from_extension(ext).ok())
+            .map(|format| format.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Error type is the `DomainError::code` of the failure ("decode_failed",
+    /// "disk_full", ...), or its whole message when it has no code
+    fn error_type(result: &ProcessingResult) -> String {
+        result
+            .error_code
+            .clone()
+            .or_else(|| result.error_message.clone())
+            .unwrap_or_else(|| "Unknown error".to_string())
+    }
+}
+
+/// What happened to every file of a batch, for auditing a run: every result with
+/// the batch summary and the settings used. Written as JSON, or as CSV with one
+/// row per output (results only) when the path ends in `.csv`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultsReport {
+    pub summary: Option<BatchSummaryDto>,
+    pub settings: OptimizationOptionsDto,
+    pub results: Vec<ProcessedImageDto>,
+}
+
+impl ResultsReport {
+    const CSV_HEADER: [&'static str; 12] = [
+        "original_path",
+        "output_path",
+        "success",
+        "skipped",
+        "original_size",
+        "output_size",
+        "compression_ratio",
+        "duration_ms",
+        "target_width",
+        "final_quality",
+        "post_action",
+        "error_message",
+    ];
+
+    pub fn new(
+        results: &[ProcessingResult],
+        summary: Option<&BatchSummary>,
+        settings: &ProcessingSettings,
+    ) -> Self {
+        Self {
+            summary: summary.map(BatchSummaryDto::from),
+            settings: OptimizationOptionsDto::from(settings),
+            results: results
+                .iter()
+                .cloned()
+                .map(ProcessedImageDto::from)
+                .collect(),
+        }
+    }
+
+    /// Write the report to `path`: CSV for a `.csv` extension, pretty-printed JSON otherwise
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv {
+            self.to_csv()
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())?
+        };
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write report to {}: {}", path.display(), e))
+    }
+
+    /// Header plus one row per result
+    pub fn to_csv(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mut csv = Self::csv_row(Self::CSV_HEADER.map(String::from));
+        for result in &self.results {
+            csv.push_str(&Self::csv_row([
+                result.original_path.clone(),
+                result.output_path.clone(),
+                result.success.to_string(),
+                result.skipped.to_string(),
+                result.original_size.to_string(),
+                result.output_size.to_string(),
+                format!("{:.2}", result.compression_ratio),
+                result.duration_ms.to_string(),
+                optional(result.target_width.map(|width| width.to_string())),
+                optional(result.final_quality.map(|quality| quality.to_string())),
+                optional(result.post_action.clone()),
+                optional(result.error_message.clone()),
+            ]));
+        }
+        csv
+    }
+
+    fn csv_row(fields: [String; 12]) -> String {
+        let mut row = fields
+            .iter()
+            .map(|field| Self::csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        row.push_str("\r\n");
+        row
+    }
+
+    /// RFC 4180: quoted (with quotes doubled) when it holds a comma, quote or line break
+    fn csv_field(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn result(path: &str, success: bool, time_ms: u64, error: Option<&str>) -> ProcessingResult {
+        ProcessingResult {
+            original_path: PathBuf::from(path),
+            output_path: PathBuf::from("out"),
+            original_size: 1000,
+            output_size: if success { 400 } else { 0 },
+            success,
+            error_message: error.map(|e| e.to_string()),
+            processing_time_ms: time_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_report_totals() {
+        let results = vec![
+            result("/a.png", true, 30, None),
+            result("/b.jpg", true, 10, None),
+            result("/c.jpg", false, 5, Some("Processing failed: bad data")),
+        ];
+
+        let report = BatchReport::from_results(&results, Duration::from_millis(50));
+
+        assert_eq!(report.total_images, 3);
+        assert_eq!(report.success_count, 2);
+        assert_eq!(report.failure_count, 1);
+        assert_eq!(report.total_bytes_in, 3000);
+        assert_eq!(report.total_bytes_out, 800);
+        assert_eq!(report.total_time_ms, 50);
+        assert_eq!(report.format_breakdown["jpg"].count, 2);
+        assert_eq!(report.format_breakdown["png"].bytes_out, 400);
+        assert_eq!(report.slowest_image.as_ref().unwrap().path, "/a.png");
+        assert_eq!(report.fastest_image.as_ref().unwrap().path, "/b.jpg");
+        assert_eq!(report.average_processing_time_ms, 20);
+    }
+
+    #[test]
+    fn test_results_report_csv_escaping() {
+        let results = vec![
+            result("/photos/a, \"best\".png", true, 30, None),
+            result("/b.jpg", false, 5, Some("Processing failed: bad data")),
+        ];
+        let report = ResultsReport::new(&results, None, &ProcessingSettings::default());
+
+        let csv = report.to_csv();
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(rows.len(), 4);
+        assert!(rows[0].starts_with("original_path,output_path,success"));
+        assert!(rows[1]
+            .starts_with("\"/photos/a, \"\"best\"\".png\",out,true,false,1000,400,60.00,30,"));
+        assert!(rows[2].ends_with(",Processing failed: bad data"));
+        assert_eq!(rows[3], "");
+    }
+
+    #[test]
+    fn test_results_report_written_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let results = vec![result("/a.png", true, 30, None)];
+        let mut settings = ProcessingSettings::default();
+        settings.set_overwrite_existing(true);
+        let report = ResultsReport::new(&results, None, &settings);
+
+        report.write(&dir.path().join("report.CSV")).unwrap();
+        report.write(&dir.path().join("report.json")).unwrap();
+
+        let csv = std::fs::read_to_string(dir.path().join("report.CSV")).unwrap();
+        assert!(csv.starts_with("original_path,"));
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("report.json")).unwrap())
+                .unwrap();
+        assert_eq!(json["results"][0]["originalPath"], "/a.png");
+        assert_eq!(json["settings"]["overwriteExisting"], true);
+        assert!(json["summary"].is_null());
+    }
+
+    #[test]
+    fn test_errors_grouped_by_type() {
+        let with_code = |path: &str, message: &str, code: &str| ProcessingResult {
+            error_code: Some(code.to_string()),
+            ..result(path, false, 0, Some(message))
+        };
+        let results = vec![
+            with_code("/a.png", "Processing failed: bad data", "decode_failed"),
+            with_code("/b.png", "Processing failed: truncated", "decode_failed"),
+            // Mismo prefijo, otro tipo de fallo
+            with_code("/c.png", "Processing failed: no space left", "disk_full"),
+            result("/d.png", false, 0, Some("Operation cancelled")),
+        ];
+
+        let report = BatchReport::from_results(&results, Duration::ZERO);
+
+        assert_eq!(report.errors.len(), 3);
+        assert_eq!(report.errors[0].error_type, "decode_failed");
+        assert_eq!(report.errors[0].count, 2);
+        assert_eq!(report.errors[1].error_type, "disk_full");
+        assert_eq!(report.errors[1].files, vec!["/c.png"]);
+        assert_eq!(report.errors[2].error_type, "Operation cancelled");
+    }
+}
@@ -1,6 +1,12 @@
+use crate::application::report::BatchReport;
 use crate::application::task_manager::TaskManager;
+use crate::domain::ImageFormat;
+use crate::infrastructure::file_system::WatchHandle;
+use crate::infrastructure::image_processor::ImageCache;
 use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 /// Application state shared across commands
 pub struct AppState {
@@ -8,6 +14,12 @@ pub struct AppState {
     pub task_manager: Arc<TaskManager>,
     /// Optional: Store processing statistics
     pub stats: Arc<Mutex<ProcessingStats>>,
+    /// Report of the last completed batch
+    pub last_report: Arc<Mutex<Option<BatchReport>>>,
+    /// Active folder watch (dropping it stops watching)
+    pub folder_watch: Mutex<Option<WatchHandle>>,
+    /// Decoded images kept between batches (used when `use_cache` is set)
+    pub image_cache: Arc<Mutex<ImageCache>>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -15,19 +27,75 @@ pub struct ProcessingStats {
     pub total_processed: usize,
     pub total_saved_bytes: u64,
     pub total_images_processed: usize,
+    /// Breakdown keyed by input format
+    pub per_format: HashMap<ImageFormat, FormatStats>,
+    /// When the first image since the last reset was recorded
+    pub session_start: Option<SystemTime>,
+}
+
+/// Totals for the images of one input format
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FormatStats {
+    pub count: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// Images written in a different format than they were read
+    pub converted: usize,
 }
 
 impl ProcessingStats {
-    pub fn add_processed(&mut self, bytes_saved: u64) {
+    /// Record a processed image. `formats` is the (input, output) pair, when both are known;
+    /// `sizes` are its (input, output) byte counts.
+    pub fn add_processed(
+        &mut self,
+        bytes_saved: u64,
+        formats: Option<(ImageFormat, ImageFormat)>,
+        sizes: (u64, u64),
+    ) {
         self.total_processed += 1;
         self.total_saved_bytes += bytes_saved;
         self.total_images_processed += 1;
+        self.session_start.get_or_insert_with(SystemTime::now);
+
+        if let Some((input, output)) = formats {
+            let entry = self.per_format.entry(input).or_default();
+            entry.count += 1;
+            entry.bytes_in += sizes.0;
+            entry.bytes_out += sizes.1;
+            if input != output {
+                entry.converted += 1;
+            }
+        }
     }
 
     pub fn reset(&mut self) {
         self.total_processed = 0;
         self.total_saved_bytes = 0;
         self.total_images_processed = 0;
+        self.per_format.clear();
+        self.session_start = None;
+    }
+
+    /// Percentage of input bytes saved for images of `format` (0 if none were processed)
+    pub fn average_compression_ratio_for_format(&self, format: ImageFormat) -> f64 {
+        match self.per_format.get(&format) {
+            Some(stats) if stats.bytes_in > 0 => {
+                let saved = stats.bytes_in.saturating_sub(stats.bytes_out) as f64;
+                saved / stats.bytes_in as f64 * 100.0
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Seconds since the first image of the session (None before any image)
+    pub fn session_duration_secs(&self) -> Option<u64> {
+        let start = self.session_start?;
+        Some(
+            SystemTime::now()
+                .duration_since(start)
+                .unwrap_or_default()
+                .as_secs(),
+        )
     }
 
     pub fn average_savings(&self) -> f64 {
@@ -40,15 +108,24 @@ impl ProcessingStats {
 
 impl AppState {
     pub fn new() -> Self {
+        let image_cache = Arc::new(Mutex::new(ImageCache::default()));
         Self {
-            task_manager: Arc::new(TaskManager::new()),
+            task_manager: Arc::new(TaskManager::with_image_cache(image_cache.clone())),
             stats: Arc::new(Mutex::new(ProcessingStats::default())),
+            last_report: Arc::new(Mutex::new(None)),
+            folder_watch: Mutex::new(None),
+            image_cache,
         }
     }
 
-    pub fn update_stats(&self, bytes_saved: u64) {
+    pub fn update_stats(
+        &self,
+        bytes_saved: u64,
+        formats: Option<(ImageFormat, ImageFormat)>,
+        sizes: (u64, u64),
+    ) {
         let mut stats = self.stats.lock();
-        stats.add_processed(bytes_saved);
+        stats.add_processed(bytes_saved, formats, sizes);
     }
 
     pub fn get_stats(&self) -> ProcessingStats {
@@ -58,6 +135,14 @@ impl AppState {
     pub fn reset_stats(&self) {
         self.stats.lock().reset();
     }
+
+    pub fn set_last_report(&self, report: BatchReport) {
+        *self.last_report.lock() = Some(report);
+    }
+
+    pub fn get_last_report(&self) -> Option<BatchReport> {
+        self.last_report.lock().clone()
+    }
 }
 
 impl Default for AppState {
@@ -65,3 +150,38 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_format_stats() {
+        let mut stats = ProcessingStats::default();
+        assert_eq!(stats.session_duration_secs(), None);
+
+        stats.add_processed(
+            600,
+            Some((ImageFormat::Png, ImageFormat::Webp)),
+            (1000, 400),
+        );
+        stats.add_processed(100, Some((ImageFormat::Png, ImageFormat::Png)), (1000, 900));
+        stats.add_processed(50, None, (100, 50));
+
+        assert_eq!(stats.total_processed, 3);
+        assert_eq!(stats.total_saved_bytes, 750);
+        let png = &stats.per_format[&ImageFormat::Png];
+        assert_eq!(png.count, 2);
+        assert_eq!(png.converted, 1);
+        assert!((stats.average_compression_ratio_for_format(ImageFormat::Png) - 35.0).abs() < 1e-9);
+        assert_eq!(
+            stats.average_compression_ratio_for_format(ImageFormat::Jpeg),
+            0.0
+        );
+        assert!(stats.session_duration_secs().is_some());
+
+        stats.reset();
+        assert!(stats.per_format.is_empty());
+        assert_eq!(stats.session_start, None);
+    }
+}
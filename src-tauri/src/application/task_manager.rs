@@ -1,124 +1,898 @@
 use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{mpsc, Semaphore};
+use uuid::Uuid;
 
-use crate::domain::{Image, ProcessingSettings, Transformation};
-use crate::infrastructure::image_processor::{BatchProcessor, ProcessingResult, ProgressCallback};
+use crate::application::report::ResultsReport;
+use crate::domain::{Image, OutputMode, ProcessingSettings, Transformation};
+use crate::infrastructure::file_system::FileHandler;
+use crate::infrastructure::image_processor::{
+    BatchProcessor, ImageCache, ImageCancelSignals, ImageProcessorImpl, PhaseTimings,
+    ProcessingResult, ProgressCallback,
+};
 
 /// Status of a processing task
 #[derive(Debug, Clone, PartialEq)]
 pub enum TaskStatus {
     Idle,
+    /// Waiting for a free slot
+    Queued,
     Running,
     Completed,
     Cancelled,
     Error(String),
 }
 
-/// Task manager for handling async image processing
-pub struct TaskManager {
-    batch_processor: Arc<BatchProcessor>,
+/// Whether the output volume can hold a batch, checked before starting it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightResult {
+    Ok,
+    /// Estimated output size and free space on the output volume, in bytes
+    InsufficientSpace {
+        needed: u64,
+        available: u64,
+    },
+}
+
+impl PreflightResult {
+    /// Compare an estimated output size with the free space (None = unknown, assume it fits)
+    pub fn new(needed: u64, available: Option<u64>) -> Self {
+        match available {
+            Some(available) if needed > available => {
+                PreflightResult::InsufficientSpace { needed, available }
+            }
+            _ => PreflightResult::Ok,
+        }
+    }
+}
+
+/// Why a batch of a folder watch wasn't processed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchBatchError {
+    /// Its outputs won't fit on the output volume (`TaskManager::preflight_check`)
+    InsufficientSpace { needed: u64, available: u64 },
+    /// The task failed
+    Task(String),
+}
+
+/// Outcome of the last batch, kept by the `TaskManager` next to its results
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchSummary {
+    /// Outputs written
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Outputs left unwritten (or copied) because they didn't compress enough
+    pub skipped: usize,
+    /// Outputs left alone by `skip_unchanged` because they were up to date
+    pub unchanged: usize,
+    /// Bytes saved by the written outputs
+    pub bytes_saved: u64,
+    /// Wall-clock time of the whole batch
+    pub duration: Duration,
+    /// Mean `ProcessingResult::compression_ratio` of the written outputs (percentage saved)
+    pub average_compression_ratio: f64,
+    /// Sum of the phase timings of every output (None unless `phase_timings` was set)
+    pub timing_totals: Option<PhaseTimings>,
+    /// Input that took longest, with its processing time in milliseconds
+    pub slowest: Option<(PathBuf, u64)>,
+    /// Why the batch stopped before processing every image (None = it ran to the end)
+    pub stop_reason: Option<String>,
+    /// Results of a dry run: nothing was written and `bytes_saved` is an estimate
+    pub dry_run: bool,
+    /// Why the `write_report` report couldn't be written (None = written or not asked for)
+    pub report_error: Option<String>,
+}
+
+impl BatchSummary {
+    /// Summarize the results of a batch that took `duration`
+    pub fn from_results(results: &[ProcessingResult], duration: Duration) -> Self {
+        let mut summary = BatchSummary {
+            succeeded: 0,
+            failed: 0,
+            skipped: 0,
+            unchanged: 0,
+            bytes_saved: 0,
+            duration,
+            average_compression_ratio: 0.0,
+            timing_totals: None,
+            slowest: None,
+            stop_reason: None,
+            dry_run: results.iter().any(|result| result.dry_run),
+            report_error: None,
+        };
+
+        let mut ratio_sum = 0.0;
+        for result in results {
+            if let Some(timings) = result.timings {
+                *summary
+                    .timing_totals
+                    .get_or_insert_with(PhaseTimings::default) += timings;
+            }
+            if summary
+                .slowest
+                .as_ref()
+                .map_or(true, |(_, ms)| result.processing_time_ms > *ms)
+            {
+                summary.slowest = Some((result.original_path.clone(), result.processing_time_ms));
+            }
+
+            match result.error_message.as_deref() {
+                Some(BatchProcessor::ABORTED_MESSAGE) => {
+                    summary.stop_reason = Some(
+                        "Stopped early: too many images failed in a row (abortAfterFailures)"
+                            .to_string(),
+                    );
+                }
+                Some(BatchProcessor::CANCELLED_MESSAGE) if summary.stop_reason.is_none() => {
+                    summary.stop_reason = Some("Cancelled by the user".to_string());
+                }
+                _ => {}
+            }
+
+            if !result.success {
+                summary.failed += 1;
+            } else if result.unchanged {
+                summary.unchanged += 1;
+            } else if result.skipped {
+                summary.skipped += 1;
+            } else {
+                summary.succeeded += 1;
+                summary.bytes_saved += result.bytes_saved();
+                ratio_sum += result.compression_ratio();
+            }
+        }
+        if summary.succeeded > 0 {
+            summary.average_compression_ratio = ratio_sum / summary.succeeded as f64;
+        }
+
+        summary
+    }
+}
+
+/// Batches waiting in the `TaskManager` queue and running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueStatus {
+    pub pending: usize,
+    /// Tasks running, priority ones included
+    pub active: usize,
+}
+
+/// Identifies one processing task of the `TaskManager`
+pub type TaskId = Uuid;
+
+/// Where a task stands, for status queries and status listeners
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskSnapshot {
+    pub task_id: TaskId,
+    pub status: TaskStatus,
+    /// Outputs finished and in the batch (None before its first progress update)
+    pub progress: Option<(usize, usize)>,
+    /// When it started running (None while queued)
+    pub started_at: Option<SystemTime>,
+}
+
+/// Called after every status change of a task
+pub type StatusListener = Arc<dyn Fn(&TaskSnapshot) + Send + Sync>;
+
+/// One processing task: its signals while it runs and its outcome once done
+struct Task {
+    status: TaskStatus,
+    /// Starts right away instead of waiting for a slot
+    priority: bool,
+    /// Waiting for a slot (counted in `queue_status().pending`)
+    waiting: bool,
     cancel_signal: Arc<AtomicBool>,
-    status: Arc<RwLock<TaskStatus>>,
-    results: Arc<Mutex<Vec<ProcessingResult>>>,
+    /// Skip one image of the task without cancelling it
+    image_cancel_signals: ImageCancelSignals,
+    results: Vec<ProcessingResult>,
+    summary: Option<BatchSummary>,
+    /// Settings the task ran with, for its report
+    settings: Option<ProcessingSettings>,
+    progress: Option<(usize, usize)>,
+    started_at: Option<SystemTime>,
+}
+
+impl Task {
+    fn new(priority: bool) -> Self {
+        Self {
+            status: TaskStatus::Queued,
+            priority,
+            waiting: false,
+            cancel_signal: Arc::new(AtomicBool::new(false)),
+            image_cancel_signals: ImageCancelSignals::default(),
+            results: Vec::new(),
+            summary: None,
+            settings: None,
+            progress: None,
+            started_at: None,
+        }
+    }
+
+    fn snapshot(&self, task_id: TaskId) -> TaskSnapshot {
+        TaskSnapshot {
+            task_id,
+            status: self.status.clone(),
+            progress: self.progress,
+            started_at: self.started_at,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            TaskStatus::Completed | TaskStatus::Cancelled | TaskStatus::Error(_)
+        )
+    }
+}
+
+/// Every task the `TaskManager` knows about
+#[derive(Default)]
+struct Tasks {
+    by_id: HashMap<TaskId, Task>,
+    /// Creation order, oldest first
+    order: Vec<TaskId>,
+    /// Task whose results `get_results` and `get_last_summary` return
+    last_finished: Option<TaskId>,
+}
+
+impl Tasks {
+    /// Most recently created task matching `filter`
+    fn latest(&self, filter: impl Fn(&Task) -> bool) -> Option<TaskId> {
+        self.order
+            .iter()
+            .rev()
+            .find(|id| self.by_id.get(id).is_some_and(&filter))
+            .copied()
+    }
+
+    /// Change the status of a task; its snapshot if it exists
+    fn set_status(&mut self, task_id: TaskId, status: TaskStatus) -> Option<TaskSnapshot> {
+        let task = self.by_id.get_mut(&task_id)?;
+        if status == TaskStatus::Running {
+            task.started_at = Some(SystemTime::now());
+        }
+        task.status = status;
+        Some(task.snapshot(task_id))
+    }
+
+    /// Forget the oldest finished tasks past `keep` (never the last one to finish)
+    fn prune(&mut self, keep: usize) {
+        let finished: Vec<TaskId> = self
+            .order
+            .iter()
+            .filter(|id| Some(**id) != self.last_finished && self.by_id[id].is_finished())
+            .copied()
+            .collect();
+        let excess = finished.len().saturating_sub(keep);
+        for id in &finished[..excess] {
+            self.by_id.remove(id);
+        }
+        let by_id = &self.by_id;
+        self.order.retain(|id| by_id.contains_key(id));
+    }
+}
+
+/// Quiet period after the last folder-watch event before its batch starts
+/// (a file being copied keeps firing events until it is complete)
+const WATCH_SETTLE: Duration = Duration::from_millis(750);
+
+/// Task manager for handling async image processing. Every batch is a task with
+/// its own id, status, results and cancel signal; heavy tasks take turns for a
+/// limited number of slots, priority ones (e.g. single-image previews) skip the line.
+pub struct TaskManager {
+    /// Shared by the processors of every task (None = always decode)
+    image_cache: Option<Arc<Mutex<ImageCache>>>,
+    /// Loads the images of the batches before they are queued
+    loader: Arc<ImageProcessorImpl>,
+    /// Shared with the progress callbacks of the running tasks
+    tasks: Arc<Mutex<Tasks>>,
+    status_listener: Mutex<Option<StatusListener>>,
+    /// One per heavy task allowed to run at once; the others wait for one in
+    /// arrival order (tokio's semaphore is fair)
+    slots: Semaphore,
 }
 
 impl TaskManager {
+    /// Heavy tasks running at once unless `with_max_running_tasks` says otherwise
+    pub const DEFAULT_MAX_RUNNING_TASKS: usize = 1;
+    /// Finished tasks whose results are kept, besides the last one
+    const KEPT_FINISHED_TASKS: usize = 16;
+
     pub fn new() -> Self {
+        Self::with_cache(None)
+    }
+
+    /// Task manager whose batches can reuse decoded images from `cache`
+    pub fn with_image_cache(cache: Arc<Mutex<ImageCache>>) -> Self {
+        Self::with_cache(Some(cache))
+    }
+
+    fn with_cache(image_cache: Option<Arc<Mutex<ImageCache>>>) -> Self {
+        let mut loader = ImageProcessorImpl::new();
+        if let Some(cache) = &image_cache {
+            loader = loader.with_image_cache(cache.clone());
+        }
         Self {
-            batch_processor: Arc::new(BatchProcessor::new()),
-            cancel_signal: Arc::new(AtomicBool::new(false)),
-            status: Arc::new(RwLock::new(TaskStatus::Idle)),
-            results: Arc::new(Mutex::new(Vec::new())),
+            image_cache,
+            loader: Arc::new(loader),
+            tasks: Arc::new(Mutex::new(Tasks::default())),
+            status_listener: Mutex::new(None),
+            slots: Semaphore::new(Self::DEFAULT_MAX_RUNNING_TASKS),
+        }
+    }
+
+    /// Let `max` heavy tasks run at once (at least one)
+    pub fn with_max_running_tasks(mut self, max: usize) -> Self {
+        self.slots = Semaphore::new(max.max(1));
+        self
+    }
+
+    /// Call `listener` after every status change of a task (replaces any previous one)
+    pub fn set_status_listener(&self, listener: StatusListener) {
+        *self.status_listener.lock() = Some(listener);
+    }
+
+    /// Tell the status listener about `snapshot`. Never call it with `tasks` locked:
+    /// the listener may query the manager.
+    fn notify(&self, snapshot: Option<TaskSnapshot>) {
+        let listener = self.status_listener.lock().clone();
+        if let (Some(listener), Some(snapshot)) = (listener, snapshot) {
+            listener(&snapshot);
+        }
+    }
+
+    /// Record the progress of task `task_id` before passing it on to `callback`
+    fn track_progress(
+        &self,
+        task_id: TaskId,
+        callback: Option<ProgressCallback>,
+    ) -> Option<ProgressCallback> {
+        let tasks = Arc::clone(&self.tasks);
+        Some(Arc::new(move |progress, results| {
+            if let Some(task) = tasks.lock().by_id.get_mut(&task_id) {
+                task.progress = Some((progress.current, progress.total));
+            }
+            if let Some(callback) = &callback {
+                callback(progress, results);
+            }
+        }))
+    }
+
+    /// Processor to load the images of a batch with
+    pub fn processor(&self) -> Arc<ImageProcessorImpl> {
+        self.loader.clone()
+    }
+
+    /// Check there is room for a batch's outputs before running it. The size comes from
+    /// `ImageProcessorImpl::estimate_output_size`, so it is only an approximation.
+    pub fn preflight_check(images: &[Image], settings: &ProcessingSettings) -> PreflightResult {
+        // Una simulación no escribe nada
+        if settings.dry_run() {
+            return PreflightResult::Ok;
+        }
+        let processor = ImageProcessorImpl::for_settings(settings);
+        let needed = images
+            .iter()
+            .filter_map(|image| {
+                processor
+                    .estimate_output_size(image, &settings.settings_for(image.path()))
+                    .ok()
+            })
+            .sum();
+
+        let output_dir = match settings.output_mode() {
+            OutputMode::Zip(archive) => archive
+                .parent()
+                .unwrap_or(settings.output_directory().as_path()),
+            // In situ: el volumen de las entradas (se toma el de la primera)
+            OutputMode::Files if settings.in_place() => images
+                .first()
+                .and_then(|image| image.path().parent())
+                .unwrap_or(settings.output_directory().as_path()),
+            OutputMode::Files => settings.output_directory().as_path(),
+        };
+        PreflightResult::new(needed, FileHandler::available_space(output_dir))
+    }
+
+    /// `preflight_check` for files that aren't loaded yet; those that fail to load
+    /// are left out of the estimate
+    pub fn preflight_paths(
+        &self,
+        paths: &[PathBuf],
+        settings: &ProcessingSettings,
+    ) -> PreflightResult {
+        let images: Vec<Image> = paths
+            .iter()
+            .filter_map(|path| self.loader.load_image_for_settings(path, settings).ok())
+            .collect();
+        Self::preflight_check(&images, settings)
+    }
+
+    /// Register a task to run one batch with; a `priority` task doesn't wait for
+    /// the heavy tasks ahead of it
+    pub fn create_task(&self, priority: bool) -> TaskId {
+        let task_id = Uuid::new_v4();
+        let task = Task::new(priority);
+        let snapshot = task.snapshot(task_id);
+        {
+            let mut tasks = self.tasks.lock();
+            tasks.by_id.insert(task_id, task);
+            tasks.order.push(task_id);
         }
+        self.notify(Some(snapshot));
+        task_id
     }
 
-    /// Start processing images asynchronously
+    /// Process images as task `task_id`
     pub async fn process_images(
         &self,
+        task_id: TaskId,
         images: Vec<Image>,
         transformation: Option<Transformation>,
         settings: ProcessingSettings,
         progress_callback: Option<ProgressCallback>,
     ) -> Result<Vec<ProcessingResult>, String> {
-        // Verificar si ya hay una tarea corriendo
-        {
-            let current_status = self.status.read().await;
-            if *current_status == TaskStatus::Running {
-                return Err("A task is already running".to_string());
+        let progress_callback = self.track_progress(task_id, progress_callback);
+        self.run(
+            task_id,
+            settings.clone(),
+            move |batch_processor, cancel_signal| {
+                batch_processor.process_batch(
+                    images,
+                    transformation,
+                    settings,
+                    cancel_signal,
+                    progress_callback,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Discover and process a folder as task `task_id`
+    pub async fn process_folder(
+        &self,
+        task_id: TaskId,
+        dir: PathBuf,
+        max_depth: Option<usize>,
+        transformation: Option<Transformation>,
+        settings: ProcessingSettings,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<Vec<ProcessingResult>, String> {
+        let progress_callback = self.track_progress(task_id, progress_callback);
+        self.run(
+            task_id,
+            settings.clone(),
+            move |batch_processor, cancel_signal| {
+                batch_processor.process_folder(
+                    &dir,
+                    max_depth,
+                    transformation,
+                    settings,
+                    cancel_signal,
+                    progress_callback,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Load and process files by path as task `task_id`
+    pub async fn process_paths(
+        &self,
+        task_id: TaskId,
+        paths: Vec<PathBuf>,
+        transformation: Option<Transformation>,
+        settings: ProcessingSettings,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Result<Vec<ProcessingResult>, String> {
+        let progress_callback = self.track_progress(task_id, progress_callback);
+        self.run(
+            task_id,
+            settings.clone(),
+            move |batch_processor, cancel_signal| {
+                batch_processor.process_paths(
+                    paths,
+                    transformation,
+                    settings,
+                    cancel_signal,
+                    progress_callback,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Process the paths sent by a folder watch until every sender is dropped.
+    /// Paths arriving less than `WATCH_SETTLE` apart form one batch, which waits for
+    /// its files to stop growing and is then queued as a task of its own, unless its
+    /// outputs won't fit on the output volume (`preflight_paths`);
+    /// `progress_callback` makes the progress callback of each task and `on_batch`
+    /// gets its results (or why it couldn't run) and start time.
+    pub async fn process_watched(
+        &self,
+        mut paths: mpsc::Receiver<PathBuf>,
+        transformation: Option<Transformation>,
+        settings: ProcessingSettings,
+        progress_callback: impl Fn(TaskId) -> Option<ProgressCallback> + Send,
+        on_batch: impl Fn(Result<Vec<ProcessingResult>, WatchBatchError>, Instant) + Send,
+    ) {
+        while let Some(first) = paths.recv().await {
+            let mut batch = vec![first];
+            while let Ok(Some(path)) = tokio::time::timeout(WATCH_SETTLE, paths.recv()).await {
+                if !batch.contains(&path) {
+                    batch.push(path);
+                }
+            }
+
+            Self::wait_until_complete(&batch).await;
+
+            let started = Instant::now();
+            if let PreflightResult::InsufficientSpace { needed, available } =
+                self.preflight_paths(&batch, &settings)
+            {
+                on_batch(
+                    Err(WatchBatchError::InsufficientSpace { needed, available }),
+                    started,
+                );
+                continue;
             }
+
+            let task_id = self.create_task(false);
+            let results = self
+                .process_paths(
+                    task_id,
+                    batch,
+                    transformation.clone(),
+                    settings.clone(),
+                    progress_callback(task_id),
+                )
+                .await
+                .map_err(WatchBatchError::Task);
+            on_batch(results, started);
         }
+    }
 
-        // Reset cancel signal y status
-        self.cancel_signal.store(false, Ordering::SeqCst);
-        *self.status.write().await = TaskStatus::Running;
-        self.results.lock().clear();
+    /// Wait until none of `paths` changes size over `WATCH_SETTLE`: a file still
+    /// being copied keeps growing even when the watcher goes quiet
+    async fn wait_until_complete(paths: &[PathBuf]) {
+        let sizes = || {
+            paths
+                .iter()
+                .map(|path| std::fs::metadata(path).map(|m| m.len()).ok())
+                .collect::<Vec<_>>()
+        };
+        let mut last = sizes();
+        loop {
+            tokio::time::sleep(WATCH_SETTLE).await;
+            let current = sizes();
+            if current == last {
+                return;
+            }
+            last = current;
+        }
+    }
 
-        // Clonar referencias para la tarea async
-        let batch_processor = Arc::clone(&self.batch_processor);
-        let cancel_signal = Arc::clone(&self.cancel_signal);
+    /// Run a batch job as task `task_id` on a blocking thread, once a slot is free
+    /// (right away for a priority task), tracking its status, cancellation and
+    /// results. Writes the batch report when `settings` asks for one.
+    async fn run<F>(
+        &self,
+        task_id: TaskId,
+        settings: ProcessingSettings,
+        job: F,
+    ) -> Result<Vec<ProcessingResult>, String>
+    where
+        F: FnOnce(&BatchProcessor, Arc<AtomicBool>) -> Vec<ProcessingResult> + Send + 'static,
+    {
+        let (priority, cancel_signal, image_cancel_signals) = {
+            let tasks = self.tasks.lock();
+            let task = tasks
+                .by_id
+                .get(&task_id)
+                .ok_or_else(|| format!("Unknown task {}", task_id))?;
+            (
+                task.priority,
+                task.cancel_signal.clone(),
+                task.image_cancel_signals.clone(),
+            )
+        };
+
+        if cancel_signal.load(Ordering::SeqCst) {
+            return Err("Cancelled before it started".to_string());
+        }
+
+        // Esperar turno detrás de los lotes ya encolados
+        let _slot = if priority {
+            None
+        } else {
+            if !self.enqueue(task_id) {
+                return Err("Cancelled before it started".to_string());
+            }
+            let slot = self.slots.acquire().await;
+            // Quitada de la cola mientras esperaba (`cancel` o `clear_queue`): ya está
+            // Cancelled, y el turno pasa al siguiente
+            if !self.dequeue(task_id) {
+                return Err("Removed from the queue".to_string());
+            }
+            Some(slot.map_err(|e| e.to_string())?)
+        };
+        let snapshot = self.tasks.lock().set_status(task_id, TaskStatus::Running);
+        self.notify(snapshot);
+
+        // Procesador propio: la cancelación también aborta sus decodificaciones RAW
+        let mut processor = ImageProcessorImpl::new().with_cancel_signal(cancel_signal.clone());
+        if let Some(cache) = &self.image_cache {
+            processor = processor.with_image_cache(cache.clone());
+        }
+        let batch_processor = BatchProcessor::new()
+            .with_processor(Arc::new(processor))
+            .with_image_cancel_signals(image_cancel_signals);
 
         // Procesar en un thread separado
-        let handle = tokio::task::spawn_blocking(move || {
-            batch_processor.process_batch(
-                images,
-                transformation,
-                settings,
-                cancel_signal,
-                progress_callback,
-            )
-        });
+        let started = Instant::now();
+        let job_cancel_signal = cancel_signal.clone();
+        let handle = tokio::task::spawn_blocking(move || job(&batch_processor, job_cancel_signal));
 
         // Esperar resultado
         match handle.await {
             Ok(processing_results) => {
-                // Verificar si fue cancelado
-                if self.cancel_signal.load(Ordering::SeqCst) {
-                    *self.status.write().await = TaskStatus::Cancelled;
+                let report_path = settings.write_report().map(Path::to_path_buf);
+                let status = if cancel_signal.load(Ordering::SeqCst) {
+                    TaskStatus::Cancelled
                 } else {
-                    *self.status.write().await = TaskStatus::Completed;
-                }
+                    TaskStatus::Completed
+                };
+                let snapshot = {
+                    let mut tasks = self.tasks.lock();
+                    // Guardar resultados y su resumen
+                    if let Some(task) = tasks.by_id.get_mut(&task_id) {
+                        task.summary = Some(BatchSummary::from_results(
+                            &processing_results,
+                            started.elapsed(),
+                        ));
+                        task.results = processing_results.clone();
+                        task.settings = Some(settings);
+                    }
+                    let snapshot = tasks.set_status(task_id, status);
+                    tasks.last_finished = Some(task_id);
+                    tasks.prune(Self::KEPT_FINISHED_TASKS);
+                    snapshot
+                };
+                self.notify(snapshot);
 
-                // Guardar resultados
-                *self.results.lock() = processing_results.clone();
+                // Un reporte fallido no invalida el lote
+                if let Some(path) = report_path {
+                    if let Err(e) = self.export_report(task_id, &path) {
+                        if let Some(summary) = self
+                            .tasks
+                            .lock()
+                            .by_id
+                            .get_mut(&task_id)
+                            .and_then(|task| task.summary.as_mut())
+                        {
+                            summary.report_error = Some(e);
+                        }
+                    }
+                }
 
                 Ok(processing_results)
             }
             Err(e) => {
                 let error_msg = format!("Task execution failed: {}", e);
-                *self.status.write().await = TaskStatus::Error(error_msg.clone());
+                let snapshot = self
+                    .tasks
+                    .lock()
+                    .set_status(task_id, TaskStatus::Error(error_msg.clone()));
+                self.notify(snapshot);
                 Err(error_msg)
             }
         }
     }
 
-    /// Cancel the current processing task
-    pub async fn cancel(&self) {
-        self.cancel_signal.store(true, Ordering::SeqCst);
-        *self.status.write().await = TaskStatus::Cancelled;
+    /// Cancel task `task_id` (a queued one is dropped before it starts), or every
+    /// running task when None; queued batches then still run
+    pub async fn cancel(&self, task_id: Option<TaskId>) -> Result<(), String> {
+        let mut snapshots = Vec::new();
+        let mut tasks = self.tasks.lock();
+        let ids: Vec<TaskId> = match task_id {
+            Some(id) if tasks.by_id.contains_key(&id) => vec![id],
+            Some(id) => return Err(format!("Unknown task {}", id)),
+            None => tasks
+                .by_id
+                .iter()
+                .filter(|(_, task)| task.status == TaskStatus::Running)
+                .map(|(id, _)| *id)
+                .collect(),
+        };
+
+        for id in ids {
+            let task = tasks.by_id.get_mut(&id).expect("task listed above");
+            if task.is_finished() {
+                continue;
+            }
+            task.cancel_signal.store(true, Ordering::SeqCst);
+            // Una tarea en curso pasa a Cancelled cuando se detiene
+            if task.status == TaskStatus::Queued {
+                task.waiting = false;
+                snapshots.push(tasks.set_status(id, TaskStatus::Cancelled));
+            }
+        }
+        drop(tasks);
+
+        for snapshot in snapshots {
+            self.notify(snapshot);
+        }
+        Ok(())
+    }
+
+    /// Skip the image loaded from `path` in running task `task_id`, or in the latest
+    /// running task when None; the rest of the batch goes on. An image already
+    /// written is left as is.
+    pub async fn cancel_image(&self, task_id: Option<TaskId>, path: &Path) -> Result<(), String> {
+        let tasks = self.tasks.lock();
+        let task_id = task_id
+            .or_else(|| tasks.latest(|task| task.status == TaskStatus::Running))
+            .ok_or_else(|| "No task is running".to_string())?;
+        let task = tasks
+            .by_id
+            .get(&task_id)
+            .filter(|task| task.status == TaskStatus::Running)
+            .ok_or_else(|| format!("Task {} is not running", task_id))?;
+
+        if !task.image_cancel_signals.cancel(path) {
+            return Err(format!("No image {} in task {}", path.display(), task_id));
+        }
+        Ok(())
+    }
+
+    /// Drop every batch waiting in the queue right away: each one is Cancelled and
+    /// never starts (the running ones go on; see `cancel`). Priority tasks don't
+    /// queue. Returns how many were dropped.
+    pub fn clear_queue(&self) -> usize {
+        let snapshots: Vec<Option<TaskSnapshot>> = {
+            let mut tasks = self.tasks.lock();
+            let queued: Vec<TaskId> = tasks
+                .by_id
+                .iter()
+                .filter(|(_, task)| task.status == TaskStatus::Queued && !task.priority)
+                .map(|(id, _)| *id)
+                .collect();
+            queued
+                .into_iter()
+                .map(|id| {
+                    let task = tasks.by_id.get_mut(&id).expect("task listed above");
+                    task.cancel_signal.store(true, Ordering::SeqCst);
+                    task.waiting = false;
+                    tasks.set_status(id, TaskStatus::Cancelled)
+                })
+                .collect()
+        };
+
+        let dropped = snapshots.len();
+        for snapshot in snapshots {
+            self.notify(snapshot);
+        }
+        dropped
+    }
+
+    /// Mark task `task_id` as waiting for a slot; false if it is no longer queued
+    fn enqueue(&self, task_id: TaskId) -> bool {
+        let mut tasks = self.tasks.lock();
+        let Some(task) = tasks.by_id.get_mut(&task_id) else {
+            return false;
+        };
+        task.waiting = task.status == TaskStatus::Queued;
+        task.waiting
+    }
+
+    /// Task `task_id` got a slot; false if it was taken out of the queue meanwhile
+    fn dequeue(&self, task_id: TaskId) -> bool {
+        self.tasks
+            .lock()
+            .by_id
+            .get_mut(&task_id)
+            .is_some_and(|task| std::mem::take(&mut task.waiting))
     }
 
-    /// Get current task status
+    /// Batches waiting and running
+    pub async fn queue_status(&self) -> QueueStatus {
+        let tasks = self.tasks.lock();
+        let count = |filter: fn(&Task) -> bool| tasks.by_id.values().filter(|t| filter(t)).count();
+        QueueStatus {
+            pending: count(|task| task.waiting),
+            active: count(|task| task.status == TaskStatus::Running),
+        }
+    }
+
+    /// Status of the latest task (Idle when there is none)
     pub async fn get_status(&self) -> TaskStatus {
-        self.status.read().await.clone()
+        self.latest_snapshot()
+            .map_or(TaskStatus::Idle, |snapshot| snapshot.status)
+    }
+
+    /// Status of task `task_id`; None if it is unknown or was forgotten
+    pub fn task_status(&self, task_id: TaskId) -> Option<TaskStatus> {
+        Some(self.task_snapshot(task_id)?.status)
+    }
+
+    /// Status, progress and start time of task `task_id`; None if it is unknown or
+    /// was forgotten
+    pub fn task_snapshot(&self, task_id: TaskId) -> Option<TaskSnapshot> {
+        Some(self.tasks.lock().by_id.get(&task_id)?.snapshot(task_id))
+    }
+
+    /// `task_snapshot` of the latest task (None when there is none)
+    pub fn latest_snapshot(&self) -> Option<TaskSnapshot> {
+        let tasks = self.tasks.lock();
+        let task_id = tasks.latest(|_| true)?;
+        Some(tasks.by_id[&task_id].snapshot(task_id))
     }
 
     /// Get results of last completed task
     pub fn get_results(&self) -> Vec<ProcessingResult> {
-        self.results.lock().clone()
+        let tasks = self.tasks.lock();
+        tasks
+            .last_finished
+            .and_then(|id| tasks.by_id.get(&id))
+            .map(|task| task.results.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get the summary of the last completed task
+    pub fn get_last_summary(&self) -> Option<BatchSummary> {
+        let task_id = self.tasks.lock().last_finished?;
+        self.task_summary(task_id)
+    }
+
+    /// Summary of task `task_id` once it is done
+    pub fn task_summary(&self, task_id: TaskId) -> Option<BatchSummary> {
+        self.tasks.lock().by_id.get(&task_id)?.summary.clone()
+    }
+
+    /// Write every result of the last completed task, its summary and its settings to
+    /// `path` (CSV or JSON, see `ResultsReport::write`)
+    pub fn export_last_report(&self, path: &Path) -> Result<(), String> {
+        let task_id = self
+            .tasks
+            .lock()
+            .last_finished
+            .ok_or_else(|| "No batch report available".to_string())?;
+        self.export_report(task_id, path)
     }
 
-    /// Check if a task is currently running
+    /// `export_last_report` for task `task_id`
+    pub fn export_report(&self, task_id: TaskId, path: &Path) -> Result<(), String> {
+        let report = {
+            let tasks = self.tasks.lock();
+            let task = tasks
+                .by_id
+                .get(&task_id)
+                .filter(|task| task.settings.is_some())
+                .ok_or_else(|| "No batch report available".to_string())?;
+            ResultsReport::new(
+                &task.results,
+                task.summary.as_ref(),
+                task.settings.as_ref().expect("checked above"),
+            )
+        };
+        report.write(path)
+    }
+
+    /// Check if any task is currently running
     pub async fn is_running(&self) -> bool {
-        *self.status.read().await == TaskStatus::Running
+        self.queue_status().await.active > 0
     }
 
-    /// Reset task manager to idle state
+    /// Forget every finished task and its results
     pub async fn reset(&self) {
-        self.cancel_signal.store(false, Ordering::SeqCst);
-        *self.status.write().await = TaskStatus::Idle;
-        self.results.lock().clear();
+        let mut tasks = self.tasks.lock();
+        tasks.last_finished = None;
+        tasks.prune(0);
     }
 }
 
@@ -142,16 +916,421 @@ mod tests {
     async fn test_cancel_signal() {
         let manager = TaskManager::new();
         assert!(!manager.is_running().await);
+        assert!(manager.cancel(None).await.is_ok());
 
-        manager.cancel().await;
-        assert_eq!(manager.get_status().await, TaskStatus::Cancelled);
+        // Cancelada mientras espera turno: no llega a ejecutarse
+        let task_id = manager.create_task(false);
+        assert_eq!(manager.get_status().await, TaskStatus::Queued);
+        manager.cancel(Some(task_id)).await.unwrap();
+        assert_eq!(manager.task_status(task_id), Some(TaskStatus::Cancelled));
+        assert!(manager
+            .run(task_id, ProcessingSettings::default(), |_, _| Vec::new())
+            .await
+            .is_err());
+
+        assert!(manager.cancel(Some(Uuid::new_v4())).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_image_needs_a_running_task() {
+        let manager = TaskManager::new();
+        assert!(manager
+            .cancel_image(None, Path::new("photo.jpg"))
+            .await
+            .is_err());
+
+        let task_id = manager.create_task(false);
+        assert!(manager
+            .cancel_image(Some(task_id), Path::new("photo.jpg"))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_batch_summary_counts() {
+        let result = |success: bool, skipped: bool, output_size: u64| ProcessingResult {
+            original_path: PathBuf::from(format!("/in/{}.png", output_size)),
+            original_size: 1000,
+            output_size,
+            success,
+            skipped,
+            processing_time_ms: output_size / 10,
+            ..Default::default()
+        };
+        let mut results = vec![
+            result(true, false, 400),
+            result(true, false, 800),
+            result(true, true, 1000),
+            result(false, false, 0),
+        ];
+        let timings = PhaseTimings {
+            decode_ms: 5,
+            transform_ms: 1,
+            encode_ms: 20,
+            write_ms: 2,
+        };
+        results[0].timings = Some(timings);
+        results[1].timings = Some(timings);
+
+        let summary = BatchSummary::from_results(&results, Duration::from_millis(120));
+        assert_eq!(
+            (summary.succeeded, summary.failed, summary.skipped),
+            (2, 1, 1)
+        );
+        assert_eq!(summary.bytes_saved, 800);
+        assert_eq!(summary.duration, Duration::from_millis(120));
+        assert!((summary.average_compression_ratio - 40.0).abs() < 1e-9);
+        assert_eq!(
+            summary
+                .timing_totals
+                .map(|t| (t.decode_ms, t.encode_ms, t.write_ms)),
+            Some((10, 40, 4))
+        );
+        assert_eq!(summary.slowest, Some((PathBuf::from("/in/1000.png"), 100)));
+
+        let empty = BatchSummary::from_results(&[], Duration::ZERO);
+        assert_eq!(empty.average_compression_ratio, 0.0);
+        assert_eq!((empty.timing_totals, empty.slowest), (None, None));
+        assert_eq!(empty.stop_reason, None);
+    }
+
+    #[test]
+    fn test_batch_summary_stop_reason() {
+        let failed = |message: &str| {
+            ProcessingResult::failed(PathBuf::from("/in/a.png"), 10, message.to_string())
+        };
+
+        let cancelled = BatchSummary::from_results(
+            &[failed(BatchProcessor::CANCELLED_MESSAGE)],
+            Duration::ZERO,
+        );
+        assert_eq!(
+            cancelled.stop_reason.as_deref(),
+            Some("Cancelled by the user")
+        );
+
+        // El umbral de fallos también levanta la señal de cancelación
+        let aborted = BatchSummary::from_results(
+            &[
+                failed(BatchProcessor::ABORTED_MESSAGE),
+                failed(BatchProcessor::CANCELLED_MESSAGE),
+            ],
+            Duration::ZERO,
+        );
+        assert!(aborted
+            .stop_reason
+            .is_some_and(|reason| reason.contains("failed in a row")));
+    }
+
+    #[test]
+    fn test_preflight_result() {
+        assert_eq!(PreflightResult::new(10, Some(100)), PreflightResult::Ok);
+        assert_eq!(PreflightResult::new(10, None), PreflightResult::Ok);
+        assert_eq!(
+            PreflightResult::new(100, Some(10)),
+            PreflightResult::InsufficientSpace {
+                needed: 100,
+                available: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_preflight_check_small_batch_fits() {
+        let output = tempfile::tempdir().unwrap();
+        let image = Image::new(
+            "/photos/a.jpg".into(),
+            crate::domain::ImageFormat::Jpeg,
+            crate::domain::Dimensions::new(64, 64).unwrap(),
+            1024,
+            None,
+        )
+        .unwrap();
+        // La carpeta de salida aún no existe: se mira su volumen
+        let settings = ProcessingSettings::with_directory(output.path().join("new"));
+
+        assert_eq!(
+            TaskManager::preflight_check(&[image], &settings),
+            PreflightResult::Ok
+        );
+    }
+
+    #[test]
+    fn test_preflight_paths_skips_unreadable_files() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let image = input.path().join("a.png");
+        image::RgbImage::new(8, 8).save(&image).unwrap();
+        let settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+
+        let paths = [image, input.path().join("missing.png")];
+        assert_eq!(
+            TaskManager::new().preflight_paths(&paths, &settings),
+            PreflightResult::Ok
+        );
     }
 
     #[tokio::test]
     async fn test_reset() {
         let manager = TaskManager::new();
-        manager.cancel().await;
+        let task_id = manager.create_task(false);
+        manager
+            .run(task_id, ProcessingSettings::default(), |_, _| Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(manager.get_status().await, TaskStatus::Completed);
+        assert!(manager.get_last_summary().is_some());
+
         manager.reset().await;
         assert_eq!(manager.get_status().await, TaskStatus::Idle);
+        assert_eq!(manager.task_status(task_id), None);
+        assert!(manager.get_last_summary().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batches_queue_instead_of_failing() {
+        let manager = TaskManager::new();
+        let slow = manager.run(
+            manager.create_task(false),
+            ProcessingSettings::default(),
+            |_, _| {
+                std::thread::sleep(Duration::from_millis(300));
+                Vec::new()
+            },
+        );
+        let queued = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            manager
+                .run(
+                    manager.create_task(false),
+                    ProcessingSettings::default(),
+                    |_, _| Vec::new(),
+                )
+                .await
+        };
+        let status = async {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            manager.queue_status().await
+        };
+
+        let (slow, queued, status) = tokio::join!(slow, queued, status);
+
+        assert!(slow.is_ok() && queued.is_ok());
+        assert_eq!(
+            status,
+            QueueStatus {
+                pending: 1,
+                active: 1
+            }
+        );
+        assert_eq!(manager.queue_status().await.pending, 0);
+    }
+
+    #[tokio::test]
+    async fn test_clear_queue_drops_waiting_batches() {
+        let manager = TaskManager::new();
+        let slow = manager.run(
+            manager.create_task(false),
+            ProcessingSettings::default(),
+            |_, _| {
+                std::thread::sleep(Duration::from_millis(300));
+                Vec::new()
+            },
+        );
+        let queued_id = manager.create_task(false);
+        let queued = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            manager
+                .run(queued_id, ProcessingSettings::default(), |_, _| Vec::new())
+                .await
+        };
+        // Sin esperar a que la tarea encolada consiga turno
+        let clear = async {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            let cleared = manager.clear_queue();
+            (
+                cleared,
+                manager.queue_status().await,
+                manager.task_status(queued_id),
+            )
+        };
+
+        let (slow, queued, (cleared, status, queued_status)) = tokio::join!(slow, queued, clear);
+
+        assert!(slow.is_ok());
+        assert!(queued.is_err());
+        assert_eq!(cleared, 1);
+        assert_eq!(
+            status,
+            QueueStatus {
+                pending: 0,
+                active: 1
+            }
+        );
+        assert_eq!(queued_status, Some(TaskStatus::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_failed_report_is_kept_in_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        // Un directorio no vacío donde va el reporte impide escribirlo
+        let report = dir.path().join("report.json");
+        std::fs::create_dir(&report).unwrap();
+        std::fs::write(report.join("keep"), b"").unwrap();
+        let mut settings = ProcessingSettings::default();
+        settings.set_write_report(Some(report));
+
+        let manager = TaskManager::new();
+        let task_id = manager.create_task(false);
+        manager
+            .run(task_id, settings, |_, _| Vec::new())
+            .await
+            .unwrap();
+
+        let summary = manager.task_summary(task_id).unwrap();
+        assert!(summary.report_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_process_watched_batches_paths() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let (sender, receiver) = mpsc::channel(8);
+        for name in ["a.png", "b.png", "a.png"] {
+            let path = input.path().join(name);
+            image::RgbImage::new(4, 4).save(&path).unwrap();
+            sender.send(path).await.unwrap();
+        }
+        drop(sender);
+
+        let batches = Mutex::new(Vec::new());
+        let reported = Arc::new(Mutex::new(0));
+        let callback: ProgressCallback = {
+            let reported = Arc::clone(&reported);
+            Arc::new(move |_, results| *reported.lock() += results.len())
+        };
+        let settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        TaskManager::new()
+            .process_watched(
+                receiver,
+                None,
+                settings,
+                |_| Some(callback.clone()),
+                |results, _| batches.lock().push(results.unwrap()),
+            )
+            .await;
+
+        let batches = batches.into_inner();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 2);
+        assert!(batches[0].iter().all(|r| r.success));
+        assert_eq!(*reported.lock(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_priority_task_skips_the_queue() {
+        let manager = TaskManager::new();
+        let heavy = manager.create_task(false);
+        let preview = manager.create_task(true);
+        let finished = Mutex::new(Vec::new());
+
+        let slow = async {
+            manager
+                .run(heavy, ProcessingSettings::default(), |_, _| {
+                    std::thread::sleep(Duration::from_millis(300));
+                    Vec::new()
+                })
+                .await
+                .unwrap();
+            finished.lock().push(heavy);
+        };
+        let quick = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(manager.task_status(heavy), Some(TaskStatus::Running));
+            manager
+                .run(preview, ProcessingSettings::default(), |_, _| Vec::new())
+                .await
+                .unwrap();
+            finished.lock().push(preview);
+        };
+        tokio::join!(slow, quick);
+
+        assert_eq!(finished.into_inner(), vec![preview, heavy]);
+        assert_eq!(manager.task_status(heavy), Some(TaskStatus::Completed));
+        assert!(manager.task_summary(preview).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_max_running_tasks() {
+        let manager = TaskManager::new().with_max_running_tasks(2);
+        let slow_job = |_: &BatchProcessor, _| {
+            std::thread::sleep(Duration::from_millis(200));
+            Vec::new()
+        };
+        let first = manager.run(
+            manager.create_task(false),
+            ProcessingSettings::default(),
+            slow_job,
+        );
+        let second = manager.run(
+            manager.create_task(false),
+            ProcessingSettings::default(),
+            slow_job,
+        );
+        let status = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            manager.queue_status().await
+        };
+
+        let (first, second, status) = tokio::join!(first, second, status);
+
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(
+            status,
+            QueueStatus {
+                pending: 0,
+                active: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_listener_and_progress() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("a.png");
+        image::RgbImage::new(4, 4).save(&path).unwrap();
+
+        let manager = TaskManager::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        manager.set_status_listener({
+            let seen = Arc::clone(&seen);
+            Arc::new(move |snapshot: &TaskSnapshot| seen.lock().push(snapshot.status.clone()))
+        });
+
+        let task_id = manager.create_task(false);
+        manager
+            .process_paths(
+                task_id,
+                vec![path],
+                None,
+                ProcessingSettings::with_directory(output.path().to_path_buf()),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *seen.lock(),
+            vec![
+                TaskStatus::Queued,
+                TaskStatus::Running,
+                TaskStatus::Completed
+            ]
+        );
+        let snapshot = manager.task_snapshot(task_id).unwrap();
+        assert_eq!(snapshot.progress, Some((1, 1)));
+        assert!(snapshot.started_at.is_some());
+        assert_eq!(manager.latest_snapshot(), Some(snapshot));
     }
 }
@@ -1,3 +1,4 @@
+use crate::domain::value_objects::{Dimensions, ImageFormat};
 use thiserror::Error;
 
 /// Domain-level errors - business logic validation errors
@@ -15,6 +16,21 @@ pub enum DomainError {
     #[error("Invalid rotation angle: {0}. Must be 0, 90, 180, or   270")]
     InvalidRotation(i32),
 
+    #[error("Invalid rotation angle: {0}. Must be a finite number of degrees")]
+    InvalidRotationAngle(f32),
+
+    #[error("Invalid resize percentage: {0}. Must be a finite number greater than 0")]
+    InvalidResizePercentage(f32),
+
+    #[error("Invalid resize edge: {0}. Must be greater than 0")]
+    InvalidResizeEdge(u32),
+
+    #[error("Invalid adjustment: {0}")]
+    InvalidAdjustment(String),
+
+    #[error("Invalid watermark: {0}")]
+    InvalidWatermark(String),
+
     #[error("File not found: {0}")]
     FileNotFound(String),
 
@@ -23,6 +39,72 @@ pub enum DomainError {
 
     #[error("Unsupported transformation: {0}")]
     UnsupportedTransformation(String),
+
+    /// Reading the file failed; unlike other errors, trying again may work
+    #[error("Read failed: {0}")]
+    ReadFailed(String),
+
+    /// The file was read but its contents aren't a valid image; retrying won't help
+    #[error("Decode failed: {0}")]
+    DecodeFailed(String),
+
+    /// Writing the output failed; like a failed read, trying again may work
+    #[error("Write failed: {0}")]
+    WriteFailed(String),
+
+    /// Writing the output ran out of disk space
+    #[error("Disk full: {0}")]
+    DiskFull(String),
+
+    /// Processing was cancelled while the image was in flight
+    #[error("cancelled")]
+    Cancelled,
+
+    #[error("Lossless output is not possible as {0}: choose PNG or WebP")]
+    LosslessNotSupported(String),
+
+    #[error(
+        "Image is {actual} but {format} allows at most {limit}: resize it or pick another format"
+    )]
+    DimensionsExceedFormatLimit {
+        format: ImageFormat,
+        limit: Dimensions,
+        actual: Dimensions,
+    },
+
+    /// A lower-layer failure with no domain meaning of its own (an optimizer or an
+    /// encoder failing, a size target out of reach...), keeping that layer's code
+    #[error("{message}")]
+    Infrastructure { code: &'static str, message: String },
+}
+
+impl DomainError {
+    /// Stable machine-readable name of the variant, for the frontend to tell
+    /// errors apart (and localize them) without parsing messages
+    pub fn code(&self) -> &'static str {
+        match self {
+            DomainError::InvalidQuality(_) => "invalid_quality",
+            DomainError::InvalidDimensions(..) => "invalid_dimensions",
+            DomainError::InvalidImageFormat(_) => "invalid_image_format",
+            DomainError::InvalidRotation(_) => "invalid_rotation",
+            DomainError::InvalidRotationAngle(_) => "invalid_rotation_angle",
+            DomainError::InvalidResizePercentage(_) => "invalid_resize_percentage",
+            DomainError::InvalidResizeEdge(_) => "invalid_resize_edge",
+            DomainError::InvalidAdjustment(_) => "invalid_adjustment",
+            DomainError::InvalidWatermark(_) => "invalid_watermark",
+            DomainError::FileNotFound(_) => "file_not_found",
+            DomainError::InvalidFilePath(_) => "invalid_file_path",
+            DomainError::UnsupportedTransformation(_) => "unsupported_transformation",
+            DomainError::ReadFailed(_) => "read_failed",
+            DomainError::DecodeFailed(_) => "decode_failed",
+            DomainError::WriteFailed(_) => "write_failed",
+            DomainError::DiskFull(_) => "disk_full",
+            DomainError::Cancelled => "cancelled",
+            DomainError::LosslessNotSupported(_) => "lossless_not_supported",
+            DomainError::DimensionsExceedFormatLimit { .. } => "dimensions_exceed_format_limit",
+            DomainError::Infrastructure { code, .. } => code,
+        }
+    }
 }
 
 pub type DomainResult<T> = Result<T, DomainError>;
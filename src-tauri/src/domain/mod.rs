@@ -5,6 +5,9 @@ pub mod value_objects;
 
 // Re-export commonly used types
 pub use error::{DomainError, DomainResult};
-pub use models::{Image, ProcessingSettings, RawQualityMode, Transformation};
-pub use services::ImageProcessor;
-pub use value_objects::{Dimensions, ImageFormat, Quality};
+pub use models::{
+    Image, OutputMode, ProcessingSettings, RawProcessingParams, RawQualityMode, SkipStrategy,
+    Transformation,
+};
+pub use services::{ImageProcessor, LoadMode};
+pub use value_objects::{ChromaSubsampling, Dimensions, ImageFormat, Quality, StandardSize};
@@ -16,6 +16,9 @@ pub struct Image {
     size_bytes: u64,
     /// Optional EXIF metadata
     metadata: Option<ImageMetadata>,
+    /// Format the file extension claims, when the contents turned out to be `format`
+    #[serde(default)]
+    extension_format: Option<ImageFormat>,
 }
 
 impl Image {
@@ -39,6 +42,7 @@ impl Image {
             dimensions,
             size_bytes,
             metadata,
+            extension_format: None,
         })
     }
 
@@ -63,6 +67,7 @@ impl Image {
             dimensions: Dimensions::new(1, 1)?, // Temporal
             size_bytes: 0,                      // Temporal
             metadata: None,
+            extension_format: None,
         })
     }
 
@@ -104,6 +109,11 @@ impl Image {
         self.metadata.as_ref()
     }
 
+    /// Format the extension claims, if it disagrees with the file contents
+    pub fn extension_format(&self) -> Option<ImageFormat> {
+        self.extension_format
+    }
+
     /// Check if image has metadata
     pub fn has_metadata(&self) -> bool {
         self.metadata.is_some()
@@ -138,9 +148,14 @@ impl Image {
     pub fn set_metadata(&mut self, metadata: Option<ImageMetadata>) {
         self.metadata = metadata;
     }
+
+    /// Record that the extension claims another format than the contents
+    pub fn set_extension_format(&mut self, extension_format: Option<ImageFormat>) {
+        self.extension_format = extension_format;
+    }
 }
 
-/// EXIF metadata from image
+/// EXIF, IPTC and XMP metadata from image
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageMetadata {
     /// Camera make
@@ -161,6 +176,18 @@ pub struct ImageMetadata {
     pub gps_coordinates: Option<(f64, f64)>,
     /// Orientation
     pub orientation: Option<u32>,
+    /// IPTC keywords (or XMP `dc:subject`)
+    pub iptc_keywords: Option<Vec<String>>,
+    /// IPTC copyright notice (or XMP `dc:rights`)
+    pub iptc_copyright: Option<String>,
+    /// IPTC caption/abstract (or XMP `dc:description`)
+    pub iptc_caption: Option<String>,
+    /// XMP star rating (0-5; rejected images have none)
+    pub xmp_rating: Option<u8>,
+    /// XMP color label ("Red", "Select", ...)
+    pub xmp_label: Option<String>,
+    /// Title (`dc:title`, from a RAW file's `.xmp` sidecar)
+    pub xmp_title: Option<String>,
 }
 
 impl ImageMetadata {
@@ -176,6 +203,12 @@ impl ImageMetadata {
             focal_length: None,
             gps_coordinates: None,
             orientation: None,
+            iptc_keywords: None,
+            iptc_copyright: None,
+            iptc_caption: None,
+            xmp_rating: None,
+            xmp_label: None,
+            xmp_title: None,
         }
     }
 
@@ -190,6 +223,12 @@ impl ImageMetadata {
             && self.focal_length.is_none()
             && self.gps_coordinates.is_none()
             && self.orientation.is_none()
+            && self.iptc_keywords.is_none()
+            && self.iptc_copyright.is_none()
+            && self.iptc_caption.is_none()
+            && self.xmp_rating.is_none()
+            && self.xmp_label.is_none()
+            && self.xmp_title.is_none()
     }
 }
 
@@ -1,7 +1,16 @@
 mod image;
 mod settings;
 mod transformation;
+mod watermark;
 
 pub use image::{Image, ImageMetadata};
-pub use settings::{ProcessingSettings, RawQualityMode};
-pub use transformation::{ResizeFilter, ResizeTransformation, Rotation, Transformation};
+pub use settings::{
+    CollisionStrategy, HighlightMode, OutputMode, PartialProcessingSettings, PngBitDepth,
+    PostAction, ProcessingProfile, ProcessingSettings, RawProcessingParams, RawQualityMode,
+    RenameMode, SkipStrategy, WhiteBalance,
+};
+pub use transformation::{
+    ArbitraryRotation, Channel, ColorAdjustment, ResizeFilter, ResizeMode, ResizeTransformation,
+    Rotation, SharpenOptions, TonalAdjustments, Transformation,
+};
+pub use watermark::{FontSize, TextWatermark, Watermark, WatermarkPosition};
@@ -1,6 +1,8 @@
-use crate::domain::value_objects::{ImageFormat, Quality};
+use crate::domain::value_objects::{ChromaSubsampling, ImageFormat, Quality};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Quality mode for RAW image decoding
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
@@ -13,53 +15,488 @@ pub enum RawQualityMode {
     Quality,    // full-res, AHD demosaicing — current behavior (slowest)
 }
 
-/// Processing settings for image optimization
+/// White balance applied while developing RAW files
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum WhiteBalance {
+    /// As shot (multipliers stored by the camera)
+    #[default]
+    Camera,
+    /// Computed from the whole image (gray world)
+    Auto,
+    /// Sensor daylight multipliers
+    Daylight,
+    /// Custom R, G, B, G2 multipliers
+    Custom(f32, f32, f32, f32),
+}
+
+/// How LibRaw handles clipped highlights
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum HighlightMode {
+    /// Clip to white (fastest)
+    #[default]
+    Clip,
+    /// Leave highlights unclipped (may show magenta casts)
+    Unclip,
+    /// Blend clipped and unclipped values
+    Blend,
+    /// Reconstruct highlights from the unclipped channels
+    Rebuild,
+}
+
+/// Development parameters for RAW decoding (ignored in `RawQualityMode::Thumbnail`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawProcessingParams {
+    pub white_balance: WhiteBalance,
+    /// Exposure correction in stops (-2.0 to +3.0)
+    pub exposure_correction: f32,
+    pub highlight_mode: HighlightMode,
+    /// Wavelet denoising threshold (0 = off, 100-1000 typical)
+    pub noise_threshold: f32,
+    /// Bits per sample of the developed image (8 or 16)
+    pub output_bps: u8,
+    /// Half-resolution output without demosaicing (also implied by `RawQualityMode::Fast`)
+    pub half_size: bool,
+}
+
+impl RawProcessingParams {
+    /// Minimum exposure correction in stops
+    pub const MIN_EXPOSURE: f32 = -2.0;
+    /// Maximum exposure correction in stops
+    pub const MAX_EXPOSURE: f32 = 3.0;
+}
+
+impl Default for RawProcessingParams {
+    fn default() -> Self {
+        Self {
+            white_balance: WhiteBalance::Camera,
+            exposure_correction: 0.0,
+            highlight_mode: HighlightMode::Clip,
+            noise_threshold: 0.0,
+            output_bps: 8,
+            half_size: false,
+        }
+    }
+}
+
+/// What to do when the encoded output doesn't reach `min_compression_ratio`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipStrategy {
+    /// Don't write anything for this image
+    #[default]
+    Skip,
+    /// Copy the original file unchanged into the output directory
+    CopyOriginal,
+    /// Write the encoded output regardless of its size
+    SaveAnyway,
+}
+
+/// Bit depth of PNG outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PngBitDepth {
+    /// Keep 16-bit channels (oxipng still drops them when it loses nothing)
+    #[default]
+    Preserve,
+    /// Scale 16-bit channels down to 8 bits (much smaller, invisible for most uses)
+    Force8bit,
+}
+
+/// Where a batch writes its outputs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum OutputMode {
+    /// Loose files in the output directory
+    #[default]
+    Files,
+    /// A single zip archive at this path
+    Zip(PathBuf),
+}
+
+/// How a renamed output avoids an existing file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RenameMode {
+    /// `photo_1.jpg`, `photo_2.jpg`, ...
+    #[default]
+    Sequential,
+    /// `photo_1700000000.jpg` (Unix timestamp in seconds)
+    Timestamp,
+    /// `photo (1).jpg`, `photo (2).jpg`, ... as file managers name copies
+    Numbered,
+}
+
+/// What to do when an output file already exists and overwriting is off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CollisionStrategy {
+    /// Fail this output
+    #[default]
+    Error,
+    /// Leave the existing file alone and report the output as skipped
+    Skip,
+    /// Write next to the existing file under a new name
+    Rename(RenameMode),
+}
+
+/// What happens to an input once all its outputs were written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PostAction {
+    /// Leave the original where it is
+    #[default]
+    Keep,
+    /// Send the original to the system trash / recycle bin
+    MoveToTrash,
+    /// Delete the original permanently
+    Delete,
+    /// Move the original into this directory
+    MoveTo(PathBuf),
+}
+
+/// Named presets for common use-cases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessingProfile {
+    /// JPEG + WebP at 80%, long edge capped at 2048px
+    Web,
+    /// Lossless PNG with metadata (DPI) preserved.
+    /// TIFF isn't an output format of this app, so PNG is the lossless choice.
+    Print,
+    /// JPEG at 70%, long edge capped at 256px
+    Thumbnail,
+    /// Lossless PNG with all metadata preserved
+    Archive,
+    /// Plain defaults, everything set by hand
+    #[default]
+    Custom,
+}
+
+/// Processing settings for image optimization.
+/// Fields missing when deserializing take their default (older preset files keep loading).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProcessingSettings {
-    /// Quality for lossy compression
-    quality: Quality,
-    /// Output format (None = keep original)
-    output_format: Option<ImageFormat>,
-    /// Output directory
+    /// Quality for lossy compression (None = not set: `quality_for` picks one per format)
+    quality: Option<Quality>,
+    /// Output formats, one file each (empty = keep original)
+    output_formats: Vec<ImageFormat>,
+    /// Output directory (empty = next to each input)
     output_directory: PathBuf,
+    /// Mirror each input's folder, relative to `source_base`, under the output directory
+    preserve_directory_structure: bool,
+    /// Folder the mirrored structure starts at (None = the deepest folder containing
+    /// every input of the batch)
+    source_base: Option<PathBuf>,
     /// Preserve EXIF metadata
     preserve_metadata: bool,
     /// Overwrite existing files
     overwrite_existing: bool,
+    /// Copy an original to `<name>.orig.<ext>` before an output overwrites it
+    create_backup: bool,
+    /// What to do with existing files when not overwriting
+    collision_strategy: CollisionStrategy,
     /// Number of parallel workers (None = auto)
     max_workers: Option<usize>,
     /// Quality mode for RAW image decoding
     raw_quality_mode: RawQualityMode,
+    /// Minimum fraction saved (0.0-1.0) for the output to be worth writing (None = always write)
+    min_compression_ratio: Option<f64>,
+    /// Strategy when the output doesn't meet `min_compression_ratio`
+    on_no_improvement: SkipStrategy,
+    /// Never write outputs larger than the original (skipped or replaced by a copy
+    /// of the original per `on_no_improvement`; `SaveAnyway` counts as skip here)
+    skip_if_larger: bool,
+    /// Rotate (90/180/270) and flip JPEG -> JPEG without re-encoding when possible
+    use_lossless_rotation: bool,
+    /// Development parameters for RAW decoding
+    raw_params: RawProcessingParams,
+    /// Image to decode from HEIC containers (0 = primary, n = n-th top-level image)
+    heic_image_index: usize,
+    /// Apply the EXIF orientation tag when decoding (outputs don't keep the tag)
+    auto_orient: bool,
+    /// Reuse decoded images from the app-wide cache instead of decoding them again
+    use_cache: bool,
+    /// Loose files or a zip archive
+    output_mode: OutputMode,
+    /// Zip entries use only the file name, without directories
+    flatten_zip: bool,
+    /// Responsive widths: one output per width (aspect preserved); empty = original size
+    sizes: Vec<u32>,
+    /// File name suffix for sized outputs; `{width}` is replaced by the width
+    size_suffix: String,
+    /// RGB color transparent areas are composited onto for formats without alpha (JPEG)
+    background_color: [u8; 3],
+    /// Maximum output size: lossy formats lower the quality to fit it
+    target_size_bytes: Option<u64>,
+    /// Hard limit for lossy WebP outputs: over it even at the lowest quality is an error
+    max_output_bytes: Option<usize>,
+    /// Quantize PNG outputs to a 256-color palette before oxipng (lossy, much smaller)
+    png_lossy: bool,
+    /// Whether 16-bit PNG sources keep their bit depth in PNG outputs
+    png_bit_depth: PngBitDepth,
+    /// Downscale outputs whose longest side exceeds this (None = no limit)
+    max_long_edge: Option<u32>,
+    /// Progressive JPEG scans; false = baseline for viewers without progressive support
+    progressive: bool,
+    /// JPEG chroma subsampling (Auto = 4:4:4 at high quality, 4:2:0 otherwise)
+    jpeg_chroma: ChromaSubsampling,
+    /// Huffman tables (and progressive scans) optimized per image; false = standard tables
+    jpeg_optimize_huffman: bool,
+    /// Only lossless outputs: WebP lossless, PNG without quantization; JPEG is refused
+    lossless: bool,
+    /// Extra attempts when reading an input fails (network shares can be briefly busy)
+    max_retries: u32,
+    /// Wait between those attempts, in milliseconds
+    retry_delay_ms: u64,
+    /// Report decode/transform/encode/write times for every output
+    phase_timings: bool,
+    /// Stop the batch after this many images fail in a row (None = never)
+    abort_after_failures: Option<usize>,
+    /// Plan the outputs (paths, collisions, size estimate) without writing anything
+    dry_run: bool,
+    /// Leave images whose outputs are up to date alone (incremental runs); out of
+    /// date outputs are regenerated in place
+    skip_unchanged: bool,
+    /// What to do with each original after it was processed successfully
+    post_action: PostAction,
+    /// Write every result, the summary and these settings here once the batch is done
+    /// (`.csv` or JSON, by extension)
+    write_report: Option<PathBuf>,
+    /// Per-image settings keyed by input path; the output location stays batch-wide
+    image_overrides: HashMap<PathBuf, ProcessingSettings>,
 }
 
 impl ProcessingSettings {
     /// Create new processing settings
     pub fn new(quality: Quality, output_directory: PathBuf) -> Self {
         Self {
-            quality,
-            output_format: None,
+            quality: Some(quality),
+            output_formats: Vec::new(),
             output_directory,
+            preserve_directory_structure: false,
+            source_base: None,
             preserve_metadata: false,
             overwrite_existing: false,
+            create_backup: false,
+            collision_strategy: CollisionStrategy::Error,
             max_workers: None,
             raw_quality_mode: RawQualityMode::Balanced,
+            min_compression_ratio: None,
+            on_no_improvement: SkipStrategy::Skip,
+            skip_if_larger: false,
+            use_lossless_rotation: true,
+            raw_params: RawProcessingParams::default(),
+            heic_image_index: 0,
+            auto_orient: true,
+            use_cache: false,
+            output_mode: OutputMode::Files,
+            flatten_zip: true,
+            sizes: Vec::new(),
+            size_suffix: "-{width}w".to_string(),
+            background_color: [255, 255, 255],
+            target_size_bytes: None,
+            max_output_bytes: None,
+            png_lossy: false,
+            png_bit_depth: PngBitDepth::Preserve,
+            max_long_edge: None,
+            progressive: true,
+            jpeg_chroma: ChromaSubsampling::Auto,
+            jpeg_optimize_huffman: true,
+            lossless: false,
+            max_retries: 0,
+            retry_delay_ms: 0,
+            phase_timings: false,
+            abort_after_failures: None,
+            dry_run: false,
+            skip_unchanged: false,
+            post_action: PostAction::Keep,
+            write_report: None,
+            image_overrides: HashMap::new(),
+        }
+    }
+
+    /// Defaults for a named use-case; the output directory is the current one
+    pub fn from_profile(profile: ProcessingProfile) -> Self {
+        let mut settings = Self::default();
+        match profile {
+            ProcessingProfile::Web => {
+                settings
+                    .set_quality(Quality::web_optimized())
+                    .set_output_formats(vec![ImageFormat::Jpeg, ImageFormat::Webp])
+                    .set_max_long_edge(Some(2048));
+            }
+            ProcessingProfile::Print => {
+                settings
+                    .set_quality(Quality::maximum())
+                    .set_output_format(Some(ImageFormat::Png))
+                    .set_preserve_metadata(true)
+                    .set_lossless(true);
+            }
+            ProcessingProfile::Thumbnail => {
+                settings
+                    .set_quality(Quality::thumbnail())
+                    .set_output_format(Some(ImageFormat::Jpeg))
+                    .set_max_long_edge(Some(256));
+            }
+            ProcessingProfile::Archive => {
+                settings
+                    .set_quality(Quality::maximum())
+                    .set_output_format(Some(ImageFormat::Png))
+                    .set_preserve_metadata(true)
+                    .set_lossless(true);
+            }
+            ProcessingProfile::Custom => {}
         }
+        settings
     }
 
-    /// Create with default quality
+    /// Create without an explicit quality (see `quality_for`)
     pub fn with_directory(output_directory: PathBuf) -> Self {
-        Self::new(Quality::default(), output_directory)
+        Self {
+            output_directory,
+            ..Self::default()
+        }
+    }
+
+    /// Copy of these settings with every field set in `other` taking priority
+    pub fn merge(&self, other: &PartialProcessingSettings) -> ProcessingSettings {
+        let mut merged = self.clone();
+
+        if let Some(quality) = other.quality {
+            merged.set_quality(quality);
+        }
+        if let Some(ref formats) = other.output_formats {
+            merged.set_output_formats(formats.clone());
+        }
+        if let Some(ref directory) = other.output_directory {
+            merged.set_output_directory(directory.clone());
+        }
+        if let Some(preserve) = other.preserve_metadata {
+            merged.set_preserve_metadata(preserve);
+        }
+        if let Some(overwrite) = other.overwrite_existing {
+            merged.set_overwrite_existing(overwrite);
+        }
+        if let Some(strategy) = other.collision_strategy {
+            merged.set_collision_strategy(strategy);
+        }
+        if let Some(workers) = other.max_workers {
+            merged.set_max_workers(workers);
+        }
+        if let Some(mode) = other.raw_quality_mode {
+            merged.set_raw_quality_mode(mode);
+        }
+        if let Some(ratio) = other.min_compression_ratio {
+            merged.set_min_compression_ratio(ratio);
+        }
+        if let Some(strategy) = other.on_no_improvement {
+            merged.set_on_no_improvement(strategy);
+        }
+        if let Some(skip) = other.skip_if_larger {
+            merged.set_skip_if_larger(skip);
+        }
+        if let Some(lossless) = other.use_lossless_rotation {
+            merged.set_use_lossless_rotation(lossless);
+        }
+        if let Some(ref params) = other.raw_params {
+            merged.set_raw_params(params.clone());
+        }
+        if let Some(index) = other.heic_image_index {
+            merged.set_heic_image_index(index);
+        }
+        if let Some(auto_orient) = other.auto_orient {
+            merged.set_auto_orient(auto_orient);
+        }
+        if let Some(use_cache) = other.use_cache {
+            merged.set_use_cache(use_cache);
+        }
+        if let Some(ref mode) = other.output_mode {
+            merged.set_output_mode(mode.clone());
+        }
+        if let Some(flatten) = other.flatten_zip {
+            merged.set_flatten_zip(flatten);
+        }
+        if let Some(ref sizes) = other.sizes {
+            merged.set_sizes(sizes.clone());
+        }
+        if let Some(ref suffix) = other.size_suffix {
+            merged.set_size_suffix(suffix.clone());
+        }
+        if let Some(color) = other.background_color {
+            merged.set_background_color(color);
+        }
+        if let Some(target) = other.target_size_bytes {
+            merged.set_target_size_bytes(target);
+        }
+        if let Some(limit) = other.max_output_bytes {
+            merged.set_max_output_bytes(limit);
+        }
+        if let Some(lossy) = other.png_lossy {
+            merged.set_png_lossy(lossy);
+        }
+        if let Some(depth) = other.png_bit_depth {
+            merged.set_png_bit_depth(depth);
+        }
+        if let Some(edge) = other.max_long_edge {
+            merged.set_max_long_edge(edge);
+        }
+        if let Some(progressive) = other.progressive {
+            merged.set_progressive(progressive);
+        }
+        if let Some(chroma) = other.jpeg_chroma {
+            merged.set_jpeg_chroma(chroma);
+        }
+        if let Some(optimize) = other.jpeg_optimize_huffman {
+            merged.set_jpeg_optimize_huffman(optimize);
+        }
+        if let Some(lossless) = other.lossless {
+            merged.set_lossless(lossless);
+        }
+        if let Some(max_retries) = other.max_retries {
+            merged.set_max_retries(max_retries);
+        }
+        if let Some(delay_ms) = other.retry_delay_ms {
+            merged.set_retry_delay_ms(delay_ms);
+        }
+        if let Some(phase_timings) = other.phase_timings {
+            merged.set_phase_timings(phase_timings);
+        }
+        if let Some(failures) = other.abort_after_failures {
+            merged.set_abort_after_failures(failures);
+        }
+        if let Some(ref overrides) = other.image_overrides {
+            merged.set_image_overrides(overrides.clone());
+        }
+
+        merged
     }
 
     /// Set quality
     pub fn set_quality(&mut self, quality: Quality) -> &mut Self {
-        self.quality = quality;
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Set output directory
+    pub fn set_output_directory(&mut self, output_directory: PathBuf) -> &mut Self {
+        self.output_directory = output_directory;
         self
     }
 
-    /// Set output format
+    /// Set a single output format (None = keep original)
     pub fn set_output_format(&mut self, format: Option<ImageFormat>) -> &mut Self {
-        self.output_format = format;
+        self.output_formats = format.into_iter().collect();
+        self
+    }
+
+    /// Set several output formats; duplicates are dropped, keeping the first occurrence
+    pub fn set_output_formats(&mut self, formats: Vec<ImageFormat>) -> &mut Self {
+        self.output_formats.clear();
+        for format in formats {
+            if !self.output_formats.contains(&format) {
+                self.output_formats.push(format);
+            }
+        }
         self
     }
 
@@ -87,14 +524,258 @@ impl ProcessingSettings {
         self
     }
 
-    /// Get quality
+    /// Set minimum compression ratio (clamped to 0.0-1.0)
+    pub fn set_min_compression_ratio(&mut self, ratio: Option<f64>) -> &mut Self {
+        self.min_compression_ratio = ratio.map(|r| r.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set strategy for images that don't meet the minimum compression ratio
+    pub fn set_on_no_improvement(&mut self, strategy: SkipStrategy) -> &mut Self {
+        self.on_no_improvement = strategy;
+        self
+    }
+
+    /// Set whether outputs larger than the original are discarded
+    pub fn set_skip_if_larger(&mut self, skip: bool) -> &mut Self {
+        self.skip_if_larger = skip;
+        self
+    }
+
+    /// Set lossless JPEG rotation
+    pub fn set_use_lossless_rotation(&mut self, lossless: bool) -> &mut Self {
+        self.use_lossless_rotation = lossless;
+        self
+    }
+
+    /// Set RAW development parameters
+    pub fn set_raw_params(&mut self, params: RawProcessingParams) -> &mut Self {
+        self.raw_params = params;
+        self
+    }
+
+    /// Set which image of a HEIC container to decode
+    pub fn set_heic_image_index(&mut self, index: usize) -> &mut Self {
+        self.heic_image_index = index;
+        self
+    }
+
+    /// Set automatic EXIF orientation
+    pub fn set_auto_orient(&mut self, auto_orient: bool) -> &mut Self {
+        self.auto_orient = auto_orient;
+        self
+    }
+
+    /// Set whether decoded images are cached
+    pub fn set_use_cache(&mut self, use_cache: bool) -> &mut Self {
+        self.use_cache = use_cache;
+        self
+    }
+
+    /// Set the background color for flattening transparency
+    pub fn set_background_color(&mut self, color: [u8; 3]) -> &mut Self {
+        self.background_color = color;
+        self
+    }
+
+    /// Set the maximum output size in bytes (None = no limit)
+    pub fn set_target_size_bytes(&mut self, target: Option<u64>) -> &mut Self {
+        self.target_size_bytes = target;
+        self
+    }
+
+    /// Set the hard size limit for lossy WebP outputs in bytes (None = no limit)
+    pub fn set_max_output_bytes(&mut self, limit: Option<usize>) -> &mut Self {
+        self.max_output_bytes = limit;
+        self
+    }
+
+    /// Set lossy PNG quantization
+    pub fn set_png_lossy(&mut self, lossy: bool) -> &mut Self {
+        self.png_lossy = lossy;
+        self
+    }
+
+    /// Set the bit depth of PNG outputs
+    pub fn set_png_bit_depth(&mut self, depth: PngBitDepth) -> &mut Self {
+        self.png_bit_depth = depth;
+        self
+    }
+
+    /// Set the maximum length of the longest side (None = no limit)
+    pub fn set_max_long_edge(&mut self, edge: Option<u32>) -> &mut Self {
+        self.max_long_edge = edge;
+        self
+    }
+
+    /// Set progressive (true) or baseline (false) JPEG encoding
+    pub fn set_progressive(&mut self, progressive: bool) -> &mut Self {
+        self.progressive = progressive;
+        self
+    }
+
+    /// Set JPEG chroma subsampling
+    pub fn set_jpeg_chroma(&mut self, chroma: ChromaSubsampling) -> &mut Self {
+        self.jpeg_chroma = chroma;
+        self
+    }
+
+    /// Set whether JPEG Huffman tables and scans are optimized per image
+    pub fn set_jpeg_optimize_huffman(&mut self, optimize: bool) -> &mut Self {
+        self.jpeg_optimize_huffman = optimize;
+        self
+    }
+
+    /// Set lossless mode
+    pub fn set_lossless(&mut self, lossless: bool) -> &mut Self {
+        self.lossless = lossless;
+        self
+    }
+
+    /// Set how many times a failed read is retried (0 = no retries)
+    pub fn set_max_retries(&mut self, max_retries: u32) -> &mut Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the wait between retries, in milliseconds
+    pub fn set_retry_delay_ms(&mut self, delay_ms: u64) -> &mut Self {
+        self.retry_delay_ms = delay_ms;
+        self
+    }
+
+    /// Set whether results report the time spent in each phase
+    pub fn set_phase_timings(&mut self, phase_timings: bool) -> &mut Self {
+        self.phase_timings = phase_timings;
+        self
+    }
+
+    /// Set how many consecutive failed images stop the batch (None = never stop)
+    pub fn set_abort_after_failures(&mut self, failures: Option<usize>) -> &mut Self {
+        self.abort_after_failures = failures;
+        self
+    }
+
+    /// Set whether the batch only reports what it would do, without writing
+    pub fn set_dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Set whether images with up-to-date outputs are skipped
+    pub fn set_skip_unchanged(&mut self, skip_unchanged: bool) -> &mut Self {
+        self.skip_unchanged = skip_unchanged;
+        self
+    }
+
+    /// Set what happens to originals once their outputs are written
+    pub fn set_post_action(&mut self, action: PostAction) -> &mut Self {
+        self.post_action = action;
+        self
+    }
+
+    /// Set where the batch report is written (None = no report)
+    pub fn set_write_report(&mut self, path: Option<PathBuf>) -> &mut Self {
+        self.write_report = path;
+        self
+    }
+
+    /// Set whether originals are backed up before an output overwrites them
+    pub fn set_create_backup(&mut self, create_backup: bool) -> &mut Self {
+        self.create_backup = create_backup;
+        self
+    }
+
+    /// Set whether outputs mirror the inputs' folders under the output directory
+    pub fn set_preserve_directory_structure(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_directory_structure = preserve;
+        self
+    }
+
+    /// Set the folder the mirrored structure starts at (None = the batch's common folder)
+    pub fn set_source_base(&mut self, source_base: Option<PathBuf>) -> &mut Self {
+        self.source_base = source_base;
+        self
+    }
+
+    /// Set how existing output files are handled when not overwriting
+    pub fn set_collision_strategy(&mut self, strategy: CollisionStrategy) -> &mut Self {
+        self.collision_strategy = strategy;
+        self
+    }
+
+    /// Set per-image settings keyed by input path (their own overrides are dropped)
+    pub fn set_image_overrides(
+        &mut self,
+        overrides: HashMap<PathBuf, ProcessingSettings>,
+    ) -> &mut Self {
+        self.image_overrides = overrides
+            .into_iter()
+            .map(|(path, mut settings)| {
+                settings.image_overrides.clear();
+                (path, settings)
+            })
+            .collect();
+        self
+    }
+
+    /// Set output mode (loose files or zip archive)
+    pub fn set_output_mode(&mut self, mode: OutputMode) -> &mut Self {
+        self.output_mode = mode;
+        self
+    }
+
+    /// Set whether zip entries drop their directories
+    pub fn set_flatten_zip(&mut self, flatten: bool) -> &mut Self {
+        self.flatten_zip = flatten;
+        self
+    }
+
+    /// Set responsive widths; duplicates are dropped, keeping the first occurrence
+    pub fn set_sizes(&mut self, sizes: Vec<u32>) -> &mut Self {
+        self.sizes.clear();
+        for size in sizes {
+            if !self.sizes.contains(&size) {
+                self.sizes.push(size);
+            }
+        }
+        self
+    }
+
+    /// Set the file name suffix template for sized outputs
+    pub fn set_size_suffix(&mut self, suffix: String) -> &mut Self {
+        self.size_suffix = suffix;
+        self
+    }
+
+    /// Get quality (the default when none was set)
     pub fn quality(&self) -> Quality {
+        self.quality.unwrap_or_default()
+    }
+
+    /// Get the quality set explicitly, if any
+    pub fn explicit_quality(&self) -> Option<Quality> {
         self.quality
     }
 
-    /// Get output format
+    /// Quality to write `output_format` with: the one set, or when none was set and
+    /// the format changes, `Quality::recommended_for` the output format
+    pub fn quality_for(&self, output_format: ImageFormat, input_format: ImageFormat) -> Quality {
+        match self.quality {
+            Some(quality) => quality,
+            None if output_format != input_format => Quality::recommended_for(output_format),
+            None => Quality::default(),
+        }
+    }
+
+    /// Get the primary (first) output format
     pub fn output_format(&self) -> Option<ImageFormat> {
-        self.output_format
+        self.output_formats.first().copied()
+    }
+
+    /// Get every output format
+    pub fn output_formats(&self) -> &[ImageFormat] {
+        &self.output_formats
     }
 
     /// Get output directory
@@ -102,6 +783,41 @@ impl ProcessingSettings {
         &self.output_directory
     }
 
+    /// Whether outputs are written next to each input (no output directory set)
+    pub fn in_place(&self) -> bool {
+        self.output_directory.as_os_str().is_empty()
+    }
+
+    /// Get whether outputs mirror the inputs' folders
+    pub fn preserve_directory_structure(&self) -> bool {
+        self.preserve_directory_structure
+    }
+
+    /// Get the folder the mirrored structure starts at
+    pub fn source_base(&self) -> Option<&Path> {
+        self.source_base.as_deref()
+    }
+
+    /// Directory the outputs of `input` go to: the output directory, or in place the
+    /// input's own. With `preserve_directory_structure` the input's folder relative
+    /// to `source_base` is appended; inputs outside `source_base` stay at the top.
+    pub fn output_directory_for<'a>(&'a self, input: &'a Path) -> Cow<'a, Path> {
+        if self.in_place() {
+            return Cow::Borrowed(input.parent().unwrap_or(Path::new("")));
+        }
+
+        let relative = self
+            .source_base
+            .as_deref()
+            .filter(|_| self.preserve_directory_structure)
+            .and_then(|base| input.parent()?.strip_prefix(base).ok())
+            .filter(|relative| !relative.as_os_str().is_empty());
+        match relative {
+            Some(relative) => Cow::Owned(self.output_directory.join(relative)),
+            None => Cow::Borrowed(&self.output_directory),
+        }
+    }
+
     /// Get preserve metadata
     pub fn preserve_metadata(&self) -> bool {
         self.preserve_metadata
@@ -112,6 +828,11 @@ impl ProcessingSettings {
         self.overwrite_existing
     }
 
+    /// Get whether originals are backed up before being overwritten
+    pub fn create_backup(&self) -> bool {
+        self.create_backup
+    }
+
     /// Get max workers
     pub fn max_workers(&self) -> Option<usize> {
         self.max_workers
@@ -122,22 +843,381 @@ impl ProcessingSettings {
         self.raw_quality_mode
     }
 
-    /// Determine the output format for a given input format
+    /// Get minimum compression ratio
+    pub fn min_compression_ratio(&self) -> Option<f64> {
+        self.min_compression_ratio
+    }
+
+    /// Get how existing output files are handled when not overwriting
+    pub fn collision_strategy(&self) -> CollisionStrategy {
+        self.collision_strategy
+    }
+
+    /// Get strategy for images that don't meet the minimum compression ratio
+    pub fn on_no_improvement(&self) -> SkipStrategy {
+        self.on_no_improvement
+    }
+
+    /// Get whether outputs larger than the original are discarded
+    pub fn skip_if_larger(&self) -> bool {
+        self.skip_if_larger
+    }
+
+    /// Get lossless JPEG rotation
+    pub fn use_lossless_rotation(&self) -> bool {
+        self.use_lossless_rotation
+    }
+
+    /// Get RAW development parameters
+    pub fn raw_params(&self) -> &RawProcessingParams {
+        &self.raw_params
+    }
+
+    /// Get which image of a HEIC container to decode
+    pub fn heic_image_index(&self) -> usize {
+        self.heic_image_index
+    }
+
+    /// Get automatic EXIF orientation
+    pub fn auto_orient(&self) -> bool {
+        self.auto_orient
+    }
+
+    /// Get whether decoded images are cached
+    pub fn use_cache(&self) -> bool {
+        self.use_cache
+    }
+
+    /// Get the background color for flattening transparency
+    pub fn background_color(&self) -> [u8; 3] {
+        self.background_color
+    }
+
+    /// Get the maximum output size in bytes
+    pub fn target_size_bytes(&self) -> Option<u64> {
+        self.target_size_bytes
+    }
+
+    /// Get the hard size limit for lossy WebP outputs in bytes
+    pub fn max_output_bytes(&self) -> Option<usize> {
+        self.max_output_bytes
+    }
+
+    /// Get lossy PNG quantization
+    pub fn png_lossy(&self) -> bool {
+        self.png_lossy
+    }
+
+    /// Get the bit depth of PNG outputs
+    pub fn png_bit_depth(&self) -> PngBitDepth {
+        self.png_bit_depth
+    }
+
+    /// Get the maximum length of the longest side
+    pub fn max_long_edge(&self) -> Option<u32> {
+        self.max_long_edge
+    }
+
+    /// Get whether JPEG outputs are progressive
+    pub fn progressive(&self) -> bool {
+        self.progressive
+    }
+
+    /// Get lossless mode
+    pub fn lossless(&self) -> bool {
+        self.lossless
+    }
+
+    /// Get how many times a failed read is retried
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Get the wait between retries, in milliseconds
+    pub fn retry_delay_ms(&self) -> u64 {
+        self.retry_delay_ms
+    }
+
+    /// Get whether results report the time spent in each phase
+    pub fn phase_timings(&self) -> bool {
+        self.phase_timings
+    }
+
+    /// Get how many consecutive failed images stop the batch
+    pub fn abort_after_failures(&self) -> Option<usize> {
+        self.abort_after_failures
+    }
+
+    /// Get whether this is a dry run (nothing is written)
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Get whether images with up-to-date outputs are skipped
+    pub fn skip_unchanged(&self) -> bool {
+        self.skip_unchanged
+    }
+
+    /// Get what happens to originals once their outputs are written
+    pub fn post_action(&self) -> &PostAction {
+        &self.post_action
+    }
+
+    /// Get where the batch report is written
+    pub fn write_report(&self) -> Option<&Path> {
+        self.write_report.as_deref()
+    }
+
+    /// Get JPEG chroma subsampling (may be `Auto`; see `ChromaSubsampling::for_quality`)
+    pub fn jpeg_chroma(&self) -> ChromaSubsampling {
+        self.jpeg_chroma
+    }
+
+    /// Get whether JPEG Huffman tables and scans are optimized per image
+    pub fn jpeg_optimize_huffman(&self) -> bool {
+        self.jpeg_optimize_huffman
+    }
+
+    /// Get the per-image settings
+    pub fn image_overrides(&self) -> &HashMap<PathBuf, ProcessingSettings> {
+        &self.image_overrides
+    }
+
+    /// Effective settings for one image: its override if there is one, writing to
+    /// this batch's output directory and mode with this batch's overwrite, backup,
+    /// collision and post-action policy
+    pub fn settings_for(&self, path: &Path) -> Cow<'_, ProcessingSettings> {
+        match self.image_overrides.get(path) {
+            Some(image_settings) => {
+                let mut settings = image_settings.clone();
+                settings.output_directory = self.output_directory.clone();
+                settings.preserve_directory_structure = self.preserve_directory_structure;
+                settings.source_base = self.source_base.clone();
+                settings.output_mode = self.output_mode.clone();
+                settings.overwrite_existing = self.overwrite_existing;
+                settings.create_backup = self.create_backup;
+                settings.collision_strategy = self.collision_strategy;
+                settings.dry_run = self.dry_run;
+                settings.skip_unchanged = self.skip_unchanged;
+                settings.post_action = self.post_action.clone();
+                settings.write_report = self.write_report.clone();
+                Cow::Owned(settings)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Get output mode
+    pub fn output_mode(&self) -> &OutputMode {
+        &self.output_mode
+    }
+
+    /// Get whether zip entries drop their directories
+    pub fn flatten_zip(&self) -> bool {
+        self.flatten_zip
+    }
+
+    /// Get responsive widths
+    pub fn sizes(&self) -> &[u32] {
+        &self.sizes
+    }
+
+    /// Get the file name suffix for an output of `width` pixels (e.g. "-640w")
+    pub fn size_suffix(&self, width: u32) -> String {
+        self.size_suffix.replace("{width}", &width.to_string())
+    }
+
+    /// Get the size suffix as configured, with `{width}` unreplaced
+    pub fn size_suffix_template(&self) -> &str {
+        &self.size_suffix
+    }
+
+    /// Serialize to TOML, e.g. to save as a preset file
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    /// Parse settings written by `to_toml`; missing fields take their default
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    /// Determine the primary output format for a given input format
     pub fn determine_output_format(&self, input_format: ImageFormat) -> ImageFormat {
-        self.output_format.unwrap_or(input_format)
+        self.output_format()
+            .unwrap_or_else(|| self.default_output_format(input_format))
+    }
+
+    /// Determine every output format for a given input format
+    pub fn determine_output_formats(&self, input_format: ImageFormat) -> Vec<ImageFormat> {
+        if self.output_formats.is_empty() {
+            vec![self.default_output_format(input_format)]
+        } else {
+            self.output_formats.clone()
+        }
+    }
+
+    /// Output format when none is set: the input's, except that in lossless mode
+    /// inputs only writable lossy (JPEG, RAW and HEIC, which become JPEG) go to PNG
+    fn default_output_format(&self, input_format: ImageFormat) -> ImageFormat {
+        match input_format {
+            ImageFormat::Jpeg | ImageFormat::Raw | ImageFormat::Heic if self.lossless => {
+                ImageFormat::Png
+            }
+            _ => input_format,
+        }
+    }
+
+    /// Every output for a given input format: each size (None = original size)
+    /// in each output format
+    pub fn determine_output_variants(
+        &self,
+        input_format: ImageFormat,
+    ) -> Vec<(Option<u32>, ImageFormat)> {
+        let formats = self.determine_output_formats(input_format);
+        let widths: Vec<Option<u32>> = if self.sizes.is_empty() {
+            vec![None]
+        } else {
+            self.sizes.iter().copied().map(Some).collect()
+        };
+
+        widths
+            .into_iter()
+            .flat_map(|width| formats.iter().map(move |&format| (width, format)))
+            .collect()
     }
 }
 
 impl Default for ProcessingSettings {
     fn default() -> Self {
         Self {
-            quality: Quality::default(),
-            output_format: None,
+            quality: None,
+            output_formats: Vec::new(),
             output_directory: PathBuf::from("."),
+            preserve_directory_structure: false,
+            source_base: None,
             preserve_metadata: false,
             overwrite_existing: false,
+            create_backup: false,
+            collision_strategy: CollisionStrategy::Error,
             max_workers: None,
             raw_quality_mode: RawQualityMode::Balanced,
+            min_compression_ratio: None,
+            on_no_improvement: SkipStrategy::Skip,
+            skip_if_larger: false,
+            use_lossless_rotation: true,
+            raw_params: RawProcessingParams::default(),
+            heic_image_index: 0,
+            auto_orient: true,
+            use_cache: false,
+            output_mode: OutputMode::Files,
+            flatten_zip: true,
+            sizes: Vec::new(),
+            size_suffix: "-{width}w".to_string(),
+            background_color: [255, 255, 255],
+            target_size_bytes: None,
+            max_output_bytes: None,
+            png_lossy: false,
+            png_bit_depth: PngBitDepth::Preserve,
+            max_long_edge: None,
+            progressive: true,
+            jpeg_chroma: ChromaSubsampling::Auto,
+            jpeg_optimize_huffman: true,
+            lossless: false,
+            max_retries: 0,
+            retry_delay_ms: 0,
+            phase_timings: false,
+            abort_after_failures: None,
+            dry_run: false,
+            skip_unchanged: false,
+            post_action: PostAction::Keep,
+            write_report: None,
+            image_overrides: HashMap::new(),
+        }
+    }
+}
+
+/// `ProcessingSettings` fields to overlay with `ProcessingSettings::merge`;
+/// None keeps the base value. Fields that are optional themselves use
+/// `Some(None)` to clear the base value.
+#[derive(Debug, Clone, Default)]
+pub struct PartialProcessingSettings {
+    pub quality: Option<Quality>,
+    pub output_formats: Option<Vec<ImageFormat>>,
+    pub output_directory: Option<PathBuf>,
+    pub preserve_metadata: Option<bool>,
+    pub overwrite_existing: Option<bool>,
+    pub collision_strategy: Option<CollisionStrategy>,
+    pub max_workers: Option<Option<usize>>,
+    pub raw_quality_mode: Option<RawQualityMode>,
+    pub min_compression_ratio: Option<Option<f64>>,
+    pub on_no_improvement: Option<SkipStrategy>,
+    pub skip_if_larger: Option<bool>,
+    pub use_lossless_rotation: Option<bool>,
+    pub raw_params: Option<RawProcessingParams>,
+    pub heic_image_index: Option<usize>,
+    pub auto_orient: Option<bool>,
+    pub use_cache: Option<bool>,
+    pub output_mode: Option<OutputMode>,
+    pub flatten_zip: Option<bool>,
+    pub sizes: Option<Vec<u32>>,
+    pub size_suffix: Option<String>,
+    pub background_color: Option<[u8; 3]>,
+    pub target_size_bytes: Option<Option<u64>>,
+    pub max_output_bytes: Option<Option<usize>>,
+    pub png_lossy: Option<bool>,
+    pub png_bit_depth: Option<PngBitDepth>,
+    pub max_long_edge: Option<Option<u32>>,
+    pub progressive: Option<bool>,
+    pub jpeg_chroma: Option<ChromaSubsampling>,
+    pub jpeg_optimize_huffman: Option<bool>,
+    pub lossless: Option<bool>,
+    pub max_retries: Option<u32>,
+    pub retry_delay_ms: Option<u64>,
+    pub phase_timings: Option<bool>,
+    pub abort_after_failures: Option<Option<usize>>,
+    pub image_overrides: Option<HashMap<PathBuf, ProcessingSettings>>,
+}
+
+/// Every field set: merging it replaces the base settings entirely
+impl From<ProcessingSettings> for PartialProcessingSettings {
+    fn from(settings: ProcessingSettings) -> Self {
+        Self {
+            quality: settings.quality,
+            output_formats: Some(settings.output_formats),
+            output_directory: Some(settings.output_directory),
+            preserve_metadata: Some(settings.preserve_metadata),
+            overwrite_existing: Some(settings.overwrite_existing),
+            collision_strategy: Some(settings.collision_strategy),
+            max_workers: Some(settings.max_workers),
+            raw_quality_mode: Some(settings.raw_quality_mode),
+            min_compression_ratio: Some(settings.min_compression_ratio),
+            on_no_improvement: Some(settings.on_no_improvement),
+            skip_if_larger: Some(settings.skip_if_larger),
+            use_lossless_rotation: Some(settings.use_lossless_rotation),
+            raw_params: Some(settings.raw_params),
+            heic_image_index: Some(settings.heic_image_index),
+            auto_orient: Some(settings.auto_orient),
+            use_cache: Some(settings.use_cache),
+            output_mode: Some(settings.output_mode),
+            flatten_zip: Some(settings.flatten_zip),
+            sizes: Some(settings.sizes),
+            size_suffix: Some(settings.size_suffix),
+            background_color: Some(settings.background_color),
+            target_size_bytes: Some(settings.target_size_bytes),
+            max_output_bytes: Some(settings.max_output_bytes),
+            png_lossy: Some(settings.png_lossy),
+            png_bit_depth: Some(settings.png_bit_depth),
+            max_long_edge: Some(settings.max_long_edge),
+            progressive: Some(settings.progressive),
+            jpeg_chroma: Some(settings.jpeg_chroma),
+            jpeg_optimize_huffman: Some(settings.jpeg_optimize_huffman),
+            lossless: Some(settings.lossless),
+            max_retries: Some(settings.max_retries),
+            retry_delay_ms: Some(settings.retry_delay_ms),
+            phase_timings: Some(settings.phase_timings),
+            abort_after_failures: Some(settings.abort_after_failures),
+            image_overrides: Some(settings.image_overrides),
         }
     }
 }
@@ -151,6 +1231,44 @@ mod tests {
         let settings = ProcessingSettings::default();
         assert_eq!(settings.quality().value(), 85);
         assert!(!settings.preserve_metadata());
+        assert!(settings.use_lossless_rotation());
+        assert!(settings.auto_orient());
+        assert!(settings.progressive());
+        assert!(settings.jpeg_optimize_huffman());
+        assert_eq!(settings.background_color(), [255, 255, 255]);
+        assert_eq!(settings.raw_params().white_balance, WhiteBalance::Camera);
+        assert_eq!(settings.raw_params().output_bps, 8);
+    }
+
+    #[test]
+    fn test_quality_for_format() {
+        let mut settings = ProcessingSettings::default();
+        assert_eq!(settings.explicit_quality(), None);
+        assert_eq!(
+            settings.quality_for(ImageFormat::Png, ImageFormat::Jpeg),
+            Quality::maximum()
+        );
+        assert_eq!(
+            settings
+                .quality_for(ImageFormat::Webp, ImageFormat::Png)
+                .value(),
+            80
+        );
+        // Mismo formato: la calidad por defecto de siempre
+        assert_eq!(
+            settings
+                .quality_for(ImageFormat::Png, ImageFormat::Png)
+                .value(),
+            85
+        );
+
+        settings.set_quality(Quality::new(60).unwrap());
+        assert_eq!(
+            settings
+                .quality_for(ImageFormat::Png, ImageFormat::Jpeg)
+                .value(),
+            60
+        );
     }
 
     #[test]
@@ -181,4 +1299,222 @@ mod tests {
             ImageFormat::Jpeg
         );
     }
+
+    #[test]
+    fn test_determine_output_formats() {
+        let mut settings = ProcessingSettings::default();
+        assert_eq!(
+            settings.determine_output_formats(ImageFormat::Png),
+            vec![ImageFormat::Png]
+        );
+
+        settings.set_output_formats(vec![
+            ImageFormat::Jpeg,
+            ImageFormat::Webp,
+            ImageFormat::Jpeg,
+        ]);
+        assert_eq!(
+            settings.determine_output_formats(ImageFormat::Png),
+            vec![ImageFormat::Jpeg, ImageFormat::Webp]
+        );
+        assert_eq!(settings.output_format(), Some(ImageFormat::Jpeg));
+    }
+
+    #[test]
+    fn test_determine_output_variants() {
+        let mut settings = ProcessingSettings::default();
+        assert_eq!(
+            settings.determine_output_variants(ImageFormat::Png),
+            vec![(None, ImageFormat::Png)]
+        );
+
+        settings
+            .set_sizes(vec![640, 320, 640])
+            .set_output_formats(vec![ImageFormat::Jpeg, ImageFormat::Webp]);
+        assert_eq!(
+            settings.determine_output_variants(ImageFormat::Png),
+            vec![
+                (Some(640), ImageFormat::Jpeg),
+                (Some(640), ImageFormat::Webp),
+                (Some(320), ImageFormat::Jpeg),
+                (Some(320), ImageFormat::Webp),
+            ]
+        );
+        assert_eq!(settings.size_suffix(640), "-640w");
+    }
+
+    #[test]
+    fn test_from_profile() {
+        let web = ProcessingSettings::from_profile(ProcessingProfile::Web);
+        assert_eq!(web.quality().value(), 80);
+        assert_eq!(
+            web.output_formats(),
+            &[ImageFormat::Jpeg, ImageFormat::Webp]
+        );
+        assert_eq!(web.max_long_edge(), Some(2048));
+
+        let thumbnail = ProcessingSettings::from_profile(ProcessingProfile::Thumbnail);
+        assert_eq!(thumbnail.quality().value(), 70);
+        assert_eq!(thumbnail.output_formats(), &[ImageFormat::Jpeg]);
+        assert_eq!(thumbnail.max_long_edge(), Some(256));
+
+        for profile in [ProcessingProfile::Print, ProcessingProfile::Archive] {
+            let settings = ProcessingSettings::from_profile(profile);
+            assert_eq!(settings.output_formats(), &[ImageFormat::Png]);
+            assert!(settings.preserve_metadata());
+            assert!(settings.lossless());
+            assert_eq!(settings.max_long_edge(), None);
+        }
+
+        let custom = ProcessingSettings::from_profile(ProcessingProfile::Custom);
+        assert_eq!(custom.quality(), ProcessingSettings::default().quality());
+        assert!(custom.output_formats().is_empty());
+    }
+
+    #[test]
+    fn test_output_directory_for() {
+        let input = Path::new("/photos/trip/a.jpg");
+        let settings = ProcessingSettings::with_directory(PathBuf::from("/out"));
+        assert!(!settings.in_place());
+        assert_eq!(settings.output_directory_for(input), Path::new("/out"));
+
+        let in_place = ProcessingSettings::with_directory(PathBuf::new());
+        assert!(in_place.in_place());
+        assert_eq!(
+            in_place.output_directory_for(input),
+            Path::new("/photos/trip")
+        );
+    }
+
+    #[test]
+    fn test_output_directory_for_preserves_structure() {
+        let mut settings = ProcessingSettings::with_directory(PathBuf::from("/out"));
+        settings
+            .set_preserve_directory_structure(true)
+            .set_source_base(Some(PathBuf::from("/photos")));
+        assert_eq!(
+            settings.output_directory_for(Path::new("/photos/2024/trip/a.jpg")),
+            Path::new("/out/2024/trip")
+        );
+        assert_eq!(
+            settings.output_directory_for(Path::new("/photos/a.jpg")),
+            Path::new("/out")
+        );
+        // Fuera de la base: arriba del todo
+        assert_eq!(
+            settings.output_directory_for(Path::new("/elsewhere/a.jpg")),
+            Path::new("/out")
+        );
+
+        settings.set_preserve_directory_structure(false);
+        assert_eq!(
+            settings.output_directory_for(Path::new("/photos/2024/a.jpg")),
+            Path::new("/out")
+        );
+    }
+
+    #[test]
+    fn test_settings_for_uses_override_with_batch_output() {
+        let mut base = ProcessingSettings::with_directory(PathBuf::from("/out"));
+        base.set_output_mode(OutputMode::Zip(PathBuf::from("/out/all.zip")));
+
+        let mut custom = ProcessingSettings::with_directory(PathBuf::from("/elsewhere"));
+        custom
+            .set_quality(Quality::maximum())
+            .set_output_format(Some(ImageFormat::Webp));
+        base.set_image_overrides(HashMap::from([(PathBuf::from("/in/a.png"), custom)]));
+
+        let a = base.settings_for(Path::new("/in/a.png"));
+        assert_eq!(a.quality(), Quality::maximum());
+        assert_eq!(a.output_formats(), &[ImageFormat::Webp]);
+        assert_eq!(a.output_directory(), Path::new("/out"));
+        assert_eq!(a.output_mode(), base.output_mode());
+
+        let b = base.settings_for(Path::new("/in/b.png"));
+        assert!(matches!(b, Cow::Borrowed(_)));
+        assert_eq!(b.quality(), Quality::default());
+    }
+
+    #[test]
+    fn test_lossless_default_output_format() {
+        let mut settings = ProcessingSettings::default();
+        settings.set_lossless(true);
+
+        assert_eq!(
+            settings.determine_output_format(ImageFormat::Raw),
+            ImageFormat::Png
+        );
+        assert_eq!(
+            settings.determine_output_formats(ImageFormat::Jpeg),
+            vec![ImageFormat::Png]
+        );
+        assert_eq!(
+            settings.determine_output_format(ImageFormat::Webp),
+            ImageFormat::Webp
+        );
+
+        settings.set_lossless(false);
+        assert_eq!(
+            settings.determine_output_format(ImageFormat::Raw),
+            ImageFormat::Raw
+        );
+    }
+
+    #[test]
+    fn test_merge_only_overrides_set_fields() {
+        let mut base = ProcessingSettings::default();
+        base.set_max_long_edge(Some(1024)).set_progressive(false);
+
+        let merged = base.merge(&PartialProcessingSettings::default());
+        assert_eq!(merged.max_long_edge(), Some(1024));
+        assert!(!merged.progressive());
+
+        let patch = PartialProcessingSettings {
+            quality: Some(Quality::new(60).unwrap()),
+            output_formats: Some(vec![ImageFormat::Webp, ImageFormat::Webp]),
+            max_long_edge: Some(None),
+            ..Default::default()
+        };
+        let merged = base.merge(&patch);
+        assert_eq!(merged.quality().value(), 60);
+        assert_eq!(merged.output_formats(), &[ImageFormat::Webp]);
+        assert_eq!(merged.max_long_edge(), None);
+        assert!(!merged.progressive());
+
+        // Todos los campos: el resultado es el propio `other`
+        let profile = ProcessingSettings::from_profile(ProcessingProfile::Thumbnail);
+        let replaced = base.merge(&PartialProcessingSettings::from(profile.clone()));
+        assert_eq!(replaced.quality(), profile.quality());
+        assert_eq!(replaced.max_long_edge(), Some(256));
+        assert!(replaced.progressive());
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut settings = ProcessingSettings::from_profile(ProcessingProfile::Web);
+        settings
+            .set_collision_strategy(CollisionStrategy::Rename(RenameMode::Numbered))
+            .set_output_mode(OutputMode::Zip(PathBuf::from("/exports/web.zip")))
+            .set_target_size_bytes(Some(200_000))
+            .set_raw_params(RawProcessingParams {
+                white_balance: WhiteBalance::Custom(2.0, 1.0, 1.5, 1.0),
+                ..Default::default()
+            });
+
+        let toml = settings.to_toml().unwrap();
+        let parsed = ProcessingSettings::from_toml(&toml).unwrap();
+        assert_eq!(parsed.to_toml().unwrap(), toml);
+        assert_eq!(parsed.quality(), settings.quality());
+        assert_eq!(parsed.output_formats(), settings.output_formats());
+        assert_eq!(parsed.max_long_edge(), Some(2048));
+        assert_eq!(parsed.output_mode(), settings.output_mode());
+
+        // Campos ausentes: valores por defecto
+        let partial = ProcessingSettings::from_toml("quality = 70\nprogressive = false\n").unwrap();
+        assert_eq!(partial.quality().value(), 70);
+        assert!(!partial.progressive());
+        assert!(partial.auto_orient());
+
+        assert!(ProcessingSettings::from_toml("quality = \"high\"").is_err());
+    }
 }
@@ -1,18 +1,43 @@
 use crate::domain::error::{DomainError, DomainResult};
+use crate::domain::models::{TextWatermark, Watermark};
 use crate::domain::value_objects::Dimensions;
 use serde::{Deserialize, Serialize};
 
 /// Represents a set of transformations to apply to an image
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Transformation {
+    /// Turn the image upright from its EXIF orientation tag before anything else, even
+    /// with the settings' `auto_orient` off (decoding already does it when it's on)
+    #[serde(default)]
+    pub auto_orient: bool,
+    /// Crop transparent/solid-color borders before any other operation
+    pub trim: bool,
+    /// Per-channel tolerance (0-255) for near-solid borders when trimming
+    pub trim_tolerance: u8,
     /// Resize transformation
     pub resize: Option<ResizeTransformation>,
     /// Rotation in degrees (0, 90, 180, 270)
     pub rotation: Option<Rotation>,
+    /// Rotation by any angle, applied after the right-angle rotation
+    pub arbitrary_rotation: Option<ArbitraryRotation>,
     /// Flip horizontal
     pub flip_horizontal: bool,
     /// Flip vertical
     pub flip_vertical: bool,
+    /// Watermark stamped after resize, rotation and flips
+    pub watermark: Option<Watermark>,
+    /// Text stamped after the image watermark
+    pub text_watermark: Option<TextWatermark>,
+    /// Convert to grayscale before encoding
+    pub grayscale: bool,
+    /// Grayscale/sepia/channel extraction, applied after rotation and flips
+    pub color_adjustment: Option<ColorAdjustment>,
+    /// Brightness/contrast/saturation, applied right after resize
+    pub tonal: Option<TonalAdjustments>,
+    /// Unsharp mask, the last operation (at the final resolution, after watermarks)
+    pub sharpen: Option<SharpenOptions>,
+    /// Gaussian blur sigma, applied after resize
+    pub blur: Option<f32>,
 }
 
 impl Transformation {
@@ -37,6 +62,12 @@ impl Transformation {
         }
     }
 
+    /// Set whether the EXIF orientation is applied first
+    pub fn set_auto_orient(&mut self, auto_orient: bool) -> &mut Self {
+        self.auto_orient = auto_orient;
+        self
+    }
+
     /// Add resize transformation
     pub fn set_resize(&mut self, resize: ResizeTransformation) -> &mut Self {
         self.resize = Some(resize);
@@ -49,6 +80,24 @@ impl Transformation {
         self
     }
 
+    /// Add arbitrary-angle rotation
+    pub fn set_arbitrary_rotation(&mut self, rotation: ArbitraryRotation) -> &mut Self {
+        self.arbitrary_rotation = Some(rotation);
+        self
+    }
+
+    /// Add watermark
+    pub fn set_watermark(&mut self, watermark: Watermark) -> &mut Self {
+        self.watermark = Some(watermark);
+        self
+    }
+
+    /// Add text watermark
+    pub fn set_text_watermark(&mut self, watermark: TextWatermark) -> &mut Self {
+        self.text_watermark = Some(watermark);
+        self
+    }
+
     /// Set flip horizontal
     pub fn set_flip_horizontal(&mut self, flip: bool) -> &mut Self {
         self.flip_horizontal = flip;
@@ -61,12 +110,129 @@ impl Transformation {
         self
     }
 
+    /// Add tonal adjustments
+    pub fn set_tonal(&mut self, tonal: TonalAdjustments) -> &mut Self {
+        self.tonal = Some(tonal);
+        self
+    }
+
+    /// Set brightness (-1.0..=1.0), keeping the other tonal adjustments
+    pub fn set_brightness(&mut self, brightness: f32) -> DomainResult<&mut Self> {
+        let brightness = TonalAdjustments::validate("brightness", brightness)?;
+        self.tonal.get_or_insert_with(Default::default).brightness = brightness;
+        Ok(self)
+    }
+
+    /// Set contrast (-1.0..=1.0), keeping the other tonal adjustments
+    pub fn set_contrast(&mut self, contrast: f32) -> DomainResult<&mut Self> {
+        let contrast = TonalAdjustments::validate("contrast", contrast)?;
+        self.tonal.get_or_insert_with(Default::default).contrast = contrast;
+        Ok(self)
+    }
+
+    /// Set saturation (-1.0..=1.0), keeping the other tonal adjustments
+    pub fn set_saturation(&mut self, saturation: f32) -> DomainResult<&mut Self> {
+        let saturation = TonalAdjustments::validate("saturation", saturation)?;
+        self.tonal.get_or_insert_with(Default::default).saturation = saturation;
+        Ok(self)
+    }
+
+    /// Set unsharp mask options
+    pub fn set_sharpen(&mut self, sharpen: SharpenOptions) -> &mut Self {
+        self.sharpen = Some(sharpen);
+        self
+    }
+
+    /// Set gaussian blur sigma
+    pub fn set_blur(&mut self, sigma: f32) -> &mut Self {
+        self.blur = Some(sigma);
+        self
+    }
+
+    /// Set grayscale conversion
+    pub fn set_grayscale(&mut self, grayscale: bool) -> &mut Self {
+        self.grayscale = grayscale;
+        self
+    }
+
+    /// Enable border trimming with the given tolerance
+    pub fn set_trim(&mut self, trim: bool, tolerance: u8) -> &mut Self {
+        self.trim = trim;
+        self.trim_tolerance = tolerance;
+        self
+    }
+
+    /// Add color adjustment
+    pub fn set_color_adjustment(&mut self, adjustment: ColorAdjustment) -> &mut Self {
+        self.color_adjustment = Some(adjustment);
+        self
+    }
+
     /// Check if transformation has any operations
     pub fn has_operations(&self) -> bool {
-        self.resize.is_some()
+        self.auto_orient
+            || self.trim
+            || self.resize.is_some()
             || self.rotation.is_some()
+            || self.arbitrary_rotation.is_some()
             || self.flip_horizontal
             || self.flip_vertical
+            || self.watermark.is_some()
+            || self.text_watermark.is_some()
+            || self.grayscale
+            || self.color_adjustment.is_some()
+            || self.tonal.is_some()
+            || self.sharpen.is_some()
+            || self.blur.is_some()
+    }
+
+    /// Drop operations that wouldn't change an image of `source_dims`:
+    /// `Rotation::None`, 0°/360° arbitrary rotations and resizes that keep the size
+    pub fn normalize(&mut self, source_dims: &Dimensions) {
+        if self.rotation == Some(Rotation::None) {
+            self.rotation = None;
+        }
+
+        if self
+            .arbitrary_rotation
+            .is_some_and(|r| r.degrees().rem_euclid(360.0) == 0.0)
+        {
+            self.arbitrary_rotation = None;
+        }
+
+        if self.tonal.is_some_and(|t| t.is_neutral()) {
+            self.tonal = None;
+        }
+
+        if self.color_adjustment.is_some_and(|c| c.is_neutral()) {
+            self.color_adjustment = None;
+        }
+
+        // Cantidad o radio 0 = sin filtro
+        if self.sharpen.is_some_and(|s| s.is_neutral()) {
+            self.sharpen = None;
+        }
+        // Sigma 0 = sin filtro
+        if self.blur.is_some_and(|sigma| sigma <= 0.0) {
+            self.blur = None;
+        }
+
+        // Con trim el resize se calcula sobre la imagen recortada
+        if !self.trim
+            && self
+                .resize
+                .and_then(|r| r.calculate_final_dimensions(source_dims).ok())
+                .is_some_and(|dims| dims == *source_dims)
+        {
+            self.resize = None;
+        }
+    }
+
+    /// Check if applying this transformation to an image of `source_dims` is a no-op
+    pub fn is_identity(&self, source_dims: &Dimensions) -> bool {
+        let mut normalized = self.clone();
+        normalized.normalize(source_dims);
+        !normalized.has_operations()
     }
 
     /// Get resize if present
@@ -78,17 +244,71 @@ impl Transformation {
     pub fn rotation(&self) -> Option<Rotation> {
         self.rotation
     }
+
+    /// Get arbitrary-angle rotation if present
+    pub fn arbitrary_rotation(&self) -> Option<&ArbitraryRotation> {
+        self.arbitrary_rotation.as_ref()
+    }
+
+    /// Get watermark if present
+    pub fn watermark(&self) -> Option<&Watermark> {
+        self.watermark.as_ref()
+    }
+
+    /// Get text watermark if present
+    pub fn text_watermark(&self) -> Option<&TextWatermark> {
+        self.text_watermark.as_ref()
+    }
+
+    /// Get tonal adjustments if present
+    pub fn tonal(&self) -> Option<&TonalAdjustments> {
+        self.tonal.as_ref()
+    }
+
+    /// Get color adjustment if present
+    pub fn color_adjustment(&self) -> Option<ColorAdjustment> {
+        self.color_adjustment
+    }
+
+    /// Get unsharp mask options if present
+    pub fn sharpen(&self) -> Option<&SharpenOptions> {
+        self.sharpen.as_ref()
+    }
+
+    /// Get gaussian blur sigma if present
+    pub fn blur(&self) -> Option<f32> {
+        self.blur
+    }
+}
+
+/// How a resize target is expressed; resolved against the source dimensions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ResizeMode {
+    /// Absolute target dimensions
+    Absolute(Dimensions),
+    /// Percentage of the original dimensions (100 = unchanged, above 100 needs `allow_upscale`)
+    Percentage(f32),
+    /// Longest side in pixels, whatever the orientation
+    LongEdge(u32),
+    /// Shortest side in pixels, whatever the orientation
+    ShortEdge(u32),
+    /// Exactly these dimensions: scaled to cover them, then the excess centre-cropped
+    /// (CSS `object-fit: cover`)
+    Cover(Dimensions),
 }
 
 /// Resize transformation options
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ResizeTransformation {
-    /// Target dimensions
-    target_dimensions: Dimensions,
+    /// Target size
+    mode: ResizeMode,
     /// Preserve aspect ratio
     preserve_aspect_ratio: bool,
     /// Resize algorithm/filter
     filter: ResizeFilter,
+    /// Let the resize enlarge the image (otherwise each axis is capped at the original)
+    #[serde(default)]
+    allow_upscale: bool,
 }
 
 impl ResizeTransformation {
@@ -99,10 +319,64 @@ impl ResizeTransformation {
         filter: ResizeFilter,
     ) -> Self {
         Self {
-            target_dimensions,
+            mode: ResizeMode::Absolute(target_dimensions),
             preserve_aspect_ratio,
             filter,
+            allow_upscale: false,
+        }
+    }
+
+    /// Create a resize relative to the original size (50.0 = half, 200.0 = double)
+    pub fn with_percentage(percentage: f32, filter: ResizeFilter) -> DomainResult<Self> {
+        if !(percentage.is_finite() && percentage > 0.0) {
+            return Err(DomainError::InvalidResizePercentage(percentage));
         }
+
+        Ok(Self {
+            mode: ResizeMode::Percentage(percentage),
+            preserve_aspect_ratio: true,
+            filter,
+            allow_upscale: false,
+        })
+    }
+
+    /// Create a resize that caps the longest side (portrait and landscape alike)
+    pub fn with_longest_edge(edge: u32, filter: ResizeFilter) -> DomainResult<Self> {
+        Self::with_edge(ResizeMode::LongEdge(edge), edge, filter)
+    }
+
+    /// Create a resize that caps the shortest side
+    pub fn with_shortest_edge(edge: u32, filter: ResizeFilter) -> DomainResult<Self> {
+        Self::with_edge(ResizeMode::ShortEdge(edge), edge, filter)
+    }
+
+    fn with_edge(mode: ResizeMode, edge: u32, filter: ResizeFilter) -> DomainResult<Self> {
+        if edge == 0 {
+            return Err(DomainError::InvalidResizeEdge(edge));
+        }
+
+        Ok(Self {
+            mode,
+            preserve_aspect_ratio: true,
+            filter,
+            allow_upscale: false,
+        })
+    }
+
+    /// Create a resize that fills `target` completely, cropping what overflows it
+    pub fn with_cover(target: Dimensions, filter: ResizeFilter) -> Self {
+        Self {
+            mode: ResizeMode::Cover(target),
+            preserve_aspect_ratio: true,
+            filter,
+            allow_upscale: false,
+        }
+    }
+
+    /// Allow the resize to enlarge images smaller than the target
+    pub fn set_allow_upscale(&mut self, allow_upscale: bool) -> &mut Self {
+        self.allow_upscale = allow_upscale;
+        self
     }
 
     /// Create resize with default filter (Lanczos3)
@@ -114,9 +388,22 @@ impl ResizeTransformation {
         )
     }
 
-    /// Get target dimensions
-    pub fn target_dimensions(&self) -> &Dimensions {
-        &self.target_dimensions
+    /// Get resize mode
+    pub fn mode(&self) -> ResizeMode {
+        self.mode
+    }
+
+    /// Get target dimensions (None for relative modes)
+    pub fn target_dimensions(&self) -> Option<&Dimensions> {
+        match &self.mode {
+            ResizeMode::Absolute(dimensions) | ResizeMode::Cover(dimensions) => Some(dimensions),
+            _ => None,
+        }
+    }
+
+    /// Check if the resize may enlarge the image
+    pub fn allow_upscale(&self) -> bool {
+        self.allow_upscale
     }
 
     /// Check if aspect ratio should be preserved
@@ -129,17 +416,57 @@ impl ResizeTransformation {
         self.filter
     }
 
-    /// Calculate final dimensions based on original dimensions
+    /// Calculate final dimensions based on original dimensions.
+    /// Without `allow_upscale` neither axis grows beyond the original.
     pub fn calculate_final_dimensions(&self, original: &Dimensions) -> DomainResult<Dimensions> {
-        if self.preserve_aspect_ratio {
-            original.fit_within(
-                self.target_dimensions.width(),
-                self.target_dimensions.height(),
-            )
-        } else {
-            Ok(self.target_dimensions)
+        let target = self.unclamped_dimensions(original)?;
+        if self.allow_upscale {
+            return Ok(target);
+        }
+
+        Dimensions::new(
+            target.width().min(original.width()),
+            target.height().min(original.height()),
+        )
+    }
+
+    /// Check if the never-upscale guard changes the result for this original size
+    pub fn upscale_prevented(&self, original: &Dimensions) -> bool {
+        !self.allow_upscale
+            && self.unclamped_dimensions(original).is_ok_and(|target| {
+                target.width() > original.width() || target.height() > original.height()
+            })
+    }
+
+    /// Dimensions requested by the mode, before the never-upscale guard
+    fn unclamped_dimensions(&self, original: &Dimensions) -> DomainResult<Dimensions> {
+        match self.mode {
+            ResizeMode::Absolute(target) if self.preserve_aspect_ratio => {
+                original.fit_within(target.width(), target.height())
+            }
+            ResizeMode::Absolute(target) | ResizeMode::Cover(target) => Ok(target),
+            ResizeMode::Percentage(percentage) => original.scale(percentage as f64 / 100.0),
+            ResizeMode::LongEdge(edge) => {
+                self.fit_edge(original, original.width().max(original.height()), edge)
+            }
+            ResizeMode::ShortEdge(edge) => {
+                self.fit_edge(original, original.width().min(original.height()), edge)
+            }
         }
     }
+
+    /// Scale so that the measured edge (`current`) becomes `target`, keeping the aspect ratio
+    fn fit_edge(
+        &self,
+        original: &Dimensions,
+        current: u32,
+        target: u32,
+    ) -> DomainResult<Dimensions> {
+        let factor = target as f64 / current as f64;
+        let width = ((original.width() as f64 * factor).round() as u32).max(1);
+        let height = ((original.height() as f64 * factor).round() as u32).max(1);
+        Dimensions::new(width, height)
+    }
 }
 
 /// Image resize filters/algorithms
@@ -155,6 +482,9 @@ pub enum ResizeFilter {
     Gaussian,
     /// Lanczos with window 3 (best quality, slower)
     Lanczos3,
+    /// Linear interpolation after a box pre-shrink on large downscales
+    /// (fastest for previews, slightly softer than Triangle)
+    Thumbnail,
 }
 
 impl Default for ResizeFilter {
@@ -188,6 +518,25 @@ impl Rotation {
         }
     }
 
+    /// Rotation and horizontal flip (applied after the rotation) that display an image
+    /// with EXIF orientation tag `orientation` upright. None for values outside 1-8.
+    pub fn from_exif_orientation(orientation: u16) -> Option<(Self, bool)> {
+        match orientation {
+            1 => Some((Rotation::None, false)),
+            2 => Some((Rotation::None, true)),
+            3 => Some((Rotation::Rotate180, false)),
+            // Voltear en vertical = girar 180° y voltear en horizontal
+            4 => Some((Rotation::Rotate180, true)),
+            // Transposición
+            5 => Some((Rotation::Clockwise90, true)),
+            6 => Some((Rotation::Clockwise90, false)),
+            // Transversa
+            7 => Some((Rotation::Clockwise270, true)),
+            8 => Some((Rotation::Clockwise270, false)),
+            _ => None,
+        }
+    }
+
     /// Get rotation angle in degrees
     pub fn degrees(&self) -> i32 {
         *self as i32
@@ -199,6 +548,272 @@ impl Rotation {
     }
 }
 
+/// Rotation by any angle (e.g. straightening a tilted scan)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArbitraryRotation {
+    /// Angle in degrees, clockwise
+    degrees: f32,
+    /// RGBA color used for the corners exposed by the rotation
+    fill_color: [u8; 4],
+    /// Crop to the largest rectangle without exposed corners instead of growing the canvas
+    auto_crop: bool,
+}
+
+impl ArbitraryRotation {
+    /// Default fill color for exposed corners (opaque white)
+    pub const DEFAULT_FILL: [u8; 4] = [255, 255, 255, 255];
+
+    /// Create a new arbitrary rotation
+    pub fn new(degrees: f32, fill_color: [u8; 4], auto_crop: bool) -> DomainResult<Self> {
+        if !degrees.is_finite() {
+            return Err(DomainError::InvalidRotationAngle(degrees));
+        }
+        Ok(Self {
+            degrees,
+            fill_color,
+            auto_crop,
+        })
+    }
+
+    /// Create rotation with white fill and a growing canvas
+    pub fn with_degrees(degrees: f32) -> DomainResult<Self> {
+        Self::new(degrees, Self::DEFAULT_FILL, false)
+    }
+
+    /// Get rotation angle in degrees (clockwise)
+    pub fn degrees(&self) -> f32 {
+        self.degrees
+    }
+
+    /// Get fill color for exposed corners
+    pub fn fill_color(&self) -> [u8; 4] {
+        self.fill_color
+    }
+
+    /// Check if output should be cropped to hide exposed corners
+    pub fn auto_crop(&self) -> bool {
+        self.auto_crop
+    }
+
+    /// Calculate output dimensions for a source of the given size
+    pub fn calculate_final_dimensions(&self, original: &Dimensions) -> DomainResult<Dimensions> {
+        let (w, h) = (original.width() as f64, original.height() as f64);
+        let radians = (self.degrees as f64).to_radians();
+        let (sin, cos) = (radians.sin().abs(), radians.cos().abs());
+
+        let (out_w, out_h) = if self.auto_crop {
+            // Largest axis-aligned rectangle that fits inside the rotated image
+            let (long, short) = if w >= h { (w, h) } else { (h, w) };
+            if short <= 2.0 * sin * cos * long || (sin - cos).abs() < 1e-10 {
+                let x = 0.5 * short;
+                if w >= h {
+                    (x / sin, x / cos)
+                } else {
+                    (x / cos, x / sin)
+                }
+            } else {
+                let cos_2a = cos * cos - sin * sin;
+                ((w * cos - h * sin) / cos_2a, (h * cos - w * sin) / cos_2a)
+            }
+        } else {
+            (w * cos + h * sin, w * sin + h * cos)
+        };
+
+        Dimensions::new((out_w.round() as u32).max(1), (out_h.round() as u32).max(1))
+    }
+}
+
+/// Tonal corrections. Every value is in -1.0..=1.0, 0.0 = unchanged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TonalAdjustments {
+    /// Shift every channel linearly (1.0 = the full range)
+    brightness: f32,
+    /// S-curve strength; negative values flatten the image
+    contrast: f32,
+    /// Scale HSL saturation; -1.0 = grayscale
+    saturation: f32,
+}
+
+impl TonalAdjustments {
+    /// Lower bound for every adjustment
+    pub const MIN: f32 = -1.0;
+    /// Upper bound for every adjustment
+    pub const MAX: f32 = 1.0;
+
+    /// Create validated adjustments
+    pub fn new(brightness: f32, contrast: f32, saturation: f32) -> DomainResult<Self> {
+        Ok(Self {
+            brightness: Self::validate("brightness", brightness)?,
+            contrast: Self::validate("contrast", contrast)?,
+            saturation: Self::validate("saturation", saturation)?,
+        })
+    }
+
+    fn validate(name: &str, value: f32) -> DomainResult<f32> {
+        if !(Self::MIN..=Self::MAX).contains(&value) {
+            return Err(DomainError::InvalidAdjustment(format!(
+                "{} {} must be between -1 and 1",
+                name, value
+            )));
+        }
+        Ok(value)
+    }
+
+    /// Get brightness (-1.0..=1.0)
+    pub fn brightness(&self) -> f32 {
+        self.brightness
+    }
+
+    /// Get contrast (-1.0..=1.0)
+    pub fn contrast(&self) -> f32 {
+        self.contrast
+    }
+
+    /// Get saturation (-1.0..=1.0)
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    /// Check if no adjustment would change the image
+    pub fn is_neutral(&self) -> bool {
+        self.brightness == 0.0 && self.contrast == 0.0 && self.saturation == 0.0
+    }
+}
+
+/// Unsharp mask: `amount` times the difference from a gaussian blur of `radius`
+/// is added back, skipping pixels that differ by less than `threshold`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SharpenOptions {
+    /// Strength of the effect (1.0 = add the full difference)
+    amount: f32,
+    /// Gaussian blur sigma in pixels
+    radius: f32,
+    /// Minimum difference (0-255) to sharpen a pixel; avoids amplifying noise
+    threshold: u8,
+}
+
+impl SharpenOptions {
+    /// Upper bound for `amount`
+    pub const MAX_AMOUNT: f32 = 5.0;
+    /// Upper bound for `radius`
+    pub const MAX_RADIUS: f32 = 100.0;
+
+    /// Create validated options
+    pub fn new(amount: f32, radius: f32, threshold: u8) -> DomainResult<Self> {
+        if !(0.0..=Self::MAX_AMOUNT).contains(&amount) {
+            return Err(DomainError::InvalidAdjustment(format!(
+                "sharpen amount {} must be between 0 and {}",
+                amount,
+                Self::MAX_AMOUNT
+            )));
+        }
+        if !(0.0..=Self::MAX_RADIUS).contains(&radius) {
+            return Err(DomainError::InvalidAdjustment(format!(
+                "sharpen radius {} must be between 0 and {}",
+                radius,
+                Self::MAX_RADIUS
+            )));
+        }
+        Ok(Self {
+            amount,
+            radius,
+            threshold,
+        })
+    }
+
+    /// Subtle sharpening, e.g. for slight downscales
+    pub fn mild() -> Self {
+        Self {
+            amount: 0.5,
+            radius: 0.8,
+            threshold: 3,
+        }
+    }
+
+    /// General-purpose sharpening after a resize (the default)
+    pub fn moderate() -> Self {
+        Self {
+            amount: 1.0,
+            radius: 1.0,
+            threshold: 2,
+        }
+    }
+
+    /// Strong sharpening for heavy downscales
+    pub fn strong() -> Self {
+        Self {
+            amount: 1.5,
+            radius: 1.5,
+            threshold: 1,
+        }
+    }
+
+    /// Get amount (0.0..=5.0)
+    pub fn amount(&self) -> f32 {
+        self.amount
+    }
+
+    /// Get radius (gaussian sigma, 0.0..=100.0)
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Get threshold (0-255)
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Check if sharpening would leave the image unchanged
+    pub fn is_neutral(&self) -> bool {
+        self.amount == 0.0 || self.radius == 0.0
+    }
+}
+
+impl Default for SharpenOptions {
+    fn default() -> Self {
+        Self::moderate()
+    }
+}
+
+/// Color conversion for black-and-white and toned output
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ColorAdjustment {
+    /// Luminance grayscale (same as `Transformation::grayscale`)
+    Grayscale,
+    /// Sepia tone blended with the original; strength in 0.0..=1.0
+    Sepia(f32),
+    /// Keep a single channel as a grayscale image
+    ExtractChannel(Channel),
+}
+
+impl ColorAdjustment {
+    /// Create a validated sepia adjustment (0.0 = unchanged, 1.0 = full sepia)
+    pub fn sepia(strength: f32) -> DomainResult<Self> {
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(DomainError::InvalidAdjustment(format!(
+                "sepia strength {} must be between 0 and 1",
+                strength
+            )));
+        }
+        Ok(ColorAdjustment::Sepia(strength))
+    }
+
+    /// Check if the adjustment wouldn't change the image
+    pub fn is_neutral(&self) -> bool {
+        matches!(self, ColorAdjustment::Sepia(strength) if *strength == 0.0)
+    }
+}
+
+/// Image channel for `ColorAdjustment::ExtractChannel`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    /// Perceptual luminance (Rec. 709 weights)
+    Luma,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,12 +831,141 @@ mod tests {
         assert!(t.has_operations());
     }
 
+    #[test]
+    fn test_normalize_collapses_no_ops() {
+        let source = Dimensions::new(800, 600).unwrap();
+        let mut transformation = Transformation::with_rotation(Rotation::None);
+        transformation
+            .set_resize(ResizeTransformation::with_dimensions(source, false))
+            .set_arbitrary_rotation(ArbitraryRotation::with_degrees(360.0).unwrap());
+
+        assert!(transformation.has_operations());
+        assert!(transformation.is_identity(&source));
+
+        transformation.normalize(&source);
+        assert!(transformation.rotation().is_none());
+        assert!(transformation.resize().is_none());
+        assert!(transformation.arbitrary_rotation().is_none());
+        assert!(!transformation.has_operations());
+
+        let mut filters = Transformation::new();
+        filters
+            .set_sharpen(SharpenOptions::new(0.0, 1.0, 0).unwrap())
+            .set_blur(0.0);
+        assert!(filters.is_identity(&source));
+    }
+
+    #[test]
+    fn test_normalize_keeps_real_operations() {
+        let source = Dimensions::new(800, 600).unwrap();
+
+        let mut resize = Transformation::with_resize(ResizeTransformation::with_dimensions(
+            Dimensions::new(400, 300).unwrap(),
+            true,
+        ));
+        resize.normalize(&source);
+        assert!(resize.resize().is_some());
+
+        let mut rotation = Transformation::with_rotation(Rotation::Clockwise90);
+        rotation.normalize(&source);
+        assert_eq!(rotation.rotation(), Some(Rotation::Clockwise90));
+
+        let mut flip = Transformation::new();
+        flip.set_flip_horizontal(true);
+        assert!(!flip.is_identity(&source));
+
+        // El tamaño de origen del resize depende del recorte
+        let mut trimmed =
+            Transformation::with_resize(ResizeTransformation::with_dimensions(source, false));
+        trimmed.set_trim(true, 0);
+        trimmed.normalize(&source);
+        assert!(trimmed.resize().is_some());
+    }
+
+    #[test]
+    fn test_normalize_resize_larger_than_source_with_aspect_ratio() {
+        // fit_within no amplía, así que el resize no cambia nada
+        let source = Dimensions::new(800, 600).unwrap();
+        let transformation = Transformation::with_resize(ResizeTransformation::with_dimensions(
+            Dimensions::new(1920, 1080).unwrap(),
+            true,
+        ));
+        assert!(transformation.is_identity(&source));
+    }
+
+    #[test]
+    fn test_color_adjustment() {
+        assert!(ColorAdjustment::sepia(0.8).is_ok());
+        assert!(matches!(
+            ColorAdjustment::sepia(1.5),
+            Err(DomainError::InvalidAdjustment(_))
+        ));
+
+        let source = Dimensions::new(800, 600).unwrap();
+        let mut transformation = Transformation::new();
+        transformation.set_color_adjustment(ColorAdjustment::sepia(0.0).unwrap());
+        assert!(transformation.is_identity(&source));
+
+        transformation.set_color_adjustment(ColorAdjustment::ExtractChannel(Channel::Red));
+        assert!(!transformation.is_identity(&source));
+    }
+
+    #[test]
+    fn test_tonal_adjustments_validation() {
+        assert!(TonalAdjustments::new(0.5, -0.2, 1.0).is_ok());
+        assert!(TonalAdjustments::new(-1.0, 1.0, -1.0).is_ok());
+        assert!(matches!(
+            TonalAdjustments::new(1.01, 0.0, 0.0),
+            Err(DomainError::InvalidAdjustment(_))
+        ));
+        assert!(TonalAdjustments::new(0.0, -1.5, 0.0).is_err());
+        assert!(TonalAdjustments::new(0.0, 0.0, f32::NAN).is_err());
+        assert!(TonalAdjustments::new(0.0, 0.0, 0.0).unwrap().is_neutral());
+    }
+
+    #[test]
+    fn test_tonal_setters() {
+        let mut transformation = Transformation::new();
+        transformation
+            .set_brightness(0.2)
+            .unwrap()
+            .set_saturation(-0.5)
+            .unwrap();
+
+        let tonal = transformation.tonal().unwrap();
+        assert_eq!(
+            (tonal.brightness(), tonal.contrast(), tonal.saturation()),
+            (0.2, 0.0, -0.5)
+        );
+        assert!(transformation.set_contrast(2.0).is_err());
+
+        transformation.set_tonal(TonalAdjustments::default());
+        transformation.normalize(&Dimensions::new(10, 10).unwrap());
+        assert!(transformation.tonal().is_none());
+    }
+
+    #[test]
+    fn test_sharpen_options() {
+        assert_eq!(SharpenOptions::default(), SharpenOptions::moderate());
+        assert!(SharpenOptions::mild().amount() < SharpenOptions::moderate().amount());
+        assert!(SharpenOptions::moderate().amount() < SharpenOptions::strong().amount());
+
+        assert!(SharpenOptions::new(1.0, 2.0, 5).is_ok());
+        assert!(matches!(
+            SharpenOptions::new(-0.5, 1.0, 0),
+            Err(DomainError::InvalidAdjustment(_))
+        ));
+        assert!(SharpenOptions::new(1.0, f32::NAN, 0).is_err());
+        assert!(SharpenOptions::new(1.0, 0.0, 0).unwrap().is_neutral());
+    }
+
     #[test]
     fn test_resize_transformation() {
         let dims = Dimensions::new(1920, 1080).unwrap();
         let resize = ResizeTransformation::with_dimensions(dims, true);
 
-        assert_eq!(resize.target_dimensions(), &dims);
+        assert_eq!(resize.mode(), ResizeMode::Absolute(dims));
+        assert_eq!(resize.target_dimensions(), Some(&dims));
         assert!(resize.preserve_aspect_ratio());
     }
 
@@ -232,6 +976,34 @@ mod tests {
         assert!(Rotation::from_degrees(45).is_err());
     }
 
+    #[test]
+    fn test_rotation_from_exif_orientation() {
+        assert_eq!(
+            Rotation::from_exif_orientation(1),
+            Some((Rotation::None, false))
+        );
+        assert_eq!(
+            Rotation::from_exif_orientation(4),
+            Some((Rotation::Rotate180, true))
+        );
+        assert_eq!(
+            Rotation::from_exif_orientation(6),
+            Some((Rotation::Clockwise90, false))
+        );
+        assert_eq!(
+            Rotation::from_exif_orientation(7),
+            Some((Rotation::Clockwise270, true))
+        );
+        assert_eq!(Rotation::from_exif_orientation(0), None);
+        assert_eq!(Rotation::from_exif_orientation(9), None);
+
+        // 5-8 giran 90°: ancho y alto se intercambian
+        for orientation in 1..=8 {
+            let (rotation, _) = Rotation::from_exif_orientation(orientation).unwrap();
+            assert_eq!(rotation.swaps_dimensions(), orientation >= 5);
+        }
+    }
+
     #[test]
     fn test_rotation_swaps_dimensions() {
         assert!(Rotation::Clockwise90.swaps_dimensions());
@@ -250,4 +1022,131 @@ mod tests {
         assert_eq!(final_dims.width(), 1000);
         assert_eq!(final_dims.height(), 500); // Mantiene aspect ratio
     }
+
+    #[test]
+    fn test_calculate_final_dimensions_percentage() {
+        let original = Dimensions::new(2000, 1000).unwrap();
+
+        let half = ResizeTransformation::with_percentage(50.0, ResizeFilter::Lanczos3).unwrap();
+        assert_eq!(
+            half.calculate_final_dimensions(&original).unwrap(),
+            Dimensions::new(1000, 500).unwrap()
+        );
+        assert!(half.target_dimensions().is_none());
+
+        // Más de 100% amplía (con allow_upscale)
+        let mut double =
+            ResizeTransformation::with_percentage(200.0, ResizeFilter::Lanczos3).unwrap();
+        double.set_allow_upscale(true);
+        assert_eq!(
+            double.calculate_final_dimensions(&original).unwrap(),
+            Dimensions::new(4000, 2000).unwrap()
+        );
+
+        assert!(ResizeTransformation::with_percentage(0.0, ResizeFilter::Lanczos3).is_err());
+        assert!(ResizeTransformation::with_percentage(f32::NAN, ResizeFilter::Lanczos3).is_err());
+    }
+
+    #[test]
+    fn test_calculate_final_dimensions_edges() {
+        let landscape = Dimensions::new(4000, 3000).unwrap();
+        let portrait = Dimensions::new(3000, 4000).unwrap();
+
+        let longest =
+            ResizeTransformation::with_longest_edge(2048, ResizeFilter::Lanczos3).unwrap();
+        assert_eq!(
+            longest.calculate_final_dimensions(&landscape).unwrap(),
+            Dimensions::new(2048, 1536).unwrap()
+        );
+        assert_eq!(
+            longest.calculate_final_dimensions(&portrait).unwrap(),
+            Dimensions::new(1536, 2048).unwrap()
+        );
+
+        let shortest =
+            ResizeTransformation::with_shortest_edge(1500, ResizeFilter::Lanczos3).unwrap();
+        assert_eq!(
+            shortest.calculate_final_dimensions(&portrait).unwrap(),
+            Dimensions::new(1500, 2000).unwrap()
+        );
+
+        assert!(ResizeTransformation::with_longest_edge(0, ResizeFilter::Lanczos3).is_err());
+    }
+
+    #[test]
+    fn test_edge_resize_upscale() {
+        let small = Dimensions::new(800, 600).unwrap();
+        let mut resize =
+            ResizeTransformation::with_longest_edge(2048, ResizeFilter::Lanczos3).unwrap();
+
+        // Sin allow_upscale no amplía
+        assert_eq!(resize.calculate_final_dimensions(&small).unwrap(), small);
+
+        resize.set_allow_upscale(true);
+        assert_eq!(
+            resize.calculate_final_dimensions(&small).unwrap(),
+            Dimensions::new(2048, 1536).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_never_upscale_guard() {
+        let original = Dimensions::new(400, 300).unwrap();
+
+        // Exact: cada eje se limita al original
+        let exact =
+            ResizeTransformation::with_dimensions(Dimensions::new(4000, 200).unwrap(), false);
+        assert_eq!(
+            exact.calculate_final_dimensions(&original).unwrap(),
+            Dimensions::new(400, 200).unwrap()
+        );
+        assert!(exact.upscale_prevented(&original));
+
+        let double = ResizeTransformation::with_percentage(200.0, ResizeFilter::Lanczos3).unwrap();
+        assert_eq!(
+            double.calculate_final_dimensions(&original).unwrap(),
+            original
+        );
+        assert!(double.upscale_prevented(&original));
+
+        let half = ResizeTransformation::with_percentage(50.0, ResizeFilter::Lanczos3).unwrap();
+        assert!(!half.upscale_prevented(&original));
+
+        let mut allowed = exact;
+        allowed.set_allow_upscale(true);
+        assert_eq!(
+            allowed.calculate_final_dimensions(&original).unwrap(),
+            Dimensions::new(4000, 200).unwrap()
+        );
+        assert!(!allowed.upscale_prevented(&original));
+    }
+
+    #[test]
+    fn test_arbitrary_rotation_grows_canvas() {
+        let original = Dimensions::new(100, 50).unwrap();
+        let rotation = ArbitraryRotation::with_degrees(90.0).unwrap();
+
+        let dims = rotation.calculate_final_dimensions(&original).unwrap();
+        assert_eq!(dims, Dimensions::new(50, 100).unwrap());
+
+        let rotation = ArbitraryRotation::with_degrees(45.0).unwrap();
+        let dims = rotation.calculate_final_dimensions(&original).unwrap();
+        assert_eq!(dims.width(), dims.height());
+        assert_eq!(dims.width(), 106);
+    }
+
+    #[test]
+    fn test_arbitrary_rotation_auto_crop() {
+        let original = Dimensions::new(100, 50).unwrap();
+        let rotation = ArbitraryRotation::new(2.5, ArbitraryRotation::DEFAULT_FILL, true).unwrap();
+
+        let dims = rotation.calculate_final_dimensions(&original).unwrap();
+        assert!(dims.width() < 100);
+        assert!(dims.height() < 50);
+    }
+
+    #[test]
+    fn test_arbitrary_rotation_rejects_nan() {
+        assert!(ArbitraryRotation::with_degrees(f32::NAN).is_err());
+    }
 }
@@ -0,0 +1,326 @@
+use crate::domain::error::{DomainError, DomainResult};
+use crate::domain::value_objects::Dimensions;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Image overlay stamped onto every output (e.g. a logo)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Path to the overlay image (PNG with alpha recommended)
+    image_path: PathBuf,
+    /// Where to place the overlay
+    position: WatermarkPosition,
+    /// Opacity (0-100)
+    opacity: u8,
+    /// Overlay width as a fraction of the base image width (0.0-1.0]
+    scale: f32,
+}
+
+impl Watermark {
+    /// Maximum opacity value
+    pub const MAX_OPACITY: u8 = 100;
+
+    /// Create a new image watermark
+    pub fn new(
+        image_path: PathBuf,
+        position: WatermarkPosition,
+        opacity: u8,
+        scale: f32,
+    ) -> DomainResult<Self> {
+        if opacity > Self::MAX_OPACITY {
+            return Err(DomainError::InvalidWatermark(format!(
+                "opacity {} must be between 0 and 100",
+                opacity
+            )));
+        }
+        if !(scale > 0.0 && scale <= 1.0) {
+            return Err(DomainError::InvalidWatermark(format!(
+                "scale {} must be greater than 0 and at most 1",
+                scale
+            )));
+        }
+
+        Ok(Self {
+            image_path,
+            position,
+            opacity,
+            scale,
+        })
+    }
+
+    /// Get overlay image path
+    pub fn image_path(&self) -> &Path {
+        &self.image_path
+    }
+
+    /// Get position
+    pub fn position(&self) -> WatermarkPosition {
+        self.position
+    }
+
+    /// Get opacity (0-100)
+    pub fn opacity(&self) -> u8 {
+        self.opacity
+    }
+
+    /// Get scale relative to base image width
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+}
+
+/// Text stamped onto every output (e.g. a copyright notice)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextWatermark {
+    text: String,
+    /// Custom TrueType/OpenType font (None = bundled default font)
+    font_path: Option<PathBuf>,
+    font_size: FontSize,
+    /// RGBA text color; alpha controls opacity
+    color: [u8; 4],
+    position: WatermarkPosition,
+    /// Distance in pixels from the edges the anchor touches
+    margin: u32,
+}
+
+impl TextWatermark {
+    /// Default color: white at 70% opacity
+    pub const DEFAULT_COLOR: [u8; 4] = [255, 255, 255, 179];
+
+    /// Create a new text watermark
+    pub fn new(
+        text: String,
+        font_size: FontSize,
+        color: [u8; 4],
+        position: WatermarkPosition,
+        margin: u32,
+    ) -> DomainResult<Self> {
+        if text.trim().is_empty() {
+            return Err(DomainError::InvalidWatermark(
+                "text must not be empty".to_string(),
+            ));
+        }
+        font_size.validate()?;
+
+        Ok(Self {
+            text,
+            font_path: None,
+            font_size,
+            color,
+            position,
+            margin,
+        })
+    }
+
+    /// Use a custom font file instead of the bundled one
+    pub fn set_font_path(&mut self, font_path: Option<PathBuf>) -> &mut Self {
+        self.font_path = font_path;
+        self
+    }
+
+    /// Get text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get custom font path
+    pub fn font_path(&self) -> Option<&Path> {
+        self.font_path.as_deref()
+    }
+
+    /// Get font size
+    pub fn font_size(&self) -> FontSize {
+        self.font_size
+    }
+
+    /// Get RGBA color
+    pub fn color(&self) -> [u8; 4] {
+        self.color
+    }
+
+    /// Get position
+    pub fn position(&self) -> WatermarkPosition {
+        self.position
+    }
+
+    /// Get margin in pixels
+    pub fn margin(&self) -> u32 {
+        self.margin
+    }
+}
+
+/// Text size, absolute or relative to the image height
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FontSize {
+    /// Size in pixels
+    Pixels(f32),
+    /// Percentage (0-100] of the base image height
+    PercentOfHeight(f32),
+}
+
+impl FontSize {
+    fn validate(&self) -> DomainResult<()> {
+        let valid = match *self {
+            FontSize::Pixels(px) => px.is_finite() && px > 0.0,
+            FontSize::PercentOfHeight(pct) => pct > 0.0 && pct <= 100.0,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(DomainError::InvalidWatermark(format!(
+                "invalid font size {:?}",
+                self
+            )))
+        }
+    }
+
+    /// Size in pixels for an image of the given height
+    pub fn to_pixels(&self, image_height: u32) -> f32 {
+        match *self {
+            FontSize::Pixels(px) => px,
+            FontSize::PercentOfHeight(pct) => image_height as f32 * pct / 100.0,
+        }
+    }
+}
+
+/// Watermark placement: nine anchor points or a custom offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum WatermarkPosition {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    #[default]
+    BottomRight,
+    /// Top-left corner of the overlay at (x, y) pixels from the base top-left
+    Custom {
+        x: u32,
+        y: u32,
+    },
+}
+
+impl WatermarkPosition {
+    /// Top-left coordinates of an overlay placed on a base image.
+    /// Anchored positions keep `margin` pixels from the edges they touch.
+    pub fn resolve(&self, base: &Dimensions, overlay: &Dimensions, margin: u32) -> (i64, i64) {
+        let (bw, bh) = (base.width() as i64, base.height() as i64);
+        let (ow, oh) = (overlay.width() as i64, overlay.height() as i64);
+        let margin = margin as i64;
+
+        let left = margin;
+        let center_x = (bw - ow) / 2;
+        let right = bw - ow - margin;
+        let top = margin;
+        let center_y = (bh - oh) / 2;
+        let bottom = bh - oh - margin;
+
+        match self {
+            WatermarkPosition::TopLeft => (left, top),
+            WatermarkPosition::Top => (center_x, top),
+            WatermarkPosition::TopRight => (right, top),
+            WatermarkPosition::Left => (left, center_y),
+            WatermarkPosition::Center => (center_x, center_y),
+            WatermarkPosition::Right => (right, center_y),
+            WatermarkPosition::BottomLeft => (left, bottom),
+            WatermarkPosition::Bottom => (center_x, bottom),
+            WatermarkPosition::BottomRight => (right, bottom),
+            WatermarkPosition::Custom { x, y } => (*x as i64, *y as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_validation() {
+        let path = PathBuf::from("/tmp/logo.png");
+        assert!(Watermark::new(path.clone(), WatermarkPosition::Center, 50, 0.2).is_ok());
+        assert!(Watermark::new(path.clone(), WatermarkPosition::Center, 101, 0.2).is_err());
+        assert!(Watermark::new(path.clone(), WatermarkPosition::Center, 50, 0.0).is_err());
+        assert!(Watermark::new(path, WatermarkPosition::Center, 50, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_text_watermark_validation() {
+        let color = TextWatermark::DEFAULT_COLOR;
+        let position = WatermarkPosition::BottomRight;
+
+        assert!(
+            TextWatermark::new("© 2024".into(), FontSize::Pixels(24.0), color, position, 10)
+                .is_ok()
+        );
+        assert!(
+            TextWatermark::new("  ".into(), FontSize::Pixels(24.0), color, position, 10).is_err()
+        );
+        assert!(
+            TextWatermark::new("a".into(), FontSize::Pixels(0.0), color, position, 10).is_err()
+        );
+        assert!(TextWatermark::new(
+            "a".into(),
+            FontSize::PercentOfHeight(150.0),
+            color,
+            position,
+            10
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_font_size_to_pixels() {
+        assert_eq!(FontSize::Pixels(32.0).to_pixels(1000), 32.0);
+        assert_eq!(FontSize::PercentOfHeight(5.0).to_pixels(1000), 50.0);
+    }
+
+    #[test]
+    fn test_resolve_anchor_positions() {
+        let base = Dimensions::new(100, 80).unwrap();
+        let overlay = Dimensions::new(20, 10).unwrap();
+
+        let cases = [
+            (WatermarkPosition::TopLeft, (0, 0)),
+            (WatermarkPosition::Top, (40, 0)),
+            (WatermarkPosition::TopRight, (80, 0)),
+            (WatermarkPosition::Left, (0, 35)),
+            (WatermarkPosition::Center, (40, 35)),
+            (WatermarkPosition::Right, (80, 35)),
+            (WatermarkPosition::BottomLeft, (0, 70)),
+            (WatermarkPosition::Bottom, (40, 70)),
+            (WatermarkPosition::BottomRight, (80, 70)),
+            (WatermarkPosition::Custom { x: 5, y: 7 }, (5, 7)),
+        ];
+
+        for (position, expected) in cases {
+            assert_eq!(
+                position.resolve(&base, &overlay, 0),
+                expected,
+                "{:?}",
+                position
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_with_margin() {
+        let base = Dimensions::new(100, 80).unwrap();
+        let overlay = Dimensions::new(20, 10).unwrap();
+
+        assert_eq!(
+            WatermarkPosition::TopLeft.resolve(&base, &overlay, 5),
+            (5, 5)
+        );
+        assert_eq!(
+            WatermarkPosition::BottomRight.resolve(&base, &overlay, 5),
+            (75, 65)
+        );
+        assert_eq!(
+            WatermarkPosition::Center.resolve(&base, &overlay, 5),
+            (40, 35)
+        );
+    }
+}
@@ -1,12 +1,34 @@
 use crate::domain::error::DomainResult;
 use crate::domain::models::{Image, ProcessingSettings, Transformation};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+/// How `ImageProcessor::load_image_with_mode` reads RAW files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoadMode {
+    /// Describe the full sensor image
+    #[default]
+    FullRaw,
+    /// Describe the preview JPEG embedded by the camera (what fast previews show);
+    /// falls back to `FullRaw` when the file has none. Other formats ignore it.
+    EmbeddedPreview,
+}
+
 /// Trait for image processing operations
 /// This is the main abstraction for the infrastructure layer
 pub trait ImageProcessor: Send + Sync {
     /// Load an image from disk
-    fn load_image(&self, path: &Path) -> DomainResult<Image>;
+    fn load_image(&self, path: &Path) -> DomainResult<Image> {
+        self.load_image_with_mode(path, LoadMode::FullRaw)
+    }
+
+    /// Load an image from disk, choosing what RAW files report
+    fn load_image_with_mode(&self, path: &Path, mode: LoadMode) -> DomainResult<Image>;
+
+    /// Load an image and return the file contents read along the way, for callers
+    /// that need the original bytes too (EXIF copies, metadata cleaning)
+    fn load_image_with_bytes(&self, path: &Path) -> DomainResult<(Image, Vec<u8>)>;
 
     /// Optimize an image with given settings
     fn optimize(&self, image: &Image, settings: &ProcessingSettings) -> DomainResult<Vec<u8>>;
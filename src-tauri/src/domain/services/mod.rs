@@ -1,3 +1,3 @@
 mod image_processor;
 
-pub use image_processor::ImageProcessor;
+pub use image_processor::{ImageProcessor, LoadMode};
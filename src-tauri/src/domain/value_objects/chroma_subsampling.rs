@@ -0,0 +1,68 @@
+use crate::domain::value_objects::Quality;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// JPEG chroma subsampling: how many luma pixels share one color sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ChromaSubsampling {
+    /// Half resolution color both ways (smallest files)
+    S420,
+    /// Half horizontal color resolution
+    S422,
+    /// Full resolution color (best for text and sharp colored edges)
+    S444,
+    /// 4:4:4 from `AUTO_FULL_QUALITY` up, 4:2:0 below
+    #[default]
+    Auto,
+}
+
+impl ChromaSubsampling {
+    /// Quality at which `Auto` stops subsampling color
+    pub const AUTO_FULL_QUALITY: u8 = 90;
+
+    /// Concrete subsampling for a given quality (resolves `Auto`)
+    pub fn for_quality(self, quality: Quality) -> Self {
+        match self {
+            ChromaSubsampling::Auto if quality.value() >= Self::AUTO_FULL_QUALITY => {
+                ChromaSubsampling::S444
+            }
+            ChromaSubsampling::Auto => ChromaSubsampling::S420,
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for ChromaSubsampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChromaSubsampling::S420 => "4:2:0",
+            ChromaSubsampling::S422 => "4:2:2",
+            ChromaSubsampling::S444 => "4:4:4",
+            ChromaSubsampling::Auto => "auto",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_depends_on_quality() {
+        let auto = ChromaSubsampling::default();
+        assert_eq!(auto, ChromaSubsampling::Auto);
+        assert_eq!(
+            auto.for_quality(Quality::new(89).unwrap()),
+            ChromaSubsampling::S420
+        );
+        assert_eq!(
+            auto.for_quality(Quality::new(90).unwrap()),
+            ChromaSubsampling::S444
+        );
+        assert_eq!(
+            ChromaSubsampling::S422.for_quality(Quality::maximum()),
+            ChromaSubsampling::S422
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::domain::error::{DomainError, DomainResult};
+use crate::domain::value_objects::ImageFormat;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -72,6 +73,73 @@ impl Dimensions {
 
         self.scale(scale_factor)
     }
+
+    /// Scale to fully cover a `target_width`x`target_height` box, preserving aspect
+    /// ratio (CSS `object-fit: cover`). Returns the scaled dimensions and the offset
+    /// of the centered target-sized crop inside them.
+    pub fn cover(&self, target_width: u32, target_height: u32) -> DomainResult<(Self, (i32, i32))> {
+        let target = Self::new(target_width, target_height)?;
+        let width_ratio = target.width as f64 / self.width as f64;
+        let height_ratio = target.height as f64 / self.height as f64;
+        let scale_factor = width_ratio.max(height_ratio);
+
+        // El redondeo no debe dejar un eje por debajo del objetivo
+        let scaled = Self::new(
+            ((self.width as f64 * scale_factor).round() as u32).max(target.width),
+            ((self.height as f64 * scale_factor).round() as u32).max(target.height),
+        )?;
+        let offset = (
+            ((scaled.width - target.width) / 2) as i32,
+            ((scaled.height - target.height) / 2) as i32,
+        );
+        Ok((scaled, offset))
+    }
+
+    /// These dimensions if `format` can encode them, otherwise
+    /// `DimensionsExceedFormatLimit` with the limit, so the caller can fit or reject
+    pub fn clamp_to_codec_limits(&self, format: ImageFormat) -> DomainResult<Self> {
+        match format.max_dimensions() {
+            Some(limit) if self.width > limit.width || self.height > limit.height => {
+                Err(DomainError::DimensionsExceedFormatLimit {
+                    format,
+                    limit,
+                    actual: *self,
+                })
+            }
+            _ => Ok(*self),
+        }
+    }
+
+    /// Standard sizes these dimensions can be reduced to without upscaling
+    pub fn standard_sizes_within(&self) -> Vec<StandardSize> {
+        StandardSize::ALL
+            .into_iter()
+            .filter(|size| {
+                let dims = size.dimensions();
+                dims.width <= self.width && dims.height <= self.height
+            })
+            .collect()
+    }
+
+    /// Standard size closest to these dimensions that doesn't upscale: the
+    /// nearest aspect ratio, then the most pixels. None if all are larger
+    pub fn best_fit_standard_size(&self) -> Option<StandardSize> {
+        let ratio_distance = |size: &StandardSize| {
+            (size.dimensions().aspect_ratio() / self.aspect_ratio())
+                .ln()
+                .abs()
+        };
+
+        self.standard_sizes_within().into_iter().min_by(|a, b| {
+            ratio_distance(a)
+                .total_cmp(&ratio_distance(b))
+                .then_with(|| {
+                    b.dimensions()
+                        .total_pixels()
+                        .cmp(&a.dimensions().total_pixels())
+                })
+        })
+    }
 }
 
 impl fmt::Display for Dimensions {
@@ -80,6 +148,78 @@ impl fmt::Display for Dimensions {
     }
 }
 
+/// Common output sizes, offered when the user has no target dimensions in mind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StandardSize {
+    /// 1280x720
+    Hd720,
+    /// 1920x1080
+    Fhd1080,
+    /// 2560x1440
+    Qhd1440,
+    /// 3840x2160
+    Uhd4K,
+    /// 1080x1080 (Instagram/Facebook feed)
+    SocialSquare,
+    /// 1080x1920 (stories and reels)
+    SocialStory,
+    /// 1080x566 (Instagram landscape, 1.91:1)
+    InstagramLandscape,
+    /// 2480x3508, A4 portrait at 300 dpi
+    #[allow(non_camel_case_types)]
+    PrintA4_300dpi,
+}
+
+impl StandardSize {
+    /// Every standard size, in the order a size picker lists them
+    pub const ALL: [StandardSize; 8] = [
+        StandardSize::Hd720,
+        StandardSize::Fhd1080,
+        StandardSize::Qhd1440,
+        StandardSize::Uhd4K,
+        StandardSize::SocialSquare,
+        StandardSize::SocialStory,
+        StandardSize::InstagramLandscape,
+        StandardSize::PrintA4_300dpi,
+    ];
+
+    /// Pixel dimensions of this size
+    pub fn dimensions(&self) -> Dimensions {
+        let (width, height) = match self {
+            StandardSize::Hd720 => (1280, 720),
+            StandardSize::Fhd1080 => (1920, 1080),
+            StandardSize::Qhd1440 => (2560, 1440),
+            StandardSize::Uhd4K => (3840, 2160),
+            StandardSize::SocialSquare => (1080, 1080),
+            StandardSize::SocialStory => (1080, 1920),
+            StandardSize::InstagramLandscape => (1080, 566),
+            StandardSize::PrintA4_300dpi => (2480, 3508),
+        };
+        Dimensions { width, height }
+    }
+
+    /// Human-readable name for the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            StandardSize::Hd720 => "HD 720p",
+            StandardSize::Fhd1080 => "Full HD 1080p",
+            StandardSize::Qhd1440 => "QHD 1440p",
+            StandardSize::Uhd4K => "4K UHD",
+            StandardSize::SocialSquare => "Social square",
+            StandardSize::SocialStory => "Social story",
+            StandardSize::InstagramLandscape => "Instagram landscape",
+            StandardSize::PrintA4_300dpi => "A4 print (300 dpi)",
+        }
+    }
+}
+
+impl fmt::Display for StandardSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.label(), self.dimensions())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +266,74 @@ mod tests {
         assert_eq!(scaled.height(), 200);
     }
 
+    #[test]
+    fn test_clamp_to_codec_limits() {
+        let dims = Dimensions::new(20000, 100).unwrap();
+        assert_eq!(dims.clamp_to_codec_limits(ImageFormat::Jpeg), Ok(dims));
+        assert_eq!(dims.clamp_to_codec_limits(ImageFormat::Png), Ok(dims));
+        assert_eq!(
+            dims.clamp_to_codec_limits(ImageFormat::Webp),
+            Err(DomainError::DimensionsExceedFormatLimit {
+                format: ImageFormat::Webp,
+                limit: Dimensions::new(16383, 16383).unwrap(),
+                actual: dims,
+            })
+        );
+    }
+
+    #[test]
+    fn test_best_fit_standard_size() {
+        // 4K de cámara: el 16:9 más grande que cabe
+        let video = Dimensions::new(3840, 2160).unwrap();
+        assert_eq!(video.best_fit_standard_size(), Some(StandardSize::Uhd4K));
+
+        // 16:9 algo menor que 4K: no se amplía
+        let screen = Dimensions::new(3000, 1688).unwrap();
+        assert_eq!(screen.best_fit_standard_size(), Some(StandardSize::Qhd1440));
+
+        let square = Dimensions::new(2000, 2000).unwrap();
+        assert_eq!(
+            square.best_fit_standard_size(),
+            Some(StandardSize::SocialSquare)
+        );
+
+        let phone = Dimensions::new(1170, 2532).unwrap();
+        assert_eq!(
+            phone.best_fit_standard_size(),
+            Some(StandardSize::SocialStory)
+        );
+
+        let tiny = Dimensions::new(640, 480).unwrap();
+        assert_eq!(tiny.best_fit_standard_size(), None);
+        assert!(tiny.standard_sizes_within().is_empty());
+    }
+
+    #[test]
+    fn test_standard_size_display() {
+        assert_eq!(
+            StandardSize::Fhd1080.to_string(),
+            "Full HD 1080p (1920x1080)"
+        );
+        assert_eq!(
+            StandardSize::PrintA4_300dpi.dimensions().total_pixels(),
+            2480 * 3508
+        );
+    }
+
+    #[test]
+    fn test_cover() {
+        let dims = Dimensions::new(2000, 1000).unwrap();
+        let (scaled, offset) = dims.cover(500, 500).unwrap();
+        assert_eq!((scaled.width(), scaled.height()), (1000, 500));
+        assert_eq!(offset, (250, 0));
+
+        let (scaled, offset) = Dimensions::new(300, 900).unwrap().cover(400, 300).unwrap();
+        assert_eq!((scaled.width(), scaled.height()), (400, 1200));
+        assert_eq!(offset, (0, 450));
+
+        assert!(dims.cover(0, 500).is_err());
+    }
+
     #[test]
     fn test_fit_within() {
         let dims = Dimensions::new(2000, 1000).unwrap();
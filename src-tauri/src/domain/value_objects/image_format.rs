@@ -1,4 +1,5 @@
 use crate::domain::error::{DomainError, DomainResult};
+use crate::domain::value_objects::Dimensions;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
@@ -10,14 +11,17 @@ pub enum ImageFormat {
     Jpeg,
     Webp,
     Gif,
-    Raw, // RAW formats (ARW, CR2, NEF, DNG, etc.) - read-only, convert to output format
-         // Formatos futuros (Fase post-MVP)
-         // Tiff,
-         // Heic,
-         // Ico,
+    Heic, // HEIC/HEIF (iPhone, Android) - read-only, needs the `heic` feature
+    Raw,  // RAW formats (ARW, CR2, NEF, DNG, etc.) - read-only, convert to output format
+          // Formatos futuros (Fase post-MVP)
+          // Tiff,
+          // Ico,
 }
 
 impl ImageFormat {
+    /// Bytes from the start of a file that `from_magic_bytes` needs to see
+    pub const MAGIC_BYTES_LEN: usize = 16;
+
     /// Get file extension for this format
     pub fn extension(&self) -> &str {
         match self {
@@ -26,6 +30,7 @@ impl ImageFormat {
             ImageFormat::Webp => "webp",
             ImageFormat::Gif => "gif",
             ImageFormat::Raw => "jpg", // RAW se convierte a JPG por defecto
+            ImageFormat::Heic => "jpg", // HEIC también se convierte a JPG
         }
     }
 
@@ -37,6 +42,7 @@ impl ImageFormat {
             ImageFormat::Webp => "image/webp",
             ImageFormat::Gif => "image/gif",
             ImageFormat::Raw => "image/x-raw", // MIME genérico para RAW
+            ImageFormat::Heic => "image/heic",
         }
     }
 
@@ -53,6 +59,23 @@ impl ImageFormat {
         matches!(self, ImageFormat::Jpeg | ImageFormat::Webp)
     }
 
+    /// Check if format can be written without losing pixel data
+    pub fn supports_lossless(&self) -> bool {
+        matches!(self, ImageFormat::Png | ImageFormat::Webp)
+    }
+
+    /// Largest image the encoder can write (None = no practical limit).
+    /// RAW and HEIC inputs are written as JPEG, so they share its limit.
+    pub fn max_dimensions(&self) -> Option<Dimensions> {
+        let (width, height) = match self {
+            ImageFormat::Jpeg | ImageFormat::Raw | ImageFormat::Heic => (65535, 65535),
+            ImageFormat::Webp => (16383, 16383),
+            ImageFormat::Gif => (65535, 65535),
+            ImageFormat::Png => return None,
+        };
+        Dimensions::new(width, height).ok()
+    }
+
     /// Check if format is a RAW format
     pub fn is_raw(&self) -> bool {
         matches!(self, ImageFormat::Raw)
@@ -65,6 +88,7 @@ impl ImageFormat {
             "jpg" | "jpeg" => Ok(ImageFormat::Jpeg),
             "webp" => Ok(ImageFormat::Webp),
             "gif" => Ok(ImageFormat::Gif),
+            "heic" | "heif" | "hif" => Ok(ImageFormat::Heic),
             // RAW formats
             "arw" | "cr2" | "cr3" | "nef" | "nrw" | "dng" | "raf" | "orf" | "rw2" | "pef"
             | "srw" | "x3f" | "raw" | "rwl" | "mrw" | "erf" | "3fr" | "ari" | "srf" | "sr2"
@@ -72,6 +96,44 @@ impl ImageFormat {
             _ => Err(DomainError::InvalidImageFormat(ext.to_string())),
         }
     }
+
+    /// Detect the format from the first bytes of a file (see `MAGIC_BYTES_LEN`).
+    /// TIFF-based files count as RAW (DNG, NEF, ARW, CR2...).
+    pub fn from_magic_bytes(header: &[u8]) -> DomainResult<Self> {
+        match header {
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Ok(ImageFormat::Png),
+            [0xFF, 0xD8, 0xFF, ..] => Ok(ImageFormat::Jpeg),
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Ok(ImageFormat::Gif),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => {
+                Ok(ImageFormat::Webp)
+            }
+            [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..] if brand.len() >= 4 => {
+                match &brand[..4] {
+                    b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"mif1"
+                    | b"msf1" => Ok(ImageFormat::Heic),
+                    b"crx " => Ok(ImageFormat::Raw),
+                    _ => Err(DomainError::InvalidImageFormat(
+                        "Unrecognized file signature".to_string(),
+                    )),
+                }
+            }
+            [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => Ok(ImageFormat::Raw),
+            // ORF y RW2: variantes TIFF con otro número mágico
+            [b'I', b'I', b'R', b'O' | b'S', ..] | [b'I', b'I', b'U', 0x00, ..] => {
+                Ok(ImageFormat::Raw)
+            }
+            // Firmas propias: RAF (Fuji), X3F (Sigma), MRW (Minolta), CRW (Canon antiguo)
+            [b'F', b'U', b'J', b'I', b'F', b'I', b'L', b'M', ..]
+            | [b'F', b'O', b'V', b'b', ..]
+            | [0x00, b'M', b'R', b'M', ..]
+            | [b'I', b'I', 0x1A, 0x00, 0x00, 0x00, b'H', b'E', b'A', b'P', ..] => {
+                Ok(ImageFormat::Raw)
+            }
+            _ => Err(DomainError::InvalidImageFormat(
+                "Unrecognized file signature".to_string(),
+            )),
+        }
+    }
 }
 
 impl FromStr for ImageFormat {
@@ -90,6 +152,7 @@ impl fmt::Display for ImageFormat {
             ImageFormat::Webp => "webp",
             ImageFormat::Gif => "gif",
             ImageFormat::Raw => "raw", // identifier, not output extension
+            ImageFormat::Heic => "heic",
         };
         write!(f, "{}", name)
     }
@@ -119,6 +182,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_heic_extensions() {
+        for ext in ["heic", "HEIF", "hif"] {
+            assert_eq!(ImageFormat::from_extension(ext).unwrap(), ImageFormat::Heic);
+        }
+        assert_eq!(ImageFormat::Heic.to_string(), "heic");
+        assert_eq!(ImageFormat::Heic.extension(), "jpg");
+    }
+
+    #[test]
+    fn test_from_magic_bytes() {
+        let cases: [(&[u8], ImageFormat); 9] = [
+            (b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", ImageFormat::Png),
+            (b"\xff\xd8\xff\xe0\0\x10JFIF", ImageFormat::Jpeg),
+            (b"GIF89a", ImageFormat::Gif),
+            (b"RIFF\x24\0\0\0WEBPVP8 ", ImageFormat::Webp),
+            (b"\0\0\0\x18ftypheic", ImageFormat::Heic),
+            (b"\0\0\0\x18ftypcrx ", ImageFormat::Raw),
+            (b"II*\0\x10\0\0\0CR", ImageFormat::Raw),
+            (b"MM\0*\0\0\0\x08", ImageFormat::Raw),
+            (b"FUJIFILMCCD-RAW 0201", ImageFormat::Raw),
+        ];
+        for (header, format) in cases {
+            assert_eq!(ImageFormat::from_magic_bytes(header).unwrap(), format);
+        }
+
+        assert!(ImageFormat::from_magic_bytes(b"").is_err());
+        assert!(ImageFormat::from_magic_bytes(b"%PDF-1.7").is_err());
+        assert!(ImageFormat::from_magic_bytes(b"\0\0\0\x18ftypisom").is_err());
+    }
+
     #[test]
     fn test_invalid_extension() {
         assert!(ImageFormat::from_extension("txt").is_err());
@@ -1,7 +1,9 @@
+mod chroma_subsampling;
 mod dimensions;
 mod image_format;
 mod quality;
 
-pub use dimensions::Dimensions;
+pub use chroma_subsampling::ChromaSubsampling;
+pub use dimensions::{Dimensions, StandardSize};
 pub use image_format::ImageFormat;
 pub use quality::Quality;
@@ -1,4 +1,5 @@
 use crate::domain::error::{DomainError, DomainResult};
+use crate::domain::value_objects::ImageFormat;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -35,6 +36,24 @@ impl Quality {
         Quality(80)
     }
 
+    /// Create Quality for thumbnails (70)
+    pub fn thumbnail() -> Self {
+        Quality(70)
+    }
+
+    /// Sensible quality for writing `format`: 85 for JPEG, 80 for WebP, and the
+    /// maximum for PNG and GIF, where quality only picks the compression effort
+    /// (100 = oxipng level 6) and never costs detail
+    pub fn recommended_for(format: ImageFormat) -> Self {
+        match format {
+            ImageFormat::Jpeg => Self::default_quality(),
+            ImageFormat::Webp => Self::web_optimized(),
+            ImageFormat::Png | ImageFormat::Gif => Self::maximum(),
+            // Solo de lectura: nunca se escriben
+            ImageFormat::Heic | ImageFormat::Raw => Self::default_quality(),
+        }
+    }
+
     /// Get the inner value
     pub fn value(&self) -> u8 {
         self.0
@@ -100,4 +119,18 @@ mod tests {
         assert_eq!(Quality::maximum().value(), 100);
         assert_eq!(Quality::web_optimized().value(), 80);
     }
+
+    #[test]
+    fn test_recommended_for() {
+        assert_eq!(Quality::recommended_for(ImageFormat::Jpeg).value(), 85);
+        assert_eq!(Quality::recommended_for(ImageFormat::Webp).value(), 80);
+        assert_eq!(
+            Quality::recommended_for(ImageFormat::Png),
+            Quality::maximum()
+        );
+        assert_eq!(
+            Quality::recommended_for(ImageFormat::Gif),
+            Quality::maximum()
+        );
+    }
 }
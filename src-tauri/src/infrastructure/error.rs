@@ -22,9 +22,43 @@ pub enum InfraError {
     #[error("JPEG optimization failed: {0}")]
     JpegOptimizationFailed(String),
 
+    #[error("WebP optimization failed: {0}")]
+    WebpOptimizationFailed(String),
+
+    #[error("Failed to parse EXIF: {0}")]
+    ExifParseError(String),
+
     #[error("Unsupported format for optimization: {0}")]
     UnsupportedFormat(String),
 
+    #[error("Failed to write archive: {0}")]
+    ArchiveError(String),
+
+    #[error("Atomic write failed: {0}")]
+    AtomicWriteError(String),
+
+    #[error("Disk full: {0}")]
+    DiskFull(String),
+
+    #[error("Cannot fit output in {requested} bytes: smallest encoding is {achieved} bytes")]
+    CannotMeetSizeTarget { achieved: usize, requested: usize },
+
+    #[error("Folder watch failed: {0}")]
+    WatchError(String),
+
+    #[error("Invalid file pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error("Invalid XMP sidecar: {0}")]
+    InvalidSidecar(String),
+
+    #[error("Failed to move to trash: {0}")]
+    TrashError(String),
+
+    /// The cancel signal was raised while decoding
+    #[error("cancelled")]
+    Cancelled,
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -35,11 +69,99 @@ pub enum InfraError {
     DomainError(#[from] DomainError),
 }
 
+impl InfraError {
+    /// Stable machine-readable name of the variant (see `DomainError::code`);
+    /// wrapped domain errors keep their own code
+    pub fn code(&self) -> &'static str {
+        match self {
+            InfraError::ImageReadError(_) => "image_read_error",
+            InfraError::ImageWriteError(_) => "image_write_error",
+            InfraError::DecodeError(_) => "decode_error",
+            InfraError::EncodeError(_) => "encode_error",
+            InfraError::PngOptimizationFailed(_) => "png_optimization_failed",
+            InfraError::JpegOptimizationFailed(_) => "jpeg_optimization_failed",
+            InfraError::WebpOptimizationFailed(_) => "webp_optimization_failed",
+            InfraError::ExifParseError(_) => "exif_parse_error",
+            InfraError::UnsupportedFormat(_) => "unsupported_format",
+            InfraError::ArchiveError(_) => "archive_error",
+            InfraError::AtomicWriteError(_) => "atomic_write_error",
+            InfraError::DiskFull(_) => "disk_full",
+            InfraError::CannotMeetSizeTarget { .. } => "cannot_meet_size_target",
+            InfraError::WatchError(_) => "watch_error",
+            InfraError::InvalidPattern(_) => "invalid_pattern",
+            InfraError::InvalidSidecar(_) => "invalid_sidecar",
+            InfraError::TrashError(_) => "trash_error",
+            InfraError::Cancelled => "cancelled",
+            // El caso más común merece su propio código
+            InfraError::IoError(e) if e.kind() == std::io::ErrorKind::NotFound => "file_not_found",
+            InfraError::IoError(_) => "io_error",
+            InfraError::ImageError(_) => "image_error",
+            InfraError::DomainError(e) => e.code(),
+        }
+    }
+}
+
 pub type InfraResult<T> = Result<T, InfraError>;
 
 // Conversión de InfraError a DomainError para mantener la separación de capas
 impl From<InfraError> for DomainError {
     fn from(err: InfraError) -> Self {
-        DomainError::UnsupportedTransformation(err.to_string())
+        match err {
+            // Fallos de lectura: pueden ser transitorios (NAS/SMB ocupados)
+            InfraError::IoError(_) | InfraError::ImageReadError(_) => {
+                DomainError::ReadFailed(err.to_string())
+            }
+            InfraError::DecodeError(message) => DomainError::DecodeFailed(message),
+            InfraError::DiskFull(message) => DomainError::DiskFull(message),
+            InfraError::Cancelled => DomainError::Cancelled,
+            // p. ej. DimensionsExceedFormatLimit: el llamador decide si ajustar o rechazar
+            InfraError::DomainError(err) => err,
+            // Sin equivalente en el dominio: conservan su código
+            InfraError::ImageWriteError(_)
+            | InfraError::EncodeError(_)
+            | InfraError::PngOptimizationFailed(_)
+            | InfraError::JpegOptimizationFailed(_)
+            | InfraError::WebpOptimizationFailed(_)
+            | InfraError::ExifParseError(_)
+            | InfraError::UnsupportedFormat(_)
+            | InfraError::ArchiveError(_)
+            | InfraError::AtomicWriteError(_)
+            | InfraError::CannotMeetSizeTarget { .. }
+            | InfraError::WatchError(_)
+            | InfraError::InvalidPattern(_)
+            | InfraError::InvalidSidecar(_)
+            | InfraError::TrashError(_)
+            | InfraError::ImageError(_) => DomainError::Infrastructure {
+                code: err.code(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_error_keeps_infra_code() {
+        let cases = [
+            (
+                InfraError::WebpOptimizationFailed("bad".to_string()),
+                "webp_optimization_failed",
+            ),
+            (
+                InfraError::CannotMeetSizeTarget {
+                    achieved: 2000,
+                    requested: 1000,
+                },
+                "cannot_meet_size_target",
+            ),
+            (InfraError::EncodeError("bad".to_string()), "encode_error"),
+            (InfraError::DecodeError("bad".to_string()), "decode_failed"),
+        ];
+        for (err, code) in cases {
+            assert_eq!(DomainError::from(err).code(), code);
+        }
     }
 }
@@ -0,0 +1,193 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+use crate::infrastructure::error::{InfraError, InfraResult};
+
+/// Which files `FileHandler::discover_images_with_options` returns, on top of
+/// being a supported image
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryOptions {
+    /// Glob patterns a file must match at least one of (empty = every file).
+    /// Patterns without `/` match the file name, the rest the path relative to the
+    /// discovered folder (e.g. `IMG_2024*`, `*.arw`, `2024/**/*.jpg`)
+    pub include: Vec<String>,
+    /// Glob patterns that drop a file even when it's included
+    pub exclude: Vec<String>,
+    /// Allowed extensions, with or without the dot (empty = every image format)
+    pub extensions: Vec<String>,
+    /// Levels to descend, as in `FileHandler::discover_images_with_depth` (None = unlimited)
+    pub max_depth: Option<usize>,
+    /// Also keep files whose extension isn't an image format but whose contents are
+    /// (e.g. a JPEG saved as `.bin`). Reads the first bytes of every such file.
+    pub sniff_content: bool,
+}
+
+/// Include/exclude globs split by what they are matched against
+struct PatternSet {
+    names: GlobSet,
+    paths: GlobSet,
+}
+
+impl PatternSet {
+    fn new(patterns: &[String]) -> InfraResult<Self> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            // Las cámaras escriben .ARW o .arw: la extensión se compara en minúsculas
+            let normalized = lowercase_extension(pattern);
+            let glob = GlobBuilder::new(&normalized)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| InfraError::InvalidPattern(e.to_string()))?;
+            if normalized.contains('/') {
+                paths.add(glob);
+            } else {
+                names.add(glob);
+            }
+        }
+
+        let build = |builder: GlobSetBuilder| {
+            builder
+                .build()
+                .map_err(|e| InfraError::InvalidPattern(e.to_string()))
+        };
+        Ok(Self {
+            names: build(names)?,
+            paths: build(paths)?,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.paths.is_empty()
+    }
+
+    /// `relative_path` uses `/` separators and a lowercase extension
+    fn is_match(&self, relative_path: &str) -> bool {
+        let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        self.names.is_match(name) || self.paths.is_match(relative_path)
+    }
+}
+
+/// Compiled `DiscoveryOptions`
+pub(super) struct DiscoveryFilter {
+    include: PatternSet,
+    exclude: PatternSet,
+    extensions: Vec<String>,
+}
+
+impl DiscoveryFilter {
+    pub(super) fn new(options: &DiscoveryOptions) -> InfraResult<Self> {
+        Ok(Self {
+            include: PatternSet::new(&options.include)?,
+            exclude: PatternSet::new(&options.exclude)?,
+            extensions: options
+                .extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                .collect(),
+        })
+    }
+
+    /// Check a file found under `root`
+    pub(super) fn matches(&self, root: &Path, path: &Path) -> bool {
+        if !self.extensions.is_empty() {
+            let ext = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if !self.extensions.contains(&ext) {
+                return false;
+            }
+        }
+
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let relative = lowercase_extension(&relative.to_string_lossy().replace('\\', "/"));
+
+        (self.include.is_empty() || self.include.is_match(&relative))
+            && !self.exclude.is_match(&relative)
+    }
+}
+
+/// Lowercase the text after the last dot of the last path component
+fn lowercase_extension(path: &str) -> String {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) => {
+            let ext_start = name_start + dot + 1;
+            format!("{}{}", &path[..ext_start], path[ext_start..].to_lowercase())
+        }
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str], extensions: &[&str]) -> DiscoveryFilter {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        DiscoveryFilter::new(&DiscoveryOptions {
+            include: strings(include),
+            exclude: strings(exclude),
+            extensions: strings(extensions),
+            max_depth: None,
+            sniff_content: false,
+        })
+        .unwrap()
+    }
+
+    fn matches(filter: &DiscoveryFilter, relative: &str) -> bool {
+        filter.matches(Path::new("/shoot"), &Path::new("/shoot").join(relative))
+    }
+
+    #[test]
+    fn test_lowercase_extension() {
+        assert_eq!(lowercase_extension("IMG_01.ARW"), "IMG_01.arw");
+        assert_eq!(lowercase_extension("Raw.D/IMG"), "Raw.D/IMG");
+        assert_eq!(lowercase_extension("*.{JPG,Png}"), "*.{jpg,png}");
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let raws = filter(&["*.ARW"], &[], &[]);
+        assert!(matches(&raws, "DSC001.ARW"));
+        assert!(matches(&raws, "day1/DSC002.arw"));
+        assert!(!matches(&raws, "DSC001.jpg"));
+
+        // El nombre no ignora mayúsculas, solo la extensión
+        let dated = filter(&["IMG_2024*"], &["*_edit.*", "rejects/**"], &[]);
+        assert!(matches(&dated, "IMG_20240101.JPG"));
+        assert!(!matches(&dated, "img_20240101.jpg"));
+        assert!(!matches(&dated, "IMG_20240101_edit.jpg"));
+        assert!(!matches(&dated, "rejects/IMG_20240102.jpg"));
+        assert!(matches(&dated, "keepers/IMG_20240102.jpg"));
+
+        // Sin include: todo salvo lo excluido
+        let no_previews = filter(&[], &["*.jpg"], &[]);
+        assert!(matches(&no_previews, "DSC001.NEF"));
+        assert!(!matches(&no_previews, "DSC001.JPG"));
+    }
+
+    #[test]
+    fn test_extension_allow_list() {
+        let only_raw = filter(&[], &[], &[".ARW", "nef"]);
+        assert!(matches(&only_raw, "a.arw"));
+        assert!(matches(&only_raw, "b.NEF"));
+        assert!(!matches(&only_raw, "c.jpg"));
+
+        let combined = filter(&["IMG_*"], &[], &["jpg"]);
+        assert!(matches(&combined, "IMG_1.JPG"));
+        assert!(!matches(&combined, "IMG_1.png"));
+        assert!(!matches(&combined, "DSC_1.jpg"));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let result = DiscoveryFilter::new(&DiscoveryOptions {
+            include: vec!["[unclosed".to_string()],
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(InfraError::InvalidPattern(_))));
+    }
+}
@@ -1,7 +1,40 @@
+mod discovery;
+mod processing_manifest;
+mod sidecar_handler;
+mod watcher;
+mod zip_exporter;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use sysinfo::Disks;
 use walkdir::WalkDir;
 
-use crate::infrastructure::image_processor::RawProcessor;
+use crate::domain::ImageFormat;
+use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::image_processor::{HeicDecoder, RawProcessor};
+use discovery::DiscoveryFilter;
+
+pub use discovery::DiscoveryOptions;
+pub use processing_manifest::{ManifestEntry, ProcessingManifest};
+pub use sidecar_handler::{SidecarHandler, XmpSidecar};
+pub use watcher::WatchHandle;
+pub use zip_exporter::ZipExporter;
+
+/// Overview of the images in a folder, from file metadata only (nothing is decoded)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirectoryStats {
+    pub total_images: usize,
+    pub total_size_bytes: u64,
+    /// Image count by format, according to the extension
+    pub by_format: HashMap<ImageFormat, usize>,
+    /// Oldest and newest modification times
+    pub oldest_file: Option<SystemTime>,
+    pub newest_file: Option<SystemTime>,
+    pub largest_file_bytes: u64,
+}
 
 /// File system utilities for reading and discovering images
 pub struct FileHandler;
@@ -9,17 +42,100 @@ pub struct FileHandler;
 impl FileHandler {
     /// Discover image files in a directory (non-recursive)
     pub fn discover_images(dir: &Path) -> Vec<PathBuf> {
+        Self::discover_images_with_depth(dir, Some(1))
+    }
+
+    /// Discover image files descending at most `max_depth` levels (None = unlimited).
+    /// A depth of 1 only lists the directory itself.
+    pub fn discover_images_with_depth(dir: &Path, max_depth: Option<usize>) -> Vec<PathBuf> {
+        Self::walk_files(dir, max_depth)
+            .filter(|path| Self::is_image_file(path))
+            .collect()
+    }
+
+    /// Discover image files that pass the include/exclude patterns and extension
+    /// allow-list of `options`. Fails only if a pattern is invalid.
+    pub fn discover_images_with_options(
+        dir: &Path,
+        options: &DiscoveryOptions,
+    ) -> InfraResult<Vec<PathBuf>> {
+        let filter = DiscoveryFilter::new(options)?;
+        Ok(Self::walk_files(dir, options.max_depth)
+            .filter(|path| {
+                Self::is_image_file(path) || (options.sniff_content && Self::is_image_content(path))
+            })
+            .filter(|path| filter.matches(dir, path))
+            .collect())
+    }
+
+    /// Count and size up the images `discover_images_with_depth` would find, reading
+    /// only their file system metadata. Files whose metadata can't be read are left out.
+    pub fn compute_directory_stats(dir: &Path, max_depth: Option<usize>) -> DirectoryStats {
+        let mut stats = DirectoryStats::default();
+
+        for path in Self::discover_images_with_depth(dir, max_depth) {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let size = metadata.len();
+            stats.total_images += 1;
+            stats.total_size_bytes += size;
+            stats.largest_file_bytes = stats.largest_file_bytes.max(size);
+
+            if let Some(format) = path
+                .extension()
+                .and_then(|ext| ImageFormat::from_extension(&ext.to_string_lossy()).ok())
+            {
+                *stats.by_format.entry(format).or_default() += 1;
+            }
+            if let Ok(modified) = metadata.modified() {
+                if stats.oldest_file.map_or(true, |oldest| modified < oldest) {
+                    stats.oldest_file = Some(modified);
+                }
+                if stats.newest_file.map_or(true, |newest| modified > newest) {
+                    stats.newest_file = Some(modified);
+                }
+            }
+        }
+        stats
+    }
+
+    /// Every file under `dir`, descending at most `max_depth` levels
+    fn walk_files(dir: &Path, max_depth: Option<usize>) -> impl Iterator<Item = PathBuf> {
         WalkDir::new(dir)
-            .max_depth(1)
+            .max_depth(max_depth.unwrap_or(usize::MAX))
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
-            .filter(|e| Self::is_image_file(e.path()))
-            .map(|e| e.path().to_path_buf())
-            .collect()
+            .map(|e| e.into_path())
+            // Las copias de seguridad del modo in situ no se vuelven a procesar
+            .filter(|path| !Self::is_backup(path))
+    }
+
+    /// Format of a file according to its first bytes, regardless of its extension
+    /// (None = unreadable or not a known image signature)
+    pub fn sniff_format(path: &Path) -> Option<ImageFormat> {
+        let mut header = Vec::with_capacity(ImageFormat::MAGIC_BYTES_LEN);
+        fs::File::open(path)
+            .ok()?
+            .take(ImageFormat::MAGIC_BYTES_LEN as u64)
+            .read_to_end(&mut header)
+            .ok()?;
+        ImageFormat::from_magic_bytes(&header).ok()
     }
 
-    /// Check if a file is an image based on extension (includes RAW formats)
+    /// Check if a file's contents are an image this build can decode, whatever its
+    /// extension says
+    pub fn is_image_content(path: &Path) -> bool {
+        match Self::sniff_format(path) {
+            Some(ImageFormat::Heic) => HeicDecoder::is_supported(),
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Check if a file is an image based on extension (includes RAW formats, and HEIC
+    /// when the build supports it)
     pub fn is_image_file(path: &Path) -> bool {
         if let Some(ext) = path.extension() {
             let ext_str = ext.to_string_lossy().to_lowercase();
@@ -29,12 +145,171 @@ impl FileHandler {
                 return true;
             }
 
+            // Check HEIC formats
+            if HeicDecoder::is_heic_format(&ext_str) {
+                return HeicDecoder::is_supported();
+            }
+
             // Check RAW formats
             RawProcessor::is_raw_format(&ext_str)
         } else {
             false
         }
     }
+
+    /// Free bytes on the volume that holds `path`, or its nearest existing ancestor
+    /// (an output directory may not be created yet). None = no mounted disk found.
+    pub fn available_space(path: &Path) -> Option<u64> {
+        let existing = path
+            .ancestors()
+            .find(|p| !p.as_os_str().is_empty() && p.exists())
+            .unwrap_or(Path::new("."));
+        let path = existing.canonicalize().ok()?;
+
+        // El punto de montaje más largo que contiene la ruta es su volumen
+        Disks::new_with_refreshed_list()
+            .list()
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map(|disk| disk.available_space())
+    }
+
+    /// Watch a folder for new or rewritten image files (e.g. tethered shooting);
+    /// `callback` gets each path, or the error the watcher ran into. Watching stops
+    /// when the handle is dropped.
+    pub fn watch(
+        dir: &Path,
+        callback: impl Fn(InfraResult<PathBuf>) + Send + 'static,
+    ) -> InfraResult<WatchHandle> {
+        WatchHandle::start(dir, callback)
+    }
+
+    /// Write `data` to `path` without ever leaving a half-written file there: the bytes
+    /// go to `<path>.tmp` in the same directory, which is then renamed over `path`
+    /// (atomic on POSIX; on Windows `fs::rename` replaces an existing file too, but
+    /// fails if it's read-only or open elsewhere). If the rename fails, falls back to
+    /// copy + delete. The temporary file is removed on any failure, and running out of
+    /// space is reported as `InfraError::DiskFull`.
+    pub fn write_atomic(path: &Path, data: &[u8]) -> InfraResult<()> {
+        let mut tmp_name = path.as_os_str().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_name);
+
+        let fail = |message: String, error: &io::Error| {
+            let _ = fs::remove_file(&tmp_path);
+            if Self::is_out_of_space(error) {
+                InfraError::DiskFull(message)
+            } else {
+                InfraError::AtomicWriteError(message)
+            }
+        };
+
+        fs::write(&tmp_path, data).map_err(|e| {
+            fail(
+                format!(
+                    "Failed to write temporary file '{}': {}",
+                    tmp_path.display(),
+                    e
+                ),
+                &e,
+            )
+        })?;
+
+        if let Err(rename_error) = fs::rename(&tmp_path, path) {
+            // p. ej. movimiento entre dispositivos, o en Windows un destino de solo
+            // lectura o abierto por otro programa: copiar y borrar el temporal
+            let copied = fs::copy(&tmp_path, path);
+            let _ = fs::remove_file(&tmp_path);
+            copied.map_err(|e| {
+                fail(
+                    format!(
+                        "Failed to copy to '{}' after the rename failed ({}): {}",
+                        path.display(),
+                        rename_error,
+                        e
+                    ),
+                    &e,
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Move `from` to `to`, never replacing an existing file. Falls back to copy + delete
+    /// when a rename isn't possible (e.g. across volumes).
+    pub fn move_file(from: &Path, to: &Path) -> InfraResult<()> {
+        if to.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("'{}' already exists", to.display()),
+            )
+            .into());
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::rename(from, to).is_err() {
+            fs::copy(from, to)?;
+            if let Err(e) = fs::remove_file(from) {
+                // No dejar dos copias: el original sigue en su sitio
+                let _ = fs::remove_file(to);
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Backup of an original about to be overwritten: `photo.jpg` -> `photo.orig.jpg`
+    pub fn backup_path(path: &Path) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        match path.extension() {
+            Some(extension) => {
+                path.with_file_name(format!("{}.orig.{}", stem, extension.to_string_lossy()))
+            }
+            None => path.with_file_name(format!("{}.orig", stem)),
+        }
+    }
+
+    /// Whether `path` is a backup written by `back_up`
+    pub fn is_backup(path: &Path) -> bool {
+        path.file_stem()
+            .is_some_and(|stem| stem.to_string_lossy().ends_with(".orig"))
+    }
+
+    /// Copy `path` to its `backup_path`. An existing backup is kept: it holds the
+    /// original from before the first in-place run.
+    pub fn back_up(path: &Path) -> InfraResult<PathBuf> {
+        let backup = Self::backup_path(path);
+        if !backup.exists() {
+            fs::copy(path, &backup)?;
+        }
+        Ok(backup)
+    }
+
+    /// Send `path` to the system trash / recycle bin
+    pub fn move_to_trash(path: &Path) -> InfraResult<()> {
+        trash::delete(path).map_err(|e| InfraError::TrashError(e.to_string()))
+    }
+
+    /// Whether an IO error means the disk (or quota) is full.
+    /// `ErrorKind::StorageFull` needs a newer Rust than our MSRV, so check the OS codes.
+    fn is_out_of_space(error: &io::Error) -> bool {
+        // ENOSPC y EDQUOT (Linux/macOS); ERROR_HANDLE_DISK_FULL y ERROR_DISK_FULL (Windows)
+        #[cfg(target_os = "linux")]
+        const CODES: &[i32] = &[28, 122];
+        #[cfg(all(unix, not(target_os = "linux")))]
+        const CODES: &[i32] = &[28, 69];
+        #[cfg(windows)]
+        const CODES: &[i32] = &[39, 112];
+        #[cfg(not(any(unix, windows)))]
+        const CODES: &[i32] = &[];
+
+        error
+            .raw_os_error()
+            .is_some_and(|code| CODES.contains(&code))
+    }
 }
 
 #[cfg(test)]
@@ -60,4 +335,207 @@ mod tests {
         assert!(!FileHandler::is_image_file(Path::new("test.txt")));
         assert!(!FileHandler::is_image_file(Path::new("test.pdf")));
     }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.png");
+        std::fs::write(&path, b"old").unwrap();
+
+        FileHandler::write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert!(!dir.path().join("out.png.tmp").exists());
+    }
+
+    #[test]
+    fn test_write_atomic_failure_removes_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        // Un directorio no vacío en el destino: rename y copia fallan
+        let path = dir.path().join("out.png");
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("keep"), b"").unwrap();
+
+        let result = FileHandler::write_atomic(&path, b"data");
+
+        match result {
+            Err(InfraError::AtomicWriteError(message)) => {
+                assert!(message.contains("rename failed"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(!dir.path().join("out.png.tmp").exists());
+        assert!(path.join("keep").exists());
+    }
+
+    #[test]
+    fn test_move_file_never_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("a.png");
+        let to = dir.path().join("done").join("a.png");
+        fs::write(&from, b"original").unwrap();
+
+        FileHandler::move_file(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read(&to).unwrap(), b"original");
+
+        fs::write(&from, b"second").unwrap();
+        assert!(FileHandler::move_file(&from, &to).is_err());
+        assert_eq!(fs::read(&from).unwrap(), b"second");
+        assert_eq!(fs::read(&to).unwrap(), b"original");
+    }
+
+    #[test]
+    fn test_backup_is_never_discovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let photo = dir.path().join("photo.jpg");
+        fs::write(&photo, b"original").unwrap();
+
+        let backup = FileHandler::back_up(&photo).unwrap();
+        assert_eq!(backup, dir.path().join("photo.orig.jpg"));
+        assert!(FileHandler::is_backup(&backup));
+        assert!(!FileHandler::is_backup(&photo));
+
+        // Una segunda copia no pisa la primera
+        fs::write(&photo, b"optimized").unwrap();
+        FileHandler::back_up(&photo).unwrap();
+        assert_eq!(fs::read(&backup).unwrap(), b"original");
+
+        assert_eq!(FileHandler::discover_images(dir.path()), vec![photo]);
+    }
+
+    #[test]
+    fn test_out_of_space_errors() {
+        #[cfg(unix)]
+        let full = io::Error::from_raw_os_error(28);
+        #[cfg(windows)]
+        let full = io::Error::from_raw_os_error(112);
+        assert!(FileHandler::is_out_of_space(&full));
+
+        assert!(!FileHandler::is_out_of_space(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+        assert!(!FileHandler::is_out_of_space(
+            &io::Error::from_raw_os_error(2)
+        ));
+    }
+
+    #[test]
+    fn test_discover_images_with_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join("top.jpg"), b"").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"").unwrap();
+        std::fs::write(dir.path().join("a").join("mid.png"), b"").unwrap();
+        std::fs::write(nested.join("deep.nef"), b"").unwrap();
+
+        assert_eq!(FileHandler::discover_images(dir.path()).len(), 1);
+        assert_eq!(
+            FileHandler::discover_images_with_depth(dir.path(), Some(2)).len(),
+            2
+        );
+        assert_eq!(
+            FileHandler::discover_images_with_depth(dir.path(), None).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_compute_directory_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("a.arw"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("b.ARW"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("c.jpeg"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("sub").join("d.png"), vec![0u8; 10]).unwrap();
+
+        let stats = FileHandler::compute_directory_stats(dir.path(), None);
+        assert_eq!(stats.total_images, 4);
+        assert_eq!(stats.total_size_bytes, 460);
+        assert_eq!(stats.largest_file_bytes, 300);
+        assert_eq!(stats.by_format[&ImageFormat::Raw], 2);
+        assert_eq!(stats.by_format[&ImageFormat::Jpeg], 1);
+        assert!(stats.oldest_file.is_some() && stats.oldest_file <= stats.newest_file);
+
+        let top_level = FileHandler::compute_directory_stats(dir.path(), Some(1));
+        assert_eq!(top_level.total_images, 3);
+        assert!(!top_level.by_format.contains_key(&ImageFormat::Png));
+
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(
+            FileHandler::compute_directory_stats(empty.path(), None),
+            DirectoryStats::default()
+        );
+    }
+
+    #[test]
+    fn test_discover_images_with_options() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("rejects")).unwrap();
+        for name in ["DSC001.ARW", "DSC001.JPG", "DSC002.arw", "notes.txt"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        std::fs::write(dir.path().join("rejects").join("DSC003.ARW"), b"").unwrap();
+
+        let options = DiscoveryOptions {
+            include: vec!["DSC*".to_string()],
+            exclude: vec!["rejects/**".to_string()],
+            extensions: vec!["arw".to_string()],
+            max_depth: None,
+            sniff_content: false,
+        };
+        let mut found = FileHandler::discover_images_with_options(dir.path(), &options).unwrap();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![dir.path().join("DSC001.ARW"), dir.path().join("DSC002.arw")]
+        );
+
+        let invalid = DiscoveryOptions {
+            exclude: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(FileHandler::discover_images_with_options(dir.path(), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_discover_misnamed_images_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(2, 2))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        // PNG guardado con una extensión que no es de imagen
+        std::fs::write(dir.path().join("export.bin"), &png).unwrap();
+        std::fs::write(dir.path().join("photo.png"), &png).unwrap();
+        std::fs::write(dir.path().join("notes.dat"), b"just some text").unwrap();
+
+        assert_eq!(
+            FileHandler::sniff_format(&dir.path().join("export.bin")),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            FileHandler::sniff_format(&dir.path().join("notes.dat")),
+            None
+        );
+
+        let by_extension =
+            FileHandler::discover_images_with_options(dir.path(), &DiscoveryOptions::default())
+                .unwrap();
+        assert_eq!(by_extension, vec![dir.path().join("photo.png")]);
+
+        let options = DiscoveryOptions {
+            sniff_content: true,
+            ..Default::default()
+        };
+        let mut by_content =
+            FileHandler::discover_images_with_options(dir.path(), &options).unwrap();
+        by_content.sort();
+        assert_eq!(
+            by_content,
+            vec![dir.path().join("export.bin"), dir.path().join("photo.png")]
+        );
+    }
 }
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::domain::models::PostAction;
+use crate::domain::{ProcessingSettings, Transformation};
+use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::file_system::FileHandler;
+
+/// What produced one output file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    pub input_path: PathBuf,
+    /// `ProcessingManifest::content_hash` of the input when the output was written
+    pub input_hash: String,
+    /// `ProcessingManifest::settings_hash` of the settings it was written with
+    pub settings_hash: String,
+}
+
+/// Record of the outputs in a folder, kept next to them so incremental runs
+/// (`skip_unchanged`) notice changed inputs and changed settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProcessingManifest {
+    /// Keyed by output file name
+    entries: BTreeMap<String, ManifestEntry>,
+}
+
+impl ProcessingManifest {
+    /// Name of the manifest file in the output directory
+    pub const FILE_NAME: &'static str = ".transform_images_manifest.json";
+
+    /// Manifest of `output_dir`; empty if there is none or it can't be parsed
+    /// (everything is then treated as changed)
+    pub fn load(output_dir: &Path) -> Self {
+        fs::read(output_dir.join(Self::FILE_NAME))
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the manifest into `output_dir`
+    pub fn save(&self, output_dir: &Path) -> InfraResult<()> {
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| InfraError::AtomicWriteError(e.to_string()))?;
+        fs::create_dir_all(output_dir)?;
+        FileHandler::write_atomic(&output_dir.join(Self::FILE_NAME), &json)
+    }
+
+    /// Entry recorded for `output`
+    pub fn entry(&self, output: &Path) -> Option<&ManifestEntry> {
+        self.entries.get(&Self::key(output)?)
+    }
+
+    /// Record that `output` was just written from `entry.input_path`
+    pub fn record(&mut self, output: &Path, entry: ManifestEntry) {
+        if let Some(key) = Self::key(output) {
+            self.entries.insert(key, entry);
+        }
+    }
+
+    /// Whether `output` exists and is up to date with `input` and `settings_hash`:
+    /// same settings and input path as recorded, and either newer than the input
+    /// or made from identical contents (a touched but unchanged input)
+    pub fn is_up_to_date(&self, input: &Path, output: &Path, settings_hash: &str) -> bool {
+        let Some(entry) = self.entry(output) else {
+            return false;
+        };
+        if entry.input_path != input || entry.settings_hash != settings_hash {
+            return false;
+        }
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        match (modified(input), modified(output)) {
+            (Some(input_time), Some(output_time)) if output_time >= input_time => true,
+            (Some(_), Some(_)) => {
+                Self::content_hash(input).is_ok_and(|hash| hash == entry.input_hash)
+            }
+            _ => false,
+        }
+    }
+
+    /// 64-bit FNV-1a of the file's bytes, as hex. Stable across runs and builds.
+    pub fn content_hash(path: &Path) -> io::Result<String> {
+        let mut file = fs::File::open(path)?;
+        let mut hasher = Fnv1a::new();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.write(&buffer[..read]);
+        }
+        Ok(hasher.hex())
+    }
+
+    /// Hash of everything that shapes an image's outputs. Run options such as
+    /// retries or workers count too: changing them only costs one full re-run.
+    pub fn settings_hash(
+        settings: &ProcessingSettings,
+        transformation: Option<&Transformation>,
+    ) -> String {
+        let mut settings = settings.clone();
+        // Los ajustes de las demás imágenes no afectan a esta, ni dónde se escribe
+        settings
+            .set_image_overrides(HashMap::new())
+            .set_preserve_directory_structure(false)
+            .set_source_base(None)
+            .set_skip_unchanged(false)
+            .set_dry_run(false)
+            .set_post_action(PostAction::Keep)
+            .set_write_report(None);
+
+        let mut hasher = Fnv1a::new();
+        hasher.write(&serde_json::to_vec(&settings).unwrap_or_default());
+        hasher.write(&serde_json::to_vec(&transformation).unwrap_or_default());
+        hasher.hex()
+    }
+
+    fn key(output: &Path) -> Option<String> {
+        Some(output.file_name()?.to_string_lossy().to_string())
+    }
+}
+
+/// 64-bit FNV-1a
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manifest = ProcessingManifest::default();
+        let entry = ManifestEntry {
+            input_path: PathBuf::from("/photos/a.png"),
+            input_hash: "0123456789abcdef".to_string(),
+            settings_hash: "fedcba9876543210".to_string(),
+        };
+        manifest.record(&dir.path().join("a.webp"), entry.clone());
+        manifest.save(dir.path()).unwrap();
+
+        let loaded = ProcessingManifest::load(dir.path());
+        assert_eq!(loaded, manifest);
+        assert_eq!(loaded.entry(Path::new("/elsewhere/a.webp")), Some(&entry));
+        assert_eq!(
+            ProcessingManifest::load(&dir.path().join("missing")),
+            ProcessingManifest::default()
+        );
+    }
+
+    #[test]
+    fn test_up_to_date_checks_settings_and_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("a.png");
+        let output = dir.path().join("out").join("a.png");
+        fs::write(&input, b"input").unwrap();
+        fs::create_dir_all(output.parent().unwrap()).unwrap();
+        fs::write(&output, b"output").unwrap();
+
+        let mut manifest = ProcessingManifest::default();
+        manifest.record(
+            &output,
+            ManifestEntry {
+                input_path: input.clone(),
+                input_hash: ProcessingManifest::content_hash(&input).unwrap(),
+                settings_hash: "settings".to_string(),
+            },
+        );
+
+        assert!(manifest.is_up_to_date(&input, &output, "settings"));
+        assert!(!manifest.is_up_to_date(&input, &output, "other settings"));
+        assert!(!manifest.is_up_to_date(&input, &dir.path().join("b.png"), "settings"));
+
+        // Entrada modificada después de la salida: cuenta el contenido
+        let later =
+            fs::metadata(&output).unwrap().modified().unwrap() + std::time::Duration::from_secs(10);
+        let touch = |contents: &[u8]| {
+            fs::write(&input, contents).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&input)
+                .unwrap()
+                .set_modified(later)
+                .unwrap();
+        };
+        touch(b"input");
+        assert!(manifest.is_up_to_date(&input, &output, "settings"));
+        touch(b"edited");
+        assert!(!manifest.is_up_to_date(&input, &output, "settings"));
+    }
+
+    #[test]
+    fn test_settings_hash_ignores_run_options() {
+        let settings = ProcessingSettings::default();
+        let hash = ProcessingManifest::settings_hash(&settings, None);
+
+        let mut dry_run = settings.clone();
+        dry_run
+            .set_dry_run(true)
+            .set_skip_unchanged(true)
+            .set_post_action(PostAction::Delete)
+            .set_write_report(Some(PathBuf::from("report.csv")));
+        assert_eq!(ProcessingManifest::settings_hash(&dry_run, None), hash);
+
+        let mut other_quality = settings.clone();
+        other_quality.set_quality(crate::domain::Quality::new(50).unwrap());
+        assert_ne!(
+            ProcessingManifest::settings_hash(&other_quality, None),
+            hash
+        );
+        assert_ne!(
+            ProcessingManifest::settings_hash(&settings, Some(&Transformation::default())),
+            hash
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::domain::models::ImageMetadata;
+use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::file_system::FileHandler;
+use crate::infrastructure::iptc_xmp;
+
+const XMP_NAMESPACE: &str = "http://ns.adobe.com/xap/1.0/";
+const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+/// Skeleton used when a RAW file has no sidecar yet
+const EMPTY_SIDECAR: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""/>
+ </rdf:RDF>
+</x:xmpmeta>
+"#;
+
+/// Fields of a `.xmp` sidecar that editors (Lightroom, darktable, ...) keep next to RAW files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XmpSidecar {
+    /// `xmp:Rating`, 0-5 stars
+    pub rating: Option<u8>,
+    /// `xmp:Label`
+    pub label: Option<String>,
+    /// `dc:title`
+    pub title: Option<String>,
+    /// `dc:description`
+    pub description: Option<String>,
+}
+
+impl XmpSidecar {
+    /// Copy the sidecar fields over `metadata`; the sidecar wins over the camera data
+    pub fn merge_into(&self, metadata: &mut ImageMetadata) {
+        if let Some(rating) = self.rating {
+            metadata.xmp_rating = Some(rating);
+        }
+        if let Some(label) = &self.label {
+            metadata.xmp_label = Some(label.clone());
+        }
+        if let Some(title) = &self.title {
+            metadata.xmp_title = Some(title.clone());
+        }
+        if let Some(description) = &self.description {
+            metadata.iptc_caption = Some(description.clone());
+        }
+    }
+}
+
+/// Reads and writes `.xmp` sidecars next to RAW files
+pub struct SidecarHandler;
+
+impl SidecarHandler {
+    /// `photo.CR2` -> `photo.xmp`
+    pub fn sidecar_path(raw_path: &Path) -> PathBuf {
+        raw_path.with_extension("xmp")
+    }
+
+    /// Read the sidecar next to `raw_path`
+    pub fn read(raw_path: &Path) -> InfraResult<XmpSidecar> {
+        let xmp = fs::read_to_string(Self::sidecar_path(raw_path))?;
+
+        Ok(XmpSidecar {
+            rating: iptc_xmp::rating(&xmp),
+            label: iptc_xmp::property(&xmp, "xmp:Label"),
+            title: iptc_xmp::property(&xmp, "dc:title"),
+            description: iptc_xmp::property(&xmp, "dc:description"),
+        })
+    }
+
+    /// Write `sidecar` next to `raw_path`. An existing sidecar is updated in place so
+    /// develop settings and other properties survive; `None` fields are removed.
+    pub fn write(raw_path: &Path, sidecar: &XmpSidecar) -> InfraResult<()> {
+        let path = Self::sidecar_path(raw_path);
+        let existing = match fs::read_to_string(&path) {
+            Ok(xmp) => xmp,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => EMPTY_SIDECAR.to_string(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let xmp = Self::update(existing, sidecar).ok_or_else(|| {
+            InfraError::InvalidSidecar(format!("No rdf:Description in {}", path.display()))
+        })?;
+        FileHandler::write_atomic(&path, xmp.as_bytes())
+    }
+
+    /// Replace the sidecar properties inside the first `rdf:Description`
+    fn update(mut xmp: String, sidecar: &XmpSidecar) -> Option<String> {
+        for name in ["xmp:Rating", "xmp:Label", "dc:title", "dc:description"] {
+            remove_attribute(&mut xmp, name);
+            remove_element(&mut xmp, name);
+        }
+
+        // `<rdf:Description .../>` necesita cuerpo para los elementos dc:*
+        let tag_end = description_tag_end(&xmp)?;
+        if xmp[..tag_end].ends_with('/') {
+            xmp.replace_range(tag_end - 1..=tag_end, ">\n  </rdf:Description>");
+        }
+
+        let mut attributes = Vec::new();
+        for (prefix, namespace) in [("xmp", XMP_NAMESPACE), ("dc", DC_NAMESPACE)] {
+            let tag = &xmp[..description_tag_end(&xmp)?];
+            if !tag.contains(&format!("xmlns:{}=", prefix)) {
+                attributes.push(format!("xmlns:{}=\"{}\"", prefix, namespace));
+            }
+        }
+        if let Some(rating) = sidecar.rating {
+            attributes.push(format!("xmp:Rating=\"{}\"", rating));
+        }
+        if let Some(label) = &sidecar.label {
+            attributes.push(format!("xmp:Label=\"{}\"", iptc_xmp::escape_xml(label)));
+        }
+        let tag_end = description_tag_end(&xmp)?;
+        let attributes: String = attributes
+            .iter()
+            .map(|attribute| format!("\n    {}", attribute))
+            .collect();
+        xmp.insert_str(tag_end, &attributes);
+
+        let mut elements = String::new();
+        for (name, value) in [
+            ("dc:title", &sidecar.title),
+            ("dc:description", &sidecar.description),
+        ] {
+            if let Some(value) = value {
+                elements.push_str(&format!(
+                    "\n   <{name}>\n    <rdf:Alt>\n     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    </rdf:Alt>\n   </{name}>",
+                    iptc_xmp::escape_xml(value)
+                ));
+            }
+        }
+        let body_start = description_tag_end(&xmp)? + 1;
+        xmp.insert_str(body_start, &elements);
+
+        Some(xmp)
+    }
+}
+
+/// Index of the `>` closing the first `<rdf:Description ...` tag
+fn description_tag_end(xmp: &str) -> Option<usize> {
+    let start = xmp.find("<rdf:Description")?;
+    Some(start + xmp[start..].find('>')?)
+}
+
+/// Remove every ` name="..."` attribute, with its leading whitespace
+fn remove_attribute(xmp: &mut String, name: &str) {
+    while let Some(range) = attribute_range(xmp, name) {
+        xmp.replace_range(range, "");
+    }
+}
+
+fn attribute_range(xmp: &str, name: &str) -> Option<std::ops::Range<usize>> {
+    xmp.match_indices(name).find_map(|(pos, _)| {
+        let start = xmp[..pos].trim_end().len();
+        if start == pos {
+            return None;
+        }
+        let after_name = pos + name.len();
+        let rest = xmp[after_name..].trim_start().strip_prefix('=')?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value_start = xmp.len() - rest.len() + 1;
+        let end = value_start + xmp[value_start..].find(quote)? + 1;
+        Some(start..end)
+    })
+}
+
+/// Remove every `<name>...</name>` or `<name/>` element, with its leading whitespace
+fn remove_element(xmp: &mut String, name: &str) {
+    while let Some(range) = element_range(xmp, name) {
+        xmp.replace_range(range, "");
+    }
+}
+
+fn element_range(xmp: &str, name: &str) -> Option<std::ops::Range<usize>> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+
+    xmp.match_indices(&open).find_map(|(pos, _)| {
+        let after_name = pos + open.len();
+        // `<dc:titleX` sería otro elemento
+        if !xmp[after_name..].starts_with(|c: char| c == '>' || c.is_whitespace() || c == '/') {
+            return None;
+        }
+        let tag_end = after_name + xmp[after_name..].find('>')?;
+        let end = if xmp[..tag_end].ends_with('/') {
+            tag_end + 1
+        } else {
+            tag_end + xmp[tag_end..].find(&close)? + close.len()
+        };
+        Some(xmp[..pos].trim_end().len()..end)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(
+            SidecarHandler::sidecar_path(Path::new("/photos/IMG_0001.CR2")),
+            PathBuf::from("/photos/IMG_0001.xmp")
+        );
+    }
+
+    #[test]
+    fn test_write_new_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let raw_path = dir.path().join("IMG_0001.NEF");
+        let sidecar = XmpSidecar {
+            rating: Some(4),
+            label: Some("Red".to_string()),
+            title: Some("Sunset & sea".to_string()),
+            description: Some("Taken at <dusk>".to_string()),
+        };
+
+        SidecarHandler::write(&raw_path, &sidecar).unwrap();
+
+        assert!(dir.path().join("IMG_0001.xmp").exists());
+        assert_eq!(SidecarHandler::read(&raw_path).unwrap(), sidecar);
+    }
+
+    #[test]
+    fn test_update_existing_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let raw_path = dir.path().join("IMG_0002.ARW");
+        fs::write(
+            SidecarHandler::sidecar_path(&raw_path),
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:crs="http://ns.adobe.com/camera-raw-settings/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmp:Rating="2"
+    xmp:Label="Green"
+    crs:Exposure2012="+0.50">
+   <dc:subject>
+    <rdf:Bag>
+     <rdf:li>beach</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+   <dc:title>
+    <rdf:Alt>
+     <rdf:li xml:lang="x-default">Old title</rdf:li>
+    </rdf:Alt>
+   </dc:title>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+"#,
+        )
+        .unwrap();
+
+        let sidecar = XmpSidecar {
+            rating: Some(5),
+            label: None,
+            title: Some("New title".to_string()),
+            description: None,
+        };
+        SidecarHandler::write(&raw_path, &sidecar).unwrap();
+
+        let xmp = fs::read_to_string(SidecarHandler::sidecar_path(&raw_path)).unwrap();
+        assert!(xmp.contains(r#"crs:Exposure2012="+0.50""#));
+        assert!(xmp.contains("<rdf:li>beach</rdf:li>"));
+        assert!(!xmp.contains("Old title"));
+        assert_eq!(xmp.matches("xmlns:xmp=").count(), 1);
+        assert_eq!(SidecarHandler::read(&raw_path).unwrap(), sidecar);
+    }
+
+    #[test]
+    fn test_merge_into_metadata() {
+        let mut metadata = ImageMetadata::empty();
+        metadata.xmp_rating = Some(1);
+        metadata.camera_make = Some("Canon".to_string());
+
+        XmpSidecar {
+            rating: Some(3),
+            title: Some("Title".to_string()),
+            ..XmpSidecar::default()
+        }
+        .merge_into(&mut metadata);
+
+        assert_eq!(metadata.xmp_rating, Some(3));
+        assert_eq!(metadata.xmp_title.as_deref(), Some("Title"));
+        assert_eq!(metadata.camera_make.as_deref(), Some("Canon"));
+    }
+}
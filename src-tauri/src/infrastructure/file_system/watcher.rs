@@ -0,0 +1,99 @@
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+
+use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::file_system::FileHandler;
+
+/// Active folder watch; watching stops when the handle is dropped
+pub struct WatchHandle {
+    watcher: RecommendedWatcher,
+    dir: PathBuf,
+}
+
+impl WatchHandle {
+    /// Watch `dir` (not its subfolders) and call `callback` with each image file
+    /// that is created or written to, or with the error the watcher ran into. A file
+    /// being copied usually fires several events, so the same path may be reported
+    /// more than once.
+    pub(crate) fn start(
+        dir: &Path,
+        callback: impl Fn(InfraResult<PathBuf>) + Send + 'static,
+    ) -> InfraResult<Self> {
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event) if Self::is_write(&event.kind) => {
+                    for path in event.paths {
+                        if FileHandler::is_image_file(&path) {
+                            callback(Ok(path));
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => callback(Err(InfraError::WatchError(e.to_string()))),
+            })
+            .map_err(|e| InfraError::WatchError(e.to_string()))?;
+
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| {
+                InfraError::WatchError(format!("Failed to watch '{}': {}", dir.display(), e))
+            })?;
+
+        Ok(Self {
+            watcher,
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// New files, content writes and files renamed into place (metadata changes don't count)
+    fn is_write(kind: &EventKind) -> bool {
+        matches!(
+            kind,
+            EventKind::Create(_)
+                | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+        )
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        let _ = self.watcher.unwatch(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_watch_reports_new_images_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let _handle = FileHandler::watch(dir.path(), move |path| {
+            if let Ok(path) = path {
+                let _ = sender.send(path);
+            }
+        })
+        .unwrap();
+
+        std::fs::write(dir.path().join("notes.txt"), b"hello").unwrap();
+        let image = dir.path().join("photo.png");
+        image::RgbImage::new(4, 4).save(&image).unwrap();
+
+        let reported = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(reported.file_name(), image.file_name());
+        while let Ok(path) = receiver.recv_timeout(Duration::from_millis(300)) {
+            assert_eq!(path.file_name(), image.file_name());
+        }
+    }
+
+    #[test]
+    fn test_watch_missing_dir_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = FileHandler::watch(&dir.path().join("missing"), |_| {});
+        assert!(matches!(result, Err(InfraError::WatchError(_))));
+    }
+}
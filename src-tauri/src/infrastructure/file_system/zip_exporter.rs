@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::infrastructure::error::{InfraError, InfraResult};
+
+/// Packs processed outputs into a zip archive, one entry at a time.
+/// Entries are stored uncompressed: the images already are.
+pub struct ZipExporter {
+    writer: ZipWriter<File>,
+    used_names: HashSet<String>,
+}
+
+impl ZipExporter {
+    /// Start a new archive at `archive_path`
+    pub fn create(archive_path: &Path) -> InfraResult<Self> {
+        if let Some(parent) = archive_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            writer: ZipWriter::new(File::create(archive_path)?),
+            used_names: HashSet::new(),
+        })
+    }
+
+    /// Add `file` as `name` (`/` separators), or as "name (1)" and so on if taken.
+    /// Returns the entry name used.
+    pub fn add_file(&mut self, name: String, file: &Path) -> InfraResult<String> {
+        let name = Self::unique_name(name, &mut self.used_names);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        self.writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| InfraError::ArchiveError(e.to_string()))?;
+        io::copy(&mut File::open(file)?, &mut self.writer)?;
+        Ok(name)
+    }
+
+    /// Write the central directory; without it the archive can't be opened
+    pub fn finish(self) -> InfraResult<()> {
+        self.writer
+            .finish()
+            .map_err(|e| InfraError::ArchiveError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Write `files` into a new archive at `archive_path`.
+    /// Entry names are each file's path relative to `root`, or only the file name
+    /// when `flatten` is set.
+    pub fn write(
+        archive_path: &Path,
+        root: &Path,
+        files: &[PathBuf],
+        flatten: bool,
+    ) -> InfraResult<()> {
+        let mut exporter = Self::create(archive_path)?;
+        for file in files {
+            exporter.add_file(Self::entry_name(file, root, flatten)?, file)?;
+        }
+        exporter.finish()
+    }
+
+    /// `path` with `/` separators, as zip entry names require
+    pub fn to_entry_name(path: &Path) -> String {
+        path.components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// How results show an entry: `export.zip!/2024/photo.jpg`
+    pub fn entry_path(archive_path: &Path, entry_name: &str) -> PathBuf {
+        PathBuf::from(format!("{}!/{}", archive_path.display(), entry_name))
+    }
+
+    /// Archive entry name with `/` separators, as the zip format requires
+    fn entry_name(file: &Path, root: &Path, flatten: bool) -> InfraResult<String> {
+        let relative = if flatten {
+            file.file_name().map(Path::new)
+        } else {
+            file.strip_prefix(root).ok()
+        };
+        let relative = relative.ok_or_else(|| {
+            InfraError::ArchiveError(format!("No entry name for {}", file.display()))
+        })?;
+
+        Ok(Self::to_entry_name(relative))
+    }
+
+    /// Append " (n)" before the extension until the name is not taken
+    fn unique_name(name: String, used: &mut HashSet<String>) -> String {
+        if used.insert(name.clone()) {
+            return name;
+        }
+
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (name.as_str(), String::new()),
+        };
+        let mut n = 1;
+        loop {
+            let candidate = format!("{} ({}){}", stem, n, extension);
+            if used.insert(candidate.clone()) {
+                return candidate;
+            }
+            n += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_name() {
+        let root = Path::new("/staging");
+        let file = Path::new("/staging/2024/photo.jpg");
+
+        assert_eq!(
+            ZipExporter::entry_name(file, root, true).unwrap(),
+            "photo.jpg"
+        );
+        assert_eq!(
+            ZipExporter::entry_name(file, root, false).unwrap(),
+            "2024/photo.jpg"
+        );
+    }
+
+    #[test]
+    fn test_unique_name() {
+        let mut used = HashSet::new();
+        assert_eq!(ZipExporter::unique_name("a.jpg".into(), &mut used), "a.jpg");
+        assert_eq!(
+            ZipExporter::unique_name("a.jpg".into(), &mut used),
+            "a (1).jpg"
+        );
+        assert_eq!(
+            ZipExporter::unique_name("a.jpg".into(), &mut used),
+            "a (2).jpg"
+        );
+        assert_eq!(
+            ZipExporter::unique_name("README".into(), &mut used),
+            "README"
+        );
+    }
+
+    #[test]
+    fn test_write_archive() {
+        let staging = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = ["a.jpg", "b.webp"]
+            .iter()
+            .map(|name| {
+                let path = staging.path().join(name);
+                std::fs::write(&path, name.as_bytes()).unwrap();
+                path
+            })
+            .collect();
+
+        let archive_path = output.path().join("export.zip");
+        ZipExporter::write(&archive_path, staging.path(), &files, true).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut content = String::new();
+        io::Read::read_to_string(&mut archive.by_name("b.webp").unwrap(), &mut content).unwrap();
+        assert_eq!(content, "b.webp");
+    }
+}
@@ -1,53 +1,328 @@
+use parking_lot::Mutex;
 use rayon::prelude::*;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use crate::domain::models::{CollisionStrategy, PostAction, RenameMode};
 use crate::domain::{
-    DomainError, DomainResult, Image, ImageProcessor, ProcessingSettings, Transformation,
+    DomainError, DomainResult, Image, ImageFormat, ImageProcessor, OutputMode, ProcessingSettings,
+    SkipStrategy, Transformation,
+};
+use crate::infrastructure::error::InfraError;
+use crate::infrastructure::file_system::{
+    FileHandler, ManifestEntry, ProcessingManifest, ZipExporter,
+};
+use crate::infrastructure::image_processor::{
+    EncodedImage, ImageCache, ImageProcessorImpl, ImageValidator, PhaseTimings,
 };
-use crate::infrastructure::image_processor::ImageProcessorImpl;
 
 /// Result of processing a single image
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ProcessingResult {
     pub original_path: PathBuf,
     pub output_path: PathBuf,
     pub original_size: u64,
     pub output_size: u64,
     pub success: bool,
+    /// Output was not written because it didn't meet `min_compression_ratio`
+    /// (or the original was copied instead)
+    pub skipped: bool,
+    /// Skipped because the output came out larger than the original (`skip_if_larger`)
+    pub skipped_larger: bool,
+    /// The resize was capped at the original size because upscaling is disabled
+    pub upscale_prevented: bool,
+    /// Responsive width this output was generated for (None = not a sized variant)
+    pub target_width: Option<u32>,
+    /// Quality the output was encoded at (None = not re-encoded)
+    pub final_quality: Option<u8>,
+    /// Whether the output fits `target_size_bytes` (None = no target set)
+    pub target_met: Option<bool>,
+    /// Problem found by `ImageValidator` before decoding (processing still went ahead)
+    pub validation_warning: Option<String>,
     pub error_message: Option<String>,
+    /// `DomainError::code` of the failure, when it came from one
+    pub error_code: Option<String>,
+    /// Wall-clock time spent on this image (load + transform + encode + save)
+    pub processing_time_ms: u64,
+    /// Breakdown of the time spent on this output (None unless `phase_timings` is set)
+    pub timings: Option<PhaseTimings>,
+    /// Times the input was read and processed or its output written: 1, plus one per
+    /// retry after a transient read or write failure (0 = never decoded, e.g. skipped
+    /// or cancelled)
+    pub attempts: u32,
+    /// Planned by a dry run: nothing was written and `output_size` is 0
+    pub dry_run: bool,
+    /// Skipped by `skip_unchanged`: the existing output is up to date (implies `skipped`)
+    pub unchanged: bool,
+    /// Expected output size in a dry run: measured for the sampled images,
+    /// extrapolated from them for the rest (None = no sample to go by)
+    pub estimated_size: Option<u64>,
+    /// What `post_action` did with the original: "trashed", "deleted", "moved to ..."
+    /// or why it failed (None = left in place)
+    pub post_action: Option<String>,
+    /// Why the `skip_unchanged` manifest of this output's folder couldn't be saved:
+    /// the image will be processed again on the next run
+    pub manifest_error: Option<String>,
 }
 
 impl ProcessingResult {
+    /// Create a failed result
+    pub fn failed(original_path: PathBuf, original_size: u64, message: String) -> Self {
+        Self {
+            original_path,
+            original_size,
+            error_message: Some(message),
+            ..Default::default()
+        }
+    }
+
+    /// Tag a failed result with the code of the error that caused it
+    pub fn with_error_code(mut self, error: &DomainError) -> Self {
+        self.error_code = Some(error.code().to_string());
+        self
+    }
+
     /// Calculate compression ratio (percentage saved)
     pub fn compression_ratio(&self) -> f64 {
         if self.original_size == 0 {
             return 0.0;
         }
-        let saved = self.original_size.saturating_sub(self.output_size) as f64;
+        let saved = self.bytes_saved() as f64;
         (saved / self.original_size as f64) * 100.0
     }
 
-    /// Calculate size reduction in bytes
+    /// Calculate size reduction in bytes (estimated for a dry run)
     pub fn bytes_saved(&self) -> u64 {
-        self.original_size.saturating_sub(self.output_size)
+        let output_size = if self.dry_run {
+            self.estimated_size.unwrap_or(self.original_size)
+        } else {
+            self.output_size
+        };
+        self.original_size.saturating_sub(output_size)
+    }
+}
+
+/// Progress snapshot reported after each image
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchProgress {
+    /// Outputs finished so far (one per size and format)
+    pub current: usize,
+    pub total: usize,
+    pub current_file: String,
+    pub elapsed_ms: u64,
+    /// Estimated time left (None until there are enough timing samples)
+    pub estimated_remaining_ms: Option<u64>,
+    /// Input bytes of the images finished so far
+    pub bytes_processed: u64,
+    pub throughput_mb_per_sec: f64,
+}
+
+/// Progress callback function type, called once per finished image with the
+/// progress so far and that image's results (one per output)
+pub type ProgressCallback = Arc<dyn Fn(&BatchProgress, &[ProcessingResult]) + Send + Sync>;
+
+/// Skip signals of the running batch, one per input path, so that one image can
+/// be dropped without cancelling the rest. Keyed by path rather than position: the
+/// batch no longer has the inputs that failed to load or were filtered out.
+/// Clones share the signals.
+#[derive(Clone, Default)]
+pub struct ImageCancelSignals(Arc<Mutex<HashMap<PathBuf, Arc<AtomicBool>>>>);
+
+impl ImageCancelSignals {
+    /// Fresh signals for a batch, returned in batch order
+    fn reset(&self, images: &[Image]) -> Vec<Arc<AtomicBool>> {
+        let mut by_path = HashMap::new();
+        let signals = images
+            .iter()
+            .map(|image| {
+                by_path
+                    .entry(image.path().to_path_buf())
+                    .or_insert_with(|| Arc::new(AtomicBool::new(false)))
+                    .clone()
+            })
+            .collect();
+        *self.0.lock() = by_path;
+        signals
+    }
+
+    /// Skip the image loaded from `path` in the running batch; false if it has no
+    /// such image
+    pub fn cancel(&self, path: &Path) -> bool {
+        match self.0.lock().get(path) {
+            Some(signal) => {
+                signal.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Archive a zip batch streams its outputs into, with where each one went
+struct ZipStream {
+    /// None once finished
+    exporter: Option<ZipExporter>,
+    /// Entry name (or why adding it failed) by original and staged output
+    entries: HashMap<(PathBuf, PathBuf), Result<String, String>>,
+}
+
+impl ZipStream {
+    /// Move a finished output from staging into the archive (once) and point
+    /// `result` at its entry
+    fn add(
+        &mut self,
+        result: ProcessingResult,
+        archive_path: &Path,
+        settings: &ProcessingSettings,
+        root: Option<&Path>,
+    ) -> ProcessingResult {
+        if !result.success || result.output_path.as_os_str().is_empty() {
+            return result;
+        }
+        let key = (result.original_path.clone(), result.output_path.clone());
+        if !self.entries.contains_key(&key) {
+            let added = match (
+                self.exporter.as_mut(),
+                BatchProcessor::archive_entry_name(&result, settings, root),
+            ) {
+                (Some(exporter), Ok(name)) => exporter
+                    .add_file(name, &result.output_path)
+                    .map_err(|e| e.to_string()),
+                (None, _) => Err("archive already finished".to_string()),
+                (_, Err(e)) => Err(e.to_string()),
+            };
+            let _ = std::fs::remove_file(&result.output_path);
+            self.entries.insert(key, added);
+        }
+        self.resolve(result, archive_path)
+    }
+
+    /// `result` pointing at its entry, or failed if it never made it into the archive
+    fn resolve(&self, mut result: ProcessingResult, archive_path: &Path) -> ProcessingResult {
+        if !result.success || result.output_path.as_os_str().is_empty() {
+            return result;
+        }
+        let key = (result.original_path.clone(), result.output_path.clone());
+        match self.entries.get(&key) {
+            Some(Ok(name)) => {
+                result.output_path = ZipExporter::entry_path(archive_path, name);
+                result
+            }
+            Some(Err(e)) => ProcessingResult::failed(
+                result.original_path,
+                result.original_size,
+                format!("Failed to write zip: {}", e),
+            ),
+            // Ya apunta al archivo
+            None => result,
+        }
     }
 }
 
-/// Progress callback function type
-pub type ProgressCallback = Arc<dyn Fn(usize, usize, &str) + Send + Sync>;
+/// Timing statistics behind the progress reports of one batch
+struct ProgressTracker {
+    started: Instant,
+    last_completion: Instant,
+    current: usize,
+    total: usize,
+    bytes_processed: u64,
+    /// Exponential moving average of the time between finished outputs (ms).
+    /// Measured between completions, so it already accounts for parallelism.
+    ms_per_output: Option<f64>,
+    /// Completions behind the moving average
+    samples: usize,
+}
+
+impl ProgressTracker {
+    /// Weight of the newest sample in the moving average
+    const SMOOTHING: f64 = 0.3;
+    /// Completions needed before the ETA is reported: the first images are
+    /// skewed by thread start-up and cold caches
+    const MIN_ETA_SAMPLES: usize = 3;
+
+    fn new(started: Instant, total: usize) -> Self {
+        Self {
+            started,
+            last_completion: started,
+            current: 0,
+            total,
+            bytes_processed: 0,
+            ms_per_output: None,
+            samples: 0,
+        }
+    }
+
+    /// Record an image of `bytes` input bytes that produced `outputs` results at `now`
+    fn record(&mut self, now: Instant, outputs: usize, bytes: u64, file: &str) -> BatchProgress {
+        self.current += outputs;
+        self.bytes_processed += bytes;
+
+        if outputs > 0 {
+            let interval = now.duration_since(self.last_completion).as_secs_f64() * 1000.0;
+            let sample = interval / outputs as f64;
+            self.ms_per_output = Some(match self.ms_per_output {
+                Some(average) => average + Self::SMOOTHING * (sample - average),
+                None => sample,
+            });
+            self.last_completion = now;
+            self.samples += 1;
+        }
+
+        let elapsed = now.duration_since(self.started);
+        let remaining = self.total.saturating_sub(self.current);
+        let seconds = elapsed.as_secs_f64();
+        let throughput_mb_per_sec = if seconds > 0.0 {
+            self.bytes_processed as f64 / (1024.0 * 1024.0) / seconds
+        } else {
+            0.0
+        };
+
+        BatchProgress {
+            current: self.current,
+            total: self.total,
+            current_file: file.to_string(),
+            elapsed_ms: elapsed.as_millis() as u64,
+            estimated_remaining_ms: self
+                .ms_per_output
+                .filter(|_| self.samples >= Self::MIN_ETA_SAMPLES)
+                .map(|ms| (ms * remaining as f64).round() as u64),
+            bytes_processed: self.bytes_processed,
+            throughput_mb_per_sec,
+        }
+    }
+}
 
 /// Batch processor for processing multiple images in parallel
 pub struct BatchProcessor {
     max_threads: Option<usize>,
+    /// Shared by every worker (and every batch): loads, decodes and encodes
+    processor: Arc<ImageProcessorImpl>,
+    /// Reset at the start of every batch
+    image_cancel_signals: ImageCancelSignals,
 }
 
 impl BatchProcessor {
+    /// Names tried by `RenameMode::Sequential` before giving up
+    const MAX_RENAME_CANDIDATES: u32 = 1000;
+    /// Error of the images left unprocessed by `cancel_processing`
+    pub const CANCELLED_MESSAGE: &'static str = "Operation cancelled";
+    /// Error of the images skipped through `ImageCancelSignals::cancel`
+    pub const SKIPPED_BY_USER_MESSAGE: &'static str = "Skipped by user";
+    /// Error of the images left unprocessed once `abort_after_failures` is reached
+    pub const ABORTED_MESSAGE: &'static str = "aborted due to failure threshold";
+    /// Images a dry run encodes in memory to estimate the output sizes
+    pub const DRY_RUN_SAMPLE_SIZE: usize = 3;
+
     /// Create a new batch processor
     pub fn new() -> Self {
         Self {
             max_threads: None,
+            processor: Arc::new(ImageProcessorImpl::new()),
+            image_cancel_signals: ImageCancelSignals::default(),
         }
     }
 
@@ -55,209 +330,2544 @@ impl BatchProcessor {
     pub fn with_threads(max_threads: usize) -> Self {
         Self {
             max_threads: Some(max_threads),
+            ..Self::new()
         }
     }
 
+    /// Share a decoded-image cache with batches whose settings enable `use_cache`
+    pub fn with_image_cache(mut self, cache: Arc<Mutex<ImageCache>>) -> Self {
+        self.processor = Arc::new(ImageProcessorImpl::new().with_image_cache(cache));
+        self
+    }
+
+    /// Process with `processor` instead of a default one (e.g. one shared with the caller)
+    pub fn with_processor(mut self, processor: Arc<ImageProcessorImpl>) -> Self {
+        self.processor = processor;
+        self
+    }
+
+    /// Skip single images through `signals` (e.g. shared with the caller)
+    pub fn with_image_cancel_signals(mut self, signals: ImageCancelSignals) -> Self {
+        self.image_cancel_signals = signals;
+        self
+    }
+
+    /// Processor the batches run on
+    pub fn processor(&self) -> &Arc<ImageProcessorImpl> {
+        &self.processor
+    }
+
+    /// The `n` results with the longest `processing_time_ms`, slowest first
+    pub fn slowest_images(results: &[ProcessingResult], n: usize) -> Vec<&ProcessingResult> {
+        let mut slowest: Vec<&ProcessingResult> = results.iter().collect();
+        slowest.sort_by(|a, b| b.processing_time_ms.cmp(&a.processing_time_ms));
+        slowest.truncate(n);
+        slowest
+    }
+
     /// Process multiple images in parallel
     pub fn process_batch(
         &self,
         images: Vec<Image>,
         transformation: Option<Transformation>,
-        settings: ProcessingSettings,
+        mut settings: ProcessingSettings,
         cancel_signal: Arc<AtomicBool>,
         progress_callback: Option<ProgressCallback>,
     ) -> Vec<ProcessingResult> {
+        Self::resolve_source_base(&mut settings, images.iter().map(Image::path));
+
+        if settings.dry_run() {
+            return self.dry_run_batch(
+                &images,
+                transformation.as_ref(),
+                &settings,
+                &cancel_signal,
+                progress_callback,
+            );
+        }
+
+        if let OutputMode::Zip(archive_path) = settings.output_mode().clone() {
+            return self.process_batch_to_zip(
+                images,
+                transformation,
+                settings,
+                &archive_path,
+                cancel_signal,
+                progress_callback,
+            );
+        }
+
+        // Una señal por imagen, en el orden del lote
+        let image_cancel_signals = self.image_cancel_signals.reset(&images);
+
         // Prevent OpenMP thread oversubscription: Rayon handles image-level parallelism,
         // LibRaw's OpenMP should use 1 thread per image instance.
         std::env::set_var("OMP_NUM_THREADS", "1");
 
-        let total = images.len();
-        let counter = Arc::new(AtomicUsize::new(0));
+        // El progreso cuenta salidas: una por tamaño y formato pedidos
+        let total: usize = images
+            .iter()
+            .map(|img| {
+                let image_settings = settings.settings_for(img.path());
+                image_settings.determine_output_variants(img.format()).len()
+            })
+            .sum();
+        let tracker = Mutex::new(ProgressTracker::new(Instant::now(), total));
+        // Fallos seguidos: con el disco de salida desmontado fallaría todo lo que queda
+        let consecutive_failures = AtomicUsize::new(0);
+        let aborted = AtomicBool::new(false);
+        // Modo incremental: qué entrada y qué ajustes produjeron cada salida, con un
+        // manifiesto por carpeta de salida (varias si se procesa in situ)
+        let manifests = settings
+            .skip_unchanged()
+            .then(|| Mutex::new(HashMap::<PathBuf, ProcessingManifest>::new()));
 
-        // Configurar pool de threads si se especificó
-        let pool = if let Some(threads) = self.max_threads {
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(threads)
-                .build()
-                .ok()
-        } else {
-            None
-        };
+        // Configurar pool de threads: primero lo que piden los settings, luego el
+        // del procesador; sin ninguno, el pool global de rayon
+        let pool = settings
+            .max_workers()
+            .or(self.max_threads)
+            .and_then(|threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .ok()
+            });
 
         // Función para procesar cada imagen
-        let process_one = |img: &Image| -> ProcessingResult {
-            // Verificar señal de cancelación
-            if cancel_signal.load(Ordering::SeqCst) {
-                return ProcessingResult {
-                    original_path: img.path().to_path_buf(),
-                    output_path: PathBuf::new(),
-                    original_size: img.size_bytes(),
-                    output_size: 0,
-                    success: false,
-                    error_message: Some("Operation cancelled".to_string()),
-                };
+        let process_one = |(index, img): (usize, &Image)| -> Vec<ProcessingResult> {
+            let fail_all = |message: &str| -> Vec<ProcessingResult> {
+                settings
+                    .settings_for(img.path())
+                    .determine_output_variants(img.format())
+                    .iter()
+                    .map(|_| {
+                        ProcessingResult::failed(
+                            img.path().to_path_buf(),
+                            img.size_bytes(),
+                            message.to_string(),
+                        )
+                    })
+                    .collect()
+            };
+            let skip_signal = &image_cancel_signals[index];
+
+            // Verificar señales de cancelación: primero la de la imagen
+            let skipped_by_user = skip_signal.load(Ordering::SeqCst);
+            if !skipped_by_user && cancel_signal.load(Ordering::SeqCst) {
+                return fail_all(if aborted.load(Ordering::SeqCst) {
+                    Self::ABORTED_MESSAGE
+                } else {
+                    Self::CANCELLED_MESSAGE
+                });
             }
 
-            let result = self.process_single_image(img, transformation.as_ref(), &settings);
+            let image_settings = settings.settings_for(img.path());
+            let output_dir = image_settings.output_directory_for(img.path()).into_owned();
+            let settings_hash = manifests.as_ref().map(|_| {
+                ProcessingManifest::settings_hash(&image_settings, transformation.as_ref())
+            });
+            // Salidas desactualizadas que el manifiesto atribuye a esta misma entrada: se
+            // regeneran en su sitio; cualquier otro archivo existente es una colisión
+            let (unchanged, replaceable) = match manifests.as_ref().zip(settings_hash.as_deref()) {
+                Some((manifests, settings_hash)) => {
+                    let mut manifests = manifests.lock();
+                    let manifest = manifests
+                        .entry(output_dir.clone())
+                        .or_insert_with_key(|dir| ProcessingManifest::load(dir));
+                    (
+                        Self::unchanged_results(img, &image_settings, manifest, settings_hash),
+                        Self::recorded_outputs(img, &image_settings, manifest),
+                    )
+                }
+                None => (None, Vec::new()),
+            };
+
+            let results = match unchanged {
+                _ if skipped_by_user => fail_all(Self::SKIPPED_BY_USER_MESSAGE),
+                // Todas las salidas al día: ni se decodifica
+                Some(results) => results,
+                None => {
+                    let started = Instant::now();
+                    let mut results = self.process_single_image(
+                        img,
+                        transformation.as_ref(),
+                        &settings,
+                        &cancel_signal,
+                        skip_signal,
+                        &replaceable,
+                    );
+                    let elapsed_ms = started.elapsed().as_millis() as u64;
+                    for result in &mut results {
+                        result.processing_time_ms = elapsed_ms;
+                    }
+                    if let (Some(manifests), Some(settings_hash)) = (&manifests, settings_hash) {
+                        let mut manifests = manifests.lock();
+                        let manifest = manifests
+                            .entry(output_dir)
+                            .or_insert_with_key(|dir| ProcessingManifest::load(dir));
+                        Self::record_outputs(img, &results, manifest, settings_hash);
+                    }
+                    results
+                }
+            };
+
+            // Una imagen con alguna salida correcta reinicia la cuenta; una saltada
+            // por el usuario no cuenta
+            let skipped_by_user = results.iter().any(|result| {
+                result.error_message.as_deref() == Some(Self::SKIPPED_BY_USER_MESSAGE)
+            });
+            if let Some(threshold) = settings.abort_after_failures().filter(|_| !skipped_by_user) {
+                if !results.is_empty() && results.iter().all(|result| !result.success) {
+                    let failures = consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+                    // El motivo lo informa BatchSummary::stop_reason
+                    if failures >= threshold && !aborted.swap(true, Ordering::SeqCst) {
+                        cancel_signal.store(true, Ordering::SeqCst);
+                    }
+                } else {
+                    consecutive_failures.store(0, Ordering::SeqCst);
+                }
+            }
 
-            // Actualizar progreso
-            let count = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            // Actualizar progreso (el callback se llama fuera del lock)
+            let file_name = img.file_name().unwrap_or("unknown");
+            let progress =
+                tracker
+                    .lock()
+                    .record(Instant::now(), results.len(), img.size_bytes(), file_name);
             if let Some(ref callback) = progress_callback {
-                let file_name = img.file_name().unwrap_or("unknown");
-                callback(count, total, file_name);
+                callback(&progress, &results);
             }
 
-            result
+            results
         };
 
         // Procesar en paralelo
-        if let Some(pool) = pool {
-            pool.install(|| images.par_iter().map(process_one).collect())
+        let mut results: Vec<ProcessingResult> = if let Some(pool) = pool {
+            pool.install(|| {
+                images
+                    .par_iter()
+                    .enumerate()
+                    .flat_map_iter(process_one)
+                    .collect()
+            })
         } else {
-            images.par_iter().map(process_one).collect()
+            images
+                .par_iter()
+                .enumerate()
+                .flat_map_iter(process_one)
+                .collect()
+        };
+
+        for (output_dir, manifest) in manifests.map(Mutex::into_inner).unwrap_or_default() {
+            if let Err(e) = manifest.save(&output_dir) {
+                let message = e.to_string();
+                for result in results.iter_mut().filter(|result| {
+                    settings
+                        .settings_for(&result.original_path)
+                        .output_directory_for(&result.original_path)
+                        == output_dir
+                }) {
+                    result.manifest_error = Some(message.clone());
+                }
+            }
         }
+        results
     }
 
-    /// Process a single image
-    fn process_single_image(
-        &self,
+    /// Skipped results for an image whose every output is up to date according to
+    /// `manifest` (None = some output is missing or stale: process the image)
+    fn unchanged_results(
         image: &Image,
-        transformation: Option<&Transformation>,
         settings: &ProcessingSettings,
-    ) -> ProcessingResult {
-        let original_path = image.path().to_path_buf();
-        let original_size = image.size_bytes();
+        manifest: &ProcessingManifest,
+        settings_hash: &str,
+    ) -> Option<Vec<ProcessingResult>> {
+        settings
+            .determine_output_variants(image.format())
+            .into_iter()
+            .map(|(width, format)| {
+                let output_path =
+                    Self::output_path_for(image.path(), format, width, settings).ok()?;
+                if !manifest.is_up_to_date(image.path(), &output_path, settings_hash) {
+                    return None;
+                }
+                let output_size = std::fs::metadata(&output_path).map_or(0, |m| m.len());
+                Some(ProcessingResult {
+                    original_path: image.path().to_path_buf(),
+                    output_path,
+                    original_size: image.size_bytes(),
+                    output_size,
+                    success: true,
+                    skipped: true,
+                    unchanged: true,
+                    target_width: width,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
 
-        // Crear procesador para este thread (stateless, barato de crear)
-        let processor = ImageProcessorImpl::new();
+    /// Outputs of `image` whose manifest entry names `image` as their input
+    fn recorded_outputs(
+        image: &Image,
+        settings: &ProcessingSettings,
+        manifest: &ProcessingManifest,
+    ) -> Vec<PathBuf> {
+        settings
+            .determine_output_variants(image.format())
+            .into_iter()
+            .filter_map(|(width, format)| {
+                Self::output_path_for(image.path(), format, width, settings).ok()
+            })
+            .filter(|output| {
+                manifest
+                    .entry(output)
+                    .is_some_and(|entry| entry.input_path == image.path())
+            })
+            .collect()
+    }
 
-        // Determinar ruta de salida
-        let output_path = match self.determine_output_path(image, settings) {
-            Ok(path) => path,
-            Err(e) => {
-                return ProcessingResult {
-                    original_path,
-                    output_path: PathBuf::new(),
-                    original_size,
-                    output_size: 0,
-                    success: false,
-                    error_message: Some(e.to_string()),
-                };
-            }
+    /// Record the outputs an image just wrote in the incremental-mode manifest
+    fn record_outputs(
+        image: &Image,
+        results: &[ProcessingResult],
+        manifest: &mut ProcessingManifest,
+        settings_hash: String,
+    ) {
+        let mut written = results
+            .iter()
+            .filter(|result| result.success && !result.output_path.as_os_str().is_empty())
+            .peekable();
+        if written.peek().is_none() {
+            return;
+        }
+        let Ok(input_hash) = ProcessingManifest::content_hash(image.path()) else {
+            return;
         };
+        for result in written {
+            manifest.record(
+                &result.output_path,
+                ManifestEntry {
+                    input_path: image.path().to_path_buf(),
+                    input_hash: input_hash.clone(),
+                    settings_hash: settings_hash.clone(),
+                },
+            );
+        }
+    }
 
-        // Procesar imagen
-        match processor.process(image, transformation, settings) {
-            Ok(data) => {
-                let output_size = data.len() as u64;
+    /// Plan every output without decoding or writing: where it would go after the
+    /// collision strategy, or why it would fail. The first `DRY_RUN_SAMPLE_SIZE`
+    /// images are encoded in memory and their compression extrapolated to the rest.
+    fn dry_run_batch(
+        &self,
+        images: &[Image],
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+        cancel_signal: &AtomicBool,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Vec<ProcessingResult> {
+        let total: usize = images
+            .iter()
+            .map(|img| {
+                let image_settings = settings.settings_for(img.path());
+                image_settings.determine_output_variants(img.format()).len()
+            })
+            .sum();
+        let mut tracker = ProgressTracker::new(Instant::now(), total);
+        // Rutas ya planeadas: dos entradas con la misma salida chocan como en un lote real
+        let mut planned: HashSet<PathBuf> = HashSet::new();
+        let (mut sampled_in, mut sampled_out) = (0u64, 0u64);
+        let mut results = Vec::new();
 
-                // Guardar archivo
-                match processor.save_image(
-                    &data,
-                    &output_path,
-                    settings.determine_output_format(image.format()),
-                ) {
-                    Ok(_) => ProcessingResult {
-                        original_path,
-                        output_path,
-                        original_size,
-                        output_size,
-                        success: true,
-                        error_message: None,
+        for (index, image) in images.iter().enumerate() {
+            if cancel_signal.load(Ordering::SeqCst) {
+                break;
+            }
+            let settings = settings.settings_for(image.path());
+            let original_path = image.path().to_path_buf();
+            let original_size = image.size_bytes();
+            let planned_result = |output_path: PathBuf, width: Option<u32>| ProcessingResult {
+                original_path: original_path.clone(),
+                output_path,
+                original_size,
+                success: true,
+                target_width: width,
+                dry_run: true,
+                ..Default::default()
+            };
+
+            let mut image_results = Vec::new();
+            let mut variants = Vec::new();
+            for (width, format) in settings.determine_output_variants(image.format()) {
+                let output_path = Self::output_path_for(image.path(), format, width, &settings)
+                    .and_then(|path| {
+                        Self::resolve_collision_with(path, &settings, &mut |candidate| {
+                            Ok(!candidate.exists() && planned.insert(candidate.to_path_buf()))
+                        })
+                    });
+                image_results.push(match output_path {
+                    Ok(Some(output_path)) => {
+                        variants.push((width, format));
+                        planned_result(output_path, width)
+                    }
+                    Ok(None) => ProcessingResult {
+                        skipped: true,
+                        ..planned_result(PathBuf::new(), width)
                     },
                     Err(e) => ProcessingResult {
-                        original_path,
-                        output_path: PathBuf::new(),
-                        original_size,
-                        output_size: 0,
-                        success: false,
-                        error_message: Some(format!("Failed to save: {}", e)),
+                        dry_run: true,
+                        ..ProcessingResult::failed(
+                            original_path.clone(),
+                            original_size,
+                            e.to_string(),
+                        )
+                        .with_error_code(&e)
                     },
+                });
+            }
+
+            // Muestra: codificar en memoria, sin guardar
+            if index < Self::DRY_RUN_SAMPLE_SIZE && !variants.is_empty() {
+                let (outputs, attempts) = Self::with_retries(&settings, cancel_signal, || {
+                    self.processor
+                        .process_variants(image, transformation, &settings, &variants)
+                });
+                let outputs = match outputs {
+                    Ok(outputs) => outputs,
+                    Err(e) => variants.iter().map(|_| Err(e.clone())).collect(),
+                };
+                let planned_outputs = image_results
+                    .iter_mut()
+                    .filter(|result| result.success && !result.skipped);
+                for (result, output) in planned_outputs.zip(outputs) {
+                    result.attempts = attempts;
+                    match output {
+                        Ok(encoded) => {
+                            let size = encoded.data.len() as u64;
+                            result.estimated_size = Some(size);
+                            sampled_in += original_size;
+                            sampled_out += size;
+                        }
+                        Err(e) => {
+                            result.success = false;
+                            result.error_message = Some(format!("Processing failed: {}", e));
+                            result.error_code = Some(e.code().to_string());
+                        }
+                    }
                 }
             }
-            Err(e) => ProcessingResult {
-                original_path,
-                output_path: PathBuf::new(),
+
+            let validation_warning = ImageValidator::validate(image.path()).warning();
+            for result in &mut image_results {
+                result.validation_warning = validation_warning.clone();
+            }
+
+            let file_name = image.file_name().unwrap_or("unknown");
+            let progress = tracker.record(
+                Instant::now(),
+                image_results.len(),
                 original_size,
-                output_size: 0,
-                success: false,
-                error_message: Some(format!("Processing failed: {}", e)),
-            },
+                file_name,
+            );
+            if let Some(ref callback) = progress_callback {
+                callback(&progress, &image_results);
+            }
+            results.extend(image_results);
         }
+
+        // Extrapolar la compresión de la muestra al resto
+        if sampled_in > 0 {
+            let ratio = sampled_out as f64 / sampled_in as f64;
+            for result in &mut results {
+                if result.success && !result.skipped && result.estimated_size.is_none() {
+                    result.estimated_size =
+                        Some((result.original_size as f64 * ratio).round() as u64);
+                }
+            }
+        }
+        results
     }
 
-    /// Determine output file path
-    fn determine_output_path(
+    /// Discover, load and process every image in a folder.
+    /// `max_depth` works like `FileHandler::discover_images_with_depth` (Some(1) = no subfolders).
+    pub fn process_folder(
         &self,
-        image: &Image,
-        settings: &ProcessingSettings,
-    ) -> DomainResult<PathBuf> {
-        let output_format = settings.determine_output_format(image.format());
-        let file_stem = image
-            .file_stem()
-            .ok_or_else(|| DomainError::InvalidFilePath("No file name".to_string()))?;
+        dir: &Path,
+        max_depth: Option<usize>,
+        transformation: Option<Transformation>,
+        mut settings: ProcessingSettings,
+        cancel_signal: Arc<AtomicBool>,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Vec<ProcessingResult> {
+        // La estructura se replica desde la carpeta elegida
+        if settings.preserve_directory_structure() && settings.source_base().is_none() {
+            settings.set_source_base(Some(dir.to_path_buf()));
+        }
+        let paths = FileHandler::discover_images_with_depth(dir, max_depth);
+        self.process_paths(
+            paths,
+            transformation,
+            settings,
+            cancel_signal,
+            progress_callback,
+        )
+    }
 
-        let output_filename = format!("{}.{}", file_stem, output_format.extension());
-        let output_path = settings.output_directory().join(output_filename);
+    /// Load and process image files by path. Files whose outputs all exist already are
+    /// skipped unless overwriting; files that fail to load are reported as failures.
+    pub fn process_paths(
+        &self,
+        mut paths: Vec<PathBuf>,
+        transformation: Option<Transformation>,
+        mut settings: ProcessingSettings,
+        cancel_signal: Arc<AtomicBool>,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Vec<ProcessingResult> {
+        // Con todas las rutas: las descartadas abajo también cuentan para la base
+        Self::resolve_source_base(&mut settings, paths.iter().map(PathBuf::as_path));
+
+        // Descartar antes de cargar las imágenes cuyas salidas ya existen todas
+        // (salvo que se vayan a renombrar o regenerar)
+        if !Self::replaces_existing(&settings)
+            && !settings.skip_unchanged()
+            && *settings.output_mode() == OutputMode::Files
+            && !matches!(settings.collision_strategy(), CollisionStrategy::Rename(_))
+        {
+            paths.retain(|path| {
+                Self::output_paths_for(path, &settings).map_or(true, |outputs| {
+                    outputs.iter().any(|output| !output.exists())
+                })
+            });
+        }
+
+        let mut images = Vec::with_capacity(paths.len());
+        let mut load_failures = Vec::new();
 
-        // Verificar si el archivo existe y no queremos sobrescribir
-        if output_path.exists() && !settings.overwrite_existing() {
-            return Err(DomainError::InvalidFilePath(format!(
-                "File already exists: {}",
-                output_path.display()
-            )));
+        for path in paths {
+            if cancel_signal.load(Ordering::SeqCst) {
+                break;
+            }
+            match self.processor.load_image_for_settings(&path, &settings) {
+                Ok(image) => images.push(image),
+                Err(e) => {
+                    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    load_failures.push(
+                        ProcessingResult::failed(path, size, format!("Failed to load: {}", e))
+                            .with_error_code(&e),
+                    );
+                }
+            }
         }
 
-        Ok(output_path)
+        let mut results = self.process_batch(
+            images,
+            transformation,
+            settings,
+            cancel_signal,
+            progress_callback,
+        );
+        results.extend(load_failures);
+        results
     }
 
-    /// Get optimal number of threads for processing
-    pub fn optimal_thread_count() -> usize {
-        // Usar número de CPUs disponibles
-        rayon::current_num_threads()
+    /// Process into a staging directory, streaming the outputs of each image into one
+    /// zip archive as soon as it's done: encoding stays parallel, archive writes are
+    /// serialized. The archive is finalized even when the batch is cancelled, with
+    /// whatever completed. Successful results point at their entry (`export.zip!/a.jpg`).
+    fn process_batch_to_zip(
+        &self,
+        images: Vec<Image>,
+        transformation: Option<Transformation>,
+        mut settings: ProcessingSettings,
+        archive_path: &Path,
+        cancel_signal: Arc<AtomicBool>,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Vec<ProcessingResult> {
+        let fail_all = |images: &[Image], message: String| -> Vec<ProcessingResult> {
+            images
+                .iter()
+                .map(|img| {
+                    ProcessingResult::failed(
+                        img.path().to_path_buf(),
+                        img.size_bytes(),
+                        message.clone(),
+                    )
+                })
+                .collect()
+        };
+
+        if archive_path.exists() && !settings.overwrite_existing() {
+            return fail_all(
+                &images,
+                format!("File already exists: {}", archive_path.display()),
+            );
+        }
+
+        let staging = std::env::temp_dir().join(format!(
+            "transform_images-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos())
+        ));
+        if let Err(e) = std::fs::create_dir_all(&staging) {
+            return fail_all(
+                &images,
+                format!("Failed to create staging directory: {}", e),
+            );
+        }
+        let exporter = match ZipExporter::create(archive_path) {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&staging);
+                return fail_all(&images, format!("Failed to write zip: {}", e));
+            }
+        };
+
+        // Con estructura: carpetas relativas a la carpeta común de las entradas
+        let root =
+            (!settings.flatten_zip()).then(|| Self::common_root(images.iter().map(Image::path)));
+        let naming = settings.clone();
+        let post_action = settings.post_action().clone();
+        // Nombre de entrada por (original, archivo temporal); CopyOriginal puede
+        // repetir el mismo archivo para varios formatos
+        let archive = Arc::new(Mutex::new(ZipStream {
+            exporter: Some(exporter),
+            entries: HashMap::new(),
+        }));
+
+        let stream_callback: ProgressCallback = {
+            let archive = Arc::clone(&archive);
+            let archive_path = archive_path.to_path_buf();
+            Arc::new(move |progress, results| {
+                let results: Vec<ProcessingResult> = {
+                    let mut archive = archive.lock();
+                    results
+                        .iter()
+                        .cloned()
+                        .map(|result| archive.add(result, &archive_path, &naming, root.as_deref()))
+                        .collect()
+                };
+                if let Some(ref callback) = progress_callback {
+                    callback(progress, &results);
+                }
+            })
+        };
+
+        // Nombres temporales siempre distintos: varias entradas pueden coincidir
+        settings
+            .set_output_directory(staging.clone())
+            .set_output_mode(OutputMode::Files)
+            .set_overwrite_existing(false)
+            .set_skip_unchanged(false)
+            .set_collision_strategy(CollisionStrategy::Rename(RenameMode::Sequential))
+            .set_post_action(PostAction::Keep);
+        let results = self.process_batch(
+            images,
+            transformation,
+            settings,
+            cancel_signal,
+            Some(stream_callback),
+        );
+
+        let mut archive = archive.lock();
+        let finished = archive.exporter.take().map_or(Ok(()), ZipExporter::finish);
+        let mut results: Vec<ProcessingResult> = results
+            .into_iter()
+            .map(|result| match finished {
+                Ok(()) => archive.resolve(result, archive_path),
+                Err(ref e) if result.success && !result.output_path.as_os_str().is_empty() => {
+                    ProcessingResult::failed(
+                        result.original_path,
+                        result.original_size,
+                        format!("Failed to write zip: {}", e),
+                    )
+                }
+                Err(_) => result,
+            })
+            .collect();
+        drop(archive);
+
+        if finished.is_ok() {
+            // "export.zip!/entry" no es un componente de ruta: comparar como texto
+            let entry_prefix = ZipExporter::entry_path(archive_path, "");
+            let entry_prefix = entry_prefix.to_string_lossy();
+            let in_archive = |output: &Path| {
+                output.to_string_lossy().starts_with(entry_prefix.as_ref())
+                    && std::fs::metadata(archive_path).is_ok_and(|metadata| metadata.len() > 0)
+            };
+            let mut by_original: HashMap<PathBuf, Vec<&mut ProcessingResult>> = HashMap::new();
+            for result in results.iter_mut() {
+                by_original
+                    .entry(result.original_path.clone())
+                    .or_default()
+                    .push(result);
+            }
+            for (original, mut outputs) in by_original {
+                Self::apply_post_action(&post_action, &original, &mut outputs, &in_archive);
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&staging);
+        results
     }
-}
 
-impl Default for BatchProcessor {
-    fn default() -> Self {
-        Self::new()
+    /// Deepest folder that contains every input
+    fn common_root<'a>(paths: impl IntoIterator<Item = &'a Path>) -> PathBuf {
+        let mut parents = paths.into_iter().filter_map(Path::parent);
+        let Some(first) = parents.next() else {
+            return PathBuf::new();
+        };
+        parents.fold(first.to_path_buf(), |root, parent| {
+            root.ancestors()
+                .find(|ancestor| parent.starts_with(ancestor))
+                .unwrap_or(Path::new(""))
+                .to_path_buf()
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// When mirroring the directory structure without a `source_base`, use the
+    /// deepest folder that contains every input
+    fn resolve_source_base<'a>(
+        settings: &mut ProcessingSettings,
+        paths: impl IntoIterator<Item = &'a Path>,
+    ) {
+        if settings.preserve_directory_structure() && settings.source_base().is_none() {
+            settings.set_source_base(Some(Self::common_root(paths)));
+        }
+    }
 
-    #[test]
-    fn test_create_batch_processor() {
-        let processor = BatchProcessor::new();
-        assert!(processor.max_threads.is_none());
+    /// Archive entry for an output: the file name it would get in the output
+    /// directory (not its staging name), under its input's folder relative to `root`
+    /// when keeping the structure
+    fn archive_entry_name(
+        result: &ProcessingResult,
+        settings: &ProcessingSettings,
+        root: Option<&Path>,
+    ) -> DomainResult<String> {
+        let original = &result.original_path;
+        let file_name = if result.skipped {
+            // Copia del original
+            original.file_name().map(PathBuf::from)
+        } else {
+            let format = ImageFormat::from_extension(
+                result
+                    .output_path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or(""),
+            )?;
+            let settings = settings.settings_for(original);
+            Self::output_path_for(original, format, result.target_width, &settings)?
+                .file_name()
+                .map(PathBuf::from)
+        }
+        .ok_or_else(|| DomainError::InvalidFilePath("No file name".to_string()))?;
+
+        let folder = root
+            .and_then(|root| original.parent()?.strip_prefix(root).ok())
+            .unwrap_or(Path::new(""));
+        Ok(ZipExporter::to_entry_name(&folder.join(file_name)))
     }
 
-    #[test]
-    fn test_create_with_threads() {
-        let processor = BatchProcessor::with_threads(4);
-        assert_eq!(processor.max_threads, Some(4));
+    /// Process a single image into every size and output format (one result per output).
+    /// `skip_signal` drops it (nothing is written) without touching the rest of the batch.
+    fn process_single_image(
+        &self,
+        image: &Image,
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+        cancel_signal: &AtomicBool,
+        skip_signal: &AtomicBool,
+        replaceable: &[PathBuf],
+    ) -> Vec<ProcessingResult> {
+        // Validar antes de decodificar: solo avisa, no aborta
+        let validation_warning = ImageValidator::validate(image.path()).warning();
+
+        // Ajustes propios de esta imagen, si los hay
+        let settings = settings.settings_for(image.path());
+        let mut results = self.process_outputs(
+            image,
+            transformation,
+            &settings,
+            cancel_signal,
+            skip_signal,
+            replaceable,
+        );
+        if validation_warning.is_some() {
+            for result in &mut results {
+                result.validation_warning = validation_warning.clone();
+            }
+        }
+        // En modo zip se aplica cuando el archivo ya está escrito
+        if *settings.output_mode() == OutputMode::Files {
+            let mut outputs: Vec<&mut ProcessingResult> = results.iter_mut().collect();
+            let on_disk = |output: &Path| {
+                std::fs::metadata(output)
+                    .is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+                    && !Self::is_same_file(output, image.path())
+            };
+            Self::apply_post_action(settings.post_action(), image.path(), &mut outputs, &on_disk);
+        }
+        results
     }
 
-    #[test]
-    fn test_optimal_thread_count() {
-        let count = BatchProcessor::optimal_thread_count();
-        assert!(count > 0);
+    /// Carry out `action` on `original` once every one of its outputs is verifiably
+    /// written: successful, not skipped, and `is_written` (by default on disk with a
+    /// nonzero size, and not the original itself). The outcome is recorded on each output.
+    fn apply_post_action(
+        action: &PostAction,
+        original: &Path,
+        outputs: &mut [&mut ProcessingResult],
+        is_written: &dyn Fn(&Path) -> bool,
+    ) {
+        if *action == PostAction::Keep || outputs.is_empty() {
+            return;
+        }
+        let all_written = outputs.iter().all(|result| {
+            result.success && !result.skipped && !result.dry_run && is_written(&result.output_path)
+        });
+        if !all_written {
+            return;
+        }
+
+        let outcome = match action {
+            PostAction::Keep => return,
+            PostAction::MoveToTrash => {
+                FileHandler::move_to_trash(original).map(|()| "trashed".to_string())
+            }
+            PostAction::Delete => std::fs::remove_file(original)
+                .map(|()| "deleted".to_string())
+                .map_err(InfraError::from),
+            PostAction::MoveTo(directory) => {
+                let destination = directory.join(original.file_name().unwrap_or_default());
+                FileHandler::move_file(original, &destination)
+                    .map(|()| format!("moved to {}", destination.display()))
+            }
+        };
+        let outcome = outcome.unwrap_or_else(|e| format!("failed: {}", e));
+        for result in outputs.iter_mut() {
+            result.post_action = Some(outcome.clone());
+        }
     }
 
-    #[test]
-    fn test_compression_ratio() {
-        let result = ProcessingResult {
-            original_path: PathBuf::from("test.png"),
-            output_path: PathBuf::from("out.png"),
-            original_size: 1000,
-            output_size: 500,
-            success: true,
-            error_message: None,
+    /// Run `operation` again, up to `max_retries` times, while it fails with a transient
+    /// IO error reading the input or writing the output (network shares sometimes
+    /// answer EBUSY/EAGAIN at first). Decode errors and a full disk are not retried:
+    /// they won't get better. Stops once `cancel_signal` is raised.
+    /// Also returns the number of attempts used.
+    fn with_retries<T>(
+        settings: &ProcessingSettings,
+        cancel_signal: &AtomicBool,
+        mut operation: impl FnMut() -> DomainResult<T>,
+    ) -> (DomainResult<T>, u32) {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match operation() {
+                // Cancelado durante la espera: no se reintenta
+                Err(DomainError::ReadFailed(_) | DomainError::WriteFailed(_))
+                    if cancel_signal.load(Ordering::SeqCst) =>
+                {
+                    return (Err(DomainError::Cancelled), attempts);
+                }
+                Err(DomainError::ReadFailed(_) | DomainError::WriteFailed(_))
+                    if attempts <= settings.max_retries() =>
+                {
+                    std::thread::sleep(Duration::from_millis(settings.retry_delay_ms()));
+                }
+                result => return (result, attempts),
+            }
+        }
+    }
+
+    /// Decode once and write every output of a single image
+    fn process_outputs(
+        &self,
+        image: &Image,
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+        cancel_signal: &AtomicBool,
+        skip_signal: &AtomicBool,
+        replaceable: &[PathBuf],
+    ) -> Vec<ProcessingResult> {
+        let original_path = image.path().to_path_buf();
+        let original_size = image.size_bytes();
+        let upscale_prevented = transformation
+            .and_then(|t| t.resize())
+            .is_some_and(|resize| resize.upscale_prevented(image.dimensions()));
+        let failed = |message: String| {
+            ProcessingResult::failed(original_path.clone(), original_size, message)
         };
 
-        assert_eq!(result.compression_ratio(), 50.0);
-        assert_eq!(result.bytes_saved(), 500);
+        // Determinar rutas de salida (sufijo de tamaño y extensión por variante)
+        let mut results = Vec::new();
+        let mut pending = Vec::new();
+        for (width, format) in settings.determine_output_variants(image.format()) {
+            match self.determine_output_path(image, format, width, settings, replaceable) {
+                Ok(Some(path)) => pending.push(((width, format), path)),
+                // Ya existe y se deja como está
+                Ok(None) => results.push(ProcessingResult {
+                    original_path: original_path.clone(),
+                    output_path: PathBuf::new(),
+                    original_size,
+                    output_size: original_size,
+                    success: true,
+                    skipped: true,
+                    target_width: width,
+                    ..Default::default()
+                }),
+                Err(e) => results.push(failed(e.to_string()).with_error_code(&e)),
+            }
+        }
+        if pending.is_empty() {
+            return results;
+        }
+
+        // Decodificar y transformar una sola vez para todas las variantes
+        let variants: Vec<(Option<u32>, ImageFormat)> =
+            pending.iter().map(|(variant, _)| *variant).collect();
+        let (outputs, attempts) = Self::with_retries(settings, cancel_signal, || {
+            self.processor
+                .process_variants(image, transformation, settings, &variants)
+        });
+        // Todas las salidas de aquí en adelante comparten los intentos de lectura
+        let decoded_from = results.len();
+        // Saltada mientras se decodificaba: no se escribe nada
+        let outputs = match outputs {
+            Ok(_) if skip_signal.load(Ordering::SeqCst) => Err(DomainError::Cancelled),
+            outputs => outputs,
+        };
+        let outputs = match outputs {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                for (_, output_path) in &pending {
+                    Self::release_reserved(output_path, settings);
+                }
+                // Cancelada a mitad de decodificación: no es un fallo de la imagen
+                let message = match &e {
+                    DomainError::Cancelled if skip_signal.load(Ordering::SeqCst) => {
+                        Self::SKIPPED_BY_USER_MESSAGE.to_string()
+                    }
+                    DomainError::Cancelled => "cancelled".to_string(),
+                    e => format!("Processing failed: {}", e),
+                };
+                results.extend(pending.iter().map(|_| ProcessingResult {
+                    attempts,
+                    ..failed(message.clone()).with_error_code(&e)
+                }));
+                return results;
+            }
+        };
+
+        let mut copied_original: Option<ProcessingResult> = None;
+        for (((width, format), output_path), output) in pending.into_iter().zip(outputs) {
+            let encoded = match output {
+                Ok(encoded) => encoded,
+                Err(e) => {
+                    Self::release_reserved(&output_path, settings);
+                    results.push(failed(format!("Processing failed: {}", e)).with_error_code(&e));
+                    continue;
+                }
+            };
+            let data = &encoded.data;
+            let output_size = data.len() as u64;
+
+            // Verificar si la compresión vale la pena
+            let larger = settings.skip_if_larger() && output_size > original_size;
+            if larger || !Self::meets_min_compression(original_size, output_size, settings) {
+                Self::release_reserved(&output_path, settings);
+                match settings.on_no_improvement() {
+                    SkipStrategy::CopyOriginal => {
+                        // El original se copia una sola vez aunque varios formatos fallen
+                        let mut copy = copied_original
+                            .get_or_insert_with(|| self.copy_original(image, settings))
+                            .clone();
+                        copy.skipped_larger = larger && copy.skipped;
+                        results.push(copy);
+                        continue;
+                    }
+                    // Una salida mayor que el original nunca se guarda con skip_if_larger
+                    SkipStrategy::SaveAnyway if !larger => {}
+                    SkipStrategy::Skip | SkipStrategy::SaveAnyway => {
+                        results.push(ProcessingResult {
+                            original_path: original_path.clone(),
+                            output_path: PathBuf::new(),
+                            original_size,
+                            output_size: original_size,
+                            success: true,
+                            skipped: true,
+                            skipped_larger: larger,
+                            target_width: width,
+                            ..Default::default()
+                        });
+                        continue;
+                    }
+                }
+            }
+
+            // La salida reemplaza al original: copia de seguridad antes de escribir
+            if settings.create_backup() && Self::is_same_file(&output_path, &original_path) {
+                if let Err(e) = FileHandler::back_up(&original_path) {
+                    results.push(failed(format!("Failed to back up the original: {}", e)));
+                    continue;
+                }
+            }
+
+            // Guardar archivo (reintentando como la lectura)
+            let started = Instant::now();
+            let (saved, save_attempts) = Self::with_retries(settings, cancel_signal, || {
+                self.processor.save_image(data, &output_path, format)
+            });
+            let timings = settings.phase_timings().then(|| PhaseTimings {
+                write_ms: PhaseTimings::since(started),
+                ..encoded.timings
+            });
+            results.push(match saved {
+                Ok(_) => ProcessingResult {
+                    original_path: original_path.clone(),
+                    output_path,
+                    original_size,
+                    output_size,
+                    success: true,
+                    upscale_prevented,
+                    target_width: width,
+                    final_quality: encoded.quality,
+                    target_met: encoded.target_met,
+                    timings,
+                    attempts: attempts + save_attempts - 1,
+                    ..Default::default()
+                },
+                Err(e) => {
+                    Self::release_reserved(&output_path, settings);
+                    ProcessingResult {
+                        attempts: attempts + save_attempts - 1,
+                        ..failed(format!("Failed to save: {}", e)).with_error_code(&e)
+                    }
+                }
+            });
+        }
+
+        // Las salidas que reintentaron la escritura ya cuentan esos intentos
+        for result in &mut results[decoded_from..] {
+            result.attempts = result.attempts.max(attempts);
+        }
+        results
+    }
+
+    /// Whether `a` and `b` name the same file (compared resolved when both exist)
+    fn is_same_file(a: &Path, b: &Path) -> bool {
+        a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+    }
+
+    /// Check if the encoded output is small enough to be worth writing
+    fn meets_min_compression(
+        original_size: u64,
+        output_size: u64,
+        settings: &ProcessingSettings,
+    ) -> bool {
+        let Some(min_ratio) = settings.min_compression_ratio() else {
+            return true;
+        };
+        if original_size == 0 {
+            return true;
+        }
+        let saved = original_size.saturating_sub(output_size) as f64 / original_size as f64;
+        saved >= min_ratio
+    }
+
+    /// Copy the original file unchanged into the output directory
+    fn copy_original(&self, image: &Image, settings: &ProcessingSettings) -> ProcessingResult {
+        let original_path = image.path().to_path_buf();
+        let original_size = image.size_bytes();
+
+        let Some(file_name) = image.file_name() else {
+            return ProcessingResult::failed(original_path, original_size, "No file name".into());
+        };
+        let output_dir = settings.output_directory_for(image.path());
+        let mut output_path = output_dir.join(file_name);
+
+        if output_path == original_path {
+            // El original ya está en el directorio de salida
+        } else {
+            output_path = match Self::resolve_collision(output_path, settings) {
+                Ok(Some(path)) => path,
+                Ok(None) => {
+                    return ProcessingResult {
+                        original_path,
+                        original_size,
+                        output_size: original_size,
+                        success: true,
+                        skipped: true,
+                        ..Default::default()
+                    }
+                }
+                Err(e) => {
+                    return ProcessingResult::failed(original_path, original_size, e.to_string())
+                        .with_error_code(&e)
+                }
+            };
+            if let Err(e) = std::fs::create_dir_all(&output_dir)
+                .and_then(|_| std::fs::copy(&original_path, &output_path))
+            {
+                Self::release_reserved(&output_path, settings);
+                return ProcessingResult::failed(
+                    original_path,
+                    original_size,
+                    format!("Failed to copy original: {}", e),
+                );
+            }
+        }
+
+        ProcessingResult {
+            original_path,
+            output_path,
+            original_size,
+            output_size: original_size,
+            success: true,
+            skipped: true,
+            ..Default::default()
+        }
+    }
+
+    /// Determine output file path for one output format and size
+    /// (None = the file exists and the collision strategy is to skip it).
+    /// Outputs in `replaceable` are written over as they are.
+    fn determine_output_path(
+        &self,
+        image: &Image,
+        output_format: ImageFormat,
+        width: Option<u32>,
+        settings: &ProcessingSettings,
+        replaceable: &[PathBuf],
+    ) -> DomainResult<Option<PathBuf>> {
+        let output_path = Self::output_path_for(image.path(), output_format, width, settings)?;
+        if replaceable.contains(&output_path) {
+            return Ok(Some(output_path));
+        }
+        Self::resolve_collision(output_path, settings)
+    }
+
+    /// Apply the collision strategy when the output exists and overwriting is off.
+    /// The chosen name is reserved by creating it empty (create-new semantics), so
+    /// parallel workers with the same output (`IMG_0001.ARW` and `IMG_0001.CR2`)
+    /// never both get it; see `release_reserved` for outputs that end up unwritten.
+    fn resolve_collision(
+        output_path: PathBuf,
+        settings: &ProcessingSettings,
+    ) -> DomainResult<Option<PathBuf>> {
+        Self::resolve_collision_with(output_path, settings, &mut Self::reserve)
+    }
+
+    /// `resolve_collision` with `reserve` deciding whether a candidate name is free
+    /// (and claiming it); a dry run only checks, without creating anything
+    fn resolve_collision_with(
+        output_path: PathBuf,
+        settings: &ProcessingSettings,
+        reserve: &mut dyn FnMut(&Path) -> DomainResult<bool>,
+    ) -> DomainResult<Option<PathBuf>> {
+        if Self::replaces_existing(settings) || reserve(&output_path)? {
+            return Ok(Some(output_path));
+        }
+
+        let already_exists = |path: &Path| {
+            DomainError::InvalidFilePath(format!("File already exists: {}", path.display()))
+        };
+        let mode = match settings.collision_strategy() {
+            CollisionStrategy::Error => return Err(already_exists(&output_path)),
+            CollisionStrategy::Skip => return Ok(None),
+            CollisionStrategy::Rename(mode) => mode,
+        };
+
+        if mode == RenameMode::Timestamp {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            let candidate = Self::with_name_suffix(&output_path, timestamp);
+            return if reserve(&candidate)? {
+                Ok(Some(candidate))
+            } else {
+                Err(already_exists(&candidate))
+            };
+        }
+
+        for n in 1..=Self::MAX_RENAME_CANDIDATES {
+            let candidate = match mode {
+                RenameMode::Numbered => Self::with_copy_number(&output_path, n),
+                _ => Self::with_name_suffix(&output_path, n),
+            };
+            if reserve(&candidate)? {
+                return Ok(Some(candidate));
+            }
+        }
+        Err(DomainError::InvalidFilePath(format!(
+            "No free file name for {} after {} attempts",
+            output_path.display(),
+            Self::MAX_RENAME_CANDIDATES
+        )))
+    }
+
+    /// Whether existing outputs are written over (in incremental mode, only the
+    /// outputs the manifest records for the same input are)
+    fn replaces_existing(settings: &ProcessingSettings) -> bool {
+        settings.overwrite_existing()
+    }
+
+    /// Create `path` empty unless it exists (false), creating its directory if needed
+    fn reserve(path: &Path) -> DomainResult<bool> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| DomainError::InvalidFilePath(e.to_string()))?;
+        }
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(DomainError::InvalidFilePath(format!(
+                "Failed to create {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Remove the empty file `resolve_collision` reserved for an output that won't be written
+    fn release_reserved(path: &Path, settings: &ProcessingSettings) {
+        if !Self::replaces_existing(settings)
+            && std::fs::metadata(path).is_ok_and(|metadata| metadata.len() == 0)
+        {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// `dir/photo.jpg` -> `dir/photo (<n>).jpg`
+    fn with_copy_number(path: &Path, n: u32) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let file_name = match path.extension() {
+            Some(extension) => format!("{} ({}).{}", stem, n, extension.to_string_lossy()),
+            None => format!("{} ({})", stem, n),
+        };
+        path.with_file_name(file_name)
+    }
+
+    /// `dir/photo.jpg` -> `dir/photo_<suffix>.jpg`
+    fn with_name_suffix(path: &Path, suffix: impl std::fmt::Display) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let file_name = match path.extension() {
+            Some(extension) => format!("{}_{}.{}", stem, suffix, extension.to_string_lossy()),
+            None => format!("{}_{}", stem, suffix),
+        };
+        path.with_file_name(file_name)
+    }
+
+    /// Output paths for an input file, one per size and output format
+    fn output_paths_for(path: &Path, settings: &ProcessingSettings) -> DomainResult<Vec<PathBuf>> {
+        // Como al cargar: el contenido manda sobre la extensión
+        let input_format = match FileHandler::sniff_format(path) {
+            Some(format) => format,
+            None => ImageFormat::from_extension(
+                path.extension().and_then(|e| e.to_str()).unwrap_or(""),
+            )?,
+        };
+        let settings = settings.settings_for(path);
+        settings
+            .determine_output_variants(input_format)
+            .into_iter()
+            .map(|(width, format)| Self::output_path_for(path, format, width, &settings))
+            .collect()
+    }
+
+    /// Output path for an input file: `<output_directory>/<stem><size suffix>.<output extension>`,
+    /// next to the input when processing in place
+    fn output_path_for(
+        path: &Path,
+        output_format: ImageFormat,
+        width: Option<u32>,
+        settings: &ProcessingSettings,
+    ) -> DomainResult<PathBuf> {
+        let file_stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| DomainError::InvalidFilePath("No file name".to_string()))?;
+
+        let suffix = width.map_or(String::new(), |w| settings.size_suffix(w));
+        let output_filename = format!("{}{}.{}", file_stem, suffix, output_format.extension());
+        Ok(settings.output_directory_for(path).join(output_filename))
+    }
+
+    /// Get optimal number of threads for processing
+    pub fn optimal_thread_count() -> usize {
+        // Usar número de CPUs disponibles
+        rayon::current_num_threads()
+    }
+}
+
+impl Default for BatchProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Dimensions, Quality};
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_create_batch_processor() {
+        let processor = BatchProcessor::new();
+        assert!(processor.max_threads.is_none());
+    }
+
+    #[test]
+    fn test_create_with_threads() {
+        let processor = BatchProcessor::with_threads(4);
+        assert_eq!(processor.max_threads, Some(4));
+    }
+
+    #[test]
+    fn test_batches_share_the_injected_processor() {
+        let shared = Arc::new(ImageProcessorImpl::new());
+        let batch = BatchProcessor::with_threads(2).with_processor(shared.clone());
+        assert!(Arc::ptr_eq(batch.processor(), &shared));
+        assert_eq!(batch.max_threads, Some(2));
+
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = input.path().join(format!("photo_{}.png", i));
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                path
+            })
+            .collect();
+
+        let settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        let results = batch.process_paths(
+            paths,
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.success));
+        // Ninguna copia retenida por los workers al terminar
+        assert_eq!(Arc::strong_count(&shared), 2);
+    }
+
+    #[test]
+    fn test_dry_run_plans_without_writing() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..5)
+            .map(|i| {
+                let path = input.path().join(format!("photo_{}.png", i));
+                image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([x as u8 * 4, y as u8 * 4, 0]))
+                    .save(&path)
+                    .unwrap();
+                path
+            })
+            .collect();
+        // Una salida ya existe: con Rename se planea otro nombre
+        std::fs::write(output.path().join("photo_4.png"), b"existing").unwrap();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings
+            .set_dry_run(true)
+            .set_collision_strategy(CollisionStrategy::Rename(RenameMode::Numbered));
+        let mut results = BatchProcessor::new().process_paths(
+            paths,
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+        results.sort_by(|a, b| a.original_path.cmp(&b.original_path));
+
+        assert_eq!(results.len(), 5);
+        assert!(results
+            .iter()
+            .all(|r| r.success && r.dry_run && r.output_size == 0));
+        assert!(results.iter().all(|r| r.estimated_size.is_some()));
+        assert_eq!(
+            results[4].output_path,
+            output.path().join("photo_4 (1).png")
+        );
+        // Solo las primeras imágenes se codifican en memoria
+        let sampled = results.iter().filter(|r| r.attempts == 1).count();
+        assert_eq!(sampled, BatchProcessor::DRY_RUN_SAMPLE_SIZE);
+
+        // Nada escrito ni reservado
+        let written: Vec<_> = std::fs::read_dir(output.path()).unwrap().collect();
+        assert_eq!(written.len(), 1);
+    }
+
+    #[test]
+    fn test_skip_unchanged_processes_only_what_changed() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = input.path().join(format!("photo_{}.png", i));
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                path
+            })
+            .collect();
+        let run = |quality: u8| {
+            let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+            settings
+                .set_skip_unchanged(true)
+                .set_quality(Quality::new(quality).unwrap());
+            let mut results = BatchProcessor::new().process_paths(
+                paths.clone(),
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            );
+            results.sort_by(|a, b| a.original_path.cmp(&b.original_path));
+            results
+        };
+
+        let first = run(80);
+        assert!(first
+            .iter()
+            .all(|r| r.success && !r.skipped && !r.unchanged));
+        assert!(output.path().join(ProcessingManifest::FILE_NAME).exists());
+
+        let second = run(80);
+        assert!(second.iter().all(|r| r.success && r.skipped && r.unchanged));
+        assert_eq!(second[0].output_path, first[0].output_path);
+
+        // Una entrada cambiada se regenera en su sitio, sin renombrar
+        image::RgbImage::from_pixel(8, 8, image::Rgb([255, 0, 0]))
+            .save(&paths[1])
+            .unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&paths[1])
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        let third = run(80);
+        assert!(third[0].unchanged && third[2].unchanged);
+        assert!(third[1].success && !third[1].unchanged);
+        assert_eq!(third[1].output_path, first[1].output_path);
+
+        // Otros ajustes: todo se regenera
+        let fourth = run(60);
+        assert!(fourth.iter().all(|r| r.success && !r.unchanged));
+    }
+
+    #[test]
+    fn test_skip_unchanged_keeps_unrelated_existing_outputs() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        // Un archivo del usuario que el manifiesto no conoce
+        let existing = output.path().join("photo.png");
+        std::fs::write(&existing, b"mine").unwrap();
+
+        let run = |strategy: CollisionStrategy| {
+            let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+            settings
+                .set_skip_unchanged(true)
+                .set_collision_strategy(strategy);
+            BatchProcessor::new().process_paths(
+                vec![path.clone()],
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            )
+        };
+
+        let results = run(CollisionStrategy::Error);
+        assert!(!results[0].success);
+        assert_eq!(std::fs::read(&existing).unwrap(), b"mine");
+
+        let results = run(CollisionStrategy::Rename(RenameMode::Numbered));
+        assert!(results[0].success);
+        assert_eq!(results[0].output_path, output.path().join("photo (1).png"));
+        assert_eq!(std::fs::read(&existing).unwrap(), b"mine");
+    }
+
+    #[test]
+    fn test_skip_unchanged_reports_unsaved_manifest() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        // Un directorio donde va el manifiesto impide guardarlo
+        std::fs::create_dir(output.path().join(ProcessingManifest::FILE_NAME)).unwrap();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_skip_unchanged(true);
+        let results = BatchProcessor::new().process_paths(
+            vec![path],
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+        assert!(results[0].success);
+        assert!(results[0].manifest_error.is_some());
+    }
+
+    #[test]
+    fn test_preserve_directory_structure() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = ["2023/a.png", "2024/trip/a.png"]
+            .iter()
+            .map(|relative| {
+                let path = input.path().join("library").join(relative);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                path
+            })
+            .collect();
+        let run = |source_base: Option<PathBuf>| {
+            let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+            settings
+                .set_preserve_directory_structure(true)
+                .set_source_base(source_base)
+                .set_overwrite_existing(true);
+            let mut results = BatchProcessor::new().process_paths(
+                paths.clone(),
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            );
+            results.sort_by(|a, b| a.original_path.cmp(&b.original_path));
+            results
+                .into_iter()
+                .map(|r| {
+                    assert!(r.success, "{:?}", r.error_message);
+                    r.output_path
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Sin base: la carpeta común de las entradas ("library")
+        assert_eq!(
+            run(None),
+            vec![
+                output.path().join("2023/a.png"),
+                output.path().join("2024/trip/a.png"),
+            ]
+        );
+        assert_eq!(
+            run(Some(input.path().to_path_buf())),
+            vec![
+                output.path().join("library/2023/a.png"),
+                output.path().join("library/2024/trip/a.png"),
+            ]
+        );
+        assert!(output.path().join("library/2024/trip/a.png").exists());
+    }
+
+    #[test]
+    fn test_in_place_backs_up_overwritten_originals() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+        let paths = vec![dir.path().join("a.png"), nested.join("b.png")];
+        for path in &paths {
+            image::RgbImage::new(8, 8).save(path).unwrap();
+        }
+        let original = std::fs::read(&paths[1]).unwrap();
+        let run = |paths: Vec<PathBuf>| {
+            let mut settings = ProcessingSettings::with_directory(PathBuf::new());
+            settings
+                .set_overwrite_existing(true)
+                .set_create_backup(true);
+            BatchProcessor::new().process_paths(
+                paths,
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            )
+        };
+
+        let results = run(paths.clone());
+        assert!(results.iter().all(|r| r.success));
+        for path in &paths {
+            assert!(results.iter().any(|r| &r.output_path == path));
+            assert!(FileHandler::backup_path(path).exists());
+        }
+        assert_eq!(
+            std::fs::read(FileHandler::backup_path(&paths[1])).unwrap(),
+            original
+        );
+
+        // Otra pasada recursiva no recoge las copias de seguridad
+        let rediscovered = FileHandler::discover_images_with_depth(dir.path(), None);
+        assert_eq!(rediscovered.len(), 2);
+        let results = run(rediscovered);
+        assert_eq!(results.len(), 2);
+        assert!(!nested.join("b.orig.orig.png").exists());
+    }
+
+    #[test]
+    fn test_post_action_only_after_written_outputs() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let new_input = |name: &str| {
+            let path = input.path().join(name);
+            image::RgbImage::new(8, 8).save(&path).unwrap();
+            path
+        };
+        let run = |path: &PathBuf, directory: &Path, action: PostAction| {
+            let mut settings = ProcessingSettings::with_directory(directory.to_path_buf());
+            settings
+                .set_overwrite_existing(true)
+                .set_post_action(action);
+            BatchProcessor::new().process_paths(
+                vec![path.clone()],
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            )
+        };
+
+        let deleted = new_input("deleted.png");
+        let results = run(&deleted, output.path(), PostAction::Delete);
+        assert!(results[0].success);
+        assert_eq!(results[0].post_action.as_deref(), Some("deleted"));
+        assert!(!deleted.exists());
+        assert!(results[0].output_path.exists());
+
+        let moved = new_input("moved.png");
+        let done = input.path().join("done");
+        let results = run(&moved, output.path(), PostAction::MoveTo(done.clone()));
+        assert!(results[0]
+            .post_action
+            .as_deref()
+            .unwrap()
+            .starts_with("moved to"));
+        assert!(!moved.exists());
+        assert!(done.join("moved.png").exists());
+
+        // En modo zip, una vez escrito el archivo
+        let zipped = new_input("zipped.png");
+        let archive_path = output.path().join("export.zip");
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings
+            .set_output_mode(OutputMode::Zip(archive_path.clone()))
+            .set_post_action(PostAction::Delete);
+        let results = BatchProcessor::new().process_paths(
+            vec![zipped.clone()],
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+        assert!(results[0].success);
+        assert_eq!(
+            results[0].output_path,
+            ZipExporter::entry_path(&archive_path, "zipped.png")
+        );
+        assert_eq!(results[0].post_action.as_deref(), Some("deleted"));
+        assert!(!zipped.exists());
+
+        // La salida reemplazó al original: no se toca
+        let in_place = new_input("in_place.png");
+        let results = run(&in_place, input.path(), PostAction::Delete);
+        assert!(results[0].success);
+        assert_eq!(results[0].output_path, in_place);
+        assert_eq!(results[0].post_action, None);
+        assert!(in_place.exists());
+
+        // Un fallo deja el original en su sitio
+        let corrupt = input.path().join("corrupt.png");
+        std::fs::write(&corrupt, b"not a png").unwrap();
+        let results = run(&corrupt, output.path(), PostAction::Delete);
+        assert!(!results[0].success);
+        assert_eq!(results[0].post_action, None);
+        assert!(corrupt.exists());
+    }
+
+    #[test]
+    fn test_optimal_thread_count() {
+        let count = BatchProcessor::optimal_thread_count();
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn test_compression_ratio() {
+        let result = ProcessingResult {
+            original_path: PathBuf::from("test.png"),
+            output_path: PathBuf::from("out.png"),
+            original_size: 1000,
+            output_size: 500,
+            success: true,
+            ..Default::default()
+        };
+
+        assert_eq!(result.compression_ratio(), 50.0);
+        assert_eq!(result.bytes_saved(), 500);
+    }
+
+    #[test]
+    fn test_meets_min_compression() {
+        let mut settings = ProcessingSettings::default();
+        assert!(BatchProcessor::meets_min_compression(1000, 1200, &settings));
+
+        settings.set_min_compression_ratio(Some(0.05));
+        assert!(BatchProcessor::meets_min_compression(1000, 900, &settings));
+        assert!(BatchProcessor::meets_min_compression(1000, 950, &settings));
+        assert!(!BatchProcessor::meets_min_compression(1000, 960, &settings));
+        assert!(!BatchProcessor::meets_min_compression(
+            1000, 1200, &settings
+        ));
+    }
+
+    #[test]
+    fn test_output_paths_for() {
+        let mut settings = ProcessingSettings::with_directory(PathBuf::from("/out"));
+        assert_eq!(
+            BatchProcessor::output_paths_for(Path::new("/in/photo.png"), &settings).unwrap(),
+            vec![PathBuf::from("/out/photo.png")]
+        );
+
+        settings.set_output_format(Some(ImageFormat::Webp));
+        assert_eq!(
+            BatchProcessor::output_paths_for(Path::new("/in/photo.png"), &settings).unwrap(),
+            vec![PathBuf::from("/out/photo.webp")]
+        );
+
+        settings.set_output_formats(vec![ImageFormat::Jpeg, ImageFormat::Webp]);
+        assert_eq!(
+            BatchProcessor::output_paths_for(Path::new("/in/photo.png"), &settings).unwrap(),
+            vec![
+                PathBuf::from("/out/photo.jpg"),
+                PathBuf::from("/out/photo.webp")
+            ]
+        );
+
+        settings
+            .set_output_format(Some(ImageFormat::Webp))
+            .set_sizes(vec![320, 640]);
+        assert_eq!(
+            BatchProcessor::output_paths_for(Path::new("/in/photo.png"), &settings).unwrap(),
+            vec![
+                PathBuf::from("/out/photo-320w.webp"),
+                PathBuf::from("/out/photo-640w.webp")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_folder_skips_existing_outputs() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+
+        for name in ["a.png", "b.png"] {
+            image::RgbImage::new(8, 8)
+                .save(input.path().join(name))
+                .unwrap();
+        }
+        std::fs::write(output.path().join("a.png"), b"existing").unwrap();
+
+        let settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        let results = BatchProcessor::new().process_folder(
+            input.path(),
+            Some(1),
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].original_path.ends_with("b.png"));
+        assert_eq!(
+            std::fs::read(output.path().join("a.png")).unwrap(),
+            b"existing"
+        );
+    }
+
+    #[test]
+    fn test_skip_if_larger() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        // Ruido guardado como JPEG: el PNG sin pérdida siempre ocupa más
+        let path = input.path().join("noise.jpg");
+        image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * 37 + y * 91) as u8, (x * y) as u8, (x ^ y) as u8 * 4])
+        })
+        .save(&path)
+        .unwrap();
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings
+            .set_output_format(Some(ImageFormat::Png))
+            .set_skip_if_larger(true);
+        let processor = BatchProcessor::new();
+
+        let results = processor.process_single_image(
+            &image,
+            None,
+            &settings,
+            &AtomicBool::new(false),
+            &AtomicBool::new(false),
+            &[],
+        );
+        assert!(results[0].success && results[0].skipped && results[0].skipped_larger);
+        assert_eq!(results[0].compression_ratio(), 0.0);
+        assert!(!output.path().join("noise.png").exists());
+
+        settings.set_on_no_improvement(SkipStrategy::CopyOriginal);
+        let results = processor.process_single_image(
+            &image,
+            None,
+            &settings,
+            &AtomicBool::new(false),
+            &AtomicBool::new(false),
+            &[],
+        );
+        assert!(results[0].skipped_larger);
+        assert_eq!(
+            std::fs::read(output.path().join("noise.jpg")).unwrap(),
+            std::fs::read(&path).unwrap()
+        );
+
+        settings.set_skip_if_larger(false);
+        let results = processor.process_single_image(
+            &image,
+            None,
+            &settings,
+            &AtomicBool::new(false),
+            &AtomicBool::new(false),
+            &[],
+        );
+        assert!(!results[0].skipped && results[0].output_size > results[0].original_size);
+    }
+
+    #[test]
+    fn test_collision_strategies() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+
+        let existing = output.path().join("photo.png");
+        std::fs::write(&existing, b"existing").unwrap();
+        std::fs::write(output.path().join("photo_1.png"), b"existing").unwrap();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        let processor = BatchProcessor::new();
+        let outputs = |settings: &ProcessingSettings| {
+            processor.process_single_image(
+                &image,
+                None,
+                settings,
+                &AtomicBool::new(false),
+                &AtomicBool::new(false),
+                &[],
+            )
+        };
+
+        let results = outputs(&settings);
+        assert!(!results[0].success);
+        assert!(results[0]
+            .error_message
+            .as_ref()
+            .unwrap()
+            .contains("already exists"));
+
+        settings.set_collision_strategy(CollisionStrategy::Skip);
+        let results = outputs(&settings);
+        assert!(results[0].success && results[0].skipped);
+
+        settings.set_collision_strategy(CollisionStrategy::Rename(RenameMode::Sequential));
+        let results = outputs(&settings);
+        assert!(results[0].success);
+        assert_eq!(results[0].output_path, output.path().join("photo_2.png"));
+
+        settings.set_collision_strategy(CollisionStrategy::Rename(RenameMode::Timestamp));
+        let results = outputs(&settings);
+        let name = results[0]
+            .output_path
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(name.strip_prefix("photo_").unwrap().parse::<u64>().is_ok());
+
+        settings.set_collision_strategy(CollisionStrategy::Rename(RenameMode::Numbered));
+        for expected in ["photo (1).png", "photo (2).png"] {
+            let results = outputs(&settings);
+            assert!(results[0].success);
+            assert_eq!(results[0].output_path, output.path().join(expected));
+        }
+
+        assert_eq!(std::fs::read(&existing).unwrap(), b"existing");
+    }
+
+    #[test]
+    fn test_parallel_outputs_with_same_name_get_distinct_paths() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let processor = ImageProcessorImpl::new();
+        // Mismo nombre de salida desde varias entradas procesadas a la vez
+        let images: Vec<Image> = (0..8)
+            .map(|i| {
+                let dir = input.path().join(format!("card{}", i));
+                std::fs::create_dir(&dir).unwrap();
+                let path = dir.join("IMG_0001.png");
+                image::RgbImage::from_pixel(8, 8, image::Rgb([i as u8, 0, 0]))
+                    .save(&path)
+                    .unwrap();
+                processor.load_image(&path).unwrap()
+            })
+            .collect();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_collision_strategy(CollisionStrategy::Rename(RenameMode::Numbered));
+        let results = BatchProcessor::with_threads(4).process_batch(
+            images,
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+
+        let outputs: HashSet<PathBuf> = results.iter().map(|r| r.output_path.clone()).collect();
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(outputs.len(), 8);
+        assert!(outputs.contains(&output.path().join("IMG_0001.png")));
+        assert!(outputs.contains(&output.path().join("IMG_0001 (7).png")));
+        assert!(outputs
+            .iter()
+            .all(|path| std::fs::metadata(path).unwrap().len() > 0));
+    }
+
+    #[test]
+    fn test_unwritten_output_releases_reserved_name() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+
+        // Nunca se alcanza: la salida se omite y no debe quedar un archivo vacío
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_min_compression_ratio(Some(1.0));
+        let results = BatchProcessor::new().process_single_image(
+            &image,
+            None,
+            &settings,
+            &AtomicBool::new(false),
+            &AtomicBool::new(false),
+            &[],
+        );
+
+        assert!(results[0].skipped);
+        assert!(!output.path().join("photo.png").exists());
+    }
+
+    #[test]
+    fn test_process_batch_writes_every_output_format() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+
+        let processor = ImageProcessorImpl::new();
+        let image = processor.load_image(&path).unwrap();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_output_formats(vec![ImageFormat::Jpeg, ImageFormat::Webp]);
+
+        let progress = Arc::new(AtomicUsize::new(0));
+        let progress_total = Arc::new(AtomicUsize::new(0));
+        let (count, total) = (progress.clone(), progress_total.clone());
+        let streamed = Arc::new(AtomicUsize::new(0));
+        let streamed_count = streamed.clone();
+        let callback: ProgressCallback = Arc::new(move |progress, results| {
+            streamed_count.fetch_add(results.len(), Ordering::SeqCst);
+            count.store(progress.current, Ordering::SeqCst);
+            total.store(progress.total, Ordering::SeqCst);
+        });
+
+        let results = BatchProcessor::new().process_batch(
+            vec![image],
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            Some(callback),
+        );
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.success));
+        assert!(output.path().join("photo.jpg").exists());
+        assert!(output.path().join("photo.webp").exists());
+        assert_eq!(progress.load(Ordering::SeqCst), 2);
+        assert_eq!(progress_total.load(Ordering::SeqCst), 2);
+        assert_eq!(streamed.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_max_workers_from_settings_runs_sequentially() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let processor = ImageProcessorImpl::new();
+        let images: Vec<Image> = (0..6)
+            .map(|i| {
+                let path = input.path().join(format!("photo_{}.png", i));
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                processor.load_image(&path).unwrap()
+            })
+            .collect();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_max_workers(Some(1));
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let callback: ProgressCallback = Arc::new(move |progress, _results| {
+            recorded
+                .lock()
+                .push((std::thread::current().id(), progress.current));
+        });
+
+        // Los settings mandan sobre los threads del procesador
+        let results = BatchProcessor::with_threads(4).process_batch(
+            images,
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            Some(callback),
+        );
+
+        assert!(results.iter().all(|r| r.success));
+        let calls = calls.lock();
+        let counts: Vec<usize> = calls.iter().map(|&(_, current)| current).collect();
+        assert_eq!(counts, (1..=6).collect::<Vec<_>>());
+        let threads: HashSet<_> = calls.iter().map(|&(thread, _)| thread).collect();
+        assert_eq!(threads.len(), 1);
+    }
+
+    #[test]
+    fn test_process_batch_to_zip() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let processor = ImageProcessorImpl::new();
+        let images = ["a.png", "b.png"]
+            .iter()
+            .map(|name| {
+                let path = input.path().join(name);
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                processor.load_image(&path).unwrap()
+            })
+            .collect();
+
+        let archive_path = output.path().join("export.zip");
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_output_mode(OutputMode::Zip(archive_path.clone()));
+
+        let results = BatchProcessor::new().process_batch(
+            images,
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(
+            results[0].output_path,
+            ZipExporter::entry_path(&archive_path, "a.png")
+        );
+        assert_eq!(
+            results[1].output_path,
+            ZipExporter::entry_path(&archive_path, "b.png")
+        );
+        // Sin archivos sueltos en el directorio de salida
+        assert_eq!(std::fs::read_dir(output.path()).unwrap().count(), 1);
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["a.png", "b.png"]);
+    }
+
+    #[test]
+    fn test_process_batch_to_zip_preserves_structure() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let processor = ImageProcessorImpl::new();
+        let images = ["2023/a.png", "2024/trip/a.png"]
+            .iter()
+            .map(|name| {
+                let path = input.path().join(name);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                processor.load_image(&path).unwrap()
+            })
+            .collect();
+
+        let archive_path = output.path().join("export.zip");
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings
+            .set_output_mode(OutputMode::Zip(archive_path.clone()))
+            .set_output_format(Some(ImageFormat::Jpeg))
+            .set_flatten_zip(false);
+
+        let results = BatchProcessor::new().process_batch(
+            images,
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(
+            results[1].output_path,
+            ZipExporter::entry_path(&archive_path, "2024/trip/a.jpg")
+        );
+        let archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["2023/a.jpg", "2024/trip/a.jpg"]);
+    }
+
+    #[test]
+    fn test_cancelled_zip_batch_still_finishes_archive() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("a.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+
+        let archive_path = output.path().join("export.zip");
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_output_mode(OutputMode::Zip(archive_path.clone()));
+
+        let results = BatchProcessor::new().process_batch(
+            vec![image],
+            None,
+            settings,
+            Arc::new(AtomicBool::new(true)),
+            None,
+        );
+
+        assert!(results.iter().all(|r| !r.success));
+        // Archivo válido aunque vacío
+        let archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 0);
+    }
+
+    #[test]
+    fn test_process_batch_writes_every_size() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(400, 200).save(&path).unwrap();
+
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_sizes(vec![100, 300, 800]);
+
+        let results = BatchProcessor::new().process_batch(
+            vec![image],
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.success));
+        for (result, width, expected) in [
+            (&results[0], 100, (100, 50)),
+            (&results[1], 300, (300, 150)),
+            // Nunca se amplía por encima del original
+            (&results[2], 800, (400, 200)),
+        ] {
+            assert_eq!(result.target_width, Some(width));
+            assert_eq!(
+                result.output_path,
+                output.path().join(format!("photo-{}w.png", width))
+            );
+            assert_eq!(
+                image::image_dimensions(&result.output_path).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_phase_timings_only_when_enabled() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(64, 64).save(&path).unwrap();
+
+        let run = |phase_timings: bool| {
+            let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+            let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+            settings
+                .set_output_formats(vec![ImageFormat::Jpeg, ImageFormat::Webp])
+                .set_overwrite_existing(true)
+                .set_phase_timings(phase_timings);
+            BatchProcessor::new().process_batch(
+                vec![image],
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            )
+        };
+
+        assert!(run(false).iter().all(|r| r.success && r.timings.is_none()));
+
+        let results = run(true);
+        assert!(results.iter().all(|r| r.success));
+        let timings: Vec<PhaseTimings> = results.iter().filter_map(|r| r.timings).collect();
+        assert_eq!(timings.len(), 2);
+        // Una sola decodificación para ambos formatos
+        assert_eq!(timings[0].decode_ms, timings[1].decode_ms);
+        assert_eq!(timings[0].transform_ms, timings[1].transform_ms);
+    }
+
+    #[test]
+    fn test_process_batch_reports_validation_warning() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        // PNG con extensión .jpg: se procesa igualmente, con aviso
+        let path = input.path().join("photo.jpg");
+        image::RgbImage::new(8, 8)
+            .save_with_format(&path, image::ImageFormat::Png)
+            .unwrap();
+
+        let image = Image::new(
+            path,
+            ImageFormat::Jpeg,
+            crate::domain::Dimensions::new(8, 8).unwrap(),
+            1,
+            None,
+        )
+        .unwrap();
+        let settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+
+        let results = BatchProcessor::new().process_batch(
+            vec![image],
+            None,
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        );
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0]
+            .validation_warning
+            .as_deref()
+            .is_some_and(|w| w.contains("png")));
+    }
+
+    #[test]
+    fn test_abort_after_consecutive_failures() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let images: Vec<Image> = (0..5)
+            .map(|i| {
+                let path = input.path().join(format!("photo_{}.png", i));
+                image::RgbImage::new(8, 8).save(&path).unwrap();
+                let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+                // Sin el archivo, cada imagen falla al leerse
+                std::fs::remove_file(&path).unwrap();
+                image
+            })
+            .collect();
+
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_abort_after_failures(Some(2));
+        let cancel_signal = Arc::new(AtomicBool::new(false));
+        let results = BatchProcessor::with_threads(1).process_batch(
+            images,
+            None,
+            settings,
+            cancel_signal.clone(),
+            None,
+        );
+
+        assert_eq!(results.len(), 5);
+        assert!(cancel_signal.load(Ordering::SeqCst));
+        let aborted = results
+            .iter()
+            .filter(|r| r.error_message.as_deref() == Some(BatchProcessor::ABORTED_MESSAGE))
+            .count();
+        assert_eq!(aborted, 3);
+    }
+
+    #[test]
+    fn test_slowest_images() {
+        let result = |path: &str, processing_time_ms| ProcessingResult {
+            original_path: PathBuf::from(path),
+            processing_time_ms,
+            ..Default::default()
+        };
+        let results = vec![result("a", 20), result("b", 50), result("c", 5)];
+
+        let slowest = BatchProcessor::slowest_images(&results, 2);
+
+        assert_eq!(slowest.len(), 2);
+        assert_eq!(slowest[0].original_path, PathBuf::from("b"));
+        assert_eq!(slowest[1].original_path, PathBuf::from("a"));
+        assert_eq!(BatchProcessor::slowest_images(&results, 10).len(), 3);
+    }
+
+    #[test]
+    fn test_cancel_single_image_keeps_the_batch_going() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        // La primera ruta no carga: el lote tiene una imagen menos que la petición
+        let corrupt = input.path().join("corrupt.png");
+        std::fs::write(&corrupt, b"not a png").unwrap();
+        let mut paths = vec![corrupt.clone()];
+        paths.extend((0..3).map(|i| {
+            let path = input.path().join(format!("photo_{}.png", i));
+            image::RgbImage::new(8, 8).save(&path).unwrap();
+            path
+        }));
+
+        // Al terminar la primera imagen se salta la segunda
+        let signals = ImageCancelSignals::default();
+        let callback: ProgressCallback = {
+            let signals = signals.clone();
+            let (skipped, corrupt) = (paths[2].clone(), corrupt.clone());
+            Arc::new(move |progress, _| {
+                if progress.current == 1 {
+                    assert!(signals.cancel(&skipped));
+                    assert!(!signals.cancel(&corrupt));
+                }
+            })
+        };
+        let cancel_signal = Arc::new(AtomicBool::new(false));
+        let results = BatchProcessor::with_threads(1)
+            .with_image_cancel_signals(signals)
+            .process_paths(
+                paths.clone(),
+                None,
+                ProcessingSettings::with_directory(output.path().to_path_buf()),
+                cancel_signal.clone(),
+                Some(callback),
+            );
+
+        let result_for = |path: &PathBuf| {
+            results
+                .iter()
+                .find(|result| &result.original_path == path)
+                .unwrap()
+        };
+        assert!(!result_for(&corrupt).success);
+        assert!(result_for(&paths[1]).success && result_for(&paths[3]).success);
+        assert_eq!(
+            result_for(&paths[2]).error_message.as_deref(),
+            Some(BatchProcessor::SKIPPED_BY_USER_MESSAGE)
+        );
+        assert!(!output.path().join("photo_1.png").exists());
+        assert!(output.path().join("photo_2.png").exists());
+        assert!(!cancel_signal.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_retries_transient_read_failures() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("share.png");
+        let away = input.path().join("share.png.moving");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+
+        let run = |max_retries: u32| {
+            // El archivo desaparece un momento, como un recurso de red ocupado
+            std::fs::rename(&path, &away).unwrap();
+            let (from, to) = (away.clone(), path.clone());
+            let restore = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                std::fs::rename(from, to).unwrap();
+            });
+
+            let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+            settings
+                .set_overwrite_existing(true)
+                .set_max_retries(max_retries)
+                .set_retry_delay_ms(25);
+            let results = BatchProcessor::new().process_batch(
+                vec![image.clone()],
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            );
+            restore.join().unwrap();
+            results
+        };
+
+        let without_retries = run(0);
+        assert!(!without_retries[0].success);
+        assert!(without_retries[0]
+            .error_message
+            .as_deref()
+            .is_some_and(|message| message.contains("Read failed")));
+        assert_eq!(without_retries[0].attempts, 1);
+
+        let with_retries = run(20);
+        assert!(
+            with_retries[0].success,
+            "{:?}",
+            with_retries[0].error_message
+        );
+        assert!(with_retries[0].output_path.exists());
+        assert!(with_retries[0].attempts > 1);
+    }
+
+    #[test]
+    fn test_retries_transient_write_failures() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let path = input.path().join("photo.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+        let image = ImageProcessorImpl::new().load_image(&path).unwrap();
+
+        let run = |max_retries: u32| {
+            // Un directorio ocupa el temporal de la escritura atómica un momento
+            let blocker = output.path().join("photo.png.tmp");
+            std::fs::create_dir(&blocker).unwrap();
+            let unblock = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                std::fs::remove_dir(blocker).unwrap();
+            });
+
+            let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+            settings
+                .set_overwrite_existing(true)
+                .set_max_retries(max_retries)
+                .set_retry_delay_ms(25);
+            let results = BatchProcessor::new().process_batch(
+                vec![image.clone()],
+                None,
+                settings,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            );
+            unblock.join().unwrap();
+            results
+        };
+
+        let without_retries = run(0);
+        assert!(!without_retries[0].success);
+        assert_eq!(
+            without_retries[0].error_code.as_deref(),
+            Some("write_failed")
+        );
+        assert_eq!(without_retries[0].attempts, 1);
+
+        let with_retries = run(20);
+        assert!(
+            with_retries[0].success,
+            "{:?}",
+            with_retries[0].error_message
+        );
+        assert!(with_retries[0].output_path.exists());
+        assert!(with_retries[0].attempts > 1);
+    }
+
+    #[test]
+    fn test_decode_errors_and_cancellation_stop_retries() {
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let mut settings = ProcessingSettings::with_directory(output.path().to_path_buf());
+        settings.set_max_retries(5).set_retry_delay_ms(1000);
+
+        // Un archivo corrupto no mejora reintentando
+        let corrupt = input.path().join("corrupt.png");
+        std::fs::write(&corrupt, b"\x89PNG\r\n\x1a\nnot really a png").unwrap();
+        let image = Image::new(
+            corrupt,
+            ImageFormat::Png,
+            Dimensions::new(8, 8).unwrap(),
+            20,
+            None,
+        )
+        .unwrap();
+        let results = BatchProcessor::new().process_single_image(
+            &image,
+            None,
+            &settings,
+            &AtomicBool::new(false),
+            &AtomicBool::new(false),
+            &[],
+        );
+        assert_eq!(results[0].attempts, 1);
+        assert!(results[0]
+            .error_message
+            .as_deref()
+            .is_some_and(|message| message.contains("Decode failed")));
+
+        // Un fallo de lectura con la cancelación activa no espera al siguiente intento
+        let missing = input.path().join("missing.png");
+        let image = Image::new(
+            missing,
+            ImageFormat::Png,
+            Dimensions::new(8, 8).unwrap(),
+            20,
+            None,
+        )
+        .unwrap();
+        let started = Instant::now();
+        let results = BatchProcessor::new().process_single_image(
+            &image,
+            None,
+            &settings,
+            &AtomicBool::new(true),
+            &AtomicBool::new(false),
+            &[],
+        );
+        assert!(started.elapsed() < Duration::from_millis(1000));
+        assert_eq!(results[0].attempts, 1);
+        assert_eq!(results[0].error_message.as_deref(), Some("cancelled"));
+        assert_eq!(results[0].error_code.as_deref(), Some("cancelled"));
+    }
+
+    #[test]
+    fn test_progress_tracker_eta() {
+        let start = Instant::now();
+        let mut tracker = ProgressTracker::new(start, 10);
+
+        // 2 salidas en 400 ms: 200 ms por salida, quedan 8
+        let first = tracker.record(start + Duration::from_millis(400), 2, 1024 * 1024, "a.png");
+        assert_eq!(first.current, 2);
+        assert_eq!(first.elapsed_ms, 400);
+        // Aún sin muestras suficientes para el ETA
+        assert_eq!(first.estimated_remaining_ms, None);
+        assert_eq!(first.bytes_processed, 1024 * 1024);
+        assert!((first.throughput_mb_per_sec - 2.5).abs() < 1e-9);
+
+        // Muestra de 100 ms: la media móvil baja a 170 ms, quedan 7
+        let second = tracker.record(start + Duration::from_millis(500), 1, 0, "b.png");
+        assert_eq!(second.current, 3);
+        assert_eq!(second.estimated_remaining_ms, None);
+        assert_eq!(second.current_file, "b.png");
+
+        // Muestra de 270 ms: la media sube a 200 ms, quedan 6
+        let third = tracker.record(start + Duration::from_millis(770), 1, 0, "c.png");
+        assert_eq!(third.estimated_remaining_ms, Some(1200));
     }
 }
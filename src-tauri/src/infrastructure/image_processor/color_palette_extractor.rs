@@ -0,0 +1,260 @@
+use image::imageops::FilterType;
+use image::DynamicImage;
+use std::path::Path;
+
+use crate::infrastructure::error::InfraResult;
+use crate::infrastructure::image_processor::DuplicateDetector;
+
+/// One dominant color of an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteColor {
+    /// Average sRGB color of the pixels in the cluster
+    pub rgb: [u8; 3],
+    /// `#rrggbb`
+    pub hex: String,
+    /// Share of the (opaque) pixels in this cluster, 0-100
+    pub percentage: f32,
+}
+
+/// Color in CIE L*a*b* (D65), where Euclidean distance follows perceived difference
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Lab {
+    l: f32,
+    a: f32,
+    b: f32,
+}
+
+impl Lab {
+    fn from_rgb([r, g, b]: [u8; 3]) -> Self {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b) = (linear(r), linear(g), linear(b));
+
+        // sRGB -> XYZ, normalizado al blanco D65
+        let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+        let f = |t: f32| {
+            if t > 0.008856 {
+                t.cbrt()
+            } else {
+                7.787 * t + 16.0 / 116.0
+            }
+        };
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+
+        Lab {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    fn distance_squared(&self, other: &Lab) -> f32 {
+        (self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2)
+    }
+}
+
+/// Dominant color extraction with k-means clustering in Lab space
+pub struct ColorPaletteExtractor;
+
+impl ColorPaletteExtractor {
+    /// Long edge of the downsampled image that gets clustered
+    const SAMPLE_EDGE: u32 = 64;
+    const MAX_ITERATIONS: usize = 20;
+    /// Pixels below this alpha don't count towards the palette
+    const MIN_ALPHA: u8 = 128;
+
+    /// Up to `num_colors` dominant colors, most common first. Fewer are returned
+    /// when the image has fewer distinct colors (or no opaque pixels).
+    pub fn extract(img: &DynamicImage, num_colors: usize) -> Vec<PaletteColor> {
+        let sample = img
+            .resize(Self::SAMPLE_EDGE, Self::SAMPLE_EDGE, FilterType::Triangle)
+            .to_rgba8();
+        let pixels: Vec<[u8; 3]> = sample
+            .pixels()
+            .filter(|pixel| pixel[3] >= Self::MIN_ALPHA)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+        if pixels.is_empty() || num_colors == 0 {
+            return Vec::new();
+        }
+
+        let labs: Vec<Lab> = pixels.iter().map(|rgb| Lab::from_rgb(*rgb)).collect();
+        let mut centroids = Self::initial_centroids(&labs, num_colors);
+        let mut assignments = vec![0usize; labs.len()];
+
+        for iteration in 0..Self::MAX_ITERATIONS {
+            let mut changed = false;
+            for (lab, assignment) in labs.iter().zip(assignments.iter_mut()) {
+                let nearest = Self::nearest(&centroids, lab);
+                if nearest != *assignment {
+                    *assignment = nearest;
+                    changed = true;
+                }
+            }
+            if !changed && iteration > 0 {
+                break;
+            }
+
+            // Recalcular centroides; un cluster vacío conserva el anterior
+            let mut sums = vec![(Lab::default(), 0usize); centroids.len()];
+            for (lab, &cluster) in labs.iter().zip(&assignments) {
+                let (sum, count) = &mut sums[cluster];
+                sum.l += lab.l;
+                sum.a += lab.a;
+                sum.b += lab.b;
+                *count += 1;
+            }
+            for (centroid, (sum, count)) in centroids.iter_mut().zip(sums) {
+                if count > 0 {
+                    let n = count as f32;
+                    *centroid = Lab {
+                        l: sum.l / n,
+                        a: sum.a / n,
+                        b: sum.b / n,
+                    };
+                }
+            }
+        }
+
+        Self::palette(&pixels, &assignments, centroids.len())
+    }
+
+    /// Decode `path` (RAW through its embedded thumbnail) and extract its palette
+    pub fn extract_from_path(path: &Path, num_colors: usize) -> InfraResult<Vec<PaletteColor>> {
+        Ok(Self::extract(&DuplicateDetector::decode(path)?, num_colors))
+    }
+
+    /// Farthest-point seeding: deterministic, and spreads the seeds over distinct
+    /// colors so small accents aren't swallowed by the background
+    fn initial_centroids(labs: &[Lab], k: usize) -> Vec<Lab> {
+        let n = labs.len() as f32;
+        let mean = labs.iter().fold(Lab::default(), |acc, lab| Lab {
+            l: acc.l + lab.l / n,
+            a: acc.a + lab.a / n,
+            b: acc.b + lab.b / n,
+        });
+
+        let mut centroids: Vec<Lab> = Vec::with_capacity(k);
+        let mut distances: Vec<f32> = labs.iter().map(|lab| lab.distance_squared(&mean)).collect();
+        while centroids.len() < k {
+            let (index, &distance) = distances
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .expect("at least one pixel");
+            // Todos los píxeles ya coinciden con algún centroide
+            if !centroids.is_empty() && distance == 0.0 {
+                break;
+            }
+            let seed = labs[index];
+            centroids.push(seed);
+            for (lab, distance) in labs.iter().zip(distances.iter_mut()) {
+                *distance = distance.min(lab.distance_squared(&seed));
+            }
+        }
+        centroids
+    }
+
+    fn nearest(centroids: &[Lab], lab: &Lab) -> usize {
+        centroids
+            .iter()
+            .enumerate()
+            .min_by(|a, b| {
+                a.1.distance_squared(lab)
+                    .total_cmp(&b.1.distance_squared(lab))
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    /// Average sRGB color and share of every non-empty cluster, largest first
+    fn palette(pixels: &[[u8; 3]], assignments: &[usize], clusters: usize) -> Vec<PaletteColor> {
+        let mut sums = vec![([0u64; 3], 0u64); clusters];
+        for (rgb, &cluster) in pixels.iter().zip(assignments) {
+            let (sum, count) = &mut sums[cluster];
+            for (channel, value) in sum.iter_mut().zip(rgb) {
+                *channel += u64::from(*value);
+            }
+            *count += 1;
+        }
+
+        let total = pixels.len() as f32;
+        let mut palette: Vec<PaletteColor> = sums
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(sum, count)| {
+                let rgb = sum.map(|channel| ((channel + count / 2) / count) as u8);
+                PaletteColor {
+                    rgb,
+                    hex: format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]),
+                    percentage: count as f32 / total * 100.0,
+                }
+            })
+            .collect();
+        palette.sort_by(|a, b| b.percentage.total_cmp(&a.percentage));
+        palette
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    #[test]
+    fn test_two_color_image() {
+        // 3/4 rojo, 1/4 azul
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, _| {
+            if x < 48 {
+                Rgb([220, 20, 20])
+            } else {
+                Rgb([20, 20, 220])
+            }
+        }));
+
+        let palette = ColorPaletteExtractor::extract(&img, 2);
+
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette[0].hex, "#dc1414");
+        assert_eq!(palette[0].rgb, [220, 20, 20]);
+        assert!((palette[0].percentage - 75.0).abs() < 2.0);
+        assert_eq!(palette[1].rgb, [20, 20, 220]);
+    }
+
+    #[test]
+    fn test_fewer_colors_than_requested() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(16, 16, Rgb([10, 200, 30])));
+
+        let palette = ColorPaletteExtractor::extract(&img, 5);
+
+        assert_eq!(palette.len(), 1);
+        assert_eq!(palette[0].hex, "#0ac81e");
+        assert!((palette[0].percentage - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_transparent_pixels_ignored() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_fn(32, 32, |x, _| {
+            if x < 16 {
+                Rgba([255, 255, 255, 0])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        }));
+
+        let palette = ColorPaletteExtractor::extract(&img, 3);
+
+        assert_eq!(palette[0].rgb, [0, 0, 0]);
+        assert!(palette[0].percentage > 90.0);
+        assert!(ColorPaletteExtractor::extract(&img, 0).is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+use image::imageops::FilterType;
+use image::DynamicImage;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+use crate::domain::{Image, RawQualityMode};
+use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::image_processor::{HeicDecoder, RawProcessor};
+
+/// Images whose perceptual hashes are within the threshold of the first one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Paths in input order; the first one is the representative to keep
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// The image to keep from this group
+    pub fn representative(&self) -> &Path {
+        &self.paths[0]
+    }
+
+    /// Images that would be dropped when deduplicating
+    pub fn duplicates(&self) -> &[PathBuf] {
+        &self.paths[1..]
+    }
+}
+
+/// Near-identical image detection using a difference hash (dHash)
+pub struct DuplicateDetector;
+
+impl DuplicateDetector {
+    /// Default maximum Hamming distance (out of 64 bits) to consider two images duplicates
+    pub const DEFAULT_THRESHOLD: u32 = 5;
+
+    /// 64-bit gradient hash: the image is reduced to 9×8 grayscale and each bit
+    /// records whether a pixel is brighter than its right neighbour
+    pub fn dhash(img: &DynamicImage) -> u64 {
+        let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+        let mut hash = 0u64;
+        for y in 0..8 {
+            for x in 0..8 {
+                let left = small.get_pixel(x, y)[0];
+                let right = small.get_pixel(x + 1, y)[0];
+                hash = (hash << 1) | u64::from(left > right);
+            }
+        }
+        hash
+    }
+
+    /// Number of differing bits between two hashes
+    pub fn distance(a: u64, b: u64) -> u32 {
+        (a ^ b).count_ones()
+    }
+
+    /// Group images whose hash is within `threshold` bits of a group's first image.
+    /// Only groups with at least two images are returned; images that fail to
+    /// decode are never reported as duplicates.
+    pub fn find_duplicates(images: &[Image], threshold: u32) -> Vec<DuplicateGroup> {
+        // Decodificar y calcular hashes en paralelo
+        let hashes: Vec<Option<u64>> = images
+            .par_iter()
+            .map(|image| Self::decode(image.path()).ok().map(|img| Self::dhash(&img)))
+            .collect();
+
+        Self::group_hashes(images, &hashes, threshold)
+    }
+
+    /// Greedy grouping in input order: each ungrouped image starts a group and
+    /// claims every later ungrouped image close enough to it
+    fn group_hashes(
+        images: &[Image],
+        hashes: &[Option<u64>],
+        threshold: u32,
+    ) -> Vec<DuplicateGroup> {
+        let mut grouped = vec![false; images.len()];
+        let mut groups = Vec::new();
+
+        for i in 0..images.len() {
+            let Some(reference) = hashes[i] else {
+                continue;
+            };
+            if grouped[i] {
+                continue;
+            }
+
+            let mut paths = vec![images[i].path().to_path_buf()];
+            for j in (i + 1)..images.len() {
+                if grouped[j] {
+                    continue;
+                }
+                if hashes[j].is_some_and(|h| Self::distance(reference, h) <= threshold) {
+                    grouped[j] = true;
+                    paths.push(images[j].path().to_path_buf());
+                }
+            }
+
+            if paths.len() > 1 {
+                groups.push(DuplicateGroup { paths });
+            }
+        }
+
+        groups
+    }
+
+    /// Decode for hashing; RAW files use the embedded preview (much faster)
+    pub(crate) fn decode(path: &Path) -> InfraResult<DynamicImage> {
+        if let Some(ext) = path.extension() {
+            let ext_str = ext.to_string_lossy();
+            if RawProcessor::is_raw_format(&ext_str) {
+                return RawProcessor::new().process_raw(path, RawQualityMode::Thumbnail);
+            }
+            if HeicDecoder::is_heic_format(&ext_str) {
+                return HeicDecoder::new().decode(path, 0);
+            }
+        }
+
+        image::open(path).map_err(|e| {
+            InfraError::ImageReadError(format!(
+                "Failed to open image file '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Dimensions, ImageFormat};
+    use image::{Rgb, RgbImage};
+
+    fn gradient(width: u32, height: u32, reverse: bool) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, _| {
+            let v = (x * 255 / (width - 1)) as u8;
+            let v = if reverse { 255 - v } else { v };
+            Rgb([v, v, v])
+        }))
+    }
+
+    fn image(name: &str) -> Image {
+        Image::new(
+            Path::new("/tmp").join(name),
+            ImageFormat::Png,
+            Dimensions::new(10, 10).unwrap(),
+            0,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dhash_ignores_scale() {
+        let small = DuplicateDetector::dhash(&gradient(90, 80, true));
+        let large = DuplicateDetector::dhash(&gradient(900, 800, true));
+        assert!(DuplicateDetector::distance(small, large) <= DuplicateDetector::DEFAULT_THRESHOLD);
+
+        let opposite = DuplicateDetector::dhash(&gradient(90, 80, false));
+        assert!(DuplicateDetector::distance(small, opposite) > 32);
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(DuplicateDetector::distance(0, 0), 0);
+        assert_eq!(DuplicateDetector::distance(0b1011, 0b0001), 2);
+        assert_eq!(DuplicateDetector::distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn test_group_hashes_keeps_input_order() {
+        let images = [
+            image("a.png"),
+            image("b.png"),
+            image("c.png"),
+            image("d.png"),
+        ];
+        let hashes = [Some(0b0000), Some(0xFFFF), Some(0b0011), None];
+
+        let groups = DuplicateDetector::group_hashes(&images, &hashes, 2);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].representative(), Path::new("/tmp/a.png"));
+        assert_eq!(groups[0].duplicates(), &[PathBuf::from("/tmp/c.png")]);
+    }
+}
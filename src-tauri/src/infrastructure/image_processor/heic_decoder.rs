@@ -0,0 +1,157 @@
+use image::DynamicImage;
+use std::path::Path;
+
+use crate::infrastructure::error::{InfraError, InfraResult};
+
+/// HEIC/HEIF decoder using libheif (requires the `heic` Cargo feature)
+pub struct HeicDecoder;
+
+impl HeicDecoder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether this build can decode HEIC files
+    pub fn is_supported() -> bool {
+        cfg!(feature = "heic")
+    }
+
+    /// Check if file extension is a HEIC/HEIF container
+    pub fn is_heic_format(extension: &str) -> bool {
+        matches!(extension.to_lowercase().as_str(), "heic" | "heif" | "hif")
+    }
+
+    /// Decode one image of the container to RGB8.
+    /// `image_index` 0 = primary image; n = n-th top-level image (burst frames, etc.)
+    #[cfg(feature = "heic")]
+    pub fn decode(&self, path: &Path, image_index: usize) -> InfraResult<DynamicImage> {
+        use image::RgbImage;
+        use libheif_rs::{ColorSpace, LibHeif, RgbChroma};
+
+        let context = Self::open(path)?;
+        let handle = Self::select_handle(&context, path, image_index)?;
+
+        let image = LibHeif::new()
+            .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+            .map_err(|e| {
+                InfraError::DecodeError(format!(
+                    "Failed to decode HEIC image from '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        let plane = image.planes().interleaved.ok_or_else(|| {
+            InfraError::DecodeError(format!(
+                "HEIC image from '{}' has no interleaved RGB plane",
+                path.display()
+            ))
+        })?;
+
+        // Copiar fila por fila: el stride de libheif puede incluir relleno
+        let row_bytes = plane.width as usize * 3;
+        let mut pixel_data = Vec::with_capacity(row_bytes * plane.height as usize);
+        for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+            pixel_data.extend_from_slice(&row[..row_bytes]);
+        }
+
+        let rgb_image =
+            RgbImage::from_raw(plane.width, plane.height, pixel_data).ok_or_else(|| {
+                InfraError::DecodeError("Failed to create RGB image from HEIC data".to_string())
+            })?;
+
+        Ok(DynamicImage::ImageRgb8(rgb_image))
+    }
+
+    #[cfg(not(feature = "heic"))]
+    pub fn decode(&self, path: &Path, _image_index: usize) -> InfraResult<DynamicImage> {
+        Err(Self::not_supported(path))
+    }
+
+    /// Read image dimensions without decoding pixels
+    #[cfg(feature = "heic")]
+    pub fn get_dimensions(path: &Path, image_index: usize) -> InfraResult<(u32, u32)> {
+        let context = Self::open(path)?;
+        let handle = Self::select_handle(&context, path, image_index)?;
+        Ok((handle.width(), handle.height()))
+    }
+
+    #[cfg(not(feature = "heic"))]
+    pub fn get_dimensions(path: &Path, _image_index: usize) -> InfraResult<(u32, u32)> {
+        Err(Self::not_supported(path))
+    }
+
+    #[cfg(feature = "heic")]
+    fn open(path: &Path) -> InfraResult<libheif_rs::HeifContext<'static>> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| InfraError::ImageReadError("Invalid file path".to_string()))?;
+
+        libheif_rs::HeifContext::read_from_file(path_str).map_err(|e| {
+            InfraError::ImageReadError(format!(
+                "Failed to open HEIC file '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    #[cfg(feature = "heic")]
+    fn select_handle(
+        context: &libheif_rs::HeifContext,
+        path: &Path,
+        image_index: usize,
+    ) -> InfraResult<libheif_rs::ImageHandle> {
+        let handle = if image_index == 0 {
+            context.primary_image_handle().ok()
+        } else {
+            context
+                .top_level_image_handles()
+                .into_iter()
+                .nth(image_index)
+        };
+
+        handle.ok_or_else(|| {
+            InfraError::ImageReadError(format!(
+                "HEIC file '{}' has no image at index {} ({} top-level images)",
+                path.display(),
+                image_index,
+                context.number_of_top_level_images()
+            ))
+        })
+    }
+
+    #[cfg(not(feature = "heic"))]
+    fn not_supported(path: &Path) -> InfraError {
+        InfraError::UnsupportedFormat(format!(
+            "HEIC support is not enabled in this build (rebuild with --features heic): {}",
+            path.display()
+        ))
+    }
+}
+
+impl Default for HeicDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_heic_format() {
+        assert!(HeicDecoder::is_heic_format("heic"));
+        assert!(HeicDecoder::is_heic_format("HEIF"));
+        assert!(HeicDecoder::is_heic_format("hif"));
+        assert!(!HeicDecoder::is_heic_format("jpg"));
+    }
+
+    #[cfg(not(feature = "heic"))]
+    #[test]
+    fn test_decode_without_feature_fails() {
+        let result = HeicDecoder::new().decode(Path::new("photo.heic"), 0);
+        assert!(matches!(result, Err(InfraError::UnsupportedFormat(_))));
+    }
+}
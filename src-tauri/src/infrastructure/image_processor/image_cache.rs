@@ -0,0 +1,196 @@
+use image::DynamicImage;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::domain::ProcessingSettings;
+
+/// Identifies one decoding of a file: the same path decoded with other RAW/HEIC
+/// options, or modified on disk since, is a different entry
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    size_bytes: u64,
+    decoding: String,
+}
+
+impl CacheKey {
+    pub fn new(path: &Path, settings: &ProcessingSettings) -> Self {
+        let metadata = std::fs::metadata(path).ok();
+        Self {
+            path: path.to_path_buf(),
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+            size_bytes: metadata.map_or(0, |m| m.len()),
+            // Solo los ajustes que cambian el resultado de decodificar
+            decoding: format!(
+                "{:?}|{:?}|{}|{}",
+                settings.raw_quality_mode(),
+                settings.raw_params(),
+                settings.heic_image_index(),
+                settings.auto_orient()
+            ),
+        }
+    }
+}
+
+struct CacheEntry {
+    image: Arc<DynamicImage>,
+    size_bytes: usize,
+    /// Value of `ImageCache::clock` when last read or written
+    last_used: u64,
+}
+
+/// Decoded images kept in memory up to a total pixel-buffer size,
+/// evicting the least recently used first
+pub struct ImageCache {
+    max_size_bytes: usize,
+    used_bytes: usize,
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+impl ImageCache {
+    /// Default capacity: 512 MiB of decoded pixels
+    pub const DEFAULT_MAX_SIZE_BYTES: usize = 512 * 1024 * 1024;
+
+    pub fn new(max_size_bytes: usize) -> Self {
+        Self {
+            max_size_bytes,
+            used_bytes: 0,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Cached image for `key`, marking it as recently used
+    pub fn get(&mut self, key: &CacheKey) -> Option<Arc<DynamicImage>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.image.clone())
+    }
+
+    /// Store a decoded image, evicting older entries to make room.
+    /// Images larger than the whole cache are not stored.
+    pub fn insert(&mut self, key: CacheKey, image: Arc<DynamicImage>) {
+        let size_bytes = image.as_bytes().len();
+        if size_bytes > self.max_size_bytes {
+            return;
+        }
+        if let Some(previous) = self.entries.remove(&key) {
+            self.used_bytes -= previous.size_bytes;
+        }
+        while self.used_bytes + size_bytes > self.max_size_bytes {
+            self.evict_oldest();
+        }
+
+        self.clock += 1;
+        self.used_bytes += size_bytes;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                image,
+                size_bytes,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Cached image for `key`, decoding it with `loader` on a miss
+    pub fn get_or_load<E>(
+        &mut self,
+        key: CacheKey,
+        loader: impl FnOnce() -> Result<DynamicImage, E>,
+    ) -> Result<Arc<DynamicImage>, E> {
+        if let Some(image) = self.get(&key) {
+            return Ok(image);
+        }
+        let image = Arc::new(loader()?);
+        self.insert(key, image.clone());
+        Ok(image)
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(entry) = oldest.and_then(|key| self.entries.remove(&key)) {
+            self.used_bytes -= entry.size_bytes;
+        }
+    }
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_SIZE_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10x10 RGB = 300 bytes of pixels
+    fn image() -> DynamicImage {
+        DynamicImage::ImageRgb8(image::RgbImage::new(10, 10))
+    }
+
+    fn key(name: &str) -> CacheKey {
+        CacheKey::new(Path::new(name), &ProcessingSettings::default())
+    }
+
+    #[test]
+    fn test_get_or_load_decodes_once() {
+        let mut cache = ImageCache::default();
+        let mut loads = 0;
+        for _ in 0..3 {
+            cache
+                .get_or_load(key("a.png"), || {
+                    loads += 1;
+                    Ok::<_, ()>(image())
+                })
+                .unwrap();
+        }
+        assert_eq!(loads, 1);
+
+        let error = cache.get_or_load(key("b.png"), || Err("decode failed"));
+        assert!(error.is_err());
+        assert!(cache.get(&key("b.png")).is_none());
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = ImageCache::new(700);
+        cache.insert(key("a.png"), Arc::new(image()));
+        cache.insert(key("b.png"), Arc::new(image()));
+        // "a" pasa a ser la más reciente: la expulsada es "b"
+        assert!(cache.get(&key("a.png")).is_some());
+        cache.insert(key("c.png"), Arc::new(image()));
+
+        assert!(cache.get(&key("a.png")).is_some());
+        assert!(cache.get(&key("b.png")).is_none());
+        assert!(cache.get(&key("c.png")).is_some());
+        assert_eq!(cache.used_bytes, 600);
+
+        // Más grande que toda la caché: no se guarda
+        cache.insert(
+            key("big.png"),
+            Arc::new(DynamicImage::ImageRgb8(image::RgbImage::new(20, 20))),
+        );
+        assert!(cache.get(&key("big.png")).is_none());
+        assert_eq!(cache.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_key_depends_on_decoding_settings() {
+        let mut settings = ProcessingSettings::default();
+        let path = Path::new("photo.heic");
+        let primary = CacheKey::new(path, &settings);
+        settings.set_heic_image_index(1);
+        assert_ne!(primary, CacheKey::new(path, &settings));
+    }
+}
@@ -0,0 +1,142 @@
+use image::{DynamicImage, GenericImageView};
+use std::path::Path;
+
+use crate::infrastructure::error::InfraResult;
+use crate::infrastructure::image_processor::DuplicateDetector;
+
+/// Per-channel and luma histograms of an 8-bit rendition of an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageHistogram {
+    pub r: [u32; 256],
+    pub g: [u32; 256],
+    pub b: [u32; 256],
+    /// Rec. 709 luma
+    pub luma: [u32; 256],
+}
+
+impl ImageHistogram {
+    /// Count every pixel in one pass. Fully transparent pixels are left out:
+    /// their color is meaningless.
+    pub fn from_dynamic_image(img: &DynamicImage) -> Self {
+        let mut histogram = Self {
+            r: [0; 256],
+            g: [0; 256],
+            b: [0; 256],
+            luma: [0; 256],
+        };
+
+        for (_, _, pixel) in img.pixels() {
+            let [r, g, b, a] = pixel.0;
+            if a == 0 {
+                continue;
+            }
+            histogram.r[r as usize] += 1;
+            histogram.g[g as usize] += 1;
+            histogram.b[b as usize] += 1;
+            // Coeficientes Rec. 709 en enteros (suman 10000)
+            let luma = (2126 * r as u32 + 7152 * g as u32 + 722 * b as u32 + 5000) / 10000;
+            histogram.luma[luma as usize] += 1;
+        }
+        histogram
+    }
+
+    /// Decode `path` (RAW through its embedded thumbnail) and build its histogram
+    pub fn from_path(path: &Path) -> InfraResult<Self> {
+        Ok(Self::from_dynamic_image(&DuplicateDetector::decode(path)?))
+    }
+
+    /// Number of pixels counted
+    pub fn pixel_count(&self) -> u64 {
+        self.luma.iter().map(|&count| u64::from(count)).sum()
+    }
+
+    /// Average luma, 0-255 (0 for an empty histogram)
+    pub fn mean_luma(&self) -> f32 {
+        let total = self.pixel_count();
+        if total == 0 {
+            return 0.0;
+        }
+        let sum: u64 = self
+            .luma
+            .iter()
+            .enumerate()
+            .map(|(value, &count)| value as u64 * u64::from(count))
+            .sum();
+        (sum as f64 / total as f64) as f32
+    }
+
+    /// Share of pixels at pure white luma, 0-100
+    pub fn clipped_highlights_percent(&self) -> f32 {
+        self.percent(self.luma[255])
+    }
+
+    /// Share of pixels at pure black luma, 0-100
+    pub fn clipped_shadows_percent(&self) -> f32 {
+        self.percent(self.luma[0])
+    }
+
+    fn percent(&self, count: u32) -> f32 {
+        let total = self.pixel_count();
+        if total == 0 {
+            return 0.0;
+        }
+        (f64::from(count) / total as f64 * 100.0) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    #[test]
+    fn test_histogram_counts_and_stats() {
+        // Mitad blanca, un cuarto negro, un cuarto gris medio
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(4, 4, |x, _| match x {
+            0 | 1 => Rgb([255, 255, 255]),
+            2 => Rgb([0, 0, 0]),
+            _ => Rgb([128, 128, 128]),
+        }));
+
+        let histogram = ImageHistogram::from_dynamic_image(&img);
+
+        assert_eq!(histogram.pixel_count(), 16);
+        assert_eq!(histogram.r[255], 8);
+        assert_eq!(histogram.g[0], 4);
+        assert_eq!(histogram.b[128], 4);
+        assert_eq!(histogram.luma[128], 4);
+        assert!((histogram.clipped_highlights_percent() - 50.0).abs() < 1e-4);
+        assert!((histogram.clipped_shadows_percent() - 25.0).abs() < 1e-4);
+        assert!((histogram.mean_luma() - (8.0 * 255.0 + 4.0 * 128.0) / 16.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_luma_weights_green_most() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(3, 1, |x, _| match x {
+            0 => Rgb([255, 0, 0]),
+            1 => Rgb([0, 255, 0]),
+            _ => Rgb([0, 0, 255]),
+        }));
+
+        let histogram = ImageHistogram::from_dynamic_image(&img);
+
+        assert_eq!(histogram.luma[54], 1);
+        assert_eq!(histogram.luma[182], 1);
+        assert_eq!(histogram.luma[18], 1);
+    }
+
+    #[test]
+    fn test_transparent_pixels_ignored() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_fn(2, 1, |x, _| {
+            Rgba([0, 0, 0, if x == 0 { 0 } else { 255 }])
+        }));
+
+        let histogram = ImageHistogram::from_dynamic_image(&img);
+
+        assert_eq!(histogram.pixel_count(), 1);
+        assert_eq!(
+            ImageHistogram::from_dynamic_image(&DynamicImage::new_rgb8(0, 0)).mean_luma(),
+            0.0
+        );
+    }
+}
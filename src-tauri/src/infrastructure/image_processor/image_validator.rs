@@ -0,0 +1,294 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::domain::ImageFormat;
+use crate::infrastructure::image_processor::HeicDecoder;
+
+/// Outcome of a pre-flight check on an image file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationResult {
+    Ok,
+    /// The file ends before its data does: (expected, actual) size in bytes.
+    /// Formats that don't record their length report a lower bound as expected.
+    Truncated(u64, u64),
+    /// The extension and the file signature disagree
+    WrongFormat {
+        extension_says: ImageFormat,
+        magic_says: ImageFormat,
+    },
+    /// Zero-byte file
+    Empty,
+    /// Unreadable file, unknown extension or signature, or a format this build can't decode
+    Unsupported(String),
+}
+
+impl ValidationResult {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ValidationResult::Ok)
+    }
+
+    /// Human-readable description of the problem, None when the file looks fine
+    pub fn warning(&self) -> Option<String> {
+        match self {
+            ValidationResult::Ok => None,
+            ValidationResult::Truncated(expected, actual) => Some(format!(
+                "File looks truncated: expected at least {} bytes, found {}",
+                expected, actual
+            )),
+            ValidationResult::WrongFormat {
+                extension_says,
+                magic_says,
+            } => Some(format!(
+                "Extension says {} but the file contents are {}",
+                extension_says, magic_says
+            )),
+            ValidationResult::Empty => Some("File is empty".to_string()),
+            ValidationResult::Unsupported(reason) => Some(reason.clone()),
+        }
+    }
+}
+
+/// Cheap corruption checks (size, signature, end markers) without decoding pixels
+pub struct ImageValidator;
+
+impl ImageValidator {
+    /// Bytes from the end of a JPEG searched for the EOI marker (trailers are common)
+    const JPEG_TAIL: u64 = 64 * 1024;
+
+    /// Check a file before processing it
+    pub fn validate(path: &Path) -> ValidationResult {
+        let size = match std::fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => return ValidationResult::Unsupported(format!("Cannot read file: {}", e)),
+        };
+        if size == 0 {
+            return ValidationResult::Empty;
+        }
+
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let Ok(extension_says) = ImageFormat::from_extension(extension) else {
+            return ValidationResult::Unsupported(format!(
+                "Unknown image extension: '{}'",
+                extension
+            ));
+        };
+        if extension_says == ImageFormat::Heic && !HeicDecoder::is_supported() {
+            return ValidationResult::Unsupported(
+                "HEIC support is not enabled in this build".to_string(),
+            );
+        }
+
+        match Self::check_contents(path, size, extension_says) {
+            Ok(result) => result,
+            Err(e) => ValidationResult::Unsupported(format!("Cannot read file: {}", e)),
+        }
+    }
+
+    fn check_contents(
+        path: &Path,
+        size: u64,
+        extension_says: ImageFormat,
+    ) -> std::io::Result<ValidationResult> {
+        let mut file = File::open(path)?;
+        let mut header = Vec::with_capacity(ImageFormat::MAGIC_BYTES_LEN);
+        file.by_ref()
+            .take(ImageFormat::MAGIC_BYTES_LEN as u64)
+            .read_to_end(&mut header)?;
+
+        let Ok(magic_says) = ImageFormat::from_magic_bytes(&header) else {
+            // Algunos RAW tienen firmas que no se reconocen: no se pueden verificar
+            if extension_says == ImageFormat::Raw {
+                return Ok(ValidationResult::Ok);
+            }
+            return Ok(ValidationResult::Unsupported(
+                "Unrecognized file signature".to_string(),
+            ));
+        };
+
+        if magic_says != extension_says {
+            return Ok(ValidationResult::WrongFormat {
+                extension_says,
+                magic_says,
+            });
+        }
+
+        match magic_says {
+            ImageFormat::Png => Self::check_png(&mut file, size),
+            ImageFormat::Jpeg => Self::check_jpeg(&mut file, size),
+            ImageFormat::Webp => Ok(Self::check_webp(&header, size)),
+            ImageFormat::Gif => Self::check_gif(&mut file, size),
+            // Contenedores sin marcador de fin sencillo
+            ImageFormat::Raw | ImageFormat::Heic => Ok(ValidationResult::Ok),
+        }
+    }
+
+    /// Walk the chunks up to IEND; a chunk running past the end means truncation
+    fn check_png(file: &mut File, size: u64) -> std::io::Result<ValidationResult> {
+        let mut position = 8u64;
+        loop {
+            // longitud (4) + tipo (4) + datos + CRC (4)
+            if position + 12 > size {
+                return Ok(ValidationResult::Truncated(position + 12, size));
+            }
+            file.seek(SeekFrom::Start(position))?;
+            let mut chunk_header = [0u8; 8];
+            file.read_exact(&mut chunk_header)?;
+
+            let length = u32::from_be_bytes([
+                chunk_header[0],
+                chunk_header[1],
+                chunk_header[2],
+                chunk_header[3],
+            ]);
+            let end = position + 12 + u64::from(length);
+            if end > size {
+                return Ok(ValidationResult::Truncated(end, size));
+            }
+            if &chunk_header[4..] == b"IEND" {
+                return Ok(ValidationResult::Ok);
+            }
+            position = end;
+        }
+    }
+
+    /// The EOI marker (FF D9) must appear near the end of the file
+    fn check_jpeg(file: &mut File, size: u64) -> std::io::Result<ValidationResult> {
+        let tail = Self::read_tail(file, size, Self::JPEG_TAIL)?;
+        if tail.windows(2).any(|w| w == [0xFF, 0xD9]) {
+            Ok(ValidationResult::Ok)
+        } else {
+            Ok(ValidationResult::Truncated(size + 2, size))
+        }
+    }
+
+    /// RIFF records the file length in its header
+    fn check_webp(header: &[u8], size: u64) -> ValidationResult {
+        let riff_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let expected = u64::from(riff_size) + 8;
+        if size < expected {
+            ValidationResult::Truncated(expected, size)
+        } else {
+            ValidationResult::Ok
+        }
+    }
+
+    /// GIF ends with the trailer byte 0x3B
+    fn check_gif(file: &mut File, size: u64) -> std::io::Result<ValidationResult> {
+        if Self::read_tail(file, size, 1)? == [0x3B] {
+            Ok(ValidationResult::Ok)
+        } else {
+            Ok(ValidationResult::Truncated(size + 1, size))
+        }
+    }
+
+    fn read_tail(file: &mut File, size: u64, length: u64) -> std::io::Result<Vec<u8>> {
+        let length = length.min(size);
+        file.seek(SeekFrom::Start(size - length))?;
+        let mut tail = Vec::with_capacity(length as usize);
+        file.take(length).read_to_end(&mut tail)?;
+        Ok(tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn encoded(format: image::ImageFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(16, 16))
+            .write_to(&mut std::io::Cursor::new(&mut bytes), format)
+            .unwrap();
+        bytes
+    }
+
+    fn write(dir: &Path, name: &str, data: &[u8]) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_valid_images() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, format) in [
+            ("a.png", image::ImageFormat::Png),
+            ("a.jpg", image::ImageFormat::Jpeg),
+            ("a.gif", image::ImageFormat::Gif),
+            ("a.webp", image::ImageFormat::WebP),
+        ] {
+            let path = write(dir.path(), name, &encoded(format));
+            assert_eq!(
+                ImageValidator::validate(&path),
+                ValidationResult::Ok,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_images() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, format) in [
+            ("a.png", image::ImageFormat::Png),
+            ("a.jpg", image::ImageFormat::Jpeg),
+            ("a.gif", image::ImageFormat::Gif),
+            ("a.webp", image::ImageFormat::WebP),
+        ] {
+            let data = encoded(format);
+            let cut = data.len() - 3;
+            let path = write(dir.path(), name, &data[..cut]);
+
+            match ImageValidator::validate(&path) {
+                ValidationResult::Truncated(expected, actual) => {
+                    assert_eq!(actual, cut as u64);
+                    assert!(expected > actual);
+                }
+                other => panic!("{}: {:?}", name, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "photo.jpg", &encoded(image::ImageFormat::Png));
+
+        assert_eq!(
+            ImageValidator::validate(&path),
+            ValidationResult::WrongFormat {
+                extension_says: ImageFormat::Jpeg,
+                magic_says: ImageFormat::Png,
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_and_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = write(dir.path(), "empty.png", b"");
+        assert_eq!(ImageValidator::validate(&empty), ValidationResult::Empty);
+
+        let text = write(dir.path(), "notes.txt", b"hello");
+        assert!(matches!(
+            ImageValidator::validate(&text),
+            ValidationResult::Unsupported(_)
+        ));
+
+        let garbage = write(dir.path(), "image.png", b"not an image");
+        assert!(matches!(
+            ImageValidator::validate(&garbage),
+            ValidationResult::Unsupported(_)
+        ));
+
+        let raf = write(dir.path(), "photo.raf", b"FUJIFILMCCD-RAW 0201");
+        assert!(ImageValidator::validate(&raf).is_ok());
+
+        // Firma RAW desconocida: no se puede verificar, se acepta
+        let kdc = write(dir.path(), "photo.kdc", b"KODAK DC50 RAW");
+        assert!(ImageValidator::validate(&kdc).is_ok());
+    }
+}
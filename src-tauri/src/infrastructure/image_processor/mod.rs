@@ -1,9 +1,23 @@
 mod batch_processor;
+mod color_palette_extractor;
+mod duplicate_detector;
+mod heic_decoder;
+mod image_cache;
+mod image_histogram;
+mod image_validator;
 pub mod optimizers;
 mod processor_impl;
 mod raw_processor;
 pub mod transformers;
 
-pub use batch_processor::{BatchProcessor, ProcessingResult, ProgressCallback};
-pub use processor_impl::ImageProcessorImpl;
+pub use batch_processor::{
+    BatchProcessor, BatchProgress, ImageCancelSignals, ProcessingResult, ProgressCallback,
+};
+pub use color_palette_extractor::{ColorPaletteExtractor, PaletteColor};
+pub use duplicate_detector::{DuplicateDetector, DuplicateGroup};
+pub use heic_decoder::HeicDecoder;
+pub use image_cache::{CacheKey, ImageCache};
+pub use image_histogram::ImageHistogram;
+pub use image_validator::{ImageValidator, ValidationResult};
+pub use processor_impl::{EncodedImage, ImageProcessorImpl, PhaseTimings};
 pub use raw_processor::RawProcessor;
@@ -0,0 +1,88 @@
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use std::borrow::Cow;
+
+/// Composite an image with alpha onto a solid `background` color, for encoders
+/// without transparency (JPEG, ...). Images without an alpha channel are borrowed
+/// unchanged, avoiding a needless conversion.
+pub fn flatten_alpha(img: &DynamicImage, background: [u8; 3]) -> Cow<'_, DynamicImage> {
+    if !img.color().has_alpha() {
+        return Cow::Borrowed(img);
+    }
+
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let blend = |value: u8, bg: u8, alpha: u8| -> u8 {
+        let alpha = u32::from(alpha);
+        ((u32::from(value) * alpha + u32::from(bg) * (255 - alpha) + 127) / 255) as u8
+    };
+
+    // Gris sobre fondo gris: se mantiene en un solo canal
+    let [r, g, b] = background;
+    if !img.color().has_color() && r == g && g == b {
+        return Cow::Owned(DynamicImage::ImageLuma8(GrayImage::from_fn(
+            width,
+            height,
+            |x, y| {
+                let pixel = rgba.get_pixel(x, y);
+                Luma([blend(pixel[0], r, pixel[3])])
+            },
+        )));
+    }
+
+    Cow::Owned(DynamicImage::ImageRgb8(RgbImage::from_fn(
+        width,
+        height,
+        |x, y| {
+            let [pr, pg, pb, alpha] = rgba.get_pixel(x, y).0;
+            Rgb([
+                blend(pr, r, alpha),
+                blend(pg, g, alpha),
+                blend(pb, b, alpha),
+            ])
+        },
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{LumaA, Rgba, RgbaImage};
+
+    #[test]
+    fn test_flatten_transparent_onto_background() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgba([0, 0, 0, 0])
+            } else {
+                Rgba([255, 0, 0, 128])
+            }
+        }));
+
+        let flat = flatten_alpha(&img, [255, 255, 255]);
+        assert!(!flat.color().has_alpha());
+        let rgb = flat.to_rgb8();
+        assert_eq!(rgb.get_pixel(0, 0).0, [255, 255, 255]);
+        assert_eq!(rgb.get_pixel(1, 0).0, [255, 127, 127]);
+    }
+
+    #[test]
+    fn test_opaque_image_is_borrowed() {
+        let img = DynamicImage::ImageRgb8(RgbImage::new(2, 2));
+        assert!(matches!(
+            flatten_alpha(&img, [255, 255, 255]),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_gray_alpha_stays_single_channel() {
+        let img = DynamicImage::ImageLumaA8(image::GrayAlphaImage::from_pixel(1, 1, LumaA([0, 0])));
+
+        let gray = flatten_alpha(&img, [200, 200, 200]);
+        assert!(matches!(*gray, DynamicImage::ImageLuma8(_)));
+        assert_eq!(gray.to_luma8().get_pixel(0, 0).0, [200]);
+
+        let tinted = flatten_alpha(&img, [255, 0, 0]);
+        assert!(matches!(*tinted, DynamicImage::ImageRgb8(_)));
+    }
+}
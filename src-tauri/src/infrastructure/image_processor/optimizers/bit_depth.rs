@@ -0,0 +1,47 @@
+use image::DynamicImage;
+use std::borrow::Cow;
+
+/// Scale 16-bit and float channels down to 8 bits, keeping the channel layout
+/// (gray stays gray, alpha stays). 8-bit images are borrowed unchanged.
+pub fn to_8bit(img: &DynamicImage) -> Cow<'_, DynamicImage> {
+    match img {
+        DynamicImage::ImageLuma8(_)
+        | DynamicImage::ImageLumaA8(_)
+        | DynamicImage::ImageRgb8(_)
+        | DynamicImage::ImageRgba8(_) => Cow::Borrowed(img),
+        DynamicImage::ImageLuma16(_) => Cow::Owned(DynamicImage::ImageLuma8(img.to_luma8())),
+        DynamicImage::ImageLumaA16(_) => {
+            Cow::Owned(DynamicImage::ImageLumaA8(img.to_luma_alpha8()))
+        }
+        other if other.color().has_alpha() => {
+            Cow::Owned(DynamicImage::ImageRgba8(other.to_rgba8()))
+        }
+        other => Cow::Owned(DynamicImage::ImageRgb8(other.to_rgb8())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, LumaA, Rgb, Rgba};
+
+    #[test]
+    fn test_sixteen_bit_scaled_down() {
+        let rgb = DynamicImage::ImageRgb16(ImageBuffer::from_pixel(2, 2, Rgb([65535, 32896, 0])));
+        let scaled = to_8bit(&rgb);
+        assert!(matches!(*scaled, DynamicImage::ImageRgb8(_)));
+        assert_eq!(scaled.to_rgb8().get_pixel(0, 0).0, [255, 128, 0]);
+
+        let rgba = DynamicImage::ImageRgba16(ImageBuffer::from_pixel(1, 1, Rgba([0, 0, 0, 0])));
+        assert!(matches!(*to_8bit(&rgba), DynamicImage::ImageRgba8(_)));
+
+        let gray = DynamicImage::ImageLumaA16(ImageBuffer::from_pixel(1, 1, LumaA([0, 65535])));
+        assert!(matches!(*to_8bit(&gray), DynamicImage::ImageLumaA8(_)));
+    }
+
+    #[test]
+    fn test_eight_bit_is_borrowed() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::new(2, 2));
+        assert!(matches!(to_8bit(&img), Cow::Borrowed(_)));
+    }
+}
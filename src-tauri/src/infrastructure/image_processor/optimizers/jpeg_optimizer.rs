@@ -1,6 +1,10 @@
-use crate::domain::value_objects::Quality;
+use crate::domain::models::Rotation;
+use crate::domain::value_objects::{ChromaSubsampling, Quality};
 use crate::infrastructure::error::{InfraError, InfraResult};
-use mozjpeg::{ColorSpace, Compress, ScanMode};
+use mozjpeg::{ColorSpace, ColorSpaceExt, Compress, ScanMode};
+use mozjpeg_sys::JXFORM_CODE;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_ulong};
 
 /// JPEG optimizer using mozjpeg
 pub struct JpegOptimizer;
@@ -14,18 +18,90 @@ impl JpegOptimizer {
     ///
     /// Creates a fresh JPEG file from pixel data only - no metadata is copied.
     /// This ensures the output is clean and optimized without EXIF/XMP/IPTC.
+    /// `progressive` = false writes a single sequential scan (baseline) for old viewers.
+    /// `chroma` sets the color subsampling (`Auto` is resolved against `quality`).
+    /// `optimize_huffman` = false keeps the standard Huffman tables and scan script:
+    /// faster, slightly larger files.
+    #[allow(clippy::too_many_arguments)]
     pub fn optimize(
         &self,
         width: usize,
         height: usize,
         rgb_data: &[u8],
         quality: Quality,
+        progressive: bool,
+        chroma: ChromaSubsampling,
+        optimize_huffman: bool,
     ) -> InfraResult<Vec<u8>> {
-        // Create mozjpeg compressor from raw RGB pixels (no metadata)
-        let mut comp = Compress::new(ColorSpace::JCS_RGB);
+        self.compress(
+            width,
+            height,
+            rgb_data,
+            ColorSpace::JCS_RGB,
+            quality,
+            progressive,
+            Some(chroma),
+            optimize_huffman,
+        )
+    }
+
+    /// Optimize JPEG image from 8-bit luma data
+    ///
+    /// Encodes a single-component JPEG (JCS_GRAYSCALE), noticeably smaller than
+    /// storing the same gray pixels as RGB.
+    pub fn optimize_grayscale(
+        &self,
+        width: usize,
+        height: usize,
+        luma_data: &[u8],
+        quality: Quality,
+        progressive: bool,
+        optimize_huffman: bool,
+    ) -> InfraResult<Vec<u8>> {
+        self.compress(
+            width,
+            height,
+            luma_data,
+            ColorSpace::JCS_GRAYSCALE,
+            quality,
+            progressive,
+            None,
+            optimize_huffman,
+        )
+    }
+
+    /// Encode pixels in the given input color space with mozjpeg
+    /// (`chroma` = None for single-component images)
+    #[allow(clippy::too_many_arguments)]
+    fn compress(
+        &self,
+        width: usize,
+        height: usize,
+        pixel_data: &[u8],
+        color_space: ColorSpace,
+        quality: Quality,
+        progressive: bool,
+        chroma: Option<ChromaSubsampling>,
+        optimize_huffman: bool,
+    ) -> InfraResult<Vec<u8>> {
+        // Create mozjpeg compressor from raw pixels (no metadata)
+        let mut comp = Compress::new(color_space);
 
         comp.set_size(width, height);
 
+        // Optimize scans for better compression
+        // This analyzes the image to find optimal scan progression
+        // (va primero: set_scan_optimization_mode llama a jpeg_set_defaults,
+        // que restablecería la calidad y el modo progresivo)
+        comp.set_scan_optimization_mode(ScanMode::AllComponentsTogether);
+        comp.set_optimize_scans(progressive && optimize_huffman);
+
+        // Submuestreo de color (también restablecido por jpeg_set_defaults)
+        if let Some(chroma) = chroma {
+            let (cb, cr) = Self::chroma_pixel_sizes(chroma.for_quality(quality));
+            comp.set_chroma_sampling_pixel_sizes(cb, cr);
+        }
+
         // Map quality slider to actual JPEG quality for better compression
         // This provides more aggressive compression while maintaining visual quality
         let jpeg_quality = self.map_quality_to_jpeg(quality);
@@ -34,18 +110,16 @@ impl JpegOptimizer {
         // STRATEGY 2: Advanced mozjpeg optimizations for 5-15% additional compression
 
         // Enable progressive encoding for better compression and progressive loading
-        // Progressive JPEGs load gradually (useful for web) and compress better
-        comp.set_progressive_mode();
-
-        // Optimize scans for better compression
-        // This analyzes the image to find optimal scan progression
-        comp.set_scan_optimization_mode(ScanMode::AllComponentsTogether);
-        comp.set_optimize_scans(true);
+        // Progressive JPEGs load gradually (useful for web) and compress better.
+        // Without it, set_optimize_scans(false) cleared the progressive scan script
+        // left by jpeg_set_defaults: one sequential scan (baseline)
+        if progressive {
+            comp.set_progressive_mode();
+        }
 
-        // Enable trellis quantization for better compression at same quality
-        // This uses dynamic programming to find optimal quantization for each DCT block
-        // Adds ~5-10% compression with minimal processing overhead
-        comp.set_optimize_coding(true);
+        // Huffman tables computed for this image instead of the standard ones
+        // (an extra pass over the coefficients; libjpeg always does it for progressive)
+        comp.set_optimize_coding(optimize_huffman);
 
         // Disable smoothing to reduce file size (imperceptible on photos)
         // Smoothing adds data that increases file size without visible benefit for most images
@@ -57,18 +131,21 @@ impl JpegOptimizer {
             .map_err(|e| InfraError::JpegOptimizationFailed(e.to_string()))?;
 
         // Validate data size before writing (avoid panic=abort crash)
-        if rgb_data.len() != width * height * 3 {
+        let expected = width * height * color_space.num_components();
+        if pixel_data.len() != expected {
             return Err(InfraError::JpegOptimizationFailed(format!(
-                "Invalid RGB data size: expected {}, got {}",
-                width * height * 3,
-                rgb_data.len()
+                "Invalid {:?} data size: expected {}, got {}",
+                color_space,
+                expected,
+                pixel_data.len()
             )));
         }
 
         // Escribir datos por scanlines
-        compressor
-            .write_scanlines(rgb_data)
-            .map_err(|e| InfraError::JpegOptimizationFailed(e.to_string()))?;
+        // Un fallo aquí dejaría un archivo truncado: propagarlo
+        compressor.write_scanlines(pixel_data).map_err(|e| {
+            InfraError::JpegOptimizationFailed(format!("Failed to write scanlines: {}", e))
+        })?;
 
         // Finalizar y obtener datos
         let compressed_data = compressor
@@ -83,11 +160,130 @@ impl JpegOptimizer {
         &self,
         img: &image::DynamicImage,
         quality: Quality,
+        progressive: bool,
+        chroma: ChromaSubsampling,
+        optimize_huffman: bool,
     ) -> InfraResult<Vec<u8>> {
+        let (width, height) = (img.width() as usize, img.height() as usize);
+
+        // Imágenes en escala de grises: un solo componente en lugar de RGB
+        if !img.color().has_color() {
+            let luma_img = img.to_luma8();
+            return self.optimize_grayscale(
+                width,
+                height,
+                luma_img.as_raw(),
+                quality,
+                progressive,
+                optimize_huffman,
+            );
+        }
+
         let rgb_img = img.to_rgb8();
-        let (width, height) = (rgb_img.width() as usize, rgb_img.height() as usize);
+        self.optimize(
+            width,
+            height,
+            rgb_img.as_raw(),
+            quality,
+            progressive,
+            chroma,
+            optimize_huffman,
+        )
+    }
+
+    /// Cb and Cr pixel sizes (luma pixels per chroma sample, horizontal x vertical)
+    fn chroma_pixel_sizes(chroma: ChromaSubsampling) -> ((u8, u8), (u8, u8)) {
+        match chroma {
+            ChromaSubsampling::S444 => ((1, 1), (1, 1)),
+            ChromaSubsampling::S422 => ((2, 1), (2, 1)),
+            ChromaSubsampling::S420 | ChromaSubsampling::Auto => ((2, 2), (2, 2)),
+        }
+    }
+
+    /// Rotate by a multiple of 90° and then flip a JPEG without decoding it
+    /// (jpegtran approach), in the same order as `Rotator::apply_transformations`
+    ///
+    /// DCT coefficient blocks are transposed/mirrored in place, so no generation loss
+    /// occurs. Fails if an edge that has to move isn't a multiple of the MCU size
+    /// (8 or 16 px), since the partial edge blocks can't be moved losslessly; callers
+    /// should fall back to the decode + encode path in that case rather than trim the
+    /// edge. Metadata markers are not copied.
+    pub fn transform_lossless(
+        &self,
+        data: &[u8],
+        rotation: Rotation,
+        flip_h: bool,
+        flip_v: bool,
+    ) -> InfraResult<Vec<u8>> {
+        // Voltear en vertical = girar 180° y voltear en horizontal, y el giro de 180°
+        // conmuta con todo: queda un giro seguido, como mucho, de un flip horizontal
+        let (degrees, flip_h) = if flip_v {
+            ((rotation.degrees() + 180) % 360, !flip_h)
+        } else {
+            (rotation.degrees(), flip_h)
+        };
 
-        self.optimize(width, height, rgb_img.as_raw(), quality)
+        let transform = match (degrees, flip_h) {
+            (90, false) => mozjpeg_sys::JXFORM_CODE_JXFORM_ROT_90,
+            (180, false) => mozjpeg_sys::JXFORM_CODE_JXFORM_ROT_180,
+            (270, false) => mozjpeg_sys::JXFORM_CODE_JXFORM_ROT_270,
+            (0, true) => mozjpeg_sys::JXFORM_CODE_JXFORM_FLIP_H,
+            (90, true) => mozjpeg_sys::JXFORM_CODE_JXFORM_TRANSPOSE,
+            (180, true) => mozjpeg_sys::JXFORM_CODE_JXFORM_FLIP_V,
+            (270, true) => mozjpeg_sys::JXFORM_CODE_JXFORM_TRANSVERSE,
+            _ => mozjpeg_sys::JXFORM_CODE_JXFORM_NONE,
+        };
+        Self::run_lossless(data, transform, true)
+    }
+
+    /// Rewrite a JPEG's entropy coding without decoding it (jpegtran -optimize)
+    ///
+    /// Huffman tables are optimized for the image and, if `progressive`, the scans are
+    /// rewritten as progressive. The DCT coefficients are copied untouched, so the
+    /// decoded pixels are identical to the input's. Metadata markers are not copied.
+    pub fn optimize_lossless(&self, data: &[u8], progressive: bool) -> InfraResult<Vec<u8>> {
+        Self::run_lossless(data, mozjpeg_sys::JXFORM_CODE_JXFORM_NONE, progressive)
+    }
+
+    fn run_lossless(
+        data: &[u8],
+        transform: JXFORM_CODE,
+        progressive: bool,
+    ) -> InfraResult<Vec<u8>> {
+        // Un mensaje claro para lo que ni siquiera es JPEG
+        if !data.starts_with(&[0xFF, 0xD8]) {
+            return Err(InfraError::JpegOptimizationFailed(
+                "Lossless JPEG transforms require JPEG input".to_string(),
+            ));
+        }
+
+        // native/jpeg_lossless.c: los errores fatales de libjpeg vuelven con longjmp,
+        // sin unwinding (que con panic = "abort" tumbaría la aplicación)
+        let mut out: *mut u8 = std::ptr::null_mut();
+        let mut out_size: c_ulong = 0;
+        let mut message = [0 as c_char; 256];
+        let status = unsafe {
+            ti_jpeg_transform(
+                data.as_ptr(),
+                data.len() as c_ulong,
+                transform as c_int,
+                c_int::from(progressive),
+                &mut out,
+                &mut out_size,
+                message.as_mut_ptr(),
+                message.len(),
+            )
+        };
+
+        if status != TI_JPEG_OK {
+            let message = unsafe { CStr::from_ptr(message.as_ptr()) };
+            return Err(InfraError::JpegOptimizationFailed(
+                message.to_string_lossy().to_string(),
+            ));
+        }
+        let output = unsafe { std::slice::from_raw_parts(out, out_size as usize).to_vec() };
+        unsafe { ti_jpeg_free(out) };
+        Ok(output)
     }
 
     /// Map quality slider (1-100) to actual JPEG quality for optimal compression
@@ -115,6 +311,25 @@ impl Default for JpegOptimizer {
     }
 }
 
+/// Status of `ti_jpeg_transform` when the output was written
+const TI_JPEG_OK: c_int = 0;
+
+extern "C" {
+    /// Lossless transform in native/jpeg_lossless.c (built by build.rs)
+    fn ti_jpeg_transform(
+        data: *const u8,
+        size: c_ulong,
+        transform: c_int,
+        progressive: c_int,
+        out: *mut *mut u8,
+        out_size: *mut c_ulong,
+        message: *mut c_char,
+        message_size: usize,
+    ) -> c_int;
+
+    fn ti_jpeg_free(buffer: *mut u8);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,4 +341,335 @@ mod tests {
     }
 
     // Tests con imágenes reales se harán en integration tests
+
+    fn encode_test_jpeg(width: u32, height: u32) -> Vec<u8> {
+        // Mitad izquierda negra, mitad derecha blanca
+        let img = image::RgbImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        });
+        JpegOptimizer::new()
+            .optimize_from_dynamic_image(
+                &image::DynamicImage::ImageRgb8(img),
+                Quality::default(),
+                true,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_grayscale_image_encodes_single_component() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        }));
+        let optimizer = JpegOptimizer::new();
+
+        let gray = optimizer
+            .optimize_from_dynamic_image(
+                &img.grayscale(),
+                Quality::default(),
+                true,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap();
+        let rgb = optimizer
+            .optimize_from_dynamic_image(
+                &img,
+                Quality::default(),
+                true,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap();
+
+        let decoded = image::load_from_memory(&gray).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::L8);
+        assert_eq!(
+            image::load_from_memory(&rgb).unwrap().color(),
+            image::ColorType::Rgb8
+        );
+        assert!(gray.len() < rgb.len());
+    }
+
+    #[test]
+    fn test_quality_changes_output_size() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, ((x * y) % 256) as u8])
+        }));
+        let optimizer = JpegOptimizer::new();
+        let encode = |value: u8| {
+            optimizer
+                .optimize_from_dynamic_image(
+                    &img,
+                    Quality::new(value).unwrap(),
+                    true,
+                    ChromaSubsampling::Auto,
+                    true,
+                )
+                .unwrap()
+                .len()
+        };
+
+        assert!(encode(10) < encode(100));
+    }
+
+    #[test]
+    fn test_chroma_subsampling() {
+        // Texto rojo sobre azul: el color cambia en cada píxel
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgb([220, 20, 20])
+            } else {
+                image::Rgb([20, 20, 220])
+            }
+        }));
+        let optimizer = JpegOptimizer::new();
+        let encode = |quality: Quality, chroma: ChromaSubsampling| {
+            optimizer
+                .optimize_from_dynamic_image(&img, quality, true, chroma, true)
+                .unwrap()
+        };
+        let quality = Quality::default();
+
+        let full = encode(quality, ChromaSubsampling::S444);
+        let half = encode(quality, ChromaSubsampling::S422);
+        let quarter = encode(quality, ChromaSubsampling::S420);
+        assert!(full.len() > half.len() && half.len() > quarter.len());
+        assert_eq!(encode(quality, ChromaSubsampling::Auto), quarter);
+        assert_eq!(
+            encode(Quality::maximum(), ChromaSubsampling::Auto),
+            encode(Quality::maximum(), ChromaSubsampling::S444)
+        );
+    }
+
+    #[test]
+    fn test_progressive_and_baseline_markers() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(32, 32, |x, y| {
+            image::Rgb([(x * 8) as u8, (y * 8) as u8, 64])
+        }));
+        let optimizer = JpegOptimizer::new();
+        // SOF0 = secuencial (baseline), SOF2 = progresivo
+        let has_marker = |data: &[u8], marker: u8| data.windows(2).any(|w| w == [0xFF, marker]);
+
+        let progressive = optimizer
+            .optimize_from_dynamic_image(
+                &img,
+                Quality::default(),
+                true,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap();
+        assert!(has_marker(&progressive, 0xC2));
+        assert!(!has_marker(&progressive, 0xC0));
+
+        let baseline = optimizer
+            .optimize_from_dynamic_image(
+                &img,
+                Quality::default(),
+                false,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap();
+        assert!(has_marker(&baseline, 0xC0));
+        assert!(!has_marker(&baseline, 0xC2));
+        assert_eq!(
+            image::load_from_memory(&baseline)
+                .unwrap()
+                .to_rgb8()
+                .dimensions(),
+            (32, 32)
+        );
+    }
+
+    #[test]
+    fn test_standard_huffman_tables() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, ((x + y) * 2) as u8])
+        }));
+        let optimizer = JpegOptimizer::new();
+        let encode = |progressive: bool, optimize_huffman: bool| {
+            optimizer
+                .optimize_from_dynamic_image(
+                    &img,
+                    Quality::default(),
+                    progressive,
+                    ChromaSubsampling::Auto,
+                    optimize_huffman,
+                )
+                .unwrap()
+        };
+
+        // Tablas estándar: más grande, pero decodifica igual
+        let optimized = encode(false, true);
+        let standard = encode(false, false);
+        assert!(standard.len() > optimized.len());
+        assert_eq!(
+            image::load_from_memory(&standard).unwrap().to_rgb8(),
+            image::load_from_memory(&optimized).unwrap().to_rgb8()
+        );
+
+        // Sigue siendo progresivo (SOF2)
+        let progressive = encode(true, false);
+        assert!(progressive.windows(2).any(|w| w == [0xFF, 0xC2]));
+    }
+
+    #[test]
+    fn test_rotate_lossless_90() {
+        let data = encode_test_jpeg(32, 16);
+
+        let rotated = JpegOptimizer::new()
+            .transform_lossless(&data, Rotation::Clockwise90, false, false)
+            .unwrap();
+        let img = image::load_from_memory(&rotated).unwrap().to_rgb8();
+
+        assert_eq!((img.width(), img.height()), (16, 32));
+        // La mitad izquierda (negra) queda arriba tras girar 90° horario
+        assert!(img.get_pixel(8, 4)[0] < 64);
+        assert!(img.get_pixel(8, 28)[0] > 192);
+    }
+
+    #[test]
+    fn test_rotate_lossless_180_roundtrip_is_exact() {
+        let data = encode_test_jpeg(32, 16);
+        let optimizer = JpegOptimizer::new();
+
+        let once = optimizer
+            .transform_lossless(&data, Rotation::Rotate180, false, false)
+            .unwrap();
+        let twice = optimizer
+            .transform_lossless(&once, Rotation::Rotate180, false, false)
+            .unwrap();
+
+        let original = image::load_from_memory(&data).unwrap().to_rgb8();
+        let restored = image::load_from_memory(&twice).unwrap().to_rgb8();
+        assert_eq!(original, restored);
+    }
+
+    #[test]
+    fn test_transform_lossless_matches_pixel_transforms() {
+        let img = image::RgbImage::from_fn(32, 16, |x, y| {
+            image::Rgb([(x * 8) as u8, (y * 16) as u8, ((x + y) * 4) as u8])
+        });
+        let data = JpegOptimizer::new()
+            .optimize_from_dynamic_image(
+                &image::DynamicImage::ImageRgb8(img),
+                Quality::maximum(),
+                true,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap();
+        let decoded = image::load_from_memory(&data).unwrap();
+        let rotator = crate::infrastructure::image_processor::transformers::Rotator::new();
+
+        for rotation in [
+            Rotation::None,
+            Rotation::Clockwise90,
+            Rotation::Rotate180,
+            Rotation::Clockwise270,
+        ] {
+            for (flip_h, flip_v) in [(false, false), (true, false), (false, true), (true, true)] {
+                let lossless = JpegOptimizer::new()
+                    .transform_lossless(&data, rotation, flip_h, flip_v)
+                    .unwrap();
+                let lossless = image::load_from_memory(&lossless).unwrap().to_rgb8();
+                let expected = rotator
+                    .apply_transformations(&decoded, Some(rotation), None, flip_h, flip_v)
+                    .unwrap()
+                    .to_rgb8();
+
+                assert_eq!(lossless.dimensions(), expected.dimensions());
+                // Mismos coeficientes; solo cambia el redondeo del muestreo de croma
+                let max_diff = lossless
+                    .as_raw()
+                    .iter()
+                    .zip(expected.as_raw())
+                    .map(|(a, b)| a.abs_diff(*b))
+                    .max()
+                    .unwrap();
+                assert!(
+                    max_diff <= 8,
+                    "{:?} {} {}: {}",
+                    rotation,
+                    flip_h,
+                    flip_v,
+                    max_diff
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_lossless_rejects_partial_mcu() {
+        let data = encode_test_jpeg(33, 17);
+        assert!(JpegOptimizer::new()
+            .transform_lossless(&data, Rotation::Clockwise90, false, false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_optimize_lossless_keeps_pixels() {
+        // Tablas Huffman estándar del encoder de `image`: hay margen para optimizar
+        let img = image::RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, ((x + y) * 2) as u8])
+        });
+        let mut data = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 90)
+            .encode_image(&img)
+            .unwrap();
+        let optimizer = JpegOptimizer::new();
+        let original = image::load_from_memory(&data).unwrap().to_rgb8();
+
+        for progressive in [true, false] {
+            let optimized = optimizer.optimize_lossless(&data, progressive).unwrap();
+            assert!(optimized.len() < data.len());
+            assert_eq!(
+                image::load_from_memory(&optimized).unwrap().to_rgb8(),
+                original
+            );
+            // SOF2 = progresivo, SOF0 = baseline
+            let sof = if progressive { 0xC2 } else { 0xC0 };
+            assert!(optimized.windows(2).any(|w| w == [0xFF, sof]));
+        }
+    }
+
+    #[test]
+    fn test_lossless_rejects_corrupt_header() {
+        let data = encode_test_jpeg(32, 16);
+        let optimizer = JpegOptimizer::new();
+
+        // SOF con 12 bits de precisión: error fatal de libjpeg
+        let sof = data
+            .windows(2)
+            .position(|w| w == [0xFF, 0xC0] || w == [0xFF, 0xC2])
+            .unwrap();
+        let mut bad_precision = data.clone();
+        bad_precision[sof + 4] = 12;
+        // Cabecera cortada a mitad de las tablas
+        let truncated = &data[..sof + 12];
+
+        for corrupt in [&bad_precision[..], truncated] {
+            assert!(optimizer
+                .transform_lossless(corrupt, Rotation::Clockwise90, false, false)
+                .is_err());
+            assert!(optimizer.optimize_lossless(corrupt, true).is_err());
+        }
+        // Y el optimizador sigue funcionando después
+        assert!(optimizer.optimize_lossless(&data, true).is_ok());
+    }
+
+    #[test]
+    fn test_rotate_lossless_rejects_non_jpeg() {
+        assert!(JpegOptimizer::new()
+            .transform_lossless(b"not a jpeg", Rotation::Rotate180, false, false)
+            .is_err());
+    }
 }
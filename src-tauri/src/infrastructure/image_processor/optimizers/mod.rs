@@ -1,7 +1,11 @@
+mod alpha;
+mod bit_depth;
 mod jpeg_optimizer;
 mod png_optimizer;
 mod webp_optimizer;
 
+pub use alpha::flatten_alpha;
+pub use bit_depth::to_8bit;
 pub use jpeg_optimizer::JpegOptimizer;
 pub use png_optimizer::PngOptimizer;
 pub use webp_optimizer::WebpOptimizer;
@@ -1,9 +1,13 @@
 use crate::domain::value_objects::Quality;
 use crate::infrastructure::error::{InfraError, InfraResult};
+use image::DynamicImage;
+use imagequant::RGBA;
 use indexmap::IndexSet;
-use oxipng::{Deflaters, Interlacing, Options, RowFilter, StripChunks};
+use oxipng::{
+    BitDepth, ColorType, Deflaters, Interlacing, Options, RawImage, RowFilter, StripChunks,
+};
 
-/// PNG lossless optimizer using oxipng
+/// PNG optimizer using oxipng (lossless), with optional imagequant palette quantization
 pub struct PngOptimizer;
 
 impl PngOptimizer {
@@ -13,7 +17,19 @@ impl PngOptimizer {
 
     /// Optimize PNG image data
     pub fn optimize(&self, input_data: &[u8], quality: Quality) -> InfraResult<Vec<u8>> {
-        let options = self.create_options(quality);
+        self.optimize_with_depth(input_data, quality, false)
+    }
+
+    /// Optimize PNG image data; with `force_8bit`, 16-bit channels are scaled down
+    /// to 8 bits (lossy) instead of kept
+    pub fn optimize_with_depth(
+        &self,
+        input_data: &[u8],
+        quality: Quality,
+        force_8bit: bool,
+    ) -> InfraResult<Vec<u8>> {
+        let mut options = self.create_options(quality);
+        options.scale_16 = force_8bit;
 
         // oxipng optimiza desde memoria
         match oxipng::optimize_from_memory(input_data, &options) {
@@ -22,6 +38,54 @@ impl PngOptimizer {
         }
     }
 
+    /// Quantize to a palette of at most 256 colors, then optimize with oxipng.
+    /// The palette carries alpha, so transparency survives quantization.
+    /// `quality` is the imagequant target (lower = fewer colors) and oxipng effort.
+    pub fn optimize_lossy(&self, img: &DynamicImage, quality: Quality) -> InfraResult<Vec<u8>> {
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let pixels: Vec<RGBA> = rgba
+            .pixels()
+            .map(|p| RGBA::new(p[0], p[1], p[2], p[3]))
+            .collect();
+
+        let (palette, indices) = Self::quantize(pixels, width, height, quality).map_err(|e| {
+            InfraError::PngOptimizationFailed(format!("Quantization failed: {}", e))
+        })?;
+
+        let palette = palette
+            .into_iter()
+            .map(|c| oxipng::RGBA8::new(c.r, c.g, c.b, c.a))
+            .collect();
+        let raw = RawImage::new(
+            width,
+            height,
+            ColorType::Indexed { palette },
+            BitDepth::Eight,
+            indices,
+        )
+        .map_err(|e| InfraError::PngOptimizationFailed(e.to_string()))?;
+
+        raw.create_optimized_png(&self.create_options(quality))
+            .map_err(|e| InfraError::PngOptimizationFailed(e.to_string()))
+    }
+
+    /// Palette and per-pixel palette indices, dithered
+    fn quantize(
+        pixels: Vec<RGBA>,
+        width: u32,
+        height: u32,
+        quality: Quality,
+    ) -> Result<(Vec<RGBA>, Vec<u8>), imagequant::Error> {
+        let mut attributes = imagequant::new();
+        attributes.set_quality(0, quality.value())?;
+
+        let mut image = attributes.new_image(pixels, width as usize, height as usize, 0.0)?;
+        let mut result = attributes.quantize(&mut image)?;
+        result.set_dithering_level(1.0)?;
+        result.remapped(&mut image)
+    }
+
     /// Create oxipng options based on quality
     ///
     /// Maps the quality slider to oxipng optimization levels (0-6).
@@ -174,4 +238,45 @@ mod tests {
 
         // Si compila, el test pasa
     }
+
+    /// Captura de pantalla simulada: degradado de fondo, paneles y una zona transparente
+    fn screenshot() -> DynamicImage {
+        DynamicImage::ImageRgba8(image::RgbaImage::from_fn(800, 600, |x, y| {
+            if x < 40 && y < 40 {
+                image::Rgba([0, 0, 0, 0])
+            } else if (100..700).contains(&x) && (80..200).contains(&y) {
+                image::Rgba([245, 245, 245, 255])
+            } else {
+                image::Rgba([(x / 4) as u8, (y / 3) as u8, ((x + y) / 6) as u8, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_lossy_shrinks_screenshot() {
+        let optimizer = PngOptimizer::new();
+        let img = screenshot();
+        let quality = Quality::default();
+
+        let mut bytes = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+        let lossless = optimizer.optimize(&bytes, quality).unwrap();
+        let lossy = optimizer.optimize_lossy(&img, quality).unwrap();
+
+        assert!(
+            lossy.len() * 2 < lossless.len(),
+            "lossy {} bytes vs lossless {} bytes",
+            lossy.len(),
+            lossless.len()
+        );
+
+        let decoded = image::load_from_memory(&lossy).unwrap().to_rgba8();
+        assert_eq!(decoded.dimensions(), (800, 600));
+        assert_eq!(decoded.get_pixel(10, 10)[3], 0);
+        assert_eq!(decoded.get_pixel(400, 400)[3], 255);
+    }
 }
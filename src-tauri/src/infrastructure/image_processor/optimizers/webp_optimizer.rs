@@ -1,34 +1,123 @@
 use crate::domain::value_objects::Quality;
-use crate::infrastructure::error::InfraResult;
-use image::DynamicImage;
+use crate::infrastructure::error::{InfraError, InfraResult};
+use image::{DynamicImage, RgbaImage};
 use webp::Encoder;
 
 /// WebP optimizer backed by libwebp via the `webp` crate.
 pub struct WebpOptimizer;
 
 impl WebpOptimizer {
+    /// Quality range `optimize_to_size` searches (libwebp scale)
+    pub const SIZE_SEARCH_MIN_QUALITY: u8 = 40;
+    pub const SIZE_SEARCH_MAX_QUALITY: u8 = 95;
+    /// Fraction under the limit `encode_image` accepts as close enough
+    pub const SIZE_TOLERANCE: f32 = 0.05;
+    /// Largest width or height libwebp encodes
+    const MAX_DIMENSION: u32 = 16383;
+
     pub fn new() -> Self {
         Self
     }
 
-    /// Encode the incoming image as WebP using lossy or lossless mode according to the requested quality.
+    /// Encode the incoming image as WebP, lossless when requested, otherwise lossy at `quality`.
     ///
     /// Creates a fresh WebP file from pixel data only - no metadata is copied.
     /// This ensures the output is clean and optimized without EXIF/XMP.
-    pub fn optimize(&self, image: &DynamicImage, quality: Quality) -> InfraResult<Vec<u8>> {
+    pub fn optimize(
+        &self,
+        image: &DynamicImage,
+        quality: Quality,
+        lossless: bool,
+    ) -> InfraResult<Vec<u8>> {
         // Convert to RGBA because the encoder expects packed RGB(A) buffers.
         let rgba = image.to_rgba8();
         // Create WebP encoder from raw RGBA pixels (no metadata)
-        let encoder = Encoder::from_rgba(rgba.as_raw(), rgba.width(), rgba.height());
+        let encoder = Self::encoder(&rgba)?;
 
-        // Use near-lossless for very high quality targets, otherwise standard lossy encoding.
-        let encoded = if quality.value() >= 98 {
-            encoder.encode_lossless()
+        // Sin pérdida, la "calidad" es el esfuerzo de compresión: el 75 de libwebp
+        let quality = if lossless {
+            75.0
         } else {
-            encoder.encode(self.map_quality(quality))
+            self.map_quality(quality)
         };
+        Self::encode(&encoder, lossless, quality)
+    }
+
+    /// Encode lossy at the highest libwebp quality between `SIZE_SEARCH_MIN_QUALITY` and
+    /// `SIZE_SEARCH_MAX_QUALITY` whose output fits in `target_bytes`, binary-searching
+    /// the quality. Stops early once the output is within `tolerance * target_bytes`
+    /// under the target (0.05 = 5%).
+    pub fn optimize_to_size(
+        &self,
+        image: &DynamicImage,
+        target_bytes: usize,
+        tolerance: f32,
+    ) -> InfraResult<Vec<u8>> {
+        let rgba = image.to_rgba8();
+        let encoder = Self::encoder(&rgba)?;
+        let encode = |quality: u8| Self::encode(&encoder, false, f32::from(quality));
+        let slack = (tolerance.max(0.0) * target_bytes as f32) as usize;
+
+        let mut high = Self::SIZE_SEARCH_MAX_QUALITY;
+        let best = encode(high)?;
+        if best.len() <= target_bytes {
+            return Ok(best);
+        }
+
+        let mut low = Self::SIZE_SEARCH_MIN_QUALITY;
+        let mut best = encode(low)?;
+        if best.len() > target_bytes {
+            return Err(InfraError::CannotMeetSizeTarget {
+                achieved: best.len(),
+                requested: target_bytes,
+            });
+        }
 
-        Ok(encoded.to_vec())
+        // Invariante: `low` cabe, `high` no
+        while high - low > 1 && target_bytes - best.len() > slack {
+            let mid = low + (high - low) / 2;
+            let data = encode(mid)?;
+            if data.len() <= target_bytes {
+                low = mid;
+                best = data;
+            } else {
+                high = mid;
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Encoder over `rgba`, after checking what libwebp would otherwise choke on
+    /// (zero or oversized dimensions, a buffer that doesn't match them)
+    fn encoder(rgba: &RgbaImage) -> InfraResult<Encoder<'_>> {
+        let (width, height) = rgba.dimensions();
+        if width == 0 || height == 0 || width > Self::MAX_DIMENSION || height > Self::MAX_DIMENSION
+        {
+            return Err(InfraError::WebpOptimizationFailed(format!(
+                "Invalid dimensions {}x{}: each side must be 1-{}",
+                width,
+                height,
+                Self::MAX_DIMENSION
+            )));
+        }
+        let expected = width as usize * height as usize * 4;
+        if rgba.as_raw().len() != expected {
+            return Err(InfraError::WebpOptimizationFailed(format!(
+                "Pixel buffer has {} bytes, expected {}",
+                rgba.as_raw().len(),
+                expected
+            )));
+        }
+        Ok(Encoder::from_rgba(rgba.as_raw(), width, height))
+    }
+
+    /// Encode through libwebp's checked entry point instead of the panicking one
+    fn encode(encoder: &Encoder, lossless: bool, quality: f32) -> InfraResult<Vec<u8>> {
+        encoder
+            .encode_simple(lossless, quality)
+            .map(|data| data.to_vec())
+            .map_err(|e| InfraError::WebpOptimizationFailed(format!("{:?}", e)))
     }
 
     fn map_quality(&self, quality: Quality) -> f32 {
@@ -49,3 +138,68 @@ impl Default for WebpOptimizer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    /// Ruido: el tamaño depende mucho de la calidad
+    fn noisy_image() -> DynamicImage {
+        let mut state = 12345u32;
+        DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |_, _| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let [r, g, b, _] = state.to_le_bytes();
+            Rgb([r, g, b])
+        }))
+    }
+
+    #[test]
+    fn test_zero_dimension_image_is_an_error() {
+        let optimizer = WebpOptimizer::new();
+        let empty = DynamicImage::new_rgba8(0, 0);
+
+        assert!(matches!(
+            optimizer.optimize(&empty, Quality::default(), false),
+            Err(InfraError::WebpOptimizationFailed(_))
+        ));
+        assert!(matches!(
+            optimizer.optimize(&DynamicImage::new_rgb8(16, 0), Quality::default(), true),
+            Err(InfraError::WebpOptimizationFailed(_))
+        ));
+        assert!(matches!(
+            optimizer.optimize_to_size(&empty, 1000, 0.0),
+            Err(InfraError::WebpOptimizationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_optimize_to_size() {
+        let optimizer = WebpOptimizer::new();
+        let img = noisy_image();
+
+        // Con límite de sobra: calidad máxima
+        let largest = optimizer.optimize_to_size(&img, usize::MAX, 0.0).unwrap();
+
+        // Un byte no cabe nunca: el error informa el tamaño a calidad mínima
+        let smallest = match optimizer.optimize_to_size(&img, 1, 0.0) {
+            Err(InfraError::CannotMeetSizeTarget {
+                achieved,
+                requested,
+            }) => {
+                assert_eq!(requested, 1);
+                achieved
+            }
+            other => panic!(
+                "expected CannotMeetSizeTarget, got {:?}",
+                other.map(|d| d.len())
+            ),
+        };
+        assert!(smallest < largest.len());
+
+        let target = (smallest + largest.len()) / 2;
+        let fitted = optimizer.optimize_to_size(&img, target, 0.0).unwrap();
+        assert!(fitted.len() <= target);
+        assert!(fitted.len() >= smallest);
+    }
+}
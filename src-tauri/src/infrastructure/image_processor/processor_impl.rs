@@ -1,18 +1,93 @@
+use image::metadata::Orientation;
 use image::{DynamicImage, ImageFormat as ImageCrateFormat};
+use parking_lot::Mutex;
+use std::borrow::Cow;
 use std::fs;
 use std::io::Cursor;
+use std::ops::AddAssign;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
 
+use crate::domain::models::{
+    ImageMetadata, PngBitDepth, ResizeFilter, ResizeTransformation, Rotation,
+};
 use crate::domain::{
-    Dimensions, DomainError, DomainResult, Image, ImageFormat, ImageProcessor, ProcessingSettings,
-    RawQualityMode, Transformation,
+    Dimensions, DomainError, DomainResult, Image, ImageFormat, ImageProcessor, LoadMode,
+    ProcessingSettings, Quality, Transformation,
 };
 use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::file_system::{FileHandler, SidecarHandler};
 use crate::infrastructure::image_processor::optimizers::{
-    JpegOptimizer, PngOptimizer, WebpOptimizer,
+    flatten_alpha, to_8bit, JpegOptimizer, PngOptimizer, WebpOptimizer,
+};
+use crate::infrastructure::image_processor::transformers::{
+    ColorAdjuster, ImageFilter, Resizer, Rotator, Sharpener, ToneAdjuster, Trimmer, Watermarker,
 };
-use crate::infrastructure::image_processor::transformers::{Resizer, Rotator};
-use crate::infrastructure::image_processor::RawProcessor;
+use crate::infrastructure::image_processor::{CacheKey, HeicDecoder, ImageCache, RawProcessor};
+use crate::infrastructure::metadata_reader::MetadataReader;
+
+/// What `ImageProcessorImpl::load_image` reads besides dimensions
+#[derive(Debug, Clone, Copy)]
+struct LoadOptions {
+    /// Read camera metadata from RAW files (slower than dimensions only)
+    read_raw_metadata: bool,
+    /// Report post-EXIF-orientation dimensions
+    auto_orient: bool,
+}
+
+impl LoadOptions {
+    /// RAW metadata when preserving it, oriented dimensions when auto-orienting
+    fn for_settings(settings: &ProcessingSettings) -> Self {
+        Self {
+            read_raw_metadata: settings.preserve_metadata(),
+            auto_orient: settings.auto_orient(),
+        }
+    }
+}
+
+/// Time spent in each phase of producing one output, in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    /// Reading and decoding the input (shared by every output of the image)
+    pub decode_ms: u64,
+    /// Transformations, long-edge cap and the resize to this output's width
+    pub transform_ms: u64,
+    /// Encoding and optimizing (lossless JPEG rewrites count here)
+    pub encode_ms: u64,
+    /// Writing the output file
+    pub write_ms: u64,
+}
+
+impl PhaseTimings {
+    /// Milliseconds since `started`
+    pub fn since(started: Instant) -> u64 {
+        started.elapsed().as_millis() as u64
+    }
+}
+
+impl AddAssign for PhaseTimings {
+    fn add_assign(&mut self, other: Self) {
+        self.decode_ms += other.decode_ms;
+        self.transform_ms += other.transform_ms;
+        self.encode_ms += other.encode_ms;
+        self.write_ms += other.write_ms;
+    }
+}
+
+/// Encoded output bytes plus how they were produced
+#[derive(Debug, Clone, Default)]
+pub struct EncodedImage {
+    pub data: Vec<u8>,
+    /// Quality the output was encoded at (None = not re-encoded, e.g. lossless rotation,
+    /// or chosen by the encoder to fit `max_output_bytes`)
+    pub quality: Option<u8>,
+    /// Whether the output fits `target_size_bytes` (None = no target set)
+    pub target_met: Option<bool>,
+    /// Time spent decoding, transforming and encoding it (`write_ms` is 0)
+    pub timings: PhaseTimings,
+}
 
 /// Main image processor implementation
 pub struct ImageProcessorImpl {
@@ -21,10 +96,33 @@ pub struct ImageProcessorImpl {
     webp_optimizer: WebpOptimizer,
     resizer: Resizer,
     rotator: Rotator,
-    raw_processor: RawProcessor,
+    tone_adjuster: ToneAdjuster,
+    color_adjuster: ColorAdjuster,
+    image_filter: ImageFilter,
+    sharpener: Sharpener,
+    watermarker: Watermarker,
+    trimmer: Trimmer,
+    /// What `load_image` reads besides dimensions
+    load_options: LoadOptions,
+    /// Decoded images shared between processors (None = always decode)
+    image_cache: Option<Arc<Mutex<ImageCache>>>,
+    /// Aborts RAW decodes in progress when raised
+    cancel_signal: Option<Arc<AtomicBool>>,
 }
 
 impl ImageProcessorImpl {
+    /// Lowest quality the target size search goes down to
+    const TARGET_MIN_QUALITY: u8 = 30;
+    /// Maximum encodes of the target size search after the first two
+    const TARGET_SEARCH_STEPS: usize = 6;
+    /// Bytes per RGB byte at full quality for a typical photo saved as JPEG
+    const ESTIMATE_JPEG_FACTOR: f64 = 0.12;
+    /// Lossy WebP size relative to JPEG at the same quality
+    const ESTIMATE_WEBP_TO_JPEG: f64 = 0.75;
+    /// RGB size over lossless output size for photos (PNG / lossless WebP)
+    const ESTIMATE_PNG_RATIO: f64 = 2.0;
+    const ESTIMATE_WEBP_LOSSLESS_RATIO: f64 = 2.6;
+
     pub fn new() -> Self {
         Self {
             png_optimizer: PngOptimizer::new(),
@@ -32,29 +130,142 @@ impl ImageProcessorImpl {
             webp_optimizer: WebpOptimizer::new(),
             resizer: Resizer::new(),
             rotator: Rotator::new(),
-            raw_processor: RawProcessor::new(),
+            tone_adjuster: ToneAdjuster::new(),
+            color_adjuster: ColorAdjuster::new(),
+            image_filter: ImageFilter::new(),
+            sharpener: Sharpener::new(),
+            watermarker: Watermarker::new(),
+            trimmer: Trimmer::new(),
+            load_options: LoadOptions {
+                read_raw_metadata: false,
+                auto_orient: true,
+            },
+            image_cache: None,
+            cancel_signal: None,
         }
     }
 
+    /// Create a processor that also reads RAW camera metadata when loading images
+    /// (used when the settings ask to preserve metadata)
+    pub fn with_preserve_metadata(preserve_metadata: bool) -> Self {
+        let mut processor = Self::new();
+        processor.load_options.read_raw_metadata = preserve_metadata;
+        processor
+    }
+
+    /// Create a processor whose `load_image` matches how `settings` decode images
+    /// (RAW metadata when preserving it, oriented dimensions when auto-orienting)
+    pub fn for_settings(settings: &ProcessingSettings) -> Self {
+        Self {
+            load_options: LoadOptions::for_settings(settings),
+            ..Self::new()
+        }
+    }
+
+    /// `load_image` as a processor made with `for_settings(settings)` would do it,
+    /// so one processor can load images for batches with different settings
+    pub fn load_image_for_settings(
+        &self,
+        path: &Path,
+        settings: &ProcessingSettings,
+    ) -> DomainResult<Image> {
+        self.describe_image(
+            path,
+            LoadMode::FullRaw,
+            None,
+            LoadOptions::for_settings(settings),
+        )
+    }
+
+    /// Decode images for `process_variants` through this cache (when the settings
+    /// enable `use_cache`)
+    pub fn with_image_cache(mut self, cache: Arc<Mutex<ImageCache>>) -> Self {
+        self.image_cache = Some(cache);
+        self
+    }
+
+    /// Abort RAW decodes in progress when `signal` is raised (they fail with
+    /// `DomainError::Cancelled`); other formats decode too fast to need it
+    pub fn with_cancel_signal(mut self, signal: Arc<AtomicBool>) -> Self {
+        self.cancel_signal = Some(signal);
+        self
+    }
+
+    /// `load_dynamic_image` through the cache, if there is one and the settings enable
+    /// `use_cache`. The lock isn't held while decoding, so parallel workers may both
+    /// decode a file that isn't cached yet.
+    fn decode(&self, path: &Path, settings: &ProcessingSettings) -> InfraResult<DynamicImage> {
+        let cache = match self.image_cache {
+            Some(ref cache) if settings.use_cache() => cache,
+            _ => return self.load_dynamic_image(path, settings),
+        };
+
+        let key = CacheKey::new(path, settings);
+        if let Some(image) = cache.lock().get(&key) {
+            return Ok((*image).clone());
+        }
+        let image = self.load_dynamic_image(path, settings)?;
+        cache.lock().insert(key, Arc::new(image.clone()));
+        Ok(image)
+    }
+
     /// Load DynamicImage from file
-    fn load_dynamic_image(&self, path: &Path, raw_quality_mode: RawQualityMode) -> InfraResult<DynamicImage> {
-        // Check if it's a RAW file
-        if let Some(ext) = path.extension() {
-            let ext_str = ext.to_string_lossy().to_string();
-            if RawProcessor::is_raw_format(&ext_str) {
-                // Use RAW processor
-                return self.raw_processor.process_raw(path, raw_quality_mode);
+    fn load_dynamic_image(
+        &self,
+        path: &Path,
+        settings: &ProcessingSettings,
+    ) -> InfraResult<DynamicImage> {
+        match Self::detect_format(path).ok().map(|(format, _)| format) {
+            // Use RAW processor (stateless, barato de crear)
+            Some(ImageFormat::Raw) => {
+                return RawProcessor::with_params(settings.raw_params().clone())
+                    .with_cancel_signal(self.cancel_signal.clone())
+                    .process_raw(path, settings.raw_quality_mode());
+            }
+            // HEIC/HEIF via libheif
+            Some(ImageFormat::Heic) => {
+                return HeicDecoder::new().decode(path, settings.heic_image_index());
             }
+            _ => {}
         }
 
-        // Use standard image decoder for other formats
-        image::open(path).map_err(|e| {
+        // Use standard image decoder for other formats (por contenido, no por extensión).
+        // Un solo read: los mismos bytes sirven para decodificar y para el EXIF
+        let data = fs::read(path).map_err(|e| {
             InfraError::ImageReadError(format!(
                 "Failed to open image file '{}': {}",
                 path.display(),
                 e
             ))
-        })
+        })?;
+        let mut img = image::ImageReader::new(Cursor::new(&data))
+            .with_guessed_format()
+            .map_err(image::ImageError::IoError)
+            .and_then(|reader| reader.decode())
+            .map_err(|e| {
+                InfraError::DecodeError(format!(
+                    "Failed to decode image file '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        // LibRaw y libheif ya orientan; el decoder estándar ignora la etiqueta EXIF
+        if settings.auto_orient() {
+            if let Some(orientation) = Self::exif_orientation(&data) {
+                img.apply_orientation(orientation);
+            }
+        }
+
+        Ok(img)
+    }
+
+    /// EXIF orientation of the file contents, if any
+    fn exif_orientation(data: &[u8]) -> Option<Orientation> {
+        MetadataReader::new()
+            .read_orientation_from_bytes(data)
+            .and_then(|value| u8::try_from(value).ok())
+            .and_then(Orientation::from_exif)
     }
 
     /// Convert domain ImageFormat to image crate format
@@ -65,18 +276,151 @@ impl ImageProcessorImpl {
             ImageFormat::Webp => ImageCrateFormat::WebP,
             ImageFormat::Gif => ImageCrateFormat::Gif,
             ImageFormat::Raw => ImageCrateFormat::Jpeg, // RAW se convierte a JPEG por defecto
+            ImageFormat::Heic => ImageCrateFormat::Jpeg,
+        }
+    }
+
+    /// Encode at `quality` (see `ProcessingSettings::quality_for`); with a target size,
+    /// lossy formats search for the highest quality up to it that fits
+    fn encode_output(
+        &self,
+        img: &DynamicImage,
+        format: ImageFormat,
+        quality: Quality,
+        settings: &ProcessingSettings,
+    ) -> InfraResult<EncodedImage> {
+        if settings.lossless() && !format.supports_lossless() {
+            return Err(DomainError::LosslessNotSupported(format.to_string()).into());
+        }
+
+        let data = self.encode_image(img, format, quality, settings)?;
+
+        // WebP con límite estricto: la calidad ya la eligió `optimize_to_size`
+        if format == ImageFormat::Webp
+            && !settings.lossless()
+            && settings.max_output_bytes().is_some()
+        {
+            return Ok(EncodedImage {
+                target_met: settings
+                    .target_size_bytes()
+                    .map(|target| data.len() as u64 <= target),
+                data,
+                quality: None,
+                ..Default::default()
+            });
+        }
+
+        let Some(target) = settings.target_size_bytes() else {
+            return Ok(EncodedImage {
+                data,
+                quality: Some(quality.value()),
+                target_met: None,
+                ..Default::default()
+            });
+        };
+
+        // PNG/GIF y el modo sin pérdida no dependen de la calidad: lo mejor posible,
+        // marcando si cabe
+        let lossy = !settings.lossless()
+            && matches!(
+                format,
+                ImageFormat::Jpeg | ImageFormat::Webp | ImageFormat::Raw | ImageFormat::Heic
+            );
+        if data.len() as u64 <= target || !lossy {
+            return Ok(EncodedImage {
+                target_met: Some(data.len() as u64 <= target),
+                data,
+                quality: Some(quality.value()),
+                ..Default::default()
+            });
+        }
+
+        self.search_quality(img, format, quality, settings, target, data)
+    }
+
+    /// Binary search for the highest quality between `TARGET_MIN_QUALITY` and
+    /// `quality` (whose output, `too_large`, exceeds `target`). If even the
+    /// minimum doesn't fit, its output is returned as the best possible.
+    fn search_quality(
+        &self,
+        img: &DynamicImage,
+        format: ImageFormat,
+        quality: Quality,
+        settings: &ProcessingSettings,
+        target: u64,
+        too_large: Vec<u8>,
+    ) -> InfraResult<EncodedImage> {
+        let mut high = quality.value();
+        let mut low = Self::TARGET_MIN_QUALITY.min(high);
+        if low == high {
+            return Ok(EncodedImage {
+                data: too_large,
+                quality: Some(high),
+                target_met: Some(false),
+                ..Default::default()
+            });
+        }
+
+        let encode = |quality: u8| -> InfraResult<Vec<u8>> {
+            self.encode_image(img, format, Quality::new(quality)?, settings)
+        };
+
+        let floor = encode(low)?;
+        if floor.len() as u64 > target {
+            return Ok(EncodedImage {
+                data: floor,
+                quality: Some(low),
+                target_met: Some(false),
+                ..Default::default()
+            });
+        }
+
+        // Invariante: `low` cabe, `high` no
+        let mut best = floor;
+        for _ in 0..Self::TARGET_SEARCH_STEPS {
+            if high - low <= 1 {
+                break;
+            }
+            let mid = low + (high - low) / 2;
+            let data = encode(mid)?;
+            if data.len() as u64 <= target {
+                low = mid;
+                best = data;
+            } else {
+                high = mid;
+            }
         }
+
+        Ok(EncodedImage {
+            data: best,
+            quality: Some(low),
+            target_met: Some(true),
+            ..Default::default()
+        })
     }
 
-    /// Encode image to bytes
+    /// Encode image to bytes at `quality`
     fn encode_image(
         &self,
         img: &DynamicImage,
         format: ImageFormat,
+        quality: Quality,
         settings: &ProcessingSettings,
     ) -> InfraResult<Vec<u8>> {
+        // Los encoders fallan con errores confusos (o panic) por encima de su límite
+        Dimensions::new(img.width(), img.height())?.clamp_to_codec_limits(format)?;
+
         let output = match format {
+            ImageFormat::Png if settings.png_lossy() && !settings.lossless() => {
+                self.png_optimizer.optimize_lossy(img, quality)?
+            }
             ImageFormat::Png => {
+                let force_8bit = settings.png_bit_depth() == PngBitDepth::Force8bit;
+                let img = if force_8bit {
+                    to_8bit(img)
+                } else {
+                    Cow::Borrowed(img)
+                };
                 let mut bytes = Vec::new();
                 let mut cursor = Cursor::new(&mut bytes);
                 img.write_to(&mut cursor, ImageCrateFormat::Png)
@@ -89,17 +433,33 @@ impl ImageProcessorImpl {
                         ))
                     })?;
                 // oxipng optimization with built-in metadata stripping
-                self.png_optimizer.optimize(&bytes, settings.quality())?
+                self.png_optimizer
+                    .optimize_with_depth(&bytes, quality, force_8bit)?
             }
-            ImageFormat::Jpeg | ImageFormat::Raw => {
+            ImageFormat::Jpeg | ImageFormat::Raw | ImageFormat::Heic => {
+                // JPEG no tiene alpha: componer sobre el color de fondo
+                let opaque = flatten_alpha(img, settings.background_color());
                 // mozjpeg creates fresh JPEG from RGB data (no EXIF copied)
-                self.jpeg_optimizer
-                    .optimize_from_dynamic_image(img, settings.quality())?
-            }
-            ImageFormat::Webp => {
-                // WebP encoder creates fresh file from pixel data (no EXIF)
-                self.webp_optimizer.optimize(img, settings.quality())?
+                self.jpeg_optimizer.optimize_from_dynamic_image(
+                    &opaque,
+                    quality,
+                    settings.progressive(),
+                    settings.jpeg_chroma(),
+                    settings.jpeg_optimize_huffman(),
+                )?
             }
+            // WebP encoder creates fresh file from pixel data (no EXIF)
+            ImageFormat::Webp => match settings.max_output_bytes() {
+                // Límite estricto: la calidad configurada no cuenta
+                Some(limit) if !settings.lossless() => self.webp_optimizer.optimize_to_size(
+                    img,
+                    limit,
+                    WebpOptimizer::SIZE_TOLERANCE,
+                )?,
+                _ => self
+                    .webp_optimizer
+                    .optimize(img, quality, settings.lossless())?,
+            },
             ImageFormat::Gif => {
                 let mut bytes = Vec::new();
                 let mut cursor = Cursor::new(&mut bytes);
@@ -133,28 +493,110 @@ impl ImageProcessorImpl {
         img: &DynamicImage,
         transformation: &Transformation,
         original_dimensions: &Dimensions,
+        orientation: Option<&Transformation>,
     ) -> InfraResult<DynamicImage> {
         let mut result = img.clone();
+        let mut source_dimensions = *original_dimensions;
+
+        // Enderezar según EXIF antes que nada: la rotación pedida se suma a esta
+        if let Some(orientation) = orientation {
+            result = self.rotator.apply_transformations(
+                &result,
+                orientation.rotation(),
+                None,
+                orientation.flip_horizontal,
+                orientation.flip_vertical,
+            )?;
+            source_dimensions = Dimensions::new(result.width(), result.height())?;
+        }
+
+        // Recortar bordes antes que nada; el resize parte del tamaño recortado
+        if transformation.trim {
+            result = self.trimmer.trim(&result, transformation.trim_tolerance);
+            source_dimensions = Dimensions::new(result.width(), result.height())?;
+        }
 
         // Aplicar resize si existe
         if let Some(resize) = transformation.resize() {
-            result = self.resizer.resize(&result, resize, original_dimensions)?;
+            result = self.resizer.resize(&result, resize, &source_dimensions)?;
+        }
+
+        // Ajustes de tono después del resize (menos píxeles que procesar)
+        if let Some(tonal) = transformation.tonal() {
+            result = self.tone_adjuster.apply(&result, tonal);
+        }
+
+        // Filtros a la resolución final (después del resize)
+        if let Some(sigma) = transformation.blur() {
+            result = self.image_filter.blur(&result, sigma);
         }
 
         // Aplicar rotaciones y flips
         result = self.rotator.apply_transformations(
             &result,
             transformation.rotation(),
+            transformation.arbitrary_rotation(),
             transformation.flip_horizontal,
             transformation.flip_vertical,
         )?;
 
+        // Conversión de color después de rotaciones y flips
+        if let Some(adjustment) = transformation.color_adjustment() {
+            result = self.color_adjuster.apply(&result, adjustment);
+        }
+
+        // Marca de agua al final para que su tamaño y orientación sean predecibles
+        if let Some(watermark) = transformation.watermark() {
+            result = self.watermarker.apply(&result, watermark)?;
+        }
+        if let Some(text_watermark) = transformation.text_watermark() {
+            result = self.watermarker.apply_text(&result, text_watermark)?;
+        }
+
+        // Escala de grises al final (también afecta a las marcas de agua)
+        if transformation.grayscale {
+            result = result.grayscale();
+        }
+
+        // Enfoque como último paso, justo antes de codificar
+        if let Some(sharpen) = transformation.sharpen() {
+            result = self.sharpener.apply(&result, sharpen);
+        }
+
         Ok(result)
     }
-}
 
-impl ImageProcessor for ImageProcessorImpl {
-    fn load_image(&self, path: &Path) -> DomainResult<Image> {
+    /// Format of a file by its contents, falling back to the extension when the signature
+    /// isn't recognized. The second value is the extension's format when it disagrees.
+    fn detect_format(path: &Path) -> DomainResult<(ImageFormat, Option<ImageFormat>)> {
+        Self::resolve_format(path, FileHandler::sniff_format(path))
+    }
+
+    /// `detect_format` with the format the contents say (None = unrecognized)
+    fn resolve_format(
+        path: &Path,
+        by_content: Option<ImageFormat>,
+    ) -> DomainResult<(ImageFormat, Option<ImageFormat>)> {
+        let by_extension =
+            ImageFormat::from_extension(path.extension().and_then(|s| s.to_str()).unwrap_or(""));
+        match (by_content, by_extension) {
+            (Some(content), Ok(extension)) if content != extension => {
+                Ok((content, Some(extension)))
+            }
+            (Some(content), _) => Ok((content, None)),
+            (None, by_extension) => by_extension.map(|format| (format, None)),
+        }
+    }
+
+    /// `load_image_with_mode`, taking the format, dimensions, size and EXIF from `data`
+    /// when the file was already read (RAW and HEIC libraries still open the file)
+    fn describe_image(
+        &self,
+        path: &Path,
+        mode: LoadMode,
+        data: Option<&[u8]>,
+        options: LoadOptions,
+    ) -> DomainResult<Image> {
         // Verificar que el archivo existe
         if !path.exists() {
             return Err(DomainError::FileNotFound(
@@ -162,9 +604,11 @@ impl ImageProcessor for ImageProcessorImpl {
             ));
         }
 
-        // Detectar formato primero
-        let format =
-            ImageFormat::from_extension(path.extension().and_then(|s| s.to_str()).unwrap_or(""))?;
+        // Detectar formato primero: el contenido manda sobre la extensión
+        let (format, extension_format) = match data {
+            Some(data) => Self::resolve_format(path, ImageFormat::from_magic_bytes(data).ok())?,
+            None => Self::detect_format(path)?,
+        };
 
         // Obtener dimensiones según el tipo de archivo
         let dimensions = if format.is_raw() {
@@ -178,63 +622,547 @@ impl ImageProcessor for ImageProcessorImpl {
             // let (width, height) = (dynamic_img.width(), dynamic_img.height());
             // Dimensions::new(width, height)?
 
-            let (width, height) = RawProcessor::get_raw_metadata(&path)
+            let preview = match mode {
+                LoadMode::EmbeddedPreview => Self::embedded_preview_dimensions(path),
+                LoadMode::FullRaw => None,
+            };
+            let (width, height) = match preview {
+                Some(dimensions) => dimensions,
+                None => RawProcessor::get_raw_metadata(path)
+                    .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?,
+            };
+            Dimensions::new(width, height)?
+        } else if format == ImageFormat::Heic {
+            // HEIC: dimensiones de la imagen primaria desde el contenedor
+            let (width, height) = HeicDecoder::get_dimensions(path, 0)
                 .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
             Dimensions::new(width, height)?
         } else {
             // Para formatos estándar: OPTIMIZACIÓN - leer SOLO metadata sin decodificar
             // Esto es MUCHO más rápido que decodificar toda la imagen
-            let reader = image::ImageReader::open(path)
-                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
-
-            // Obtener dimensiones SIN decodificar
-            let dimensions_result = reader
-                .into_dimensions()
-                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
+            let dimensions_result = match data {
+                Some(data) => image::ImageReader::new(Cursor::new(data))
+                    .with_guessed_format()
+                    .map_err(image::ImageError::IoError)
+                    .and_then(|reader| reader.into_dimensions()),
+                None => image::ImageReader::open(path)
+                    .and_then(|reader| reader.with_guessed_format())
+                    .map_err(image::ImageError::IoError)
+                    // Obtener dimensiones SIN decodificar
+                    .and_then(|reader| reader.into_dimensions()),
+            }
+            .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
             let (width, height) = dimensions_result;
             Dimensions::new(width, height)?
         };
 
         // Obtener metadata del archivo (tamaño)
-        let metadata_fs =
-            fs::metadata(path).map_err(|e| DomainError::InvalidFilePath(e.to_string()))?;
-        let size_bytes = metadata_fs.len();
+        let size_bytes = match data {
+            Some(data) => data.len() as u64,
+            None => fs::metadata(path)
+                .map_err(|e| DomainError::InvalidFilePath(e.to_string()))?
+                .len(),
+        };
+
+        // EXIF, IPTC y XMP para JPEG/PNG/WebP (y RAW vía LibRaw si se preserva
+        // metadata, más su sidecar .xmp); si no se puede leer, la imagen carga igual sin metadata
+        let metadata = match format {
+            ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Webp => {
+                let reader = MetadataReader::new();
+                match data {
+                    Some(data) => Some(reader.read_metadata_from_bytes(data)),
+                    None => reader.read_metadata(path).ok(),
+                }
+                .filter(|metadata| !metadata.is_empty())
+            }
+            ImageFormat::Raw => {
+                let mut metadata = if options.read_raw_metadata {
+                    RawProcessor::get_raw_camera_metadata(path)
+                        .unwrap_or_else(|_| ImageMetadata::empty())
+                } else {
+                    ImageMetadata::empty()
+                };
+                // Rating, etiqueta y título de Lightroom/darktable viven en el .xmp
+                if let Ok(sidecar) = SidecarHandler::read(path) {
+                    sidecar.merge_into(&mut metadata);
+                }
+                Some(metadata).filter(|metadata| !metadata.is_empty())
+            }
+            ImageFormat::Gif | ImageFormat::Heic => None,
+        };
+
+        // Orientaciones EXIF 5-8 intercambian ancho y alto al decodificar
+        let dimensions = match metadata.as_ref().and_then(|m| m.orientation) {
+            Some(5..=8) if options.auto_orient => {
+                Dimensions::new(dimensions.height(), dimensions.width())?
+            }
+            _ => dimensions,
+        };
 
         // Crear Image (solo metadata, no la imagen decodificada para formatos estándar)
-        let image = Image::new(
-            path.to_path_buf(),
+        let mut image = Image::new(path.to_path_buf(), format, dimensions, size_bytes, metadata)?;
+        image.set_extension_format(extension_format);
+
+        Ok(image)
+    }
+
+    /// Dimensions of the preview embedded in a RAW file (None = no usable preview)
+    fn embedded_preview_dimensions(path: &Path) -> Option<(u32, u32)> {
+        let jpeg = RawProcessor::extract_embedded_jpeg(path).ok()?;
+        image::ImageReader::with_format(Cursor::new(jpeg), ImageCrateFormat::Jpeg)
+            .into_dimensions()
+            .ok()
+    }
+
+    /// Right-angle rotation and flips, as (rotation, flip_h, flip_v), that can be applied
+    /// to a JPEG without decoding, i.e. the transformation requests nothing else
+    fn lossless_orientation(
+        image: &Image,
+        transformation: &Transformation,
+    ) -> Option<(Rotation, bool, bool)> {
+        if image.format() != ImageFormat::Jpeg {
+            return None;
+        }
+
+        let rotation = transformation.rotation().unwrap_or(Rotation::None);
+        let (flip_h, flip_v) = (transformation.flip_horizontal, transformation.flip_vertical);
+        let only_orientation = (rotation != Rotation::None || flip_h || flip_v)
+            // Con una etiqueta EXIF por aplicar, la rotación pedida va encima de ella
+            && !(transformation.auto_orient && Self::auto_orient(image).is_some())
+            && !transformation.trim
+            && transformation.resize().is_none()
+            && transformation.arbitrary_rotation().is_none()
+            && transformation.watermark().is_none()
+            && transformation.text_watermark().is_none()
+            && !transformation.grayscale
+            && transformation.color_adjustment().is_none()
+            && transformation.tonal().is_none()
+            && transformation.sharpen().is_none()
+            && transformation.blur().is_none();
+
+        only_orientation.then_some((rotation, flip_h, flip_v))
+    }
+
+    /// Decode and transform once, then encode for each of `formats`.
+    /// The outer error means the image couldn't be decoded or transformed;
+    /// each format's encode can fail on its own.
+    pub fn process_formats(
+        &self,
+        image: &Image,
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+        formats: &[ImageFormat],
+    ) -> DomainResult<Vec<DomainResult<Vec<u8>>>> {
+        let variants: Vec<(Option<u32>, ImageFormat)> =
+            formats.iter().map(|&format| (None, format)).collect();
+        let outputs = self.process_variants(image, transformation, settings, &variants)?;
+        Ok(outputs
+            .into_iter()
+            .map(|output| output.map(|encoded| encoded.data))
+            .collect())
+    }
+
+    /// Like `process_formats`, but each output may also be downscaled to a width
+    /// (None = transformed size, aspect ratio preserved). Widths are resized from
+    /// the largest down, each from the previous intermediate.
+    pub fn process_variants(
+        &self,
+        image: &Image,
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+        variants: &[(Option<u32>, ImageFormat)],
+    ) -> DomainResult<Vec<DomainResult<EncodedImage>>> {
+        // Transformaciones que no cambian nada: solo re-encodear
+        let transformation = transformation.filter(|t| !t.is_identity(image.dimensions()));
+
+        // JPEG -> JPEG sin pérdida (rotación u optimización): evita decodificar y re-encodear
+        // (si no cabe en el tamaño objetivo se re-encodea)
+        let started = Instant::now();
+        let lossless = if variants.contains(&(None, ImageFormat::Jpeg)) {
+            self.lossless_output(image, transformation, settings)
+                .filter(|bytes| {
+                    settings
+                        .target_size_bytes()
+                        .map_or(true, |target| bytes.len() as u64 <= target)
+                })
+                .map(|data| EncodedImage {
+                    data,
+                    quality: None,
+                    target_met: settings.target_size_bytes().map(|_| true),
+                    timings: PhaseTimings {
+                        encode_ms: PhaseTimings::since(started),
+                        ..Default::default()
+                    },
+                })
+        } else {
+            None
+        };
+        if let Some(ref encoded) = lossless {
+            if variants.iter().all(|&v| v == (None, ImageFormat::Jpeg)) {
+                return Ok(variants.iter().map(|_| Ok(encoded.clone())).collect());
+            }
+        }
+
+        // Cargar imagen (los fallos de lectura quedan como `ReadFailed` para reintentar)
+        let started = Instant::now();
+        let mut dynamic_img = self.decode(image.path(), settings)?;
+        let decode_ms = PhaseTimings::since(started);
+
+        // Aplicar transformaciones si existen
+        let started = Instant::now();
+        if let Some(trans) = transformation {
+            let orientation = Self::pending_orientation(image, trans, settings);
+            dynamic_img = self
+                .apply_transformations(
+                    &dynamic_img,
+                    trans,
+                    image.dimensions(),
+                    orientation.as_ref(),
+                )
+                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
+        }
+
+        // Límite del lado mayor de los ajustes: solo reduce
+        if let Some(edge) = settings.max_long_edge() {
+            dynamic_img = self
+                .cap_long_edge(&dynamic_img, edge)
+                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
+        }
+
+        let transform_ms = PhaseTimings::since(started);
+
+        // `resize_ms`: reducción al ancho de la variante
+        let encode = |img: &DynamicImage, format: ImageFormat, resize_ms: u64| {
+            let started = Instant::now();
+            let mut encoded = self
+                .encode_output(
+                    img,
+                    format,
+                    settings.quality_for(format, image.format()),
+                    settings,
+                )
+                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
+            encoded.timings = PhaseTimings {
+                decode_ms,
+                transform_ms: transform_ms + resize_ms,
+                encode_ms: PhaseTimings::since(started),
+                write_ms: 0,
+            };
+            Ok(encoded)
+        };
+
+        // Optimizar y encodear cada variante a partir de la misma imagen
+        let mut outputs: Vec<Option<DomainResult<EncodedImage>>> = variants
+            .iter()
+            .map(|&(width, format)| match (width, &lossless) {
+                (None, Some(encoded)) if format == ImageFormat::Jpeg => Some(Ok(encoded.clone())),
+                (None, _) => Some(encode(&dynamic_img, format, 0)),
+                (Some(_), _) => None,
+            })
+            .collect();
+
+        // Anchos de mayor a menor: cada uno se reduce desde el anterior
+        let mut widths: Vec<u32> = variants.iter().filter_map(|&(width, _)| width).collect();
+        widths.sort_unstable_by(|a, b| b.cmp(a));
+        widths.dedup();
+
+        let filter = transformation
+            .and_then(|t| t.resize())
+            .map_or(ResizeFilter::Lanczos3, |resize| resize.filter());
+        let (full_width, full_height) = (dynamic_img.width(), dynamic_img.height());
+        let mut intermediate = dynamic_img;
+
+        for width in widths {
+            let height =
+                ((width as f64 * full_height as f64 / full_width as f64).round() as u32).max(1);
+            let started = Instant::now();
+            let sized = Dimensions::new(width, height)
+                .map_err(InfraError::from)
+                .and_then(|target| {
+                    let current = Dimensions::new(intermediate.width(), intermediate.height())?;
+                    self.resizer.resize(
+                        &intermediate,
+                        &ResizeTransformation::new(target, false, filter),
+                        &current,
+                    )
+                });
+            let resize_ms = PhaseTimings::since(started);
+
+            for (output, &(variant_width, format)) in outputs.iter_mut().zip(variants) {
+                if variant_width != Some(width) {
+                    continue;
+                }
+                *output = Some(match sized {
+                    Ok(ref img) => encode(img, format, resize_ms),
+                    Err(ref e) => Err(DomainError::UnsupportedTransformation(e.to_string())),
+                });
+            }
+
+            if let Ok(img) = sized {
+                intermediate = img;
+            }
+        }
+
+        Ok(outputs.into_iter().flatten().collect())
+    }
+
+    /// Process an image held in memory into the primary output format of `settings`,
+    /// without touching the filesystem. The input format is `hint_format`, or detected
+    /// from the magic bytes. RAW and HEIC are refused: their decoders read files.
+    pub fn process_in_memory(
+        &self,
+        data: &[u8],
+        hint_format: Option<ImageFormat>,
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+    ) -> DomainResult<Vec<u8>> {
+        let input_format = match hint_format {
+            Some(format) => format,
+            None => ImageFormat::from_magic_bytes(data)?,
+        };
+        if matches!(input_format, ImageFormat::Raw | ImageFormat::Heic) {
+            return Err(DomainError::InvalidImageFormat(format!(
+                "{} images can only be processed from a file",
+                input_format
+            )));
+        }
+
+        let mut img = image::load_from_memory_with_format(data, Self::convert_format(input_format))
+            .map_err(|e| DomainError::DecodeFailed(e.to_string()))?;
+
+        // Sin `Image` que guarde la orientación: se endereza al decodificar
+        if settings.auto_orient() || transformation.is_some_and(|t| t.auto_orient) {
+            if let Some(orientation) = Self::exif_orientation(data) {
+                img.apply_orientation(orientation);
+            }
+        }
+
+        let dimensions = Dimensions::new(img.width(), img.height())?;
+        if let Some(trans) = transformation.filter(|t| !t.is_identity(&dimensions)) {
+            img = self
+                .apply_transformations(&img, trans, &dimensions, None)
+                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
+        }
+        if let Some(edge) = settings.max_long_edge() {
+            img = self
+                .cap_long_edge(&img, edge)
+                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
+        }
+
+        let format = settings.determine_output_format(input_format);
+        let encoded = self.encode_output(
+            &img,
             format,
-            dimensions,
-            size_bytes,
-            None, // Metadata EXIF se agregará en Fase 7
+            settings.quality_for(format, input_format),
+            settings,
         )?;
+        Ok(encoded.data)
+    }
 
-        Ok(image)
+    /// Rough total size of every output `settings` produce for `image`, without
+    /// decoding or encoding anything. Heuristics for typical photos: screenshots,
+    /// noise or transformations can be far off, so use it only for disk space planning.
+    pub fn estimate_output_size(
+        &self,
+        image: &Image,
+        settings: &ProcessingSettings,
+    ) -> DomainResult<u64> {
+        let dimensions = image.dimensions();
+        let mut total = 0;
+        for (width, format) in settings.determine_output_variants(image.format()) {
+            let quality = f64::from(settings.quality_for(format, image.format()).as_normalized());
+            // Mismo tamaño que process_variants: límite del lado mayor y luego el ancho
+            let (mut w, mut h) = (
+                f64::from(dimensions.width()),
+                f64::from(dimensions.height()),
+            );
+            if let Some(edge) = settings.max_long_edge() {
+                let scale = (f64::from(edge) / w.max(h)).min(1.0);
+                w *= scale;
+                h *= scale;
+            }
+            if let Some(width) = width {
+                h *= f64::from(width) / w;
+                w = f64::from(width);
+            }
+            let rgb_bytes = (w.round().max(1.0) * h.round().max(1.0)) * 3.0;
+
+            let jpeg = rgb_bytes * quality * Self::ESTIMATE_JPEG_FACTOR;
+            let estimate = match format {
+                ImageFormat::Webp if settings.lossless() => {
+                    rgb_bytes / Self::ESTIMATE_WEBP_LOSSLESS_RATIO
+                }
+                ImageFormat::Webp => jpeg * Self::ESTIMATE_WEBP_TO_JPEG,
+                // Paleta de 256 colores: un byte por píxel
+                ImageFormat::Png if settings.png_lossy() && !settings.lossless() => {
+                    rgb_bytes / 3.0 / Self::ESTIMATE_PNG_RATIO
+                }
+                ImageFormat::Gif => rgb_bytes / 3.0 / Self::ESTIMATE_PNG_RATIO,
+                ImageFormat::Png => rgb_bytes / Self::ESTIMATE_PNG_RATIO,
+                ImageFormat::Jpeg | ImageFormat::Raw | ImageFormat::Heic => jpeg,
+            };
+
+            let estimate = estimate.round() as u64;
+            total += settings
+                .target_size_bytes()
+                .map_or(estimate, |target| estimate.min(target));
+        }
+
+        Ok(total)
+    }
+
+    /// Downscale so the longest side is at most `edge` (never enlarges)
+    fn cap_long_edge(&self, img: &DynamicImage, edge: u32) -> InfraResult<DynamicImage> {
+        let cap = ResizeTransformation::with_longest_edge(edge, ResizeFilter::Lanczos3)?;
+        let current = Dimensions::new(img.width(), img.height())?;
+        self.resizer.resize(img, &cap, &current)
+    }
+
+    /// JPEG output produced at the coefficient level, without decoding: rotated or flipped if
+    /// the transformation is nothing else, or with optimized entropy coding if there is no
+    /// transformation and the quality is maximum (nothing asks to give up detail).
+    /// With a pending EXIF orientation, only in that last case, turned upright with
+    /// `auto_orient` (the output doesn't keep the tag).
+    fn lossless_output(
+        &self,
+        image: &Image,
+        transformation: Option<&Transformation>,
+        settings: &ProcessingSettings,
+    ) -> Option<Vec<u8>> {
+        let orientation = if settings.auto_orient() || transformation.is_some_and(|t| t.auto_orient)
+        {
+            Self::auto_orient(image)
+        } else {
+            None
+        };
+        // La salida sin pérdida conserva el tamaño original
+        let exceeds_cap = settings.max_long_edge().is_some_and(|edge| {
+            let dimensions = image.dimensions();
+            dimensions.width().max(dimensions.height()) > edge
+        });
+        if exceeds_cap {
+            return None;
+        }
+
+        let max_quality = settings.quality() == Quality::maximum();
+        match (transformation, orientation) {
+            // Combinar la orientación con los cambios pedidos: vía decodificación
+            (Some(_), Some(_)) => None,
+            (None, Some(ref orientation)) if settings.use_lossless_rotation() && max_quality => {
+                let orientation = Self::lossless_orientation(image, orientation)?;
+                self.try_transform_lossless(image, orientation)
+            }
+            (None, Some(_)) => None,
+            (Some(transformation), None) if settings.use_lossless_rotation() => {
+                let orientation = Self::lossless_orientation(image, transformation)?;
+                self.try_transform_lossless(image, orientation)
+            }
+            (Some(_), None) => None,
+            (None, None) if image.format() == ImageFormat::Jpeg && max_quality => {
+                // Si libjpeg rechaza el archivo, None: se decodifica y se re-encodea
+                let data = fs::read(image.path()).ok()?;
+                self.jpeg_optimizer
+                    .optimize_lossless(&data, settings.progressive())
+                    .ok()
+            }
+            (None, None) => None,
+        }
+    }
+
+    /// Transformation that turns an image with an EXIF orientation tag upright
+    /// (None = no tag, or already upright)
+    pub fn auto_orient(image: &Image) -> Option<Transformation> {
+        let orientation = image.metadata()?.orientation?;
+        let (rotation, flip_horizontal) =
+            Rotation::from_exif_orientation(u16::try_from(orientation).ok()?)?;
+        if rotation == Rotation::None && !flip_horizontal {
+            return None;
+        }
+
+        let mut transformation = Transformation::with_rotation(rotation);
+        transformation.flip_horizontal = flip_horizontal;
+        Some(transformation)
+    }
+
+    /// Orientation `transformation.auto_orient` still has to apply after decoding:
+    /// None when decoding already did it (the settings' `auto_orient`, and LibRaw and
+    /// libheif always do)
+    fn pending_orientation(
+        image: &Image,
+        transformation: &Transformation,
+        settings: &ProcessingSettings,
+    ) -> Option<Transformation> {
+        let decoder_orients = settings.auto_orient()
+            || matches!(image.format(), ImageFormat::Raw | ImageFormat::Heic);
+        if !transformation.auto_orient || decoder_orients {
+            return None;
+        }
+        Self::auto_orient(image)
+    }
+
+    /// Rotate and flip the JPEG file at the coefficient level.
+    /// Returns None if it can't be done losslessly (e.g. size not a multiple of the MCU)
+    fn try_transform_lossless(
+        &self,
+        image: &Image,
+        (rotation, flip_h, flip_v): (Rotation, bool, bool),
+    ) -> Option<Vec<u8>> {
+        let data = fs::read(image.path()).ok()?;
+        self.jpeg_optimizer
+            .transform_lossless(&data, rotation, flip_h, flip_v)
+            .ok()
+    }
+}
+
+impl ImageProcessor for ImageProcessorImpl {
+    fn load_image_with_mode(&self, path: &Path, mode: LoadMode) -> DomainResult<Image> {
+        self.describe_image(path, mode, None, self.load_options)
+    }
+
+    fn load_image_with_bytes(&self, path: &Path) -> DomainResult<(Image, Vec<u8>)> {
+        if !path.exists() {
+            return Err(DomainError::FileNotFound(
+                path.to_string_lossy().to_string(),
+            ));
+        }
+
+        let data = fs::read(path).map_err(|e| DomainError::ReadFailed(e.to_string()))?;
+        let image = self.describe_image(path, LoadMode::FullRaw, Some(&data), self.load_options)?;
+        Ok((image, data))
     }
 
     fn optimize(&self, image: &Image, settings: &ProcessingSettings) -> DomainResult<Vec<u8>> {
         // Cargar imagen
         let dynamic_img = self
-            .load_dynamic_image(image.path(), settings.raw_quality_mode())
+            .load_dynamic_image(image.path(), settings)
             .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
 
         // Determinar formato de salida
         let output_format = settings.determine_output_format(image.format());
 
         // Encodear y optimizar
-        self.encode_image(&dynamic_img, output_format, settings)
+        let quality = settings.quality_for(output_format, image.format());
+        self.encode_output(&dynamic_img, output_format, quality, settings)
+            .map(|encoded| encoded.data)
             .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))
     }
 
     fn transform(&self, image: &Image, transformation: &Transformation) -> DomainResult<Vec<u8>> {
-        // Cargar imagen
+        // Rotación y flips JPEG sin pérdida (sin decodificar)
+        if let Some(orientation) = Self::lossless_orientation(image, transformation) {
+            if let Some(bytes) = self.try_transform_lossless(image, orientation) {
+                return Ok(bytes);
+            }
+        }
+
+        // Cargar imagen (ya orientada: los ajustes por defecto aplican la etiqueta EXIF)
         let dynamic_img = self
-            .load_dynamic_image(image.path(), RawQualityMode::Balanced)
+            .load_dynamic_image(image.path(), &ProcessingSettings::default())
             .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
 
         // Aplicar transformaciones
         let transformed = self
-            .apply_transformations(&dynamic_img, transformation, image.dimensions())
+            .apply_transformations(&dynamic_img, transformation, image.dimensions(), None)
             .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
 
         // Encodear (sin optimización especial)
@@ -253,24 +1181,10 @@ impl ImageProcessor for ImageProcessorImpl {
         transformation: Option<&Transformation>,
         settings: &ProcessingSettings,
     ) -> DomainResult<Vec<u8>> {
-        // Cargar imagen
-        let mut dynamic_img = self
-            .load_dynamic_image(image.path(), settings.raw_quality_mode())
-            .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
-
-        // Aplicar transformaciones si existen
-        if let Some(trans) = transformation {
-            dynamic_img = self
-                .apply_transformations(&dynamic_img, trans, image.dimensions())
-                .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))?;
-        }
-
-        // Determinar formato de salida
         let output_format = settings.determine_output_format(image.format());
-
-        // Optimizar y encodear
-        self.encode_image(&dynamic_img, output_format, settings)
-            .map_err(|e| DomainError::UnsupportedTransformation(e.to_string()))
+        let mut outputs =
+            self.process_formats(image, transformation, settings, &[output_format])?;
+        outputs.remove(0)
     }
 
     fn save_image(
@@ -284,8 +1198,11 @@ impl ImageProcessor for ImageProcessorImpl {
             fs::create_dir_all(parent).map_err(|e| DomainError::InvalidFilePath(e.to_string()))?;
         }
 
-        // Escribir archivo
-        fs::write(output_path, data).map_err(|e| DomainError::InvalidFilePath(e.to_string()))?;
+        // Escribir a un temporal y renombrar: nunca queda un archivo a medias
+        FileHandler::write_atomic(output_path, data).map_err(|e| match e {
+            InfraError::DiskFull(_) => DomainError::from(e),
+            other => DomainError::WriteFailed(other.to_string()),
+        })?;
 
         Ok(())
     }
@@ -296,3 +1213,585 @@ impl Default for ImageProcessorImpl {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChromaSubsampling;
+    use image::{Rgb, RgbImage};
+
+    /// Ruido pseudoaleatorio: el tamaño JPEG depende mucho de la calidad
+    fn noisy_image() -> DynamicImage {
+        let mut state = 12345u32;
+        DynamicImage::ImageRgb8(RgbImage::from_fn(128, 128, |_, _| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let [r, g, b, _] = state.to_le_bytes();
+            Rgb([r, g, b])
+        }))
+    }
+
+    fn settings_with_target(target: u64) -> ProcessingSettings {
+        let mut settings = ProcessingSettings::default();
+        settings.set_target_size_bytes(Some(target));
+        settings
+    }
+
+    #[test]
+    fn test_estimate_output_size() {
+        let processor = ImageProcessorImpl::new();
+        let image = Image::new(
+            "/photos/a.jpg".into(),
+            ImageFormat::Jpeg,
+            Dimensions::new(4000, 3000).unwrap(),
+            3_000_000,
+            None,
+        )
+        .unwrap();
+        let estimate = |settings: &ProcessingSettings| {
+            processor.estimate_output_size(&image, settings).unwrap()
+        };
+
+        let mut settings = ProcessingSettings::default();
+        let jpeg = estimate(&settings);
+        // 12 MP * 3 * 0.85 * 0.12
+        assert_eq!(jpeg, 3_672_000);
+
+        settings.set_quality(Quality::new(50).unwrap());
+        assert!(estimate(&settings) < jpeg);
+
+        settings.set_output_format(Some(ImageFormat::Webp));
+        let webp = estimate(&settings);
+        settings.set_output_format(Some(ImageFormat::Jpeg));
+        assert!(webp < estimate(&settings));
+
+        settings.set_output_format(Some(ImageFormat::Png));
+        let png = estimate(&settings);
+        settings.set_png_lossy(true);
+        assert!(estimate(&settings) < png);
+
+        // Dos anchos (paleta, un byte por píxel / 2): la suma de ambas salidas
+        settings.set_sizes(vec![400, 800]);
+        assert_eq!(estimate(&settings), 60_000 + 240_000);
+
+        settings
+            .set_sizes(Vec::new())
+            .set_target_size_bytes(Some(1000));
+        assert_eq!(estimate(&settings), 1000);
+    }
+
+    #[test]
+    fn test_webp_max_output_bytes() {
+        let processor = ImageProcessorImpl::new();
+        let img = noisy_image();
+        let mut settings = ProcessingSettings::default();
+        settings.set_max_output_bytes(Some(1));
+
+        assert!(matches!(
+            processor.encode_output(&img, ImageFormat::Webp, settings.quality(), &settings),
+            Err(InfraError::CannotMeetSizeTarget { requested: 1, .. })
+        ));
+
+        // Solo WebP: el límite no afecta a JPEG
+        assert!(processor
+            .encode_output(&img, ImageFormat::Jpeg, settings.quality(), &settings)
+            .is_ok());
+
+        settings.set_max_output_bytes(Some(usize::MAX));
+        let encoded = processor
+            .encode_output(&img, ImageFormat::Webp, settings.quality(), &settings)
+            .unwrap();
+        assert_eq!(encoded.quality, None);
+        assert_eq!(encoded.target_met, None);
+    }
+
+    #[test]
+    fn test_png_force_8bit() {
+        let processor = ImageProcessorImpl::new();
+        // Bytes altos y bajos distintos: reducir a 8 bits pierde información
+        let img = DynamicImage::ImageRgb16(image::ImageBuffer::from_fn(16, 16, |x, y| {
+            Rgb([x as u16 * 4099, y as u16 * 4099, 1234])
+        }));
+        let encode = |settings: &ProcessingSettings| {
+            let data = processor
+                .encode_image(&img, ImageFormat::Png, Quality::default(), settings)
+                .unwrap();
+            image::load_from_memory(&data).unwrap().color()
+        };
+
+        let mut settings = ProcessingSettings::default();
+        assert_eq!(encode(&settings), image::ColorType::Rgb16);
+
+        settings.set_png_bit_depth(PngBitDepth::Force8bit);
+        assert_eq!(encode(&settings), image::ColorType::Rgb8);
+    }
+
+    #[test]
+    fn test_target_size_search_converges_under_target() {
+        let processor = ImageProcessorImpl::new();
+        let img = noisy_image();
+        let size_at = |quality: u8| {
+            processor
+                .encode_image(
+                    &img,
+                    ImageFormat::Jpeg,
+                    Quality::new(quality).unwrap(),
+                    &ProcessingSettings::default(),
+                )
+                .unwrap()
+                .len() as u64
+        };
+        // Alcanzable: entre el tamaño a la calidad mínima y a la configurada
+        let target = (size_at(ImageProcessorImpl::TARGET_MIN_QUALITY) + size_at(85)) / 2;
+
+        let settings = settings_with_target(target);
+        let encoded = processor
+            .encode_output(&img, ImageFormat::Jpeg, settings.quality(), &settings)
+            .unwrap();
+
+        assert_eq!(encoded.target_met, Some(true));
+        assert!(encoded.data.len() as u64 <= target);
+        let quality = encoded.quality.unwrap();
+        assert!((ImageProcessorImpl::TARGET_MIN_QUALITY..85).contains(&quality));
+
+        // Convergió: una calidad más ya no cabe
+        assert!(size_at(quality + 1) > target);
+    }
+
+    #[test]
+    fn test_target_size_already_met_keeps_quality() {
+        let processor = ImageProcessorImpl::new();
+        let encoded = processor
+            .encode_output(
+                &noisy_image(),
+                ImageFormat::Webp,
+                Quality::default(),
+                &settings_with_target(u64::MAX),
+            )
+            .unwrap();
+
+        assert_eq!(encoded.quality, Some(85));
+        assert_eq!(encoded.target_met, Some(true));
+    }
+
+    #[test]
+    fn test_unreachable_target_is_flagged() {
+        let processor = ImageProcessorImpl::new();
+        let settings = settings_with_target(100);
+
+        let jpeg = processor
+            .encode_output(
+                &noisy_image(),
+                ImageFormat::Jpeg,
+                settings.quality(),
+                &settings,
+            )
+            .unwrap();
+        assert_eq!(jpeg.target_met, Some(false));
+        assert_eq!(jpeg.quality, Some(ImageProcessorImpl::TARGET_MIN_QUALITY));
+
+        let png = processor
+            .encode_output(
+                &noisy_image(),
+                ImageFormat::Png,
+                settings.quality(),
+                &settings,
+            )
+            .unwrap();
+        assert_eq!(png.target_met, Some(false));
+        assert_eq!(png.quality, Some(85));
+    }
+
+    #[test]
+    fn test_lossless_refuses_jpeg() {
+        let processor = ImageProcessorImpl::new();
+        let mut settings = ProcessingSettings::default();
+        settings.set_lossless(true);
+
+        let result = processor.encode_output(
+            &noisy_image(),
+            ImageFormat::Jpeg,
+            settings.quality(),
+            &settings,
+        );
+        assert!(matches!(
+            result,
+            Err(InfraError::DomainError(DomainError::LosslessNotSupported(
+                _
+            )))
+        ));
+
+        let png = processor
+            .encode_output(
+                &noisy_image(),
+                ImageFormat::Png,
+                settings.quality(),
+                &settings,
+            )
+            .unwrap();
+        let decoded = image::load_from_memory(&png.data).unwrap().to_rgb8();
+        assert_eq!(decoded, noisy_image().to_rgb8());
+    }
+
+    #[test]
+    fn test_encode_rejects_dimensions_over_format_limit() {
+        let processor = ImageProcessorImpl::new();
+        let settings = ProcessingSettings::default();
+        let wide = DynamicImage::ImageRgb8(RgbImage::new(16384, 1));
+
+        let result =
+            processor.encode_output(&wide, ImageFormat::Webp, settings.quality(), &settings);
+        assert!(matches!(
+            result,
+            Err(InfraError::DomainError(
+                DomainError::DimensionsExceedFormatLimit { .. }
+            ))
+        ));
+        // Llega intacto a la capa de dominio, con el límite para ajustar
+        let error = processor
+            .encode_output(&wide, ImageFormat::Webp, settings.quality(), &settings)
+            .unwrap_err();
+        assert_eq!(
+            DomainError::from(error),
+            DomainError::DimensionsExceedFormatLimit {
+                format: ImageFormat::Webp,
+                limit: Dimensions::new(16383, 16383).unwrap(),
+                actual: Dimensions::new(16384, 1).unwrap(),
+            }
+        );
+        assert!(processor
+            .encode_output(&wide, ImageFormat::Png, settings.quality(), &settings)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_max_long_edge_caps_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.png");
+        RgbImage::new(64, 32).save(&path).unwrap();
+
+        let processor = ImageProcessorImpl::new();
+        let image = processor.load_image(&path).unwrap();
+        let mut settings = ProcessingSettings::default();
+
+        for (edge, expected) in [(16, (16, 8)), (128, (64, 32))] {
+            settings.set_max_long_edge(Some(edge));
+            let outputs = processor
+                .process_variants(&image, None, &settings, &[(None, ImageFormat::Png)])
+                .unwrap();
+            let data = &outputs[0].as_ref().unwrap().data;
+            let decoded = image::load_from_memory(data).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), expected);
+        }
+    }
+
+    #[test]
+    fn test_jpeg_without_transformation_optimized_losslessly() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        noisy_image().to_rgb8().save(&path).unwrap();
+
+        let processor = ImageProcessorImpl::new();
+        let image = processor.load_image(&path).unwrap();
+        let original = image::open(&path).unwrap().to_rgb8();
+        let mut settings = ProcessingSettings::default();
+        settings.set_quality(Quality::maximum());
+
+        let outputs = processor
+            .process_variants(&image, None, &settings, &[(None, ImageFormat::Jpeg)])
+            .unwrap();
+        let encoded = outputs[0].as_ref().unwrap();
+        assert_eq!(encoded.quality, None);
+        assert!((encoded.data.len() as u64) < image.size_bytes());
+        assert_eq!(
+            image::load_from_memory(&encoded.data).unwrap().to_rgb8(),
+            original
+        );
+
+        // Por debajo del máximo se re-encodea
+        settings.set_quality(Quality::default());
+        let outputs = processor
+            .process_variants(&image, None, &settings, &[(None, ImageFormat::Jpeg)])
+            .unwrap();
+        assert!(outputs[0].as_ref().unwrap().quality.is_some());
+    }
+
+    #[test]
+    fn test_jpeg_rejected_by_libjpeg_is_reencoded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        let processor = ImageProcessorImpl::new();
+
+        // Progresivo sin tablas Huffman antes del primer scan: libjpeg lo rechaza, el
+        // decodificador de image usa las tablas estándar
+        let data = processor
+            .jpeg_optimizer
+            .optimize_from_dynamic_image(
+                &noisy_image(),
+                Quality::maximum(),
+                true,
+                ChromaSubsampling::Auto,
+                true,
+            )
+            .unwrap();
+        let mut stripped = data[..2].to_vec();
+        let mut i = 2;
+        while data[i + 1] != 0xDA {
+            let length = usize::from(u16::from_be_bytes([data[i + 2], data[i + 3]]));
+            if data[i + 1] != 0xC4 {
+                stripped.extend_from_slice(&data[i..i + 2 + length]);
+            }
+            i += 2 + length;
+        }
+        stripped.extend_from_slice(&data[i..]);
+        fs::write(&path, &stripped).unwrap();
+
+        assert!(processor
+            .jpeg_optimizer
+            .optimize_lossless(&stripped, true)
+            .is_err());
+        let image = processor.load_image(&path).unwrap();
+        let mut settings = ProcessingSettings::default();
+        settings.set_quality(Quality::maximum());
+
+        let outputs = processor
+            .process_variants(&image, None, &settings, &[(None, ImageFormat::Jpeg)])
+            .unwrap();
+        let encoded = outputs[0].as_ref().unwrap();
+        assert!(encoded.quality.is_some());
+        assert!(image::load_from_memory(&encoded.data).is_ok());
+    }
+
+    #[test]
+    fn test_jpeg_rotate_and_flip_without_reencoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let processor = ImageProcessorImpl::new();
+        let settings = ProcessingSettings::default();
+        // Girar y voltear en vertical mueve los dos bordes (transversa)
+        let mut transformation = Transformation::with_rotation(Rotation::Clockwise90);
+        transformation.set_flip_vertical(true);
+
+        // 32x16: múltiplo del MCU, sin pérdida; 33x17: vuelve a decodificar y encodear
+        for ((width, height), lossless) in [((32, 16), true), ((33, 17), false)] {
+            let path = dir.path().join(format!("{}x{}.jpg", width, height));
+            RgbImage::new(width, height).save(&path).unwrap();
+            let image = processor.load_image(&path).unwrap();
+
+            let outputs = processor
+                .process_variants(
+                    &image,
+                    Some(&transformation),
+                    &settings,
+                    &[(None, ImageFormat::Jpeg)],
+                )
+                .unwrap();
+            let encoded = outputs[0].as_ref().unwrap();
+            assert_eq!(encoded.quality.is_none(), lossless);
+            let decoded = image::load_from_memory(&encoded.data).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (height, width));
+        }
+    }
+
+    #[test]
+    fn test_misnamed_file_loaded_by_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        // PNG con extensión .jpg, como los que dejan algunos exportadores
+        let path = dir.path().join("screenshot.jpg");
+        RgbImage::new(24, 12)
+            .save_with_format(&path, image::ImageFormat::Png)
+            .unwrap();
+
+        let processor = ImageProcessorImpl::new();
+        let image = processor.load_image(&path).unwrap();
+        assert_eq!(image.format(), ImageFormat::Png);
+        assert_eq!(image.extension_format(), Some(ImageFormat::Jpeg));
+        assert_eq!(
+            (image.dimensions().width(), image.dimensions().height()),
+            (24, 12)
+        );
+
+        let outputs = processor
+            .process_variants(
+                &image,
+                None,
+                &ProcessingSettings::default(),
+                &[(None, ImageFormat::Png)],
+            )
+            .unwrap();
+        let decoded = image::load_from_memory(&outputs[0].as_ref().unwrap().data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (24, 12));
+
+        // Extensión y contenido de acuerdo: nada que registrar
+        let png = dir.path().join("screenshot.png");
+        RgbImage::new(4, 4).save(&png).unwrap();
+        assert_eq!(processor.load_image(&png).unwrap().extension_format(), None);
+    }
+
+    #[test]
+    fn test_load_image_with_bytes_matches_load_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.jpg");
+        RgbImage::new(24, 12)
+            .save_with_format(&path, image::ImageFormat::Png)
+            .unwrap();
+
+        let processor = ImageProcessorImpl::new();
+        let (image, data) = processor.load_image_with_bytes(&path).unwrap();
+        assert_eq!(data, fs::read(&path).unwrap());
+        assert_eq!(image.size_bytes(), data.len() as u64);
+
+        let loaded = processor.load_image(&path).unwrap();
+        assert_eq!(image.format(), loaded.format());
+        assert_eq!(image.extension_format(), Some(ImageFormat::Jpeg));
+        assert_eq!(image.dimensions(), loaded.dimensions());
+
+        assert!(matches!(
+            processor.load_image_with_bytes(&dir.path().join("missing.jpg")),
+            Err(DomainError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_auto_orient_from_exif_tag() {
+        let file = tempfile::Builder::new().suffix(".jpg").tempfile().unwrap();
+        let with_orientation = |orientation: Option<u32>| {
+            let mut metadata = ImageMetadata::empty();
+            metadata.orientation = orientation;
+            Image::new(
+                file.path().to_path_buf(),
+                ImageFormat::Jpeg,
+                Dimensions::new(32, 16).unwrap(),
+                1024,
+                Some(metadata),
+            )
+            .unwrap()
+        };
+
+        let upright = ImageProcessorImpl::auto_orient(&with_orientation(Some(6))).unwrap();
+        assert_eq!(upright.rotation(), Some(Rotation::Clockwise90));
+        assert!(!upright.flip_horizontal);
+
+        let mirrored = ImageProcessorImpl::auto_orient(&with_orientation(Some(2))).unwrap();
+        assert_eq!(
+            mirrored.rotation().unwrap_or(Rotation::None),
+            Rotation::None
+        );
+        assert!(mirrored.flip_horizontal);
+
+        // Sin etiqueta, ya derecha o con un valor inválido: nada que corregir
+        assert!(ImageProcessorImpl::auto_orient(&with_orientation(None)).is_none());
+        assert!(ImageProcessorImpl::auto_orient(&with_orientation(Some(1))).is_none());
+        assert!(ImageProcessorImpl::auto_orient(&with_orientation(Some(9))).is_none());
+    }
+
+    #[test]
+    fn test_transformation_auto_orient_composes_with_rotation() {
+        let file = tempfile::Builder::new().suffix(".png").tempfile().unwrap();
+        image::RgbImage::new(32, 16).save(file.path()).unwrap();
+        let mut metadata = ImageMetadata::empty();
+        metadata.orientation = Some(6);
+        let image = Image::new(
+            file.path().to_path_buf(),
+            ImageFormat::Png,
+            Dimensions::new(32, 16).unwrap(),
+            1024,
+            Some(metadata),
+        )
+        .unwrap();
+        // El decoder no orienta: lo hace la transformación
+        let mut settings = ProcessingSettings::default();
+        settings.set_auto_orient(false);
+        let processor = ImageProcessorImpl::new();
+        let output_dimensions = |transformation: &Transformation| {
+            let encoded = processor
+                .process_variants(
+                    &image,
+                    Some(transformation),
+                    &settings,
+                    &[(None, ImageFormat::Png)],
+                )
+                .unwrap()
+                .remove(0)
+                .unwrap();
+            let output = image::load_from_memory(&encoded.data).unwrap();
+            (output.width(), output.height())
+        };
+
+        let mut upright = Transformation::new();
+        upright.set_auto_orient(true);
+        assert_eq!(output_dimensions(&upright), (16, 32));
+
+        // 90° de la etiqueta más 90° pedidos
+        upright.set_rotation(Rotation::Clockwise90);
+        assert_eq!(output_dimensions(&upright), (32, 16));
+
+        let mut rotated = Transformation::with_rotation(Rotation::Clockwise90);
+        rotated.set_auto_orient(false);
+        assert_eq!(output_dimensions(&rotated), (16, 32));
+    }
+
+    #[test]
+    fn test_process_in_memory() {
+        let processor = ImageProcessorImpl::new();
+        let mut png = Vec::new();
+        noisy_image()
+            .write_to(&mut Cursor::new(&mut png), ImageCrateFormat::Png)
+            .unwrap();
+
+        let mut settings = ProcessingSettings::default();
+        settings.set_output_format(Some(ImageFormat::Jpeg));
+        let resize = ResizeTransformation::new(
+            Dimensions::new(64, 32).unwrap(),
+            false,
+            ResizeFilter::Lanczos3,
+        );
+        let output = processor
+            .process_in_memory(
+                &png,
+                None,
+                Some(&Transformation::with_resize(resize)),
+                &settings,
+            )
+            .unwrap();
+
+        assert_eq!(
+            ImageFormat::from_magic_bytes(&output).unwrap(),
+            ImageFormat::Jpeg
+        );
+        let decoded = image::load_from_memory(&output).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+
+        // Sin formato de salida: el de la entrada
+        let output = processor
+            .process_in_memory(
+                &png,
+                Some(ImageFormat::Png),
+                None,
+                &ProcessingSettings::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            ImageFormat::from_magic_bytes(&output).unwrap(),
+            ImageFormat::Png
+        );
+    }
+
+    #[test]
+    fn test_process_in_memory_rejects_unknown_and_raw_input() {
+        let processor = ImageProcessorImpl::new();
+        let settings = ProcessingSettings::default();
+
+        assert!(matches!(
+            processor.process_in_memory(b"not an image", None, None, &settings),
+            Err(DomainError::InvalidImageFormat(_))
+        ));
+        assert!(matches!(
+            processor.process_in_memory(b"II*\0rest", None, None, &settings),
+            Err(DomainError::InvalidImageFormat(_))
+        ));
+        // El formato indicado manda: estos bytes no son un JPEG
+        assert!(matches!(
+            processor.process_in_memory(b"not an image", Some(ImageFormat::Jpeg), None, &settings),
+            Err(DomainError::DecodeFailed(_))
+        ));
+    }
+}
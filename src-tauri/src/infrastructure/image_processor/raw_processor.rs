@@ -1,9 +1,13 @@
-use image::{DynamicImage, RgbImage};
-use std::ffi::CString;
+use image::{DynamicImage, ImageBuffer, Rgb, RgbImage};
+use std::ffi::{c_int, c_void, CString};
 use std::io::Cursor;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use crate::domain::RawQualityMode;
+use crate::domain::models::ImageMetadata;
+use crate::domain::models::{HighlightMode, WhiteBalance};
+use crate::domain::{RawProcessingParams, RawQualityMode};
 use crate::infrastructure::error::{InfraError, InfraResult};
 
 /// Helper: Convert LibRaw error code to human-readable message
@@ -16,19 +20,59 @@ fn libraw_error_message(code: i32) -> &'static str {
         -5 => "No thumbnail in file",
         -6 => "Unsupported thumbnail format",
         -7 => "Cannot parse input file",
-        -100007 => "Cancelled by user callback",
-        -100008 => "Bad crop box",
+        -100007 => "Insufficient memory",
+        -100008 => "Corrupt or unreadable RAW data",
+        -100009 => "Input/output error",
+        -100010 => "Cancelled by user callback",
+        -100011 => "Bad crop box",
         _ => "Unknown error",
     }
 }
 
 /// RAW image processor using LibRaw directly via FFI
 /// Supports ALL cameras including Sony a7C, Canon R5, Nikon Z9,   etc.
-pub struct RawProcessor;
+pub struct RawProcessor {
+    params: RawProcessingParams,
+    /// Raised to abort a decode in progress (checked from LibRaw's progress callback)
+    cancel_signal: Option<Arc<AtomicBool>>,
+}
+
+/// LibRaw progress handler: a nonzero return aborts the current call with
+/// LIBRAW_CANCELLED_BY_CALLBACK. `data` is the `AtomicBool` of the cancel signal.
+extern "C" fn cancel_check(
+    data: *mut c_void,
+    _stage: c_int,
+    _iteration: c_int,
+    _expected: c_int,
+) -> c_int {
+    // SAFETY: process_raw registra un puntero al AtomicBool de su Arc, que vive
+    // mientras dure la llamada
+    let cancelled = unsafe { &*(data as *const AtomicBool) };
+    c_int::from(cancelled.load(Ordering::Relaxed))
+}
 
 impl RawProcessor {
     pub fn new() -> Self {
-        Self
+        Self::with_params(RawProcessingParams::default())
+    }
+
+    /// Create with custom development parameters
+    pub fn with_params(params: RawProcessingParams) -> Self {
+        Self {
+            params,
+            cancel_signal: None,
+        }
+    }
+
+    /// Abort `process_raw` as soon as `signal` is raised instead of finishing the decode
+    pub fn with_cancel_signal(mut self, signal: Option<Arc<AtomicBool>>) -> Self {
+        self.cancel_signal = signal;
+        self
+    }
+
+    /// Get development parameters
+    pub fn params(&self) -> &RawProcessingParams {
+        &self.params
     }
 
     /// Convert RAW file to DynamicImage using LibRaw FFI
@@ -62,11 +106,8 @@ impl RawProcessor {
 
             // Always-on performance params
             libraw_sys::libraw_set_no_auto_bright(data, 1);
-            libraw_sys::libraw_set_highlight(data, 0);
             libraw_sys::libraw_set_fbdd_noiserd(data, 0);
             libraw_sys::libraw_set_output_color(data, 1);
-            libraw_sys::libraw_set_output_bps(data, 8);
-            (*data).params.use_camera_wb = 1;
 
             match quality_mode {
                 RawQualityMode::Thumbnail => unreachable!(),
@@ -84,6 +125,16 @@ impl RawProcessor {
                 }
             }
 
+            self.apply_params(&mut (*data).params);
+
+            if let Some(signal) = &self.cancel_signal {
+                libraw_sys::libraw_set_progress_handler(
+                    data,
+                    cancel_check,
+                    Arc::as_ptr(signal) as *mut c_void,
+                );
+            }
+
             let ret = libraw_sys::libraw_open_file(data, c_path.as_ptr());
             if ret != 0 {
                 return Err(InfraError::ImageReadError(format!(
@@ -93,6 +144,9 @@ impl RawProcessor {
             }
 
             let ret = libraw_sys::libraw_unpack(data);
+            if ret == libraw_sys::LIBRAW_CANCELLED_BY_CALLBACK {
+                return Err(InfraError::Cancelled);
+            }
             if ret != 0 {
                 return Err(InfraError::DecodeError(format!(
                     "Failed to unpack RAW data from '{}': {} (error {})",
@@ -101,6 +155,9 @@ impl RawProcessor {
             }
 
             let ret = libraw_sys::libraw_dcraw_process(data);
+            if ret == libraw_sys::LIBRAW_CANCELLED_BY_CALLBACK {
+                return Err(InfraError::Cancelled);
+            }
             if ret != 0 {
                 return Err(InfraError::DecodeError(format!(
                     "Failed to process RAW data from '{}': {} (error {})",
@@ -118,13 +175,89 @@ impl RawProcessor {
             }
 
             let _processed_guard = ProcessedImageGuard(processed);
-            self.convert_libraw_to_dynamic_image(processed)
+            Self::convert_libraw_to_dynamic_image(processed)
+        }
+    }
+
+    /// Copy development parameters into LibRaw's output params (before libraw_dcraw_process)
+    fn apply_params(&self, output: &mut libraw_sys::libraw_output_params_t) {
+        let params = &self.params;
+
+        let (use_camera_wb, use_auto_wb, user_mul) = match params.white_balance {
+            WhiteBalance::Camera => (1, 0, [0.0; 4]),
+            WhiteBalance::Auto => (0, 1, [0.0; 4]),
+            // Sin multiplicadores de cámara ni automáticos LibRaw usa los de luz día (pre_mul)
+            WhiteBalance::Daylight => (0, 0, [0.0; 4]),
+            WhiteBalance::Custom(r, g, b, g2) => (0, 0, [r, g, b, g2]),
+        };
+        output.use_camera_wb = use_camera_wb;
+        output.use_auto_wb = use_auto_wb;
+        output.user_mul = user_mul;
+
+        let exposure_shift = exposure_shift(params.exposure_correction);
+        output.exp_correc = i32::from(exposure_shift != 1.0);
+        output.exp_shift = exposure_shift;
+        output.exp_preser = 0.0;
+
+        output.highlight = highlight_code(params.highlight_mode);
+        output.threshold = params.noise_threshold.max(0.0);
+        output.output_bps = if params.output_bps == 16 { 16 } else { 8 };
+        if params.half_size {
+            output.half_size = 1;
         }
     }
 
     /// Extract embedded JPEG thumbnail from RAW file — no demosaicing, ~100x faster.
     /// Falls back to Balanced demosaicing if no usable thumbnail is found.
     fn extract_thumbnail(&self, path: &Path, c_path: &CString) -> InfraResult<DynamicImage> {
+        match Self::read_thumbnail(path, c_path)? {
+            // Thumbnail is a raw JPEG blob — decode it directly with the image crate
+            Some(Thumbnail::Jpeg(jpeg_bytes)) => {
+                image::load_from_memory_with_format(&jpeg_bytes, image::ImageFormat::Jpeg)
+                    .map_err(|e| InfraError::DecodeError(format!(
+                        "Failed to decode embedded JPEG thumbnail from '{}': {}",
+                        path.display(), e
+                    )))
+            }
+            // Thumbnail is already a decoded RGB bitmap
+            Some(Thumbnail::Bitmap(img)) => Ok(img),
+            // No thumbnail in this file — fall back to Balanced demosaicing
+            None => self.process_raw(path, RawQualityMode::Balanced),
+        }
+    }
+
+    /// Preview image the camera embedded in the RAW file (the one its review screen
+    /// shows), as JPEG bytes. Only the preview is unpacked: 10-100x faster than
+    /// demosaicing. Bitmap previews are encoded to JPEG.
+    pub fn extract_embedded_jpeg(path: &Path) -> InfraResult<Vec<u8>> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| InfraError::ImageReadError("Invalid path".to_string()))?;
+        let c_path = CString::new(path_str)
+            .map_err(|e| InfraError::ImageReadError(format!("Path conversion failed: {}", e)))?;
+
+        match Self::read_thumbnail(path, &c_path)? {
+            Some(Thumbnail::Jpeg(jpeg_bytes)) => Ok(jpeg_bytes),
+            Some(Thumbnail::Bitmap(img)) => {
+                let mut bytes = Vec::new();
+                img.to_rgb8()
+                    .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Jpeg)
+                    .map_err(|e| InfraError::EncodeError(format!(
+                        "Failed to encode embedded preview from '{}': {}",
+                        path.display(), e
+                    )))?;
+                Ok(bytes)
+            }
+            None => Err(InfraError::DecodeError(format!(
+                "No embedded preview in '{}'",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Unpack only the embedded thumbnail (skips all sensor data decoding).
+    /// None = the file has no usable thumbnail.
+    fn read_thumbnail(path: &Path, c_path: &CString) -> InfraResult<Option<Thumbnail>> {
         unsafe {
             let data = libraw_sys::libraw_init(0);
             if data.is_null() {
@@ -143,19 +276,14 @@ impl RawProcessor {
                 )));
             }
 
-            // Unpack only the thumbnail — skips all sensor data decoding
-            let ret = libraw_sys::libraw_unpack_thumb(data);
-            if ret != 0 {
-                // No thumbnail in this file — fall back to Balanced demosaicing
-                drop(_guard);
-                return self.process_raw(path, RawQualityMode::Balanced);
+            if libraw_sys::libraw_unpack_thumb(data) != 0 {
+                return Ok(None);
             }
 
             let mut err_code: i32 = 0;
             let thumb = libraw_sys::libraw_dcraw_make_mem_thumb(data, &mut err_code);
             if thumb.is_null() {
-                drop(_guard);
-                return self.process_raw(path, RawQualityMode::Balanced);
+                return Ok(None);
             }
 
             let _thumb_guard = ProcessedImageGuard(thumb);
@@ -163,18 +291,13 @@ impl RawProcessor {
 
             match img.image_type {
                 libraw_sys::LibRaw_image_formats::LIBRAW_IMAGE_JPEG => {
-                    // Thumbnail is a raw JPEG blob — decode it directly with the image crate
+                    // Copiar: LibRaw libera su memoria con el guard
                     let data_size = img.data_size as usize;
                     let jpeg_bytes = std::slice::from_raw_parts(img.data.as_ptr(), data_size);
-                    image::load_from_memory_with_format(jpeg_bytes, image::ImageFormat::Jpeg)
-                        .map_err(|e| InfraError::DecodeError(format!(
-                            "Failed to decode embedded JPEG thumbnail from '{}': {}",
-                            path.display(), e
-                        )))
+                    Ok(Some(Thumbnail::Jpeg(jpeg_bytes.to_vec())))
                 }
                 libraw_sys::LibRaw_image_formats::LIBRAW_IMAGE_BITMAP => {
-                    // Thumbnail is already a decoded RGB bitmap
-                    self.convert_libraw_to_dynamic_image(thumb)
+                    Self::convert_libraw_to_dynamic_image(thumb).map(|img| Some(Thumbnail::Bitmap(img)))
                 }
             }
         }
@@ -224,9 +347,54 @@ impl RawProcessor {
         }
     }
 
+    /// Fast camera metadata extraction from RAW files (make, model, ISO, exposure...)
+    /// Only parses the file header: no libraw_unpack / libraw_dcraw_process
+    pub fn get_raw_camera_metadata(path: &Path) -> InfraResult<ImageMetadata> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| InfraError::ImageReadError("Invalid path".to_string()))?;
+        let c_path = CString::new(path_str)
+            .map_err(|e| InfraError::ImageReadError(format!("Path conversion failed: {}", e)))?;
+
+        unsafe {
+            let raw = libraw_sys::libraw_init(0);
+            if raw.is_null() {
+                return Err(InfraError::DecodeError(
+                    "Failed to initialize LibRaw".to_string(),
+                ));
+            }
+
+            let _guard = LibRawGuard(raw);
+
+            let ret = libraw_sys::libraw_open_file(raw, c_path.as_ptr());
+            if ret != 0 {
+                return Err(InfraError::ImageReadError(format!(
+                    "Failed to open RAW file '{}': {} (error {})",
+                    path.display(),
+                    libraw_error_message(ret),
+                    ret
+                )));
+            }
+
+            let idata = &(*raw).idata;
+            let other = &(*raw).other;
+
+            let mut metadata = ImageMetadata::empty();
+            metadata.camera_make = c_chars_to_string(&idata.make);
+            metadata.camera_model = c_chars_to_string(&idata.model);
+            metadata.iso_speed = positive(other.iso_speed).map(|iso| iso.round() as u32);
+            metadata.exposure_time = positive(other.shutter).map(format_shutter);
+            metadata.f_number = positive(other.aperture).map(|f| f as f64);
+            metadata.focal_length = positive(other.focal_len).map(|f| f as f64);
+            metadata.date_time =
+                (other.timestamp > 0).then(|| format_timestamp(other.timestamp as i64));
+
+            Ok(metadata)
+        }
+    }
+
     /// Convertir libraw_processed_image_t a DynamicImage
     unsafe fn convert_libraw_to_dynamic_image(
-        &self,
         processed: *mut libraw_sys::libraw_processed_image_t,
     ) -> InfraResult<DynamicImage> {
         let img = &*processed;
@@ -243,6 +411,24 @@ impl RawProcessor {
             )));
         }
 
+        if img.bits == 16 {
+            // Muestras de 16 bits en el orden de bytes nativo
+            let sample_count = (width * height * 3) as usize;
+            let byte_slice = std::slice::from_raw_parts(img.data.as_ptr(), sample_count * 2);
+            let samples: Vec<u16> = byte_slice
+                .chunks_exact(2)
+                .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+                .collect();
+
+            let rgb_image = ImageBuffer::<Rgb<u16>, _>::from_raw(width, height, samples)
+                .ok_or_else(|| {
+                    InfraError::DecodeError(
+                        "Failed to create 16-bit RGB image from RAW data".to_string(),
+                    )
+                })?;
+            return Ok(DynamicImage::ImageRgb16(rgb_image));
+        }
+
         // Convertir datos de LibRaw a Vec
         // Nota: Debemos copiar porque LibRaw posee la memoria original y será liberada
         let data_size = (width * height * 3) as usize;
@@ -293,6 +479,88 @@ impl RawProcessor {
     }
 }
 
+/// Helper: NUL-terminated C char buffer to trimmed String (None if empty)
+fn c_chars_to_string(chars: &[std::os::raw::c_char]) -> Option<String> {
+    let bytes: Vec<u8> = chars
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    let text = String::from_utf8_lossy(&bytes).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Helper: LibRaw uses 0 for "unknown"
+fn positive(value: f32) -> Option<f32> {
+    (value.is_finite() && value > 0.0).then_some(value)
+}
+
+/// Helper: shutter speed in seconds to EXIF-style text ("1/250", "2", "2.5")
+fn format_shutter(seconds: f32) -> String {
+    if seconds < 1.0 {
+        format!("1/{}", (1.0 / seconds).round() as u32)
+    } else if seconds.fract() == 0.0 {
+        format!("{}", seconds as u32)
+    } else {
+        format!("{:.1}", seconds)
+    }
+}
+
+/// Helper: Unix timestamp to "YYYY-MM-DD HH:MM:SS" (UTC), same layout as EXIF dates
+fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Días desde 1970-01-01 a fecha civil (algoritmo de Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Helper: exposure correction in stops to LibRaw's linear exp_shift (0.25-8.0)
+fn exposure_shift(stops: f32) -> f32 {
+    if !stops.is_finite() {
+        return 1.0;
+    }
+    let stops = stops.clamp(
+        RawProcessingParams::MIN_EXPOSURE,
+        RawProcessingParams::MAX_EXPOSURE,
+    );
+    2f32.powf(stops)
+}
+
+/// Helper: LibRaw highlight mode (0 clip, 1 unclip, 2 blend, 3-9 rebuild)
+fn highlight_code(mode: HighlightMode) -> i32 {
+    match mode {
+        HighlightMode::Clip => 0,
+        HighlightMode::Unclip => 1,
+        HighlightMode::Blend => 2,
+        HighlightMode::Rebuild => 5,
+    }
+}
+
+/// Embedded thumbnail as LibRaw returns it
+enum Thumbnail {
+    Jpeg(Vec<u8>),
+    Bitmap(DynamicImage),
+}
+
 // RAII guard para libraw_data_t - limpia automáticamente cuando   se destruye
 struct LibRawGuard(*mut libraw_sys::libraw_data_t);
 
@@ -340,8 +608,76 @@ mod tests {
         assert!(!RawProcessor::is_raw_format("png"));
     }
 
+    #[test]
+    fn test_cancel_check_follows_signal() {
+        let signal = Arc::new(AtomicBool::new(false));
+        let data = Arc::as_ptr(&signal) as *mut c_void;
+
+        assert_eq!(cancel_check(data, 0, 0, 0), 0);
+        signal.store(true, Ordering::SeqCst);
+        assert_eq!(cancel_check(data, 0, 0, 0), 1);
+        assert_eq!(
+            libraw_error_message(libraw_sys::LIBRAW_CANCELLED_BY_CALLBACK),
+            "Cancelled by user callback"
+        );
+    }
+
+    #[test]
+    fn test_c_chars_to_string() {
+        let mut buffer = [0 as std::os::raw::c_char; 64];
+        for (i, b) in b"Sony ".iter().enumerate() {
+            buffer[i] = *b as std::os::raw::c_char;
+        }
+        assert_eq!(c_chars_to_string(&buffer).as_deref(), Some("Sony"));
+        assert_eq!(c_chars_to_string(&[0; 8]), None);
+    }
+
+    #[test]
+    fn test_format_shutter() {
+        assert_eq!(format_shutter(0.004), "1/250");
+        assert_eq!(format_shutter(2.0), "2");
+        assert_eq!(format_shutter(2.5), "2.5");
+        assert_eq!(positive(0.0), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1_714_559_400), "2024-05-01 10:30:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+    }
+
     #[test]
     fn test_create_processor() {
-        let _processor = RawProcessor::new();
+        let processor = RawProcessor::new();
+        assert_eq!(processor.params(), &RawProcessingParams::default());
+    }
+
+    #[test]
+    fn test_exposure_shift() {
+        assert_eq!(exposure_shift(0.0), 1.0);
+        assert_eq!(exposure_shift(1.0), 2.0);
+        assert_eq!(exposure_shift(-2.0), 0.25);
+        assert_eq!(exposure_shift(10.0), 8.0);
+        assert_eq!(exposure_shift(f32::NAN), 1.0);
+    }
+
+    #[test]
+    fn test_highlight_code() {
+        assert_eq!(highlight_code(HighlightMode::Clip), 0);
+        assert_eq!(highlight_code(HighlightMode::Blend), 2);
+        assert_eq!(highlight_code(HighlightMode::Rebuild), 5);
+    }
+
+    #[test]
+    fn test_extract_embedded_jpeg_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.arw");
+        assert!(RawProcessor::extract_embedded_jpeg(&missing).is_err());
+
+        // No es un RAW: LibRaw no puede abrirlo
+        let text = dir.path().join("notes.arw");
+        std::fs::write(&text, b"not a raw file").unwrap();
+        assert!(RawProcessor::extract_embedded_jpeg(&text).is_err());
     }
 }
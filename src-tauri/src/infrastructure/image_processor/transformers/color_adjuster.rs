@@ -0,0 +1,140 @@
+use crate::domain::models::{Channel, ColorAdjustment};
+use image::imageops::colorops;
+use image::{DynamicImage, GrayAlphaImage, GrayImage, Luma, LumaA, Rgba};
+
+/// Grayscale, sepia and channel extraction
+pub struct ColorAdjuster;
+
+impl ColorAdjuster {
+    /// Standard sepia matrix (rows produce R, G, B from the input R, G, B)
+    const SEPIA: [[f32; 3]; 3] = [
+        [0.393, 0.769, 0.189],
+        [0.349, 0.686, 0.168],
+        [0.272, 0.534, 0.131],
+    ];
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Apply a color adjustment, keeping the alpha channel
+    pub fn apply(&self, img: &DynamicImage, adjustment: ColorAdjustment) -> DynamicImage {
+        match adjustment {
+            ColorAdjustment::Grayscale | ColorAdjustment::ExtractChannel(Channel::Luma) => {
+                Self::luma(img)
+            }
+            ColorAdjustment::Sepia(strength) => Self::sepia(img, strength),
+            ColorAdjustment::ExtractChannel(Channel::Red) => Self::extract_channel(img, 0),
+            ColorAdjustment::ExtractChannel(Channel::Green) => Self::extract_channel(img, 1),
+            ColorAdjustment::ExtractChannel(Channel::Blue) => Self::extract_channel(img, 2),
+        }
+    }
+
+    fn luma(img: &DynamicImage) -> DynamicImage {
+        if img.color().has_alpha() {
+            DynamicImage::ImageLumaA8(colorops::grayscale_alpha(img))
+        } else {
+            DynamicImage::ImageLuma8(colorops::grayscale(img))
+        }
+    }
+
+    /// Blend the sepia-toned pixel with the original by `strength` (0.0-1.0)
+    fn sepia(img: &DynamicImage, strength: f32) -> DynamicImage {
+        let mut rgba = img.to_rgba8();
+
+        for pixel in rgba.pixels_mut() {
+            let [r, g, b, a] = pixel.0;
+            let original = [r, g, b].map(f32::from);
+            let mut out = [0u8; 3];
+            for (i, row) in Self::SEPIA.iter().enumerate() {
+                let toned = row[0] * original[0] + row[1] * original[1] + row[2] * original[2];
+                out[i] = (original[i] + (toned - original[i]) * strength)
+                    .round()
+                    .clamp(0.0, 255.0) as u8;
+            }
+            *pixel = Rgba([out[0], out[1], out[2], a]);
+        }
+
+        if img.color().has_alpha() {
+            DynamicImage::ImageRgba8(rgba)
+        } else {
+            DynamicImage::ImageRgba8(rgba).to_rgb8().into()
+        }
+    }
+
+    /// Single RGB channel (0 = red, 1 = green, 2 = blue) as a grayscale image
+    fn extract_channel(img: &DynamicImage, index: usize) -> DynamicImage {
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+
+        if img.color().has_alpha() {
+            DynamicImage::ImageLumaA8(GrayAlphaImage::from_fn(width, height, |x, y| {
+                let pixel = rgba.get_pixel(x, y);
+                LumaA([pixel[index], pixel[3]])
+            }))
+        } else {
+            DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+                Luma([rgba.get_pixel(x, y)[index]])
+            }))
+        }
+    }
+}
+
+impl Default for ColorAdjuster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, RgbaImage};
+
+    fn pixel(img: &DynamicImage) -> [u8; 4] {
+        img.to_rgba8().get_pixel(0, 0).0
+    }
+
+    #[test]
+    fn test_sepia_full_strength() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(2, 2, Rgb([100, 100, 100])));
+        let result = ColorAdjuster::new().apply(&img, ColorAdjustment::Sepia(1.0));
+
+        assert!(!result.color().has_alpha());
+        assert_eq!(pixel(&result), [135, 120, 94, 255]);
+    }
+
+    #[test]
+    fn test_sepia_zero_strength_is_unchanged() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(2, 2, Rgb([10, 200, 30])));
+        let result = ColorAdjuster::new().apply(&img, ColorAdjustment::Sepia(0.0));
+        assert_eq!(result, img);
+    }
+
+    #[test]
+    fn test_extract_channel() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 40])));
+        let adjuster = ColorAdjuster::new();
+
+        for (channel, value) in [
+            (Channel::Red, 10),
+            (Channel::Green, 20),
+            (Channel::Blue, 30),
+        ] {
+            let result = adjuster.apply(&img, ColorAdjustment::ExtractChannel(channel));
+            assert!(!result.color().has_color());
+            assert_eq!(pixel(&result), [value, value, value, 40]);
+        }
+    }
+
+    #[test]
+    fn test_grayscale_matches_luma() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(2, 2, Rgb([200, 50, 50])));
+        let adjuster = ColorAdjuster::new();
+
+        let gray = adjuster.apply(&img, ColorAdjustment::Grayscale);
+        let luma = adjuster.apply(&img, ColorAdjustment::ExtractChannel(Channel::Luma));
+        assert_eq!(gray, luma);
+        assert_eq!(gray, img.grayscale());
+    }
+}
@@ -0,0 +1,93 @@
+use image::DynamicImage;
+
+/// Gaussian blur filter
+pub struct ImageFilter;
+
+impl ImageFilter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Apply a gaussian blur with the given sigma (0 = no-op)
+    pub fn blur(&self, img: &DynamicImage, sigma: f32) -> DynamicImage {
+        match Self::effective_sigma(img, sigma) {
+            Some(sigma) => img.blur(sigma),
+            None => img.clone(),
+        }
+    }
+
+    /// The image crate treats sigma 0 as 0.8, so skip it explicitly.
+    /// Sigmas larger than the image only make the kernel (and the run time) grow
+    /// without changing the result much, so cap them at the longest side.
+    pub(super) fn effective_sigma(img: &DynamicImage, sigma: f32) -> Option<f32> {
+        if !sigma.is_finite() || sigma <= 0.0 {
+            return None;
+        }
+        let max_sigma = img.width().max(img.height()).max(1) as f32;
+        Some(sigma.min(max_sigma))
+    }
+}
+
+impl Default for ImageFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    /// Mitad izquierda negra, mitad derecha blanca
+    fn edge_image(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_zero_sigma_is_noop() {
+        let img = edge_image(16, 16);
+        let filter = ImageFilter::new();
+
+        assert_eq!(filter.blur(&img, 0.0), img);
+        assert_eq!(filter.blur(&img, -1.0), img);
+        assert_eq!(filter.blur(&img, f32::NAN), img);
+    }
+
+    #[test]
+    fn test_blur_softens_edge() {
+        let img = edge_image(32, 8);
+        let result = ImageFilter::new().blur(&img, 2.0).to_rgb8();
+
+        let left = result.get_pixel(15, 4)[0];
+        let right = result.get_pixel(16, 4)[0];
+        assert!(
+            left > 0 && right < 255,
+            "left = {}, right = {}",
+            left,
+            right
+        );
+        assert_eq!((result.width(), result.height()), (32, 8));
+    }
+
+    #[test]
+    fn test_huge_sigma_on_small_image() {
+        let img = edge_image(4, 4);
+        let filter = ImageFilter::new();
+
+        let blurred = filter.blur(&img, 10_000.0).to_rgb8();
+        assert_eq!((blurred.width(), blurred.height()), (4, 4));
+
+        let one_pixel = DynamicImage::ImageRgb8(RgbImage::from_pixel(1, 1, Rgb([10, 20, 30])));
+        assert_eq!(
+            filter.blur(&one_pixel, 50.0).to_rgb8().get_pixel(0, 0).0,
+            [10, 20, 30]
+        );
+    }
+}
@@ -1,5 +1,17 @@
+mod color_adjuster;
+mod image_filter;
 mod resizer;
 mod rotator;
+mod sharpen;
+mod tone_adjuster;
+mod trimmer;
+mod watermarker;
 
+pub use color_adjuster::ColorAdjuster;
+pub use image_filter::ImageFilter;
 pub use resizer::Resizer;
 pub use rotator::Rotator;
+pub use sharpen::Sharpener;
+pub use tone_adjuster::ToneAdjuster;
+pub use trimmer::Trimmer;
+pub use watermarker::Watermarker;
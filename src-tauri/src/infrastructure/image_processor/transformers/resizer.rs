@@ -1,4 +1,5 @@
-use crate::domain::models::{ResizeFilter, ResizeTransformation};
+use crate::domain::models::{ResizeFilter, ResizeMode, ResizeTransformation};
+use crate::domain::value_objects::Dimensions;
 use crate::infrastructure::error::InfraResult;
 use image::{imageops::FilterType, DynamicImage};
 
@@ -6,6 +7,9 @@ use image::{imageops::FilterType, DynamicImage};
 pub struct Resizer;
 
 impl Resizer {
+    /// Downscale ratio above which `ResizeFilter::Thumbnail` box-shrinks first
+    const PRE_SHRINK_RATIO: u32 = 4;
+
     pub fn new() -> Self {
         Self
     }
@@ -15,20 +19,63 @@ impl Resizer {
         &self,
         img: &DynamicImage,
         transformation: &ResizeTransformation,
-        original_dimensions: &crate::domain::value_objects::Dimensions,
+        original_dimensions: &Dimensions,
     ) -> InfraResult<DynamicImage> {
+        // Ya limitado por el guard de no-ampliación si no se permite
         let final_dims = transformation.calculate_final_dimensions(original_dimensions)?;
+        if (final_dims.width(), final_dims.height()) == (img.width(), img.height()) {
+            return Ok(img.clone());
+        }
+
         let filter = Self::convert_filter(transformation.filter());
 
-        let resized = if transformation.preserve_aspect_ratio() {
-            img.resize(final_dims.width(), final_dims.height(), filter)
+        // Cubrir: escalar hasta cubrir la caja y recortar el sobrante centrado
+        if let ResizeMode::Cover(_) = transformation.mode() {
+            let source = Dimensions::new(img.width(), img.height())?;
+            let (scaled, (x, y)) = source.cover(final_dims.width(), final_dims.height())?;
+            return Ok(img
+                .resize_exact(scaled.width(), scaled.height(), filter)
+                .crop_imm(x as u32, y as u32, final_dims.width(), final_dims.height()));
+        }
+
+        // Los objetivos relativos ya conservan la proporción: usar las dimensiones exactas
+        let fit_within = transformation.preserve_aspect_ratio()
+            && matches!(transformation.mode(), ResizeMode::Absolute(_));
+
+        // Miniaturas: reducir primero con un promedio de cajas (barato) a 2x el destino
+        let pre_shrunk = match transformation.filter() {
+            ResizeFilter::Thumbnail => {
+                Self::pre_shrink_dimensions(img, final_dims.width(), final_dims.height()).map(
+                    |(width, height)| {
+                        if fit_within {
+                            img.thumbnail(width, height)
+                        } else {
+                            img.thumbnail_exact(width, height)
+                        }
+                    },
+                )
+            }
+            _ => None,
+        };
+        let source = pre_shrunk.as_ref().unwrap_or(img);
+
+        let resized = if fit_within {
+            source.resize(final_dims.width(), final_dims.height(), filter)
         } else {
-            img.resize_exact(final_dims.width(), final_dims.height(), filter)
+            source.resize_exact(final_dims.width(), final_dims.height(), filter)
         };
 
         Ok(resized)
     }
 
+    /// Size of the box pre-shrink for a downscale to `width`x`height`, or None when
+    /// the image shrinks by `PRE_SHRINK_RATIO` or less on some axis
+    fn pre_shrink_dimensions(img: &DynamicImage, width: u32, height: u32) -> Option<(u32, u32)> {
+        let ratio = (img.width() / width.max(1)).min(img.height() / height.max(1));
+        (ratio > Self::PRE_SHRINK_RATIO)
+            .then(|| (width.saturating_mul(2), height.saturating_mul(2)))
+    }
+
     /// Convert domain ResizeFilter to image crate FilterType
     fn convert_filter(filter: ResizeFilter) -> FilterType {
         match filter {
@@ -37,6 +84,7 @@ impl Resizer {
             ResizeFilter::CatmullRom => FilterType::CatmullRom,
             ResizeFilter::Gaussian => FilterType::Gaussian,
             ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+            ResizeFilter::Thumbnail => FilterType::Triangle,
         }
     }
 }
@@ -50,7 +98,7 @@ impl Default for Resizer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    // use crate::domain::value_objects::Dimensions;
+    use image::{Rgb, RgbImage};
 
     #[test]
     fn test_convert_filter() {
@@ -58,6 +106,61 @@ mod tests {
             Resizer::convert_filter(ResizeFilter::Lanczos3),
             FilterType::Lanczos3
         );
+        assert_eq!(
+            Resizer::convert_filter(ResizeFilter::Thumbnail),
+            FilterType::Triangle
+        );
+    }
+
+    #[test]
+    fn test_thumbnail_pre_shrinks_large_downscales() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(1000, 500, Rgb([200, 100, 50])));
+
+        assert_eq!(
+            Resizer::pre_shrink_dimensions(&img, 100, 50),
+            Some((200, 100))
+        );
+        // 4x o menos: directamente con Triangle
+        assert_eq!(Resizer::pre_shrink_dimensions(&img, 250, 125), None);
+        assert_eq!(Resizer::pre_shrink_dimensions(&img, 100, 500), None);
+
+        let source = Dimensions::new(1000, 500).unwrap();
+        let resize = ResizeTransformation::with_longest_edge(100, ResizeFilter::Thumbnail).unwrap();
+        let thumbnail = Resizer::new().resize(&img, &resize, &source).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 50));
+        assert_eq!(thumbnail.to_rgb8().get_pixel(50, 25).0, [200, 100, 50]);
+
+        let exact = ResizeTransformation::new(
+            Dimensions::new(120, 30).unwrap(),
+            false,
+            ResizeFilter::Thumbnail,
+        );
+        let stretched = Resizer::new().resize(&img, &exact, &source).unwrap();
+        assert_eq!((stretched.width(), stretched.height()), (120, 30));
+    }
+
+    #[test]
+    fn test_cover_landscape_into_square() {
+        // Mitad izquierda roja, derecha azul: el recorte central conserva ambas
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(400, 200, |x, _| {
+            if x < 200 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        }));
+        let source = Dimensions::new(400, 200).unwrap();
+        let cover = ResizeTransformation::with_cover(
+            Dimensions::new(100, 100).unwrap(),
+            ResizeFilter::Triangle,
+        );
+
+        let covered = Resizer::new().resize(&img, &cover, &source).unwrap();
+
+        assert_eq!((covered.width(), covered.height()), (100, 100));
+        let covered = covered.to_rgb8();
+        assert_eq!(covered.get_pixel(5, 50).0, [255, 0, 0]);
+        assert_eq!(covered.get_pixel(95, 50).0, [0, 0, 255]);
     }
 
     // Tests con imágenes reales en integration tests
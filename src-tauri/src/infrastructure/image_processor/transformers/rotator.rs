@@ -1,6 +1,7 @@
-use crate::domain::models::Rotation;
+use crate::domain::models::{ArbitraryRotation, Rotation};
+use crate::domain::value_objects::Dimensions;
 use crate::infrastructure::error::InfraResult;
-use image::DynamicImage;
+use image::{DynamicImage, ImageBuffer, Pixel, Primitive, Rgba};
 
 /// Image rotator and flipper
 pub struct Rotator;
@@ -22,6 +23,109 @@ impl Rotator {
         Ok(rotated)
     }
 
+    /// Rotate an image by any angle (clockwise), filling exposed corners
+    ///
+    /// Uses inverse mapping with bilinear sampling, so edges are antialiased
+    /// against the fill color. Hand-rolled rather than `imageproc`'s
+    /// `rotate_about_center` (a whole dependency that keeps the canvas size, so it
+    /// would need padding first); samples in the source's depth, so 16-bit and
+    /// float images stay 16-bit and float (as RGBA).
+    pub fn rotate_arbitrary(
+        &self,
+        img: &DynamicImage,
+        rotation: &ArbitraryRotation,
+    ) -> InfraResult<DynamicImage> {
+        let rotated = match img {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                DynamicImage::ImageRgba32F(Self::rotate_buffer(&img.to_rgba32f(), rotation)?)
+            }
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_) => {
+                DynamicImage::ImageRgba16(Self::rotate_buffer(&img.to_rgba16(), rotation)?)
+            }
+            _ => DynamicImage::ImageRgba8(Self::rotate_buffer(&img.to_rgba8(), rotation)?),
+        };
+
+        Ok(rotated)
+    }
+
+    fn rotate_buffer<P: Channel>(
+        source: &ImageBuffer<Rgba<P>, Vec<P>>,
+        rotation: &ArbitraryRotation,
+    ) -> InfraResult<ImageBuffer<Rgba<P>, Vec<P>>>
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        let original = Dimensions::new(source.width(), source.height())?;
+        let final_dims = rotation.calculate_final_dimensions(&original)?;
+        // El color de relleno es de 8 bits: escalarlo al rango del canal
+        let fill = Rgba(
+            rotation
+                .fill_color()
+                .map(|c| P::from_value(c as f64 / 255.0 * P::DEFAULT_MAX_VALUE.value())),
+        );
+
+        let radians = (rotation.degrees() as f64).to_radians();
+        let (sin, cos) = radians.sin_cos();
+        let src_cx = source.width() as f64 / 2.0;
+        let src_cy = source.height() as f64 / 2.0;
+        let dst_cx = final_dims.width() as f64 / 2.0;
+        let dst_cy = final_dims.height() as f64 / 2.0;
+
+        Ok(ImageBuffer::from_fn(
+            final_dims.width(),
+            final_dims.height(),
+            |x, y| {
+                // Centro del pixel destino relativo al centro de la imagen
+                let dx = x as f64 + 0.5 - dst_cx;
+                let dy = y as f64 + 0.5 - dst_cy;
+
+                // Rotación inversa hacia la imagen origen
+                let sx = dx * cos + dy * sin + src_cx - 0.5;
+                let sy = -dx * sin + dy * cos + src_cy - 0.5;
+
+                Self::sample_bilinear(source, sx, sy, fill)
+            },
+        ))
+    }
+
+    /// Bilinear sample at (x, y); neighbors outside the image use the fill color
+    fn sample_bilinear<P: Channel>(
+        img: &ImageBuffer<Rgba<P>, Vec<P>>,
+        x: f64,
+        y: f64,
+        fill: Rgba<P>,
+    ) -> Rgba<P>
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+
+        let pixel_at = |px: f64, py: f64| -> Rgba<P> {
+            if px < 0.0 || py < 0.0 || px >= img.width() as f64 || py >= img.height() as f64 {
+                fill
+            } else {
+                *img.get_pixel(px as u32, py as u32)
+            }
+        };
+
+        let p00 = pixel_at(x0, y0);
+        let p10 = pixel_at(x0 + 1.0, y0);
+        let p01 = pixel_at(x0, y0 + 1.0);
+        let p11 = pixel_at(x0 + 1.0, y0 + 1.0);
+
+        Rgba(std::array::from_fn(|c| {
+            let top = p00[c].value() * (1.0 - fx) + p10[c].value() * fx;
+            let bottom = p01[c].value() * (1.0 - fx) + p11[c].value() * fx;
+            P::from_value(top * (1.0 - fy) + bottom * fy)
+        }))
+    }
+
     /// Flip image horizontally
     pub fn flip_horizontal(&self, img: &DynamicImage) -> InfraResult<DynamicImage> {
         Ok(img.fliph())
@@ -37,6 +141,7 @@ impl Rotator {
         &self,
         img: &DynamicImage,
         rotation: Option<Rotation>,
+        arbitrary_rotation: Option<&ArbitraryRotation>,
         flip_h: bool,
         flip_v: bool,
     ) -> InfraResult<DynamicImage> {
@@ -47,6 +152,11 @@ impl Rotator {
             result = self.rotate(&result, rot)?;
         }
 
+        // Rotación libre después de la rotación en ángulo recto
+        if let Some(arbitrary) = arbitrary_rotation {
+            result = self.rotate_arbitrary(&result, arbitrary)?;
+        }
+
         // Luego flips
         if flip_h {
             result = self.flip_horizontal(&result)?;
@@ -60,6 +170,42 @@ impl Rotator {
     }
 }
 
+/// Channel types `rotate_arbitrary` samples in
+trait Channel: Primitive {
+    fn value(self) -> f64;
+    fn from_value(value: f64) -> Self;
+}
+
+impl Channel for u8 {
+    fn value(self) -> f64 {
+        self as f64
+    }
+
+    fn from_value(value: f64) -> Self {
+        value.round().clamp(0.0, u8::MAX as f64) as u8
+    }
+}
+
+impl Channel for u16 {
+    fn value(self) -> f64 {
+        self as f64
+    }
+
+    fn from_value(value: f64) -> Self {
+        value.round().clamp(0.0, u16::MAX as f64) as u16
+    }
+}
+
+impl Channel for f32 {
+    fn value(self) -> f64 {
+        self as f64
+    }
+
+    fn from_value(value: f64) -> Self {
+        value as f32
+    }
+}
+
 impl Default for Rotator {
     fn default() -> Self {
         Self::new()
@@ -69,11 +215,68 @@ impl Default for Rotator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use image::RgbaImage;
 
     #[test]
     fn test_create_rotator() {
         let _rotator = Rotator::new();
     }
 
+    #[test]
+    fn test_rotate_arbitrary_grows_canvas_and_fills_corners() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 50, Rgba([0, 0, 0, 255])));
+        let rotation = ArbitraryRotation::new(30.0, [255, 0, 0, 255], false).unwrap();
+
+        let rotated = Rotator::new().rotate_arbitrary(&img, &rotation).unwrap();
+        let expected = rotation
+            .calculate_final_dimensions(&Dimensions::new(100, 50).unwrap())
+            .unwrap();
+
+        assert_eq!(rotated.width(), expected.width());
+        assert_eq!(rotated.height(), expected.height());
+        let rgba = rotated.to_rgba8();
+        assert_eq!(*rgba.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        let center = rgba.get_pixel(rotated.width() / 2, rotated.height() / 2);
+        assert_eq!(*center, Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_rotate_arbitrary_zero_degrees_is_identity() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_fn(8, 4, |x, y| {
+            Rgba([(x * 30) as u8, (y * 60) as u8, 0, 255])
+        }));
+        let rotation = ArbitraryRotation::with_degrees(0.0).unwrap();
+
+        let rotated = Rotator::new().rotate_arbitrary(&img, &rotation).unwrap();
+
+        assert_eq!(rotated.to_rgba8(), img.to_rgba8());
+    }
+
+    #[test]
+    fn test_rotate_arbitrary_auto_crop_has_no_fill() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(200, 100, Rgba([0, 0, 0, 255])));
+        let rotation = ArbitraryRotation::new(10.0, [255, 0, 0, 255], true).unwrap();
+
+        let rotated = Rotator::new().rotate_arbitrary(&img, &rotation).unwrap();
+
+        assert!(rotated.width() < 200);
+        assert!(rotated.to_rgba8().pixels().all(|p| p[0] < 128));
+    }
+
+    #[test]
+    fn test_rotate_arbitrary_keeps_16_bit_depth() {
+        // 1000 no cabe en 8 bits: se perdería al pasar por RGBA8
+        let img = DynamicImage::ImageRgb16(ImageBuffer::from_pixel(100, 50, image::Rgb([1000; 3])));
+        let rotation = ArbitraryRotation::new(30.0, [255, 0, 0, 255], false).unwrap();
+
+        let rotated = Rotator::new().rotate_arbitrary(&img, &rotation).unwrap();
+
+        assert_eq!(rotated.color(), image::ColorType::Rgba16);
+        let rgba = rotated.as_rgba16().unwrap();
+        assert_eq!(*rgba.get_pixel(0, 0), Rgba([65535, 0, 0, 65535]));
+        let center = rgba.get_pixel(rotated.width() / 2, rotated.height() / 2);
+        assert_eq!(*center, Rgba([1000, 1000, 1000, 65535]));
+    }
+
     // Tests con imágenes reales en integration tests
 }
@@ -0,0 +1,171 @@
+use crate::domain::models::SharpenOptions;
+use crate::infrastructure::image_processor::transformers::ImageFilter;
+use image::{imageops, DynamicImage, ImageBuffer, Pixel};
+
+/// Unsharp mask sharpening
+pub struct Sharpener;
+
+impl Sharpener {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Add `amount` times the difference from a gaussian blur of `radius` to every
+    /// color channel (not alpha), skipping differences below `threshold`
+    pub fn apply(&self, img: &DynamicImage, options: &SharpenOptions) -> DynamicImage {
+        let Some(sigma) = ImageFilter::effective_sigma(img, options.radius()) else {
+            return img.clone();
+        };
+        if options.amount() <= 0.0 {
+            return img.clone();
+        }
+
+        let amount = options.amount();
+        let threshold = options.threshold();
+        match img {
+            DynamicImage::ImageLuma8(gray) => {
+                DynamicImage::ImageLuma8(Self::unsharp(gray, 1, sigma, amount, threshold))
+            }
+            DynamicImage::ImageLumaA8(gray) => {
+                DynamicImage::ImageLumaA8(Self::unsharp(gray, 1, sigma, amount, threshold))
+            }
+            DynamicImage::ImageRgb8(rgb) => {
+                DynamicImage::ImageRgb8(Self::unsharp(rgb, 3, sigma, amount, threshold))
+            }
+            DynamicImage::ImageRgba8(rgba) => {
+                DynamicImage::ImageRgba8(Self::unsharp(rgba, 3, sigma, amount, threshold))
+            }
+            // 16 bits / float: pasar a 8 bits (el umbral es de 8 bits)
+            other if other.color().has_alpha() => {
+                self.apply(&DynamicImage::ImageRgba8(other.to_rgba8()), options)
+            }
+            other => self.apply(&DynamicImage::ImageRgb8(other.to_rgb8()), options),
+        }
+    }
+
+    /// Unsharp mask over the first `color_channels` channels of every pixel
+    fn unsharp<P>(
+        img: &ImageBuffer<P, Vec<u8>>,
+        color_channels: usize,
+        sigma: f32,
+        amount: f32,
+        threshold: u8,
+    ) -> ImageBuffer<P, Vec<u8>>
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+    {
+        let blurred = imageops::blur(img, sigma);
+        let mut result = img.clone();
+
+        for (pixel, blurred_pixel) in result.pixels_mut().zip(blurred.pixels()) {
+            let channels = pixel.channels_mut();
+            let blurred_channels = blurred_pixel.channels();
+            for (value, &soft) in channels
+                .iter_mut()
+                .zip(blurred_channels)
+                .take(color_channels)
+            {
+                let diff = *value as f32 - soft as f32;
+                // Diferencias pequeñas suelen ser ruido: no tocarlas
+                if diff.abs() < threshold as f32 {
+                    continue;
+                }
+                *value = (*value as f32 + amount * diff).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for Sharpener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{LumaA, Rgb, RgbImage};
+
+    /// Mitad izquierda negra, mitad derecha blanca
+    fn edge_image(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_neutral_options_are_noop() {
+        let img = edge_image(16, 16);
+        let sharpener = Sharpener::new();
+
+        assert_eq!(
+            sharpener.apply(&img, &SharpenOptions::new(0.0, 1.0, 0).unwrap()),
+            img
+        );
+        assert_eq!(
+            sharpener.apply(&img, &SharpenOptions::new(1.0, 0.0, 0).unwrap()),
+            img
+        );
+    }
+
+    #[test]
+    fn test_sharpen_increases_edge_contrast() {
+        let img = ImageFilter::new().blur(&edge_image(32, 8), 1.5);
+        let before = img.to_rgb8();
+        let after = Sharpener::new()
+            .apply(&img, &SharpenOptions::new(1.0, 1.5, 1).unwrap())
+            .to_rgb8();
+
+        let before_step = before.get_pixel(17, 4)[0] as i32 - before.get_pixel(14, 4)[0] as i32;
+        let after_step = after.get_pixel(17, 4)[0] as i32 - after.get_pixel(14, 4)[0] as i32;
+        assert!(after_step > before_step);
+
+        let stronger = Sharpener::new()
+            .apply(&img, &SharpenOptions::new(3.0, 1.5, 1).unwrap())
+            .to_rgb8();
+        let stronger_step =
+            stronger.get_pixel(17, 4)[0] as i32 - stronger.get_pixel(14, 4)[0] as i32;
+        assert!(stronger_step > after_step);
+    }
+
+    #[test]
+    fn test_threshold_skips_small_differences() {
+        // Degradado suave: cada píxel difiere muy poco de su versión difuminada
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(32, 8, |x, _| {
+            let value = 100 + (x % 2) as u8 * 2;
+            Rgb([value, value, value])
+        }));
+
+        let untouched = Sharpener::new().apply(&img, &SharpenOptions::new(2.0, 1.0, 10).unwrap());
+        assert_eq!(untouched, img);
+
+        let sharpened = Sharpener::new().apply(&img, &SharpenOptions::new(2.0, 1.0, 0).unwrap());
+        assert_ne!(sharpened, img);
+    }
+
+    #[test]
+    fn test_keeps_alpha_and_huge_radius() {
+        let img = DynamicImage::ImageLumaA8(ImageBuffer::from_fn(8, 8, |x, _| {
+            LumaA([if x < 4 { 0 } else { 255 }, 128])
+        }));
+        let result = Sharpener::new().apply(&img, &SharpenOptions::strong());
+        assert!(result
+            .as_luma_alpha8()
+            .unwrap()
+            .pixels()
+            .all(|p| p[1] == 128));
+
+        let small = edge_image(4, 4);
+        let sharpened = Sharpener::new()
+            .apply(&small, &SharpenOptions::new(1.0, 100.0, 0).unwrap())
+            .to_rgb8();
+        assert_eq!((sharpened.width(), sharpened.height()), (4, 4));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::domain::models::TonalAdjustments;
+use image::{DynamicImage, Rgba};
+
+/// Brightness, contrast and saturation adjuster
+pub struct ToneAdjuster;
+
+impl ToneAdjuster {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Apply tonal adjustments (brightness, then contrast, then saturation),
+    /// keeping the alpha channel
+    pub fn apply(&self, img: &DynamicImage, tonal: &TonalAdjustments) -> DynamicImage {
+        let mut result = img.clone();
+
+        if tonal.brightness() != 0.0 || tonal.contrast() != 0.0 {
+            let curve = Self::tone_curve(tonal.brightness(), tonal.contrast());
+            result = Self::map_channels(result, &curve);
+        }
+
+        // Las imágenes en escala de grises no tienen saturación
+        if tonal.saturation() != 0.0 && result.color().has_color() {
+            result = Self::saturate(&result, tonal.saturation());
+        }
+
+        result
+    }
+
+    /// Lookup table for every 8-bit value: linear brightness shift, then an S-curve
+    /// blended in by `contrast` (negative = inverse curve, flattening the image)
+    fn tone_curve(brightness: f32, contrast: f32) -> [u8; 256] {
+        let mut curve = [0u8; 256];
+        for (value, out) in curve.iter_mut().enumerate() {
+            let x = (value as f32 / 255.0 + brightness).clamp(0.0, 1.0);
+            let s_curve = x * x * (3.0 - 2.0 * x);
+            let y = x + contrast * (s_curve - x);
+            *out = (y * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+        curve
+    }
+
+    /// Map every color channel (not alpha) through the curve
+    fn map_channels(img: DynamicImage, curve: &[u8; 256]) -> DynamicImage {
+        match img {
+            DynamicImage::ImageLuma8(mut gray) => {
+                gray.pixels_mut().for_each(|p| p[0] = curve[p[0] as usize]);
+                DynamicImage::ImageLuma8(gray)
+            }
+            DynamicImage::ImageLumaA8(mut gray) => {
+                gray.pixels_mut().for_each(|p| p[0] = curve[p[0] as usize]);
+                DynamicImage::ImageLumaA8(gray)
+            }
+            DynamicImage::ImageRgb8(mut rgb) => {
+                for pixel in rgb.pixels_mut() {
+                    pixel.0 = pixel.0.map(|c| curve[c as usize]);
+                }
+                DynamicImage::ImageRgb8(rgb)
+            }
+            DynamicImage::ImageRgba8(mut rgba) => {
+                for pixel in rgba.pixels_mut() {
+                    let [r, g, b, a] = pixel.0;
+                    *pixel = Rgba([curve[r as usize], curve[g as usize], curve[b as usize], a]);
+                }
+                DynamicImage::ImageRgba8(rgba)
+            }
+            // 16 bits / float: pasar a 8 bits (la curva es de 8 bits)
+            other if other.color().has_alpha() => {
+                Self::map_channels(DynamicImage::ImageRgba8(other.to_rgba8()), curve)
+            }
+            other => Self::map_channels(DynamicImage::ImageRgb8(other.to_rgb8()), curve),
+        }
+    }
+
+    /// Scale HSL saturation by (1 + amount), keeping hue and lightness
+    fn saturate(img: &DynamicImage, amount: f32) -> DynamicImage {
+        let factor = 1.0 + amount;
+        let mut rgba = img.to_rgba8();
+
+        for pixel in rgba.pixels_mut() {
+            let (h, s, l) = Self::rgb_to_hsl(pixel[0], pixel[1], pixel[2]);
+            let [r, g, b] = Self::hsl_to_rgb(h, (s * factor).clamp(0.0, 1.0), l);
+            *pixel = Rgba([r, g, b, pixel[3]]);
+        }
+
+        if img.color().has_alpha() {
+            DynamicImage::ImageRgba8(rgba)
+        } else {
+            DynamicImage::ImageRgba8(rgba).to_rgb8().into()
+        }
+    }
+
+    /// RGB (0-255) to HSL (hue in 0-1, saturation 0-1, lightness 0-1)
+    fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+        let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+
+        if max == min {
+            return (0.0, 0.0, l);
+        }
+
+        let d = max - min;
+        let s = if l > 0.5 {
+            d / (2.0 - max - min)
+        } else {
+            d / (max + min)
+        };
+        let h = if max == r {
+            (g - b) / d + if g < b { 6.0 } else { 0.0 }
+        } else if max == g {
+            (b - r) / d + 2.0
+        } else {
+            (r - g) / d + 4.0
+        };
+
+        (h / 6.0, s, l)
+    }
+
+    /// HSL back to RGB (0-255)
+    fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [u8; 3] {
+        let to_u8 = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        if s == 0.0 {
+            return [to_u8(l); 3];
+        }
+
+        let q = if l < 0.5 {
+            l * (1.0 + s)
+        } else {
+            l + s - l * s
+        };
+        let p = 2.0 * l - q;
+        let hue_to_rgb = |t: f32| {
+            let t = t.rem_euclid(1.0);
+            if t < 1.0 / 6.0 {
+                p + (q - p) * 6.0 * t
+            } else if t < 0.5 {
+                q
+            } else if t < 2.0 / 3.0 {
+                p + (q - p) * (2.0 / 3.0 - t) * 6.0
+            } else {
+                p
+            }
+        };
+
+        [
+            to_u8(hue_to_rgb(h + 1.0 / 3.0)),
+            to_u8(hue_to_rgb(h)),
+            to_u8(hue_to_rgb(h - 1.0 / 3.0)),
+        ]
+    }
+}
+
+impl Default for ToneAdjuster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn solid(color: [u8; 3]) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 4, Rgb(color)))
+    }
+
+    fn adjust(img: &DynamicImage, brightness: f32, contrast: f32, saturation: f32) -> [u8; 3] {
+        let tonal = TonalAdjustments::new(brightness, contrast, saturation).unwrap();
+        ToneAdjuster::new()
+            .apply(img, &tonal)
+            .to_rgb8()
+            .get_pixel(0, 0)
+            .0
+    }
+
+    #[test]
+    fn test_hsl_roundtrip() {
+        for color in [
+            [255, 0, 0],
+            [12, 200, 99],
+            [128, 128, 128],
+            [0, 0, 0],
+            [250, 240, 10],
+        ] {
+            let (h, s, l) = ToneAdjuster::rgb_to_hsl(color[0], color[1], color[2]);
+            assert_eq!(ToneAdjuster::hsl_to_rgb(h, s, l), color);
+        }
+    }
+
+    #[test]
+    fn test_brightness() {
+        assert_eq!(
+            adjust(&solid([100, 100, 100]), 0.2, 0.0, 0.0),
+            [151, 151, 151]
+        );
+        assert_eq!(adjust(&solid([100, 100, 100]), -1.0, 0.0, 0.0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_contrast_spreads_values() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgb([80, 80, 80])
+            } else {
+                Rgb([180, 180, 180])
+            }
+        }));
+        let tonal = TonalAdjustments::new(0.0, 0.5, 0.0).unwrap();
+        let result = ToneAdjuster::new().apply(&img, &tonal).to_rgb8();
+
+        assert!(result.get_pixel(0, 0)[0] < 80);
+        assert!(result.get_pixel(1, 0)[0] > 180);
+    }
+
+    #[test]
+    fn test_contrast_curve_keeps_ends_and_midpoint() {
+        for contrast in [-1.0, 0.5, 1.0] {
+            let curve = ToneAdjuster::tone_curve(0.0, contrast);
+            assert_eq!((curve[0], curve[255]), (0, 255));
+            assert!(curve[127].abs_diff(127) <= 1);
+            assert!(curve.windows(2).all(|w| w[0] <= w[1]));
+        }
+
+        // Contraste negativo acerca los valores al gris medio
+        let flat = ToneAdjuster::tone_curve(0.0, -1.0);
+        assert!(flat[40] > 40 && flat[215] < 215);
+    }
+
+    #[test]
+    fn test_full_desaturation_gives_gray() {
+        let [r, g, b] = adjust(&solid([200, 50, 50]), 0.0, 0.0, -1.0);
+        assert_eq!(r, g);
+        assert_eq!(g, b);
+    }
+
+    #[test]
+    fn test_saturation_keeps_alpha_and_luma() {
+        let rgba =
+            DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, Rgba([200, 50, 50, 90])));
+        let tonal = TonalAdjustments::new(0.0, 0.0, 0.5).unwrap();
+        let result = ToneAdjuster::new().apply(&rgba, &tonal);
+        assert_eq!(result.to_rgba8().get_pixel(0, 0)[3], 90);
+
+        let luma = DynamicImage::ImageLuma8(image::GrayImage::new(2, 2));
+        assert_eq!(ToneAdjuster::new().apply(&luma, &tonal), luma);
+    }
+}
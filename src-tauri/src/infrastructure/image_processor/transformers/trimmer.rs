@@ -0,0 +1,112 @@
+use image::{DynamicImage, Rgba, RgbaImage};
+
+/// Auto-crop of transparent or solid-color borders
+pub struct Trimmer;
+
+impl Trimmer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Crop to the bounding box of the content. The border color is taken from the
+    /// top-left pixel; `tolerance` (0-255 per channel) also trims near-solid borders.
+    /// A uniform image is returned unchanged.
+    pub fn trim(&self, img: &DynamicImage, tolerance: u8) -> DynamicImage {
+        match Self::content_bounds(&img.to_rgba8(), tolerance) {
+            Some((x, y, width, height)) if (width, height) != (img.width(), img.height()) => {
+                img.crop_imm(x, y, width, height)
+            }
+            _ => img.clone(),
+        }
+    }
+
+    /// Bounding box (x, y, width, height) of the pixels that differ from the border color,
+    /// None when every pixel matches it
+    fn content_bounds(rgba: &RgbaImage, tolerance: u8) -> Option<(u32, u32, u32, u32)> {
+        let border = *rgba.get_pixel_checked(0, 0)?;
+
+        let mut min_x = u32::MAX;
+        let mut min_y = u32::MAX;
+        let mut max_x = 0;
+        let mut max_y = 0;
+
+        for (x, y, pixel) in rgba.enumerate_pixels() {
+            if Self::is_border(pixel, &border, tolerance) {
+                continue;
+            }
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+
+        (min_x <= max_x).then(|| (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+    }
+
+    /// Transparent borders match any (nearly) transparent pixel, whatever its color
+    fn is_border(pixel: &Rgba<u8>, border: &Rgba<u8>, tolerance: u8) -> bool {
+        if border[3] == 0 {
+            return pixel[3] <= tolerance;
+        }
+        pixel
+            .0
+            .iter()
+            .zip(border.0.iter())
+            .all(|(&a, &b)| a.abs_diff(b) <= tolerance)
+    }
+}
+
+impl Default for Trimmer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fondo `background` con un rectángulo rojo opaco en (x, y, w, h)
+    fn framed(background: Rgba<u8>, rect: (u32, u32, u32, u32)) -> DynamicImage {
+        let (rx, ry, rw, rh) = rect;
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(20, 10, |x, y| {
+            if x >= rx && x < rx + rw && y >= ry && y < ry + rh {
+                Rgba([255, 0, 0, 255])
+            } else {
+                background
+            }
+        }))
+    }
+
+    #[test]
+    fn test_trim_transparent_border() {
+        let img = framed(Rgba([0, 0, 0, 0]), (3, 2, 5, 4));
+        let trimmed = Trimmer::new().trim(&img, 0);
+
+        assert_eq!((trimmed.width(), trimmed.height()), (5, 4));
+        assert_eq!(trimmed.to_rgba8().get_pixel(0, 0).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_trim_solid_border_with_tolerance() {
+        let mut img = framed(Rgba([255, 255, 255, 255]), (4, 4, 2, 2)).to_rgba8();
+        // Ruido casi blanco en el borde
+        img.put_pixel(0, 9, Rgba([250, 252, 255, 255]));
+        let img = DynamicImage::ImageRgba8(img);
+
+        let strict = Trimmer::new().trim(&img, 0);
+        assert_eq!((strict.width(), strict.height()), (6, 6));
+
+        let tolerant = Trimmer::new().trim(&img, 8);
+        assert_eq!((tolerant.width(), tolerant.height()), (2, 2));
+    }
+
+    #[test]
+    fn test_uniform_image_is_unchanged() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([0, 0, 0, 0])));
+        assert_eq!(Trimmer::new().trim(&img, 0), img);
+
+        let solid = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([9, 9, 9, 255])));
+        assert_eq!(Trimmer::new().trim(&solid, 0), solid);
+    }
+}
@@ -0,0 +1,404 @@
+use crate::domain::models::{TextWatermark, Watermark, WatermarkPosition};
+use crate::domain::value_objects::Dimensions;
+use crate::infrastructure::error::{InfraError, InfraResult};
+use ab_glyph::{point, Font, FontRef, FontVec, PxScale, ScaleFont};
+use image::{imageops, imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+
+/// Font used when a text watermark doesn't specify one (DejaVu Sans, see assets/fonts)
+const DEFAULT_FONT: &[u8] = include_bytes!("../../../../assets/fonts/DejaVuSans.ttf");
+
+/// Watermark compositor
+pub struct Watermarker;
+
+impl Watermarker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Load the watermark image from disk and composite it onto the base image
+    pub fn apply(&self, img: &DynamicImage, watermark: &Watermark) -> InfraResult<DynamicImage> {
+        let overlay = image::open(watermark.image_path()).map_err(|e| {
+            InfraError::ImageReadError(format!(
+                "Failed to open watermark image '{}': {}",
+                watermark.image_path().display(),
+                e
+            ))
+        })?;
+
+        self.apply_overlay(img, &overlay, watermark)
+    }
+
+    /// Composite an already loaded overlay onto the base image
+    pub fn apply_overlay(
+        &self,
+        img: &DynamicImage,
+        overlay: &DynamicImage,
+        watermark: &Watermark,
+    ) -> InfraResult<DynamicImage> {
+        // Escalar el overlay relativo al ancho de la imagen base
+        let target_width = ((img.width() as f32 * watermark.scale()).round() as u32).max(1);
+        let target_height = ((overlay.height() as f64 * target_width as f64
+            / overlay.width() as f64)
+            .round() as u32)
+            .max(1);
+        let mut overlay = overlay
+            .resize_exact(target_width, target_height, FilterType::Lanczos3)
+            .to_rgba8();
+
+        Self::apply_opacity(&mut overlay, watermark.opacity());
+
+        Self::place(img, &overlay, watermark.position(), 0)
+    }
+
+    /// Render the text and composite it onto the base image
+    pub fn apply_text(
+        &self,
+        img: &DynamicImage,
+        watermark: &TextWatermark,
+    ) -> InfraResult<DynamicImage> {
+        let size = watermark.font_size().to_pixels(img.height()).max(1.0);
+
+        let overlay = match watermark.font_path() {
+            Some(path) => {
+                let data = std::fs::read(path).map_err(|e| {
+                    InfraError::ImageReadError(format!(
+                        "Failed to read font '{}': {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                let font = FontVec::try_from_vec(data).map_err(|e| {
+                    InfraError::DecodeError(format!("Invalid font '{}': {}", path.display(), e))
+                })?;
+                Self::render_text(&font, watermark.text(), size, watermark.color())
+            }
+            None => {
+                let font = FontRef::try_from_slice(DEFAULT_FONT)
+                    .map_err(|e| InfraError::DecodeError(format!("Invalid bundled font: {}", e)))?;
+                Self::render_text(&font, watermark.text(), size, watermark.color())
+            }
+        };
+
+        Self::place(img, &overlay, watermark.position(), watermark.margin())
+    }
+
+    /// Rasterize text (one line per '\n') into a tightly sized RGBA image
+    fn render_text<F: Font>(font: &F, text: &str, size: f32, color: [u8; 4]) -> RgbaImage {
+        let font = font.as_scaled(PxScale::from(size));
+        let line_height = font.height() + font.line_gap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        let line_width = |line: &str| {
+            let mut width = 0.0;
+            let mut previous = None;
+            for c in line.chars() {
+                let id = font.glyph_id(c);
+                if let Some(prev) = previous {
+                    width += font.kern(prev, id);
+                }
+                width += font.h_advance(id);
+                previous = Some(id);
+            }
+            width
+        };
+
+        let width = lines.iter().map(|l| line_width(l)).fold(0.0, f32::max);
+        let height = line_height * lines.len() as f32;
+        let mut canvas =
+            RgbaImage::new(width.ceil().max(1.0) as u32, height.ceil().max(1.0) as u32);
+
+        for (row, line) in lines.iter().enumerate() {
+            let baseline = font.ascent() + line_height * row as f32;
+            let mut caret = 0.0;
+            let mut previous = None;
+
+            for c in line.chars() {
+                let id = font.glyph_id(c);
+                if let Some(prev) = previous {
+                    caret += font.kern(prev, id);
+                }
+                let glyph = id.with_scale_and_position(font.scale(), point(caret, baseline));
+                caret += font.h_advance(id);
+                previous = Some(id);
+
+                let Some(outlined) = font.outline_glyph(glyph) else {
+                    continue;
+                };
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    let x = bounds.min.x as i32 + gx as i32;
+                    let y = bounds.min.y as i32 + gy as i32;
+                    if x < 0 || y < 0 || x >= canvas.width() as i32 || y >= canvas.height() as i32 {
+                        return;
+                    }
+                    let alpha = (coverage.clamp(0.0, 1.0) * color[3] as f32).round() as u8;
+                    let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+                    // Glifos que se solapan: conservar la cobertura mayor
+                    if alpha > pixel[3] {
+                        *pixel = Rgba([color[0], color[1], color[2], alpha]);
+                    }
+                });
+            }
+        }
+
+        canvas
+    }
+
+    /// Resolve the overlay position and composite it (shared by image and text watermarks)
+    fn place(
+        img: &DynamicImage,
+        overlay: &RgbaImage,
+        position: WatermarkPosition,
+        margin: u32,
+    ) -> InfraResult<DynamicImage> {
+        let base_dims = Dimensions::new(img.width(), img.height())?;
+        let overlay_dims = Dimensions::new(overlay.width(), overlay.height())?;
+        let (x, y) = position.resolve(&base_dims, &overlay_dims, margin);
+
+        Ok(Self::composite(img, overlay, x, y))
+    }
+
+    /// Alpha-blend the overlay onto the base, keeping the base's alpha-less type when possible
+    pub(crate) fn composite(
+        img: &DynamicImage,
+        overlay: &RgbaImage,
+        x: i64,
+        y: i64,
+    ) -> DynamicImage {
+        let mut base = img.to_rgba8();
+        imageops::overlay(&mut base, overlay, x, y);
+
+        if img.color().has_alpha() {
+            DynamicImage::ImageRgba8(base)
+        } else {
+            DynamicImage::ImageRgba8(base).to_rgb8().into()
+        }
+    }
+
+    /// Scale the alpha channel by opacity (0-100)
+    pub(crate) fn apply_opacity(overlay: &mut RgbaImage, opacity: u8) {
+        if opacity >= Watermark::MAX_OPACITY {
+            return;
+        }
+        for pixel in overlay.pixels_mut() {
+            pixel[3] = (pixel[3] as u16 * opacity as u16 / 100) as u8;
+        }
+    }
+}
+
+impl Default for Watermarker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::FontSize;
+    use image::{Rgb, RgbImage};
+    use std::path::PathBuf;
+
+    fn watermark(position: WatermarkPosition, opacity: u8, scale: f32) -> Watermark {
+        Watermark::new(PathBuf::from("/tmp/logo.png"), position, opacity, scale).unwrap()
+    }
+
+    fn black_overlay() -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 10, Rgba([0, 0, 0, 255])))
+    }
+
+    #[test]
+    fn test_anchor_positions() {
+        let base = DynamicImage::ImageRgb8(RgbImage::from_pixel(100, 100, Rgb([255, 255, 255])));
+        let cases = [
+            (WatermarkPosition::TopLeft, (5, 5)),
+            (WatermarkPosition::Top, (50, 5)),
+            (WatermarkPosition::TopRight, (95, 5)),
+            (WatermarkPosition::Left, (5, 50)),
+            (WatermarkPosition::Center, (50, 50)),
+            (WatermarkPosition::Right, (95, 50)),
+            (WatermarkPosition::BottomLeft, (5, 95)),
+            (WatermarkPosition::Bottom, (50, 95)),
+            (WatermarkPosition::BottomRight, (95, 95)),
+            (WatermarkPosition::Custom { x: 30, y: 60 }, (35, 65)),
+        ];
+
+        for (position, (px, py)) in cases {
+            let result = Watermarker::new()
+                .apply_overlay(&base, &black_overlay(), &watermark(position, 100, 0.1))
+                .unwrap()
+                .to_rgb8();
+
+            assert_eq!(*result.get_pixel(px, py), Rgb([0, 0, 0]), "{:?}", position);
+            let black = result.pixels().filter(|p| p.0 == [0, 0, 0]).count();
+            assert_eq!(black, 100, "{:?}", position);
+        }
+    }
+
+    #[test]
+    fn test_scale_relative_to_base_width() {
+        let base = DynamicImage::ImageRgb8(RgbImage::from_pixel(200, 100, Rgb([255, 255, 255])));
+        let overlay = DynamicImage::ImageRgba8(RgbaImage::from_pixel(40, 20, Rgba([0, 0, 0, 255])));
+
+        let result = Watermarker::new()
+            .apply_overlay(
+                &base,
+                &overlay,
+                &watermark(WatermarkPosition::Center, 100, 0.5),
+            )
+            .unwrap()
+            .to_rgb8();
+
+        // 50% de 200px = 100px de ancho, manteniendo aspect ratio = 50px de alto
+        let black = result.pixels().filter(|p| p.0 == [0, 0, 0]).count();
+        assert_eq!(black, 100 * 50);
+    }
+
+    #[test]
+    fn test_opacity_on_opaque_base() {
+        let base = DynamicImage::ImageRgb8(RgbImage::from_pixel(100, 100, Rgb([255, 255, 255])));
+
+        let result = Watermarker::new()
+            .apply_overlay(
+                &base,
+                &black_overlay(),
+                &watermark(WatermarkPosition::TopLeft, 50, 0.1),
+            )
+            .unwrap();
+
+        assert!(!result.color().has_alpha());
+        let pixel = result.to_rgb8().get_pixel(0, 0).0;
+        assert!((pixel[0] as i32 - 128).abs() <= 2, "got {:?}", pixel);
+        assert_eq!(result.to_rgb8().get_pixel(50, 50).0, [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_opacity_on_transparent_base() {
+        let base = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 100, Rgba([0, 0, 0, 0])));
+        let overlay =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 10, Rgba([255, 0, 0, 255])));
+
+        let result = Watermarker::new()
+            .apply_overlay(
+                &base,
+                &overlay,
+                &watermark(WatermarkPosition::TopLeft, 50, 0.1),
+            )
+            .unwrap();
+
+        assert!(result.color().has_alpha());
+        let pixel = result.to_rgba8().get_pixel(0, 0).0;
+        assert_eq!(pixel[0], 255);
+        assert!((pixel[3] as i32 - 127).abs() <= 2, "got {:?}", pixel);
+        assert_eq!(result.to_rgba8().get_pixel(50, 50).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_zero_opacity_leaves_base_untouched() {
+        let base = DynamicImage::ImageRgb8(RgbImage::from_pixel(100, 100, Rgb([255, 255, 255])));
+
+        let result = Watermarker::new()
+            .apply_overlay(
+                &base,
+                &black_overlay(),
+                &watermark(WatermarkPosition::Center, 0, 0.1),
+            )
+            .unwrap();
+
+        assert_eq!(result.to_rgb8(), base.to_rgb8());
+    }
+
+    #[test]
+    fn test_apply_missing_file() {
+        let base = DynamicImage::ImageRgb8(RgbImage::new(10, 10));
+        let wm = Watermark::new(
+            PathBuf::from("/nonexistent/logo.png"),
+            WatermarkPosition::Center,
+            100,
+            0.5,
+        )
+        .unwrap();
+
+        assert!(Watermarker::new().apply(&base, &wm).is_err());
+    }
+
+    fn text_watermark(position: WatermarkPosition, size: FontSize, margin: u32) -> TextWatermark {
+        TextWatermark::new(
+            "© 2024 Dereck".to_string(),
+            size,
+            [255, 0, 0, 255],
+            position,
+            margin,
+        )
+        .unwrap()
+    }
+
+    /// Bounding box (min_x, min_y, max_x, max_y) of pixels that changed from white
+    fn ink_bounds(img: &RgbImage) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for (x, y, pixel) in img.enumerate_pixels() {
+            if pixel.0 != [255, 255, 255] {
+                let b = bounds.get_or_insert((x, y, x, y));
+                *b = (b.0.min(x), b.1.min(y), b.2.max(x), b.3.max(y));
+            }
+        }
+        bounds
+    }
+
+    #[test]
+    fn test_text_watermark_renders_with_bundled_font() {
+        let base = DynamicImage::ImageRgb8(RgbImage::from_pixel(400, 200, Rgb([255, 255, 255])));
+
+        let result = Watermarker::new()
+            .apply_text(
+                &base,
+                &text_watermark(WatermarkPosition::BottomRight, FontSize::Pixels(24.0), 10),
+            )
+            .unwrap()
+            .to_rgb8();
+
+        let (min_x, min_y, max_x, max_y) = ink_bounds(&result).expect("text was not drawn");
+        // Anclado abajo a la derecha respetando el margen
+        assert!(max_x <= 390 && max_x > 370, "max_x = {}", max_x);
+        assert!(max_y <= 190 && max_y > 170, "max_y = {}", max_y);
+        assert!(min_x > 200 && min_y > 150);
+        // Color del texto
+        assert!(result.pixels().any(|p| p.0 == [255, 0, 0]));
+    }
+
+    #[test]
+    fn test_text_size_relative_to_height() {
+        let base = DynamicImage::ImageRgb8(RgbImage::from_pixel(800, 400, Rgb([255, 255, 255])));
+        let watermarker = Watermarker::new();
+
+        let ink_height = |pct: f32| {
+            let result = watermarker
+                .apply_text(
+                    &base,
+                    &text_watermark(WatermarkPosition::Center, FontSize::PercentOfHeight(pct), 0),
+                )
+                .unwrap()
+                .to_rgb8();
+            let (_, min_y, _, max_y) = ink_bounds(&result).unwrap();
+            max_y - min_y
+        };
+
+        let small = ink_height(5.0);
+        let large = ink_height(10.0);
+        assert!(
+            large > small * 3 / 2,
+            "small = {}, large = {}",
+            small,
+            large
+        );
+    }
+
+    #[test]
+    fn test_text_watermark_missing_font() {
+        let base = DynamicImage::ImageRgb8(RgbImage::new(10, 10));
+        let mut wm = text_watermark(WatermarkPosition::Center, FontSize::Pixels(12.0), 0);
+        wm.set_font_path(Some(PathBuf::from("/nonexistent/font.ttf")));
+
+        assert!(Watermarker::new().apply_text(&base, &wm).is_err());
+    }
+}
@@ -0,0 +1,393 @@
+use img_parts::jpeg::{markers, Jpeg};
+use img_parts::Bytes;
+
+use crate::domain::models::ImageMetadata;
+
+/// Header of the Photoshop APP13 segment that carries the IPTC block
+const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\0";
+/// Image resource ID of the IPTC-NAA record inside the APP13 segment
+const IPTC_RESOURCE_ID: u16 = 0x0404;
+
+/// IPTC Application Record (record 2) datasets
+const IPTC_KEYWORDS: u8 = 25;
+const IPTC_COPYRIGHT: u8 = 116;
+const IPTC_CAPTION: u8 = 120;
+
+/// Fill the IPTC fields of `metadata` from the IPTC block of a JPEG
+/// (Photoshop APP13 segment). Other containers and files without IPTC are left alone.
+pub fn read_iptc(data: &[u8], metadata: &mut ImageMetadata) {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return;
+    }
+    let Ok(jpeg) = Jpeg::from_bytes(Bytes::copy_from_slice(data)) else {
+        return;
+    };
+
+    let Some(iptc) = jpeg
+        .segments_by_marker(markers::APP13)
+        .filter_map(|segment| segment.contents().strip_prefix(PHOTOSHOP_HEADER))
+        .find_map(iptc_resource)
+    else {
+        return;
+    };
+
+    let mut keywords = Vec::new();
+    for (dataset, value) in iptc_datasets(iptc) {
+        let text = String::from_utf8_lossy(value).trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        match dataset {
+            IPTC_KEYWORDS => keywords.push(text),
+            IPTC_COPYRIGHT => metadata.iptc_copyright = Some(text),
+            IPTC_CAPTION => metadata.iptc_caption = Some(text),
+            _ => {}
+        }
+    }
+    if !keywords.is_empty() {
+        metadata.iptc_keywords = Some(keywords);
+    }
+}
+
+/// Fill `xmp_rating` and `xmp_label` from the XMP packet of the file, and the IPTC
+/// fields IPTC left empty from their Dublin Core equivalents (`dc:subject`,
+/// `dc:rights`, `dc:description`)
+///
+/// The packet is found by scanning for `<x:xmpmeta`, so it works for any container
+/// that stores it uncompressed (JPEG APP1, PNG iTXt, WebP, TIFF). Properties are
+/// matched by their usual prefixes (`xmp:`, `dc:`), written as attributes or elements.
+pub fn read_xmp(data: &[u8], metadata: &mut ImageMetadata) {
+    let Some(xmp) = xmp_packet(data) else {
+        return;
+    };
+
+    metadata.xmp_rating = rating(xmp);
+    metadata.xmp_label = property(xmp, "xmp:Label");
+
+    if metadata.iptc_keywords.is_none() {
+        let keywords = element_body(xmp, "dc:subject")
+            .map(list_items)
+            .unwrap_or_default();
+        metadata.iptc_keywords = (!keywords.is_empty()).then_some(keywords);
+    }
+    if metadata.iptc_copyright.is_none() {
+        metadata.iptc_copyright = property(xmp, "dc:rights");
+    }
+    if metadata.iptc_caption.is_none() {
+        metadata.iptc_caption = property(xmp, "dc:description");
+    }
+}
+
+/// Data of the IPTC-NAA resource among the Photoshop image resources ("8BIM" blocks)
+fn iptc_resource(mut resources: &[u8]) -> Option<&[u8]> {
+    while resources.len() >= 12 && resources.starts_with(b"8BIM") {
+        let id = u16::from_be_bytes([resources[4], resources[5]]);
+        // Nombre pascal, relleno hasta longitud par (contando el byte de longitud)
+        let name_len = resources[6] as usize;
+        let name_end = 7 + name_len + (name_len + 1) % 2;
+        let size_bytes = resources.get(name_end..name_end + 4)?;
+        let size = u32::from_be_bytes(size_bytes.try_into().ok()?) as usize;
+        let data_start = name_end + 4;
+        let data = resources.get(data_start..data_start + size)?;
+
+        if id == IPTC_RESOURCE_ID {
+            return Some(data);
+        }
+        // Los datos también se rellenan hasta longitud par
+        resources = resources.get(data_start + size + size % 2..)?;
+    }
+    None
+}
+
+/// (dataset, value) pairs of the Application Record (record 2)
+fn iptc_datasets(mut iptc: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut datasets = Vec::new();
+    while iptc.len() >= 5 && iptc[0] == 0x1C {
+        let (record, dataset) = (iptc[1], iptc[2]);
+        let len = u16::from_be_bytes([iptc[3], iptc[4]]) as usize;
+        // Bit alto = longitud extendida (solo para datos binarios grandes): parar
+        if len & 0x8000 != 0 {
+            break;
+        }
+        let Some(value) = iptc.get(5..5 + len) else {
+            break;
+        };
+        if record == 2 {
+            datasets.push((dataset, value));
+        }
+        iptc = &iptc[5 + len..];
+    }
+    datasets
+}
+
+/// The `<x:xmpmeta>` element of the file, if any
+fn xmp_packet(data: &[u8]) -> Option<&str> {
+    let start = find(data, b"<x:xmpmeta")?;
+    let end_tag = b"</x:xmpmeta>";
+    let end = start + find(&data[start..], end_tag)? + end_tag.len();
+    std::str::from_utf8(&data[start..end]).ok()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// `xmp:Rating` as 0-5 stars (-1 = rejected, reported as no rating)
+pub(crate) fn rating(xmp: &str) -> Option<u8> {
+    property(xmp, "xmp:Rating")
+        .and_then(|rating| rating.parse::<f64>().ok())
+        .filter(|rating| (0.0..=5.0).contains(rating))
+        .map(|rating| rating.round() as u8)
+}
+
+/// Value of a simple or language-alternative property, whether written as an
+/// attribute (`xmp:Rating="5"`) or an element (`<xmp:Rating>5</xmp:Rating>`)
+pub(crate) fn property(xmp: &str, name: &str) -> Option<String> {
+    let value = match attribute(xmp, name) {
+        Some(value) => decode_entities(value),
+        None => {
+            let body = element_body(xmp, name)?;
+            // rdf:Alt: el primer elemento suele ser x-default
+            if body.contains("<rdf:li") {
+                list_items(body).into_iter().next()?
+            } else {
+                decode_entities(body)
+            }
+        }
+    };
+    let value = value.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Raw value of the `name="..."` attribute
+fn attribute<'a>(xmp: &'a str, name: &str) -> Option<&'a str> {
+    xmp.match_indices(name).find_map(|(pos, _)| {
+        let preceded_by_space = xmp[..pos].ends_with(char::is_whitespace);
+        let rest = xmp[pos + name.len()..].trim_start().strip_prefix('=')?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &rest[1..];
+        let end = value.find(quote)?;
+        preceded_by_space.then_some(&value[..end])
+    })
+}
+
+/// Contents of the first non-empty `<name ...>...</name>` element
+fn element_body<'a>(xmp: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+
+    xmp.match_indices(&open).find_map(|(pos, _)| {
+        let rest = &xmp[pos + open.len()..];
+        // `<dc:subjectX` sería otro elemento
+        if !rest.starts_with(|c: char| c == '>' || c.is_whitespace() || c == '/') {
+            return None;
+        }
+        let tag_end = rest.find('>')?;
+        if rest[..tag_end].ends_with('/') {
+            return None;
+        }
+        let body = &rest[tag_end + 1..];
+        Some(&body[..body.find(&close)?])
+    })
+}
+
+/// Text of every `<rdf:li>` in a `rdf:Bag`, `rdf:Seq` or `rdf:Alt`
+fn list_items(body: &str) -> Vec<String> {
+    body.match_indices("<rdf:li")
+        .filter_map(|(pos, _)| {
+            let rest = &body[pos..];
+            let start = rest.find('>')? + 1;
+            let end = rest.find("</rdf:li>")?;
+            let item = decode_entities(rest.get(start..end)?).trim().to_string();
+            (!item.is_empty()).then_some(item)
+        })
+        .collect()
+}
+
+/// Escape text for an XML attribute or element
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Replace the predefined XML entities and character references
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semicolon) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semicolon];
+        let replacement = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match replacement {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[semicolon + 1..];
+            }
+            // '&' suelto: dejarlo tal cual
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use img_parts::jpeg::JpegSegment;
+
+    fn iptc_dataset(dataset: u8, value: &str) -> Vec<u8> {
+        let mut bytes = vec![0x1C, 2, dataset];
+        bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    /// JPEG con un segmento APP13 de Photoshop: un recurso cualquiera y luego IPTC
+    fn jpeg_with_iptc(iptc: &[u8]) -> Vec<u8> {
+        let mut app13 = PHOTOSHOP_HEADER.to_vec();
+        // Recurso 0x03ED sin nombre, 3 bytes (+1 de relleno)
+        app13.extend_from_slice(b"8BIM\x03\xED\x00\x00\x00\x00\x00\x03abc\x00");
+        app13.extend_from_slice(b"8BIM\x04\x04\x00\x00");
+        app13.extend_from_slice(&(iptc.len() as u32).to_be_bytes());
+        app13.extend_from_slice(iptc);
+
+        let mut jpeg_bytes = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(8, 8))
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg_bytes),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        let mut jpeg = Jpeg::from_bytes(Bytes::from(jpeg_bytes)).unwrap();
+        jpeg.segments_mut().insert(
+            0,
+            JpegSegment::new_with_contents(markers::APP13, Bytes::from(app13)),
+        );
+        jpeg.encoder().bytes().to_vec()
+    }
+
+    #[test]
+    fn test_read_iptc() {
+        let mut iptc = iptc_dataset(IPTC_KEYWORDS, "beach");
+        iptc.extend(iptc_dataset(IPTC_KEYWORDS, "sunset"));
+        iptc.extend(iptc_dataset(IPTC_COPYRIGHT, "© 2024 Ana Ruiz"));
+        iptc.extend(iptc_dataset(IPTC_CAPTION, "Playa al atardecer"));
+
+        let mut metadata = ImageMetadata::empty();
+        read_iptc(&jpeg_with_iptc(&iptc), &mut metadata);
+        assert_eq!(
+            metadata.iptc_keywords,
+            Some(vec!["beach".to_string(), "sunset".to_string()])
+        );
+        assert_eq!(metadata.iptc_copyright.as_deref(), Some("© 2024 Ana Ruiz"));
+        assert_eq!(metadata.iptc_caption.as_deref(), Some("Playa al atardecer"));
+
+        // Sin IPTC, o datos truncados: nada
+        let mut empty = ImageMetadata::empty();
+        read_iptc(&jpeg_with_iptc(&[0x1C, 2, IPTC_CAPTION, 0, 50]), &mut empty);
+        read_iptc(b"\x89PNG", &mut empty);
+        assert!(empty.is_empty());
+    }
+
+    const XMP: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmp:Rating="4"
+    xmp:Label="Red">
+   <dc:subject>
+    <rdf:Bag>
+     <rdf:li>mountains</rdf:li>
+     <rdf:li>snow &amp; ice</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+   <dc:rights>
+    <rdf:Alt>
+     <rdf:li xml:lang="x-default">&#169; Studio</rdf:li>
+    </rdf:Alt>
+   </dc:rights>
+   <dc:description/>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    #[test]
+    fn test_read_xmp() {
+        let mut data = b"\xFF\xD8\xFF\xE1junk".to_vec();
+        data.extend_from_slice(XMP.as_bytes());
+
+        let mut metadata = ImageMetadata::empty();
+        metadata.iptc_copyright = Some("IPTC copyright".to_string());
+        read_xmp(&data, &mut metadata);
+
+        assert_eq!(metadata.xmp_rating, Some(4));
+        assert_eq!(metadata.xmp_label.as_deref(), Some("Red"));
+        assert_eq!(
+            metadata.iptc_keywords,
+            Some(vec!["mountains".to_string(), "snow & ice".to_string()])
+        );
+        // IPTC manda; XMP solo completa lo que falta
+        assert_eq!(metadata.iptc_copyright.as_deref(), Some("IPTC copyright"));
+        assert_eq!(metadata.iptc_caption, None);
+    }
+
+    #[test]
+    fn test_xmp_element_form_and_rejected_rating() {
+        let xmp = "<x:xmpmeta><rdf:Description><xmp:Rating>-1</xmp:Rating>\
+                   <xmp:Label>Select</xmp:Label>\
+                   <dc:rights><rdf:Alt><rdf:li xml:lang=\"x-default\">CC BY</rdf:li></rdf:Alt></dc:rights>\
+                   </rdf:Description></x:xmpmeta>";
+        let mut metadata = ImageMetadata::empty();
+        read_xmp(xmp.as_bytes(), &mut metadata);
+
+        assert_eq!(metadata.xmp_rating, None);
+        assert_eq!(metadata.xmp_label.as_deref(), Some("Select"));
+        assert_eq!(metadata.iptc_copyright.as_deref(), Some("CC BY"));
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &amp; &#x41;&#66;"),
+            "a <b> & AB"
+        );
+        assert_eq!(
+            decode_entities("R&D &unknown; tail &"),
+            "R&D &unknown; tail &"
+        );
+        assert_eq!(
+            decode_entities(&escape_xml("\"R&D\" <tag>")),
+            "\"R&D\" <tag>"
+        );
+    }
+}
@@ -22,6 +22,7 @@ impl MetadataCleaner {
             ImageFormat::Webp => self.strip_webp_metadata(data),
             ImageFormat::Gif => Ok(data.to_vec()), // GIF raramente tiene EXIF
             ImageFormat::Raw => Ok(data.to_vec()), // RAW ya fue procesado, no tiene EXIF
+            ImageFormat::Heic => Ok(data.to_vec()), // HEIC solo es formato de entrada
         }
     }
 
@@ -0,0 +1,346 @@
+use exif::{Exif, In, Reader, Tag, Value};
+use img_parts::{Bytes, DynImage, ImageEXIF};
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+
+use crate::domain::models::ImageMetadata;
+use crate::infrastructure::error::{InfraError, InfraResult};
+use crate::infrastructure::iptc_xmp;
+
+/// Metadata reader - Lee EXIF de JPEG/PNG/WebP, más IPTC y XMP
+pub struct MetadataReader;
+
+impl MetadataReader {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Lee los campos EXIF relevantes de un archivo
+    pub fn read_exif(&self, path: &Path) -> InfraResult<ImageMetadata> {
+        let file = File::open(path)?;
+        let exif = Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .map_err(|e| InfraError::ExifParseError(format!("{}: {}", path.display(), e)))?;
+
+        Ok(Self::to_metadata(&exif))
+    }
+
+    /// Como `read_exif`, sobre el archivo ya leído en memoria
+    pub fn read_exif_from_bytes(&self, data: &[u8]) -> InfraResult<ImageMetadata> {
+        let exif = Reader::new()
+            .read_from_container(&mut Cursor::new(data))
+            .map_err(|e| InfraError::ExifParseError(e.to_string()))?;
+
+        Ok(Self::to_metadata(&exif))
+    }
+
+    /// Lee EXIF, IPTC y XMP de un archivo (cada bloque que falte queda vacío)
+    pub fn read_metadata(&self, path: &Path) -> InfraResult<ImageMetadata> {
+        Ok(self.read_metadata_from_bytes(&fs::read(path)?))
+    }
+
+    /// Como `read_metadata`, sobre el archivo ya leído en memoria
+    pub fn read_metadata_from_bytes(&self, data: &[u8]) -> ImageMetadata {
+        let mut metadata = self
+            .read_exif_from_bytes(data)
+            .unwrap_or_else(|_| ImageMetadata::empty());
+        iptc_xmp::read_iptc(data, &mut metadata);
+        iptc_xmp::read_xmp(data, &mut metadata);
+        metadata
+    }
+
+    /// Lee solo la etiqueta de orientación (1-8); None si no hay EXIF o etiqueta
+    pub fn read_orientation(&self, path: &Path) -> Option<u32> {
+        let file = File::open(path).ok()?;
+        let exif = Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()?;
+        Self::uint(&exif, Tag::Orientation)
+    }
+
+    /// Como `read_orientation`, sobre el archivo ya leído en memoria
+    pub fn read_orientation_from_bytes(&self, data: &[u8]) -> Option<u32> {
+        let exif = Reader::new()
+            .read_from_container(&mut Cursor::new(data))
+            .ok()?;
+        Self::uint(&exif, Tag::Orientation)
+    }
+
+    /// Miniatura JPEG que la cámara o el editor guarda en el EXIF (IFD1) de un
+    /// JPEG o WebP; None si no hay una
+    pub fn read_thumbnail_from_bytes(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let image = DynImage::from_bytes(Bytes::copy_from_slice(data)).ok()??;
+        let exif = Reader::new().read_raw(image.exif()?.to_vec()).ok()?;
+        let offset = exif
+            .get_field(Tag::JPEGInterchangeFormat, In::THUMBNAIL)?
+            .value
+            .get_uint(0)? as usize;
+        let length = exif
+            .get_field(Tag::JPEGInterchangeFormatLength, In::THUMBNAIL)?
+            .value
+            .get_uint(0)? as usize;
+
+        // Los offsets son relativos al bloque TIFF
+        let thumbnail = exif.buf().get(offset..offset.checked_add(length)?)?;
+        thumbnail
+            .starts_with(&[0xFF, 0xD8])
+            .then(|| thumbnail.to_vec())
+    }
+
+    fn to_metadata(exif: &Exif) -> ImageMetadata {
+        // DateTimeOriginal es la fecha de captura; DateTime puede ser la de edición
+        let date_time = [Tag::DateTimeOriginal, Tag::DateTime]
+            .iter()
+            .find_map(|&tag| exif.get_field(tag, In::PRIMARY))
+            .map(|field| field.display_value().to_string());
+
+        ImageMetadata {
+            camera_make: Self::ascii(exif, Tag::Make),
+            camera_model: Self::ascii(exif, Tag::Model),
+            date_time,
+            iso_speed: Self::uint(exif, Tag::PhotographicSensitivity),
+            exposure_time: exif
+                .get_field(Tag::ExposureTime, In::PRIMARY)
+                .map(|field| field.display_value().to_string()),
+            f_number: Self::rational(exif, Tag::FNumber),
+            focal_length: Self::rational(exif, Tag::FocalLength),
+            gps_coordinates: Self::gps(exif),
+            orientation: Self::uint(exif, Tag::Orientation),
+            ..ImageMetadata::empty()
+        }
+    }
+
+    fn ascii(exif: &Exif, tag: Tag) -> Option<String> {
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Ascii(parts) => {
+                let text = parts.first().map(|bytes| {
+                    String::from_utf8_lossy(bytes)
+                        .trim_end_matches('\0')
+                        .trim()
+                        .to_string()
+                })?;
+                (!text.is_empty()).then_some(text)
+            }
+            _ => None,
+        }
+    }
+
+    fn uint(exif: &Exif, tag: Tag) -> Option<u32> {
+        exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+    }
+
+    fn rational(exif: &Exif, tag: Tag) -> Option<f64> {
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Rational(values) => values.first().map(|r| r.to_f64()),
+            _ => None,
+        }
+    }
+
+    /// Convierte grados/minutos/segundos + referencia (N/S, E/W) a decimal
+    fn gps(exif: &Exif) -> Option<(f64, f64)> {
+        let coordinate = |tag: Tag, ref_tag: Tag, negative: u8| -> Option<f64> {
+            let degrees = match &exif.get_field(tag, In::PRIMARY)?.value {
+                Value::Rational(dms) if dms.len() >= 3 => {
+                    dms[0].to_f64() + dms[1].to_f64() / 60.0 + dms[2].to_f64() / 3600.0
+                }
+                _ => return None,
+            };
+            let is_negative = match &exif.get_field(ref_tag, In::PRIMARY)?.value {
+                Value::Ascii(parts) => parts.first().and_then(|p| p.first()) == Some(&negative),
+                _ => false,
+            };
+            Some(if is_negative { -degrees } else { degrees })
+        };
+
+        Some((
+            coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S')?,
+            coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W')?,
+        ))
+    }
+}
+
+impl Default for MetadataReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+    use img_parts::jpeg::Jpeg;
+
+    fn field(tag: Tag, value: Value) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        }
+    }
+
+    fn rational(num: u32, denom: u32) -> Rational {
+        Rational { num, denom }
+    }
+
+    /// JPEG con un bloque EXIF sintético
+    fn jpeg_with_exif() -> Vec<u8> {
+        let fields = vec![
+            field(Tag::Make, Value::Ascii(vec![b"Canon".to_vec()])),
+            field(Tag::Model, Value::Ascii(vec![b"EOS R5".to_vec()])),
+            field(Tag::Orientation, Value::Short(vec![6])),
+            field(
+                Tag::DateTimeOriginal,
+                Value::Ascii(vec![b"2024:05:01 10:30:00".to_vec()]),
+            ),
+            field(Tag::PhotographicSensitivity, Value::Short(vec![400])),
+            field(Tag::ExposureTime, Value::Rational(vec![rational(1, 250)])),
+            field(Tag::FNumber, Value::Rational(vec![rational(28, 10)])),
+            field(Tag::FocalLength, Value::Rational(vec![rational(50, 1)])),
+            field(Tag::GPSLatitudeRef, Value::Ascii(vec![b"S".to_vec()])),
+            field(
+                Tag::GPSLatitude,
+                Value::Rational(vec![rational(33, 1), rational(30, 1), rational(0, 1)]),
+            ),
+            field(Tag::GPSLongitudeRef, Value::Ascii(vec![b"E".to_vec()])),
+            field(
+                Tag::GPSLongitude,
+                Value::Rational(vec![rational(151, 1), rational(15, 1), rational(0, 1)]),
+            ),
+        ];
+
+        let mut writer = Writer::new();
+        for f in &fields {
+            writer.push_field(f);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        let mut jpeg = Jpeg::from_bytes(Bytes::from(encode_jpeg(8, 8))).unwrap();
+        jpeg.set_exif(Some(Bytes::from(tiff.into_inner())));
+        jpeg.encoder().bytes().to_vec()
+    }
+
+    fn encode_jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut jpeg_bytes = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(width, height))
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg_bytes),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        jpeg_bytes
+    }
+
+    fn write_temp(data: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".jpg").tempfile().unwrap();
+        std::io::Write::write_all(&mut file, data).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_read_orientation() {
+        let reader = MetadataReader::new();
+
+        let file = write_temp(&jpeg_with_exif());
+        assert_eq!(reader.read_orientation(file.path()), Some(6));
+
+        let plain = write_temp(&[0xFF, 0xD8, 0xFF, 0xD9]);
+        assert_eq!(reader.read_orientation(plain.path()), None);
+
+        assert_eq!(
+            reader.read_orientation_from_bytes(&jpeg_with_exif()),
+            Some(6)
+        );
+        assert_eq!(
+            reader.read_orientation_from_bytes(&[0xFF, 0xD8, 0xFF, 0xD9]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_exif_fields() {
+        let file = write_temp(&jpeg_with_exif());
+        let metadata = MetadataReader::new().read_exif(file.path()).unwrap();
+
+        assert_eq!(metadata.camera_make.as_deref(), Some("Canon"));
+        assert_eq!(metadata.camera_model.as_deref(), Some("EOS R5"));
+        assert_eq!(metadata.orientation, Some(6));
+        assert_eq!(metadata.iso_speed, Some(400));
+        assert_eq!(metadata.exposure_time.as_deref(), Some("1/250"));
+        assert_eq!(metadata.f_number, Some(2.8));
+        assert_eq!(metadata.focal_length, Some(50.0));
+        assert_eq!(metadata.date_time.as_deref(), Some("2024-05-01 10:30:00"));
+
+        let (lat, lon) = metadata.gps_coordinates.unwrap();
+        assert!((lat + 33.5).abs() < 1e-9);
+        assert!((lon - 151.25).abs() < 1e-9);
+
+        let from_bytes = MetadataReader::new()
+            .read_exif_from_bytes(&jpeg_with_exif())
+            .unwrap();
+        assert_eq!(from_bytes.camera_model, metadata.camera_model);
+        assert_eq!(from_bytes.gps_coordinates, metadata.gps_coordinates);
+    }
+
+    #[test]
+    fn test_read_exif_without_exif_fails() {
+        let file = write_temp(&encode_jpeg(8, 8));
+        assert!(matches!(
+            MetadataReader::new().read_exif(file.path()),
+            Err(InfraError::ExifParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_metadata_with_xmp() {
+        let xmp = b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta><rdf:Description \
+                    xmp:Rating=\"5\" dc:description=\"Cover\"/></x:xmpmeta>";
+        let mut jpeg = Jpeg::from_bytes(Bytes::from(jpeg_with_exif())).unwrap();
+        jpeg.segments_mut().insert(
+            1,
+            img_parts::jpeg::JpegSegment::new_with_contents(
+                img_parts::jpeg::markers::APP1,
+                Bytes::from_static(xmp),
+            ),
+        );
+        let data = jpeg.encoder().bytes().to_vec();
+
+        let metadata = MetadataReader::new().read_metadata_from_bytes(&data);
+        assert_eq!(metadata.camera_make.as_deref(), Some("Canon"));
+        assert_eq!(metadata.xmp_rating, Some(5));
+        assert_eq!(metadata.iptc_caption.as_deref(), Some("Cover"));
+
+        // Sin EXIF ni nada más: vacío, no error
+        let file = write_temp(&[0xFF, 0xD8, 0xFF, 0xD9]);
+        assert!(MetadataReader::new()
+            .read_metadata(file.path())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_read_thumbnail() {
+        let thumbnail = encode_jpeg(4, 4);
+        let mut writer = Writer::new();
+        let make = field(Tag::Make, Value::Ascii(vec![b"Canon".to_vec()]));
+        writer.push_field(&make);
+        writer.set_jpeg(&thumbnail, In::THUMBNAIL);
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        let mut jpeg = Jpeg::from_bytes(Bytes::from(encode_jpeg(16, 16))).unwrap();
+        jpeg.set_exif(Some(Bytes::from(tiff.into_inner())));
+        let data = jpeg.encoder().bytes().to_vec();
+
+        let reader = MetadataReader::new();
+        assert_eq!(reader.read_thumbnail_from_bytes(&data), Some(thumbnail));
+
+        // EXIF sin IFD1, o sin EXIF
+        assert_eq!(reader.read_thumbnail_from_bytes(&jpeg_with_exif()), None);
+        assert_eq!(reader.read_thumbnail_from_bytes(&encode_jpeg(8, 8)), None);
+        assert_eq!(reader.read_thumbnail_from_bytes(b"not an image"), None);
+    }
+}
@@ -1,7 +1,9 @@
 pub mod error;
 pub mod file_system;
 pub mod image_processor;
+pub mod iptc_xmp;
 pub mod metadata_cleaner;
+pub mod metadata_reader;
 
 // pub use error::{InfraError, InfraResult};
 // pub use image_processor::ImageProcessorImpl;
@@ -9,7 +9,9 @@ pub mod infrastructure;
 pub use domain::{
     error::{DomainError, DomainResult},
     models::{
-        Image, ProcessingSettings, ResizeFilter, ResizeTransformation, Rotation, Transformation,
+        ArbitraryRotation, Channel, ColorAdjustment, FontSize, Image, ProcessingSettings,
+        ResizeFilter, ResizeMode, ResizeTransformation, Rotation, TextWatermark, TonalAdjustments,
+        Transformation, Watermark, WatermarkPosition,
     },
     services::ImageProcessor,
     value_objects::{Dimensions, ImageFormat, Quality},
@@ -27,21 +29,48 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             let app_state = application::state::AppState::new();
+            // El frontend se entera de cada cambio de estado sin sondear
+            app_state
+                .task_manager
+                .set_status_listener(application::commands::status_listener(app.handle().clone()));
             app.manage(app_state);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             application::commands::greet,
             application::commands::load_image_info,
+            application::commands::load_image_info_with_thumbnail,
             application::commands::load_images_info,
+            application::commands::load_raw_preview,
             application::commands::load_images_from_folder,
+            application::commands::get_directory_stats,
+            application::commands::validate_images,
+            application::commands::estimate_batch_size,
+            application::commands::extract_palette,
+            application::commands::compute_histogram,
+            application::commands::get_suggested_sizes,
             application::commands::process_images,
+            application::commands::process_image_bytes,
+            application::commands::batch_process_folder,
+            application::commands::start_folder_watch,
+            application::commands::stop_folder_watch,
+            application::commands::enqueue_images,
+            application::commands::get_queue_status,
+            application::commands::clear_queue,
             application::commands::cancel_processing,
+            application::commands::cancel_single_image,
             application::commands::get_processing_status,
             application::commands::is_processing,
             application::commands::get_stats,
             application::commands::reset_stats,
+            application::commands::get_last_report,
+            application::commands::get_last_summary,
+            application::commands::export_report,
+            application::commands::export_last_report,
             application::commands::get_optimal_threads,
+            application::commands::list_profiles,
+            application::commands::load_profile,
+            application::commands::save_profile,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,10 +1,13 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type {
+  BatchReport,
+  BatchSummary,
   ImageInfo,
   OptimizationOptions,
   ProcessedImage,
   ProgressPayload,
+  TaskStatus,
   TransformationOptions,
 } from "../../models/types";
 
@@ -12,12 +15,42 @@ export interface BatchProcessRequest {
   imagePaths: string[];
   optimizationOptions: OptimizationOptions;
   transformationOptions?: TransformationOptions;
+  /** Options for individual images keyed by path; only the fields set differ from optimizationOptions */
+  perImageOverrides?: Record<string, Partial<OptimizationOptions>>;
+  /** Cap on "processing-progress" events per second (default 10, 0 = no limit) */
+  maxProgressEventsPerSecond?: number;
+  /** Only plan the outputs and estimate their size; nothing is written and stats are left alone (default false) */
+  dryRun?: boolean;
+  /** Start right away instead of waiting for the running batch, for small jobs such as previews (default false) */
+  priority?: boolean;
+}
+
+/** Results of a batch with the id of the task it ran as */
+export interface ProcessedBatch {
+  taskId: string;
+  results: ProcessedImage[];
 }
 
 export interface ProcessingStats {
   totalProcessed: number;
   totalSavedBytes: number;
   averageSavings: number;
+  /** Breakdown by input format, sorted by format name */
+  perFormat: FormatStats[];
+  /** Seconds since the first image of the session */
+  sessionDurationSecs?: number | null;
+}
+
+export interface FormatStats {
+  /** Input format ("png", "jpg", "raw", ...) */
+  format: string;
+  count: number;
+  bytesIn: number;
+  bytesOut: number;
+  /** Images written in a different format than they were read */
+  converted: number;
+  /** Percentage of input bytes saved */
+  averageCompressionRatio: number;
 }
 
 export class ImageService {
@@ -52,22 +85,40 @@ export class ImageService {
   /**
    * Process images with optimization and optional transformations
    */
-  async processImages(request: BatchProcessRequest): Promise<ProcessedImage[]> {
+  async processImages(request: BatchProcessRequest): Promise<ProcessedBatch> {
     return invoke("process_images", { request });
   }
 
   /**
-   * Cancel ongoing processing operation
+   * Discover and process every image in a folder
+   * (maxDepth 1 = no subfolders, undefined = unlimited)
+   */
+  async batchProcessFolder(
+    folderPath: string,
+    optimizationOptions: OptimizationOptions,
+    transformationOptions?: TransformationOptions,
+    maxDepth?: number,
+  ): Promise<ProcessedImage[]> {
+    return invoke("batch_process_folder", {
+      folderPath,
+      maxDepth,
+      optimizationOptions,
+      transformationOptions,
+    });
+  }
+
+  /**
+   * Cancel a processing task, or every running task when no id is given
    */
-  async cancelProcessing(): Promise<void> {
-    return invoke("cancel_processing");
+  async cancelProcessing(taskId?: string): Promise<void> {
+    return invoke("cancel_processing", { taskId });
   }
 
   /**
-   * Get current processing status
+   * Get the status of a processing task, or of the latest one
    */
-  async getProcessingStatus(): Promise<string> {
-    return invoke("get_processing_status");
+  async getProcessingStatus(taskId?: string): Promise<TaskStatus> {
+    return invoke("get_processing_status", { taskId });
   }
 
   /**
@@ -91,6 +142,34 @@ export class ImageService {
     return invoke("reset_stats");
   }
 
+  /**
+   * Get the report of the last completed batch
+   */
+  async getLastReport(): Promise<BatchReport | null> {
+    return invoke("get_last_report");
+  }
+
+  /**
+   * Get the summary of the last completed batch
+   */
+  async getLastSummary(): Promise<BatchSummary | null> {
+    return invoke("get_last_summary");
+  }
+
+  /**
+   * Export the last batch report as JSON
+   */
+  async exportReport(path: string): Promise<void> {
+    return invoke("export_report", { path });
+  }
+
+  /**
+   * Export every result of the last batch with its summary and settings (CSV for a .csv path, JSON otherwise)
+   */
+  async exportLastReport(path: string): Promise<void> {
+    return invoke("export_last_report", { path });
+  }
+
   /**
    * Get optimal thread count
    */
@@ -118,4 +197,31 @@ export class ImageService {
       );
     });
   }
+
+  /**
+   * Listen to per-image results, emitted as each output finishes
+   */
+  onResult(callback: (result: ProcessedImage) => void): Promise<UnlistenFn> {
+    return listen<ProcessedImage>("processing-result", (event) => {
+      callback(event.payload);
+    });
+  }
+
+  /**
+   * Listen to the summary emitted when a batch finishes
+   */
+  onComplete(callback: (summary: BatchSummary) => void): Promise<UnlistenFn> {
+    return listen<BatchSummary>("processing-complete", (event) => {
+      callback(event.payload);
+    });
+  }
+
+  /**
+   * Listen to status changes of any task (queued, running, finished)
+   */
+  onStatusChanged(callback: (status: TaskStatus) => void): Promise<UnlistenFn> {
+    return listen<TaskStatus>("status-changed", (event) => {
+      callback(event.payload);
+    });
+  }
 }
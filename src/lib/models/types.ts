@@ -1,42 +1,348 @@
 // Type definitions for frontend
 
+/** What RAW files report when loaded: the full sensor image or the camera's embedded preview */
+export type LoadMode = "fullRaw" | "embeddedPreview";
+
 export interface ImageInfo {
   path: string;
   format: string;
   width: number;
   height: number;
   sizeBytes: number;
+  /** Format the extension claims when the contents are actually `format` */
+  extensionFormat?: string | null;
+  /** EXIF, IPTC and XMP fields found in the file */
+  metadata?: ImageMetadata | null;
+  /** Base64 JPEG embedded in the file (RAW preview or EXIF thumbnail), when requested */
+  thumbnailData?: string | null;
+}
+
+export interface ImageMetadata {
+  cameraMake?: string | null;
+  cameraModel?: string | null;
+  dateTime?: string | null;
+  isoSpeed?: number | null;
+  exposureTime?: string | null;
+  fNumber?: number | null;
+  focalLength?: number | null;
+  /** [latitude, longitude] */
+  gpsCoordinates?: [number, number] | null;
+  orientation?: number | null;
+  iptcKeywords?: string[] | null;
+  iptcCopyright?: string | null;
+  iptcCaption?: string | null;
+  /** Star rating 0-5 */
+  xmpRating?: number | null;
+  xmpLabel?: string | null;
+  /** Title from a RAW file's .xmp sidecar */
+  xmpTitle?: string | null;
 }
 
 export interface OptimizationOptions {
-  quality: number;
+  /** "web" | "print" | "thumbnail" | "archive" | "custom": seeds the options, explicit fields override it */
+  profile?: string;
+  /** 1-100 (default: the profile's, else 85, or the output format's recommended quality when converting) */
+  quality?: number;
   outputFormat?: string;
+  /** Several output formats in one pass (overrides outputFormat) */
+  outputFormats?: string[];
+  /** Empty = write each output next to its input */
   outputDirectory: string;
-  preserveMetadata: boolean;
+  /** Mirror the inputs' folders under outputDirectory (default false) */
+  preserveDirectoryStructure?: boolean;
+  /** Folder the mirrored structure starts at (default: the deepest folder containing every input) */
+  sourceBase?: string;
+  preserveMetadata?: boolean;
   overwriteExisting: boolean;
+  /** Copy an original to name.orig.ext before an output overwrites it (default false) */
+  createBackup?: boolean;
+  /** Skip images whose outputs are up to date, going by a manifest in the output folder (default false) */
+  skipUnchanged?: boolean;
+  /** Done to each original once all its outputs are written (default "keep"); "move" needs postActionDirectory */
+  postAction?: "keep" | "trash" | "delete" | "move";
+  postActionDirectory?: string;
+  /** Write every result, the summary and these settings here after the batch (CSV for a .csv path, JSON otherwise) */
+  writeReport?: string;
+  /** When not overwriting: fail (default), skip, or rename as name_1, name_2... / name_<timestamp> / name (1), name (2)... */
+  collisionStrategy?: "error" | "skip" | "rename" | "timestamp" | "numbered";
   rawQualityMode?: string;
+  minCompressionRatio?: number;
+  onNoImprovement?: "skip" | "copyOriginal" | "saveAnyway";
+  /** Keep the original when the output is larger: copy it (onNoImprovement "copyOriginal") or skip */
+  skipIfLarger?: boolean;
+  useLosslessRotation?: boolean;
+  rawProcessing?: RawProcessingOptions;
+  /** Image to decode from HEIC containers (0 = primary) */
+  heicImageIndex?: number;
+  /** Skip near-identical images, keeping the first of each group */
+  deduplicate?: boolean;
+  /** Apply the EXIF orientation tag on load (default true) */
+  autoOrient?: boolean;
+  /** Keep decoded images in memory so re-processing them skips decoding (default false) */
+  useCache?: boolean;
+  /** Write the whole batch into this .zip instead of loose files */
+  zipPath?: string;
+  /** Zip entries use only the file name (default true) */
+  flattenZip?: boolean;
+  /** Responsive widths (e.g. [320, 640, 1280]); one output per width */
+  sizes?: number[];
+  /** File name suffix for sized outputs, {width} is replaced (default "-{width}w") */
+  sizeSuffix?: string;
+  /** "#RRGGBB" background for transparent images saved as JPEG (default white) */
+  backgroundColor?: string;
+  /** Maximum output size in bytes; JPEG/WebP lower the quality to fit it */
+  targetSizeBytes?: number;
+  /** Hard limit in bytes for lossy WebP outputs; images that can't fit it fail */
+  maxOutputBytes?: number;
+  /** Quantize PNG outputs to a 256-color palette (lossy, much smaller) */
+  pngLossy?: boolean;
+  /** Scale 16-bit PNG sources down to 8 bits per channel in PNG outputs (default false) */
+  pngForce8bit?: boolean;
+  /** Downscale outputs whose longest side exceeds this many pixels */
+  maxLongEdge?: number;
+  /** Progressive JPEG (default true); false writes baseline JPEGs for legacy viewers */
+  progressive?: boolean;
+  /** JPEG chroma subsampling (default "auto": 4:4:4 from quality 90, 4:2:0 below) */
+  jpegChroma?: "420" | "422" | "444" | "auto";
+  /** Optimize JPEG Huffman tables per image (default true); false is faster but larger */
+  jpegOptimizeHuffman?: boolean;
+  /** Only lossless outputs (WebP lossless, unquantized PNG); JPEG outputs are refused */
+  lossless?: boolean;
+  /** Retries when reading an input fails, e.g. on a busy network share; decode errors are never retried (default 0) */
+  maxRetries?: number;
+  /** Milliseconds to wait between those retries (default 0) */
+  retryDelayMs?: number;
+  /** Report decode/transform/encode/write times for every output (default false) */
+  phaseTimings?: boolean;
+  /** Images processed at the same time (default: one per CPU core) */
+  maxWorkers?: number;
+  /** Stop the batch after this many images fail in a row (default: never) */
+  abortAfterFailures?: number;
+}
+
+export interface RawProcessingOptions {
+  whiteBalance?: "camera" | "auto" | "daylight" | "custom";
+  /** R, G, B, G2 multipliers used when whiteBalance is "custom" */
+  customMultipliers?: [number, number, number, number];
+  /** Exposure correction in stops (-2 to +3) */
+  exposureCorrection?: number;
+  highlightMode?: "clip" | "unclip" | "blend" | "rebuild";
+  /** Wavelet denoising threshold (0 = off) */
+  noiseThreshold?: number;
+  outputBps?: 8 | 16;
+  halfSize?: boolean;
 }
 
 export interface TransformationOptions {
+  /** Turn the image upright from its EXIF orientation first, even with autoOrient off in the options (default false) */
+  autoOrient?: boolean;
+  /** Crop transparent/solid-color borders before any other operation */
+  trim?: boolean;
+  /** Per-channel tolerance (0-255) for near-solid borders */
+  trimTolerance?: number;
+  /** Either mode + value, or exactly one of width/height, percentage, longestEdge or shortestEdge */
   resize?: {
-    width: number;
-    height: number;
-    preserveAspectRatio: boolean;
+    /** "absolute" and "cover" (fill the box, cropping the excess) use width/height; the other modes take `value` */
+    mode?: "absolute" | "cover" | "percentage" | "longEdge" | "shortEdge";
+    /** Percent or pixels for `mode` */
+    value?: number;
+    width?: number;
+    height?: number;
+    /** Relative to the original size (50 = half, above 100 upscales) */
+    percentage?: number;
+    /** Longest side in pixels, whatever the orientation */
+    longestEdge?: number;
+    /** Shortest side in pixels, whatever the orientation */
+    shortestEdge?: number;
+    /** Let the resize enlarge smaller images (default false: never upscale) */
+    allowUpscale?: boolean;
+    preserveAspectRatio?: boolean;
+    /** "lanczos3" (default), "nearest", "triangle", "catmullrom", "gaussian",
+     *  or "thumbnail" (fastest, for previews) */
     filter?: string;
   };
   rotate?: number;
+  rotateFillColor?: string;
+  rotateAutoCrop?: boolean;
   flipHorizontal: boolean;
   flipVertical: boolean;
+  watermark?: WatermarkOptions;
+  textWatermark?: TextWatermarkOptions;
+  grayscale?: boolean;
+  /** Color conversion after rotation and flips */
+  colorAdjustment?: "grayscale" | "sepia" | "red" | "green" | "blue" | "luma";
+  /** Sepia strength 0-1 (default 1) */
+  sepiaStrength?: number;
+  /** Brightness/contrast/saturation corrections */
+  tonal?: TonalAdjustments;
+  /** Unsharp mask, applied last */
+  sharpen?: SharpenOptions;
+  /** Gaussian blur sigma (0-100, 0 = off) */
+  blur?: number;
+}
+
+export interface SharpenOptions {
+  /** Base values; the other fields override them (default "moderate") */
+  preset?: "mild" | "moderate" | "strong";
+  /** Strength 0-5 (1 = add the full difference) */
+  amount?: number;
+  /** Gaussian sigma in pixels (0-100) */
+  radius?: number;
+  /** Minimum difference (0-255) to sharpen a pixel */
+  threshold?: number;
+}
+
+export interface TonalAdjustments {
+  /** Each -1..1, 0 or missing = unchanged */
+  brightness?: number;
+  contrast?: number;
+  saturation?: number;
+}
+
+export type WatermarkPosition =
+  | "topLeft"
+  | "top"
+  | "topRight"
+  | "left"
+  | "center"
+  | "right"
+  | "bottomLeft"
+  | "bottom"
+  | "bottomRight"
+  | "custom";
+
+export interface WatermarkOptions {
+  imagePath: string;
+  position: WatermarkPosition;
+  x?: number;
+  y?: number;
+  opacity: number;
+  scale: number;
+}
+
+export interface TextWatermarkOptions {
+  text: string;
+  fontPath?: string;
+  fontSize: number;
+  fontSizeUnit?: "px" | "percent";
+  /** "#RRGGBB" or "#RRGGBBAA" */
+  color?: string;
+  position: WatermarkPosition;
+  x?: number;
+  y?: number;
+  margin?: number;
 }
 
 export interface ProcessedImage {
   originalPath: string;
+  /** Written file, or `archive.zip!/inner/path.jpg` for a zip export */
   outputPath: string;
   originalSize: number;
   outputSize: number;
   compressionRatio: number;
   success: boolean;
+  skipped: boolean;
+  /** The output was larger than the original, which was kept instead */
+  skippedLarger: boolean;
+  /** Resize was capped at the original size because upscaling is disabled */
+  upscalePrevented: boolean;
+  /** Responsive width this output was generated for */
+  targetWidth?: number;
+  /** Quality the output was encoded at */
+  finalQuality?: number;
+  /** Whether the output fits targetSizeBytes (absent = no target) */
+  targetMet?: boolean;
+  /** Problem found before decoding (processing still went ahead) */
+  validationWarning?: string;
   errorMessage?: string;
+  /** Machine-readable kind of the failure, e.g. "decode_failed" or "disk_full" */
+  errorCode?: string | null;
+  /** Wall-clock time spent on the input image */
+  durationMs: number;
+  /** Time per phase (only with phaseTimings) */
+  timings?: PhaseTimings | null;
+  /** Times the input was read: 1 plus the retries used (0 = never decoded) */
+  attempts: number;
+  /** Planned by a dry run; outputSize is 0 */
+  dryRun: boolean;
+  /** The existing output was up to date and left alone (skipUnchanged) */
+  unchanged: boolean;
+  /** Expected output size in a dry run (absent without a sample to estimate from) */
+  estimatedSize?: number | null;
+  /** What happened to the original: "trashed", "deleted", "moved to ..." or why it failed (absent = left in place) */
+  postAction?: string | null;
+  /** Why the skipUnchanged manifest couldn't be saved (the image will be processed again next run) */
+  manifestError?: string | null;
+}
+
+export interface PhaseTimings {
+  decodeMs: number;
+  transformMs: number;
+  encodeMs: number;
+  writeMs: number;
+}
+
+/** Rough heuristics for typical photos, not guaranteed sizes */
+export interface SizeEstimate {
+  totalEstimatedBytes: number;
+  /** [path, estimated bytes] for every image that could be read */
+  perImage: [string, number][];
+}
+
+/** What a folder holds, from file metadata only */
+export interface DirectoryStats {
+  totalImages: number;
+  totalSizeBytes: number;
+  /** Image count by format ("raw", "jpg", ...) */
+  byFormat: Record<string, number>;
+  /** Oldest modification time, ms since the Unix epoch */
+  oldestFile?: number | null;
+  /** Newest modification time, ms since the Unix epoch */
+  newestFile?: number | null;
+  largestFileBytes: number;
+}
+
+/** Dominant color of an image */
+export interface PaletteColor {
+  rgb: [number, number, number];
+  /** "#rrggbb" */
+  hex: string;
+  /** Share of the image's pixels, 0-100 */
+  percentage: number;
+}
+
+/** 256-bin histograms (index = 0-255 value) plus exposure diagnostics */
+export interface Histogram {
+  r: number[];
+  g: number[];
+  b: number[];
+  luma: number[];
+  meanLuma: number;
+  /** Pixels at pure white luma, 0-100 */
+  clippedHighlightsPercent: number;
+  /** Pixels at pure black luma, 0-100 */
+  clippedShadowsPercent: number;
+}
+
+export type StandardSize =
+  | "hd720"
+  | "fhd1080"
+  | "qhd1440"
+  | "uhd4K"
+  | "socialSquare"
+  | "socialStory"
+  | "instagramLandscape"
+  | "printA4_300dpi";
+
+/** Option of the output size picker */
+export interface SuggestedSize {
+  size: StandardSize;
+  /** e.g. "Full HD 1080p (1920x1080)" */
+  label: string;
+  width: number;
+  height: number;
+  /** Best fit for the image (preselect it) */
+  recommended: boolean;
 }
 
 export interface ProgressPayload {
@@ -44,4 +350,122 @@ export interface ProgressPayload {
   total: number;
   currentFile: string;
   percentage: number;
+  elapsedMs: number;
+  /** Estimated time left, smoothed over recent images (absent for the first few) */
+  estimatedRemainingMs?: number;
+  throughputMbPerSec: number;
+  bytesProcessed: number;
+  /** Task the progress belongs to */
+  taskId?: string | null;
+}
+
+export type TaskState =
+  | "idle"
+  | "queued"
+  | "running"
+  | "completed"
+  | "cancelled"
+  | "error";
+
+export interface TaskStatus {
+  /** Absent when no task was ever created (state "idle") */
+  taskId?: string | null;
+  state: TaskState;
+  /** Why the task failed (state "error") */
+  errorMessage?: string | null;
+  current?: number | null;
+  total?: number | null;
+  /** When the task started running, in milliseconds since the Unix epoch */
+  startedAt?: number | null;
+}
+
+export interface FormatBreakdown {
+  count: number;
+  successCount: number;
+  bytesIn: number;
+  bytesOut: number;
+}
+
+export interface ImageTiming {
+  path: string;
+  processingTimeMs: number;
+}
+
+export interface ProcessingErrorSummary {
+  /** Error code of the failures (e.g. "decode_failed"), or their message when they have none */
+  errorType: string;
+  count: number;
+  files: string[];
+}
+
+export interface BatchReport {
+  totalImages: number;
+  successCount: number;
+  failureCount: number;
+  skippedCount: number;
+  totalBytesIn: number;
+  totalBytesOut: number;
+  formatBreakdown: Record<string, FormatBreakdown>;
+  slowestImage?: ImageTiming;
+  fastestImage?: ImageTiming;
+  /** Mean processing time of the successful images */
+  averageProcessingTimeMs: number;
+  totalTimeMs: number;
+  errors: ProcessingErrorSummary[];
+}
+
+/** Summary of one batch, sent with the "processing-complete" event */
+export interface BatchSummary {
+  succeeded: number;
+  failed: number;
+  skipped: number;
+  /** Up-to-date outputs left alone (skipUnchanged) */
+  unchanged: number;
+  bytesSaved: number;
+  durationMs: number;
+  /** Mean percentage saved by the written outputs */
+  averageCompressionRatio: number;
+  /** Phase times summed over every output (only with phaseTimings) */
+  timingTotals?: PhaseTimings | null;
+  slowestFile?: string | null;
+  slowestFileMs?: number | null;
+  /** Why the batch stopped early (absent when every image was processed) */
+  stopReason?: string | null;
+  /** Nothing was written: bytesSaved is an estimate */
+  dryRun: boolean;
+  /** Why the writeReport report couldn't be written */
+  reportError?: string | null;
+  /** Task the batch ran as */
+  taskId?: string | null;
+}
+
+export interface DiscoveryOptions {
+  /** Glob patterns (e.g. "IMG_2024*", "*.arw"); extensions match in any case */
+  include?: string[];
+  /** Glob patterns of files to leave out */
+  exclude?: string[];
+  /** Only these extensions (e.g. ["arw", "nef"]) */
+  extensions?: string[];
+  /** Also load images in subfolders (default false) */
+  recursive?: boolean;
+  /** Also load files with a non-image extension whose contents are an image (default false) */
+  sniffContent?: boolean;
+}
+
+export interface ValidationResult {
+  path: string;
+  status: "ok" | "truncated" | "wrongFormat" | "empty" | "unsupported";
+  message?: string;
+}
+
+/**
+ * Error every command rejects with. `code` names the kind of failure
+ * ("file_not_found", "invalid_image_format", "invalid_options", ...) for
+ * localized messages; `message` is the English description
+ */
+export interface CommandError {
+  code: string;
+  message: string;
+  /** File the error concerns, when there is one */
+  path?: string | null;
 }
@@ -1,23 +1,78 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn, } from "@tauri-apps/api/event";
 import type {
+  BatchSummary,
+  CommandError,
+  DirectoryStats,
+  DiscoveryOptions,
+  Histogram,
   ImageInfo,
+  LoadMode,
   OptimizationOptions,
+  PaletteColor,
   ProcessedImage,
   ProgressPayload,
+  SizeEstimate,
+  SuggestedSize,
+  TaskStatus,
   TransformationOptions,
+  ValidationResult,
 } from "../models/types";
 
 export interface BatchProcessRequest {
   imagePaths: string[];
   optimizationOptions: OptimizationOptions;
   transformationOptions?: TransformationOptions;
+  /** Options for individual images keyed by path; only the fields set differ from optimizationOptions */
+  perImageOverrides?: Record<string, Partial<OptimizationOptions>>;
+  /** Cap on "processing-progress" events per second (default 10, 0 = no limit) */
+  maxProgressEventsPerSecond?: number;
+  /** Only plan the outputs and estimate their size; nothing is written and stats are left alone (default false) */
+  dryRun?: boolean;
+  /** Start right away instead of waiting for the running batch, for small jobs such as previews (default false) */
+  priority?: boolean;
+}
+
+/** Results of a batch with the id of the task it ran as */
+export interface ProcessedBatch {
+  taskId: string;
+  results: ProcessedImage[];
+}
+
+/** A batch queued without waiting for it */
+export interface QueuedTask {
+  taskId: string;
+  /** Batches queued or running ahead of it */
+  ahead: number;
+}
+
+/** Batches waiting in the processing queue and running */
+export interface QueueStatus {
+  pending: number;
+  /** Tasks running, priority ones included */
+  active: number;
 }
 
 export interface ProcessingStats {
   totalProcessed: number;
   totalSavedBytes: number;
   averageSavings: number;
+  /** Breakdown by input format, sorted by format name */
+  perFormat: FormatStats[];
+  /** Seconds since the first image of the session */
+  sessionDurationSecs?: number | null;
+}
+
+export interface FormatStats {
+  /** Input format ("png", "jpg", "raw", ...) */
+  format: string;
+  count: number;
+  bytesIn: number;
+  bytesOut: number;
+  /** Images written in a different format than they were read */
+  converted: number;
+  /** Percentage of input bytes saved */
+  averageCompressionRatio: number;
 }
 
 export class ImageService {
@@ -31,43 +86,198 @@ export class ImageService {
   /**
    * Load single image metadata
    */
-  async loadImageInfo(path: string): Promise<ImageInfo> {
-    return invoke("load_image_info", { path });
+  async loadImageInfo(
+    path: string,
+    mode?: LoadMode,
+    includeThumbnail?: boolean,
+  ): Promise<ImageInfo> {
+    return invoke("load_image_info", { path, mode, includeThumbnail });
+  }
+
+  /**
+   * Load image metadata plus its embedded JPEG thumbnail (base64 in thumbnailData)
+   */
+  async loadImageInfoWithThumbnail(path: string): Promise<ImageInfo> {
+    return invoke("load_image_info_with_thumbnail", { path });
   }
 
   /**
    * Load multiple images metadata
    */
-  async loadImagesInfo(paths: string[]): Promise<ImageInfo[]> {
-    return invoke("load_images_info", { paths });
+  async loadImagesInfo(paths: string[], mode?: LoadMode): Promise<ImageInfo[]> {
+    return invoke("load_images_info", { paths, mode });
+  }
+
+  /**
+   * Embedded preview JPEG of a RAW file (much faster than decoding it)
+   */
+  async loadRawPreview(path: string): Promise<Uint8Array> {
+    const bytes = await invoke<number[]>("load_raw_preview", { path });
+    return new Uint8Array(bytes);
+  }
+
+  /**
+   * Image count, total size and dates of a folder, without decoding anything
+   */
+  async getDirectoryStats(folder: string, maxDepth?: number): Promise<DirectoryStats> {
+    return invoke("get_directory_stats", { folder, maxDepth });
+  }
+
+  /**
+   * Load images from a folder, optionally filtered by patterns and extensions
+   */
+  async loadImagesFromFolder(
+    folderPath: string,
+    options?: DiscoveryOptions,
+  ): Promise<ImageInfo[]> {
+    return invoke("load_images_from_folder", { folderPath, options });
+  }
+
+  /**
+   * Check image files for corruption before processing
+   */
+  async validateImages(paths: string[]): Promise<ValidationResult[]> {
+    return invoke("validate_images", { paths });
+  }
+
+  /**
+   * Approximate the output size of a batch without processing it
+   */
+  async estimateBatchSize(
+    paths: string[],
+    options: OptimizationOptions,
+  ): Promise<SizeEstimate> {
+    return invoke("estimate_batch_size", { paths, options });
+  }
+
+  /**
+   * Dominant colors of an image, most common first
+   */
+  async extractPalette(path: string, numColors: number): Promise<PaletteColor[]> {
+    return invoke("extract_palette", { path, numColors });
   }
 
   /**
-   * Load images from a folder
+   * RGB and luma histograms of an image, with clipping statistics
    */
-  async loadImagesFromFolder(folderPath: string): Promise<ImageInfo[]> {
-    return invoke("load_images_from_folder", { folderPath });
+  async computeHistogram(path: string): Promise<Histogram> {
+    return invoke("compute_histogram", { path });
+  }
+
+  /**
+   * Standard output sizes the image can be reduced to, best fit flagged as recommended
+   */
+  async getSuggestedSizes(path: string): Promise<SuggestedSize[]> {
+    return invoke("get_suggested_sizes", { path });
+  }
+
+  /**
+   * Process image bytes without writing any file (format detected from the contents when omitted)
+   */
+  async processImageBytes(
+    data: Uint8Array,
+    options: OptimizationOptions,
+    transform?: TransformationOptions,
+    format?: string,
+  ): Promise<Uint8Array> {
+    const bytes = await invoke<number[]>("process_image_bytes", {
+      data: Array.from(data),
+      format,
+      options,
+      transform,
+    });
+    return new Uint8Array(bytes);
   }
 
   /**
    * Process images with optimization and optional transformations
    */
-  async processImages(request: BatchProcessRequest): Promise<ProcessedImage[]> {
+  async processImages(request: BatchProcessRequest): Promise<ProcessedBatch> {
     return invoke("process_images", { request });
   }
 
   /**
-   * Cancel ongoing processing operation
+   * Watch a folder and process images as they arrive (replaces any previous watch)
+   */
+  async startFolderWatch(
+    folder: string,
+    optimizationOptions: OptimizationOptions,
+    transformationOptions?: TransformationOptions
+  ): Promise<void> {
+    return invoke("start_folder_watch", {
+      folder,
+      optimizationOptions,
+      transformationOptions,
+    });
+  }
+
+  /**
+   * Stop the active folder watch
+   */
+  async stopFolderWatch(): Promise<void> {
+    return invoke("stop_folder_watch");
+  }
+
+  /**
+   * Listen to the results of each batch processed by the folder watch
    */
-  async cancelProcessing(): Promise<void> {
-    return invoke("cancel_processing");
+  onWatchProcessed(
+    callback: (results: ProcessedImage[]) => void
+  ): Promise<UnlistenFn> {
+    return listen<ProcessedImage[]>("watch-processed", (event) => {
+      callback(event.payload);
+    });
+  }
+
+  /**
+   * Listen to folder watch failures: the watcher itself or a batch that couldn't run
+   */
+  onWatchError(callback: (error: CommandError) => void): Promise<UnlistenFn> {
+    return listen<CommandError>("watch-error", (event) => {
+      callback(event.payload);
+    });
+  }
+
+  /**
+   * Queue a batch without waiting for it; resolves with its task id and the batches ahead of it
+   */
+  async enqueueImages(request: BatchProcessRequest): Promise<QueuedTask> {
+    return invoke("enqueue_images", { request });
+  }
+
+  /**
+   * Batches waiting in the queue and running
+   */
+  async getQueueStatus(): Promise<QueueStatus> {
+    return invoke("get_queue_status");
+  }
+
+  /**
+   * Drop every queued batch (the running one goes on); resolves with how many were dropped
+   */
+  async clearQueue(): Promise<number> {
+    return invoke("clear_queue");
+  }
+
+  /**
+   * Cancel a task (a queued one never starts), or every running task when no id is given
+   */
+  async cancelProcessing(taskId?: string): Promise<void> {
+    return invoke("cancel_processing", { taskId });
+  }
+
+  /**
+   * Skip one image of a running task (the latest one by default), by its input path
+   */
+  async cancelSingleImage(path: string, taskId?: string): Promise<void> {
+    return invoke("cancel_single_image", { path, taskId });
   }
 
   /**
-   * Get current processing status
+   * Get the status of a task, or of the latest one
    */
-  async getProcessingStatus(): Promise<string> {
-    return invoke("get_processing_status");
+  async getProcessingStatus(taskId?: string): Promise<TaskStatus> {
+    return invoke("get_processing_status", { taskId });
   }
 
   /**
@@ -98,6 +308,27 @@ export class ImageService {
     return invoke("get_optimal_threads");
   }
 
+  /**
+   * Names of the saved settings presets
+   */
+  async listProfiles(): Promise<string[]> {
+    return invoke("list_profiles");
+  }
+
+  /**
+   * Load a saved settings preset, every option filled in
+   */
+  async loadProfile(name: string): Promise<OptimizationOptions> {
+    return invoke("load_profile", { name });
+  }
+
+  /**
+   * Save options as a named preset (letters, digits, spaces, '-' and '_'); replaces one with the same name
+   */
+  async saveProfile(name: string, settings: OptimizationOptions): Promise<void> {
+    return invoke("save_profile", { name, settings });
+  }
+
   /**
    * Listen to progress events
    */
@@ -118,4 +349,31 @@ export class ImageService {
       );
     });
   }
+
+  /**
+   * Listen to per-image results, emitted as each output finishes
+   */
+  onResult(callback: (result: ProcessedImage) => void): Promise<UnlistenFn> {
+    return listen<ProcessedImage>("processing-result", (event) => {
+      callback(event.payload);
+    });
+  }
+
+  /**
+   * Listen to the summary emitted when a batch finishes
+   */
+  onComplete(callback: (summary: BatchSummary) => void): Promise<UnlistenFn> {
+    return listen<BatchSummary>("processing-complete", (event) => {
+      callback(event.payload);
+    });
+  }
+
+  /**
+   * Listen to status changes of any task (queued, running, finished)
+   */
+  onStatusChanged(callback: (status: TaskStatus) => void): Promise<UnlistenFn> {
+    return listen<TaskStatus>("status-changed", (event) => {
+      callback(event.payload);
+    });
+  }
 }